@@ -1,8 +1,17 @@
 use lambda_http::service_fn;
 use lib_config::{config::Config, logs::setup_log, //traces::setup_tracing_level
 };
+use lib_engage::services::account_deletion::AccountDeletionService;
+use lib_licenses::repositories::assets::AssetRepo;
+use lib_licenses::repositories::shorter::ShorterRepo;
+use lib_licenses::services::assets::AssetService;
+use lib_users::repositories::audit::AuditRepo;
+use lib_users::repositories::login_attempts::LoginAttemptsRepo;
 use lib_users::repositories::users::UsersRepo;
+use lib_users::services::audit::AuditService;
+use lib_users::services::login_attempts::LoginAttemptService;
 use lib_users::services::users::UsersService;
+use lib_util_jwt::jwt::{TokenVerifier, AUDIENCE_ADMIN};
 use my_lambda::{error::ApiLambdaAdminUserError, function_handler};
 
 mod my_lambda;
@@ -22,9 +31,32 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let user_repo = UsersRepo::new(&config);
     let user_service = UsersService::new(user_repo);
 
+    let login_attempt_repo = LoginAttemptsRepo::new(&config);
+    let login_attempt_service = LoginAttemptService::new(login_attempt_repo);
+
+    let asset_repo = AssetRepo::new(&config);
+    let shorter_repo = ShorterRepo::new(&config);
+    let asset_service = AssetService::new(asset_repo, shorter_repo);
+
+    let account_deletion_service = AccountDeletionService::new(&config);
+
+    let audit_repo = AuditRepo::new(&config);
+    let audit_service = AuditService::new(audit_repo);
+
+    let verifier = TokenVerifier::new(&config, AUDIENCE_ADMIN);
+
     log::info!("lambda ready, awaiting for events.");
     let resp = lambda_http::run(service_fn(|event| {
-        function_handler(&config, &user_service, event)
+        function_handler(
+            &config,
+            &user_service,
+            &login_attempt_service,
+            &asset_service,
+            &account_deletion_service,
+            &audit_service,
+            &verifier,
+            event,
+        )
     }))
     .await;
 
@@ -0,0 +1,69 @@
+use std::str::FromStr;
+
+use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
+use lib_config::config::Config;
+use lib_config::result::ResultE;
+use lib_licenses::errors::asset::{AssetDynamoDBError, AssetNoExistsError};
+use lib_licenses::models::asset::AssetStatus;
+use lib_licenses::services::assets::{AssetManipulation, AssetService, UpdatableFildsAsset};
+use uuid::Uuid;
+
+use super::build_resp;
+
+//#[instrument]
+pub async fn disable_asset(
+    req: &Request,
+    c: &Context,
+    config: &Config,
+    asset_service: &AssetService,
+    id: &String,
+) -> ResultE<Response<String>> {
+    change_asset_status(req, c, config, asset_service, id, &AssetStatus::Disabled).await
+}
+
+//#[instrument]
+pub async fn enable_asset(
+    req: &Request,
+    c: &Context,
+    config: &Config,
+    asset_service: &AssetService,
+    id: &String,
+) -> ResultE<Response<String>> {
+    change_asset_status(req, c, config, asset_service, id, &AssetStatus::Enabled).await
+}
+
+//#[instrument]
+async fn change_asset_status(
+    _req: &Request,
+    _c: &Context,
+    _config: &Config,
+    asset_service: &AssetService,
+    id: &String,
+    status: &AssetStatus,
+) -> ResultE<Response<String>> {
+    let asset_id = match Uuid::from_str(id.as_str()) {
+        Err(_) => {
+            return build_resp(
+                "id param must be UUID".to_string(),
+                StatusCode::NOT_ACCEPTABLE,
+            )
+        }
+        Ok(asset_id) => asset_id,
+    };
+    let changes = UpdatableFildsAsset {
+        license: None,
+        status: Some(status.to_string()),
+    };
+    match asset_service.update(&asset_id, &changes).await {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<AssetDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else if let Some(m) = e.downcast_ref::<AssetNoExistsError>() {
+                build_resp(m.to_string(), StatusCode::NOT_FOUND)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+        Ok(_) => build_resp("".to_string(), StatusCode::OK),
+    }
+}
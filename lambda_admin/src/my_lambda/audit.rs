@@ -0,0 +1,69 @@
+use std::str::FromStr;
+
+use lambda_http::http::Method;
+use lib_licenses::services::assets::{AssetManipulation, AssetService};
+use lib_users::models::audit::{AuditAction, AuditEntry};
+use lib_users::services::audit::{AuditManipulation, AuditService};
+use lib_users::services::users::{UserManipulation, UsersService};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+// the admin routes that change something, each one leaves an entry in the audit log
+pub fn audited_action(method: &Method, route: &str) -> Option<AuditAction> {
+    match (method, route) {
+        (&Method::PUT, "2") => Some(AuditAction::UpdateUser),
+        (&Method::DELETE, "2") => Some(AuditAction::DeleteUser),
+        (&Method::POST, "3") => Some(AuditAction::ResetPassword),
+        (&Method::POST, "4") => Some(AuditAction::PromoteUser),
+        (&Method::POST, "5") => Some(AuditAction::DowngradeUser),
+        (&Method::POST, "7") => Some(AuditAction::UnlockUser),
+        (&Method::PUT, "8") => Some(AuditAction::UpdateRoles),
+        (&Method::POST, "9") => Some(AuditAction::DisableAsset),
+        (&Method::POST, "10") => Some(AuditAction::EnableAsset),
+        _ => None,
+    }
+}
+
+// the target as the admin api shows it, none when it doesn't exist (anymore)
+pub async fn snapshot(
+    action: &AuditAction,
+    target_id: &String,
+    user_service: &UsersService,
+    asset_service: &AssetService,
+) -> Option<Value> {
+    match action {
+        AuditAction::DisableAsset | AuditAction::EnableAsset => {
+            let asset_id = Uuid::from_str(target_id.as_str()).ok()?;
+            let asset = asset_service.get_by_id(&asset_id).await.ok()?;
+            Some(json!(asset))
+        }
+        // the row outlives the account, so it keeps no email, wallet or device of it
+        AuditAction::DeleteUser => {
+            let user = user_service.get_by_id(target_id).await.ok()?;
+            Some(json!({
+                "user_id": user.user_id(),
+                "status": user.status(),
+                "roles": user.roles(),
+            }))
+        }
+        _ => {
+            let user = user_service.get_by_id(target_id).await.ok()?;
+            Some(json!(user))
+        }
+    }
+}
+
+// the action already happened, a failure here is logged but doesn't undo it: the entry is there,
+// only without its outcome
+pub async fn complete(audit_service: &AuditService, entry: &AuditEntry) {
+    if let Err(e) = audit_service.complete(entry).await {
+        log::error!(
+            "audit outcome lost: {} by {} on {} request {}: {}",
+            entry.action(),
+            entry.actor_id(),
+            entry.target_id(),
+            entry.request_id(),
+            e
+        );
+    }
+}
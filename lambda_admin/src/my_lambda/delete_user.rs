@@ -0,0 +1,56 @@
+use lambda_http::RequestPayloadExt;
+use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
+use lib_config::config::Config;
+use lib_config::result::ResultE;
+use lib_engage::errors::account_deletion::AccountDeletionError;
+use lib_engage::services::account_deletion::{AccountDeletionService, AssetPolicy};
+use lib_licenses::errors::owner::{OwnerDynamoDBError, OwnerShareError};
+use lib_users::errors::users::{UserDynamoDBError, UserNoExistsError};
+use serde::Deserialize;
+
+use super::build_resp;
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteUserPayload {
+    // anonymize or transfer
+    pub asset_policy: String,
+    pub transfer_to: Option<String>,
+}
+
+//#[instrument]
+pub async fn delete_user(
+    req: &Request,
+    _c: &Context,
+    _config: &Config,
+    account_deletion_service: &AccountDeletionService,
+    id: &String,
+) -> ResultE<Response<String>> {
+    let payload = match req.payload::<DeleteUserPayload>() {
+        Err(e) => return build_resp(e.to_string(), StatusCode::BAD_REQUEST),
+        Ok(None) => return build_resp("no payload found".to_string(), StatusCode::BAD_REQUEST),
+        Ok(Some(payload)) => payload,
+    };
+    let policy = match AssetPolicy::parse(&payload.asset_policy, &payload.transfer_to) {
+        Err(e) => return build_resp(e.to_string(), StatusCode::BAD_REQUEST),
+        Ok(policy) => policy,
+    };
+
+    match account_deletion_service.delete(id, &policy).await {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<AccountDeletionError>() {
+                build_resp(m.to_string(), StatusCode::BAD_REQUEST)
+            } else if let Some(m) = e.downcast_ref::<UserNoExistsError>() {
+                build_resp(m.to_string(), StatusCode::NOT_FOUND)
+            } else if let Some(m) = e.downcast_ref::<OwnerShareError>() {
+                build_resp(m.to_string(), StatusCode::CONFLICT)
+            } else if let Some(m) = e.downcast_ref::<UserDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else if let Some(m) = e.downcast_ref::<OwnerDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+        Ok(_) => build_resp("".to_string(), StatusCode::OK),
+    }
+}
@@ -0,0 +1,70 @@
+use std::str::FromStr;
+
+use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
+use lib_config::config::Config;
+use lib_config::result::ResultE;
+use lib_users::errors::audit::{AuditDynamoDBError, AuditParamNotAccepted};
+use lib_users::models::audit::{AuditAction, AuditSearch};
+use lib_users::models::user_search::SEARCH_PAGE_SIZE_MIN;
+use lib_users::services::audit::{AuditManipulation, AuditService};
+use serde_json::json;
+
+use super::build_resp;
+use super::get_users::{query_param, query_time};
+
+// actor, target, action, month (YYYY-MM), from, to, order (asc or desc), pageSize
+// and the cursor handed out with the previous page
+fn search_params(req: &Request) -> Result<AuditSearch, String> {
+    let mut filter = AuditSearch::new();
+    filter.actor_id = query_param(req, "actor");
+    filter.target_id = query_param(req, "target");
+    filter.month = query_param(req, "month");
+    filter.cursor = query_param(req, "cursor");
+    if let Some(action) = query_param(req, "action") {
+        match AuditAction::from_str(&action) {
+            Err(_) => return Err(format!("unknown action {}", action)),
+            Ok(action) => filter.action = Some(action),
+        }
+    }
+    filter.from = query_time(req, "from")?;
+    filter.to = query_time(req, "to")?;
+    filter.ascending = match query_param(req, "order").as_deref() {
+        None | Some("desc") => false,
+        Some("asc") => true,
+        Some(other) => return Err(format!("order is {}, use asc or desc", other)),
+    };
+    filter.page_size = match query_param(req, "pageSize") {
+        None => SEARCH_PAGE_SIZE_MIN,
+        Some(value) => match value.parse::<u32>() {
+            Err(_) => return Err(format!("pageSize is {}, it must be a number", value)),
+            Ok(size) => size,
+        },
+    };
+    Ok(filter)
+}
+
+//#[instrument]
+pub async fn get_audit(
+    req: &Request,
+    _c: &Context,
+    _config: &Config,
+    audit_service: &AuditService,
+) -> ResultE<Response<String>> {
+    let filter = match search_params(req) {
+        Err(message) => return build_resp(message, StatusCode::BAD_REQUEST),
+        Ok(filter) => filter,
+    };
+
+    match audit_service.search(&filter).await {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<AuditParamNotAccepted>() {
+                build_resp(m.to_string(), StatusCode::BAD_REQUEST)
+            } else if let Some(m) = e.downcast_ref::<AuditDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+        Ok(page) => build_resp(json!(page).to_string(), StatusCode::OK),
+    }
+}
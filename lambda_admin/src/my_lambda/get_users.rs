@@ -1,16 +1,73 @@
+use chrono::{DateTime, Utc};
 use lambda_http::RequestExt;
 use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
 use lib_config::config::Config;
 use lib_config::result::ResultE;
+use lib_users::errors::users::{UserDynamoDBError, UserParamNotAccepted};
+use lib_users::models::user::{UserRoles, UserStatus};
+use lib_users::models::user_search::{UserSearch, SEARCH_PAGE_SIZE_MIN};
 use lib_users::services::users::{UserManipulation, UsersService};
 use serde_json::json;
 //use tracing::instrument;
 
 use super::build_resp;
 
-const PAGESIZE_MAX: u32 = 20;
-const PAGESIZE_MIN: u32 = 5;
-const PAGENUM_MIN: u32 = 1;
+pub(super) fn query_param(req: &Request, name: &str) -> Option<String> {
+    req.query_string_parameters()
+        .first(name)
+        .filter(|value| !value.is_empty())
+        .map(|value| value.to_string())
+}
+
+pub(super) fn query_time(req: &Request, name: &str) -> Result<Option<DateTime<Utc>>, String> {
+    match query_param(req, name) {
+        None => Ok(None),
+        Some(value) => match DateTime::parse_from_rfc3339(&value) {
+            Err(_) => Err(format!(
+                "{} must be an RFC 3339 date, like 2024-01-31T00:00:00Z",
+                name
+            )),
+            Ok(time) => Ok(Some(time.with_timezone(&Utc))),
+        },
+    }
+}
+
+// emailPrefix, wallet, device, role, status, createdFrom, createdTo, order (asc or desc),
+// pageSize and the cursor handed out with the previous page
+fn search_params(req: &Request) -> Result<UserSearch, String> {
+    let mut filter = UserSearch::new();
+    filter.email_prefix = query_param(req, "emailPrefix");
+    filter.wallet = query_param(req, "wallet");
+    filter.device = query_param(req, "device");
+    filter.cursor = query_param(req, "cursor");
+    if let Some(role) = query_param(req, "role") {
+        match UserRoles::deserialize(&role) {
+            None => return Err(format!("unknown role {}", role)),
+            Some(role) => filter.role = Some(role),
+        }
+    }
+    if let Some(status) = query_param(req, "status") {
+        match UserStatus::parse(&status) {
+            None => return Err(format!("unknown status {}", status)),
+            Some(status) => filter.status = Some(status),
+        }
+    }
+    filter.created_from = query_time(req, "createdFrom")?;
+    filter.created_to = query_time(req, "createdTo")?;
+    filter.ascending = match query_param(req, "order").as_deref() {
+        None | Some("desc") => false,
+        Some("asc") => true,
+        Some(other) => return Err(format!("order is {}, use asc or desc", other)),
+    };
+    filter.page_size = match query_param(req, "pageSize") {
+        None => SEARCH_PAGE_SIZE_MIN,
+        Some(value) => match value.parse::<u32>() {
+            Err(_) => return Err(format!("pageSize is {}, it must be a number", value)),
+            Ok(size) => size,
+        },
+    };
+    Ok(filter)
+}
 
 //#[instrument]
 pub async fn get_users(
@@ -19,55 +76,21 @@ pub async fn get_users(
     _config: &Config,
     user_service: &UsersService,
 ) -> ResultE<Response<String>> {
-    let page_number;
-    //let page_size = PAGESIZE_MIN;
-    match req.query_string_parameters().all("pageNumber") {
-        None => page_number = PAGENUM_MIN,
-        Some(vstr) => {
-            let _first = vstr.first();
-            match _first {
-                None => page_number = PAGENUM_MIN,
-                Some(value) => page_number = value.parse::<u32>().unwrap(),
-            }
-        }
-    }
-    let page_size;
+    let filter = match search_params(req) {
+        Err(message) => return build_resp(message, StatusCode::BAD_REQUEST),
+        Ok(filter) => filter,
+    };
 
-    match req.query_string_parameters().all("pageSize") {
-        None => page_size = PAGESIZE_MIN,
-        Some(vstr) => {
-            let _first = vstr.first();
-            match _first {
-                None => page_size = PAGESIZE_MIN,
-                Some(value) => page_size = value.parse::<u32>().unwrap(),
+    match user_service.search(&filter).await {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<UserParamNotAccepted>() {
+                build_resp(m.to_string(), StatusCode::BAD_REQUEST)
+            } else if let Some(m) = e.downcast_ref::<UserDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
             }
         }
-    }
-    if page_number < PAGENUM_MIN {
-        let message = format!("pageNumber is {}, when the minimum value is 1", page_number);
-        return build_resp(message.to_string(), StatusCode::BAD_REQUEST);
-        //return HttpResponse::BadRequest().body(message);
-    }
-    if page_size < PAGESIZE_MIN || page_size > PAGESIZE_MAX {
-        let message = format!(
-            "pageSize is {0}, but it must be between {1} and {2}",
-            page_size, PAGENUM_MIN, PAGESIZE_MAX
-        );
-        //return HttpResponse::BadRequest().body(message);
-        return build_resp(message.to_string(), StatusCode::BAD_REQUEST);
-    }
-
-    let res = user_service.get_all(page_number, page_size).await;
-    match res {
-        Err(e) =>
-        //HttpResponse::InternalServerError().finish(),
-        {
-            return build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR);
-        }
-        Ok(vec_user) =>
-        //HttpResponse::Ok().json(vec_user)
-        {
-            return build_resp(json!(vec_user).to_string(), StatusCode::OK);
-        }
+        Ok(page) => build_resp(json!(page).to_string(), StatusCode::OK),
     }
 }
@@ -0,0 +1,67 @@
+use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
+use lib_config::config::Config;
+use lib_config::result::ResultE;
+use lib_users::errors::login_attempts::LoginAttemptDynamoDBError;
+use lib_users::errors::users::{UserDynamoDBError, UserNoExistsError};
+use lib_users::services::login_attempts::{LoginAttemptManipulation, LoginAttemptService};
+use lib_users::services::users::{UserManipulation, UsersService};
+use serde_json::json;
+
+use super::build_resp;
+
+//#[instrument]
+pub async fn get_locked_users(
+    _req: &Request,
+    _c: &Context,
+    _config: &Config,
+    login_attempt_service: &LoginAttemptService,
+) -> ResultE<Response<String>> {
+    match login_attempt_service.get_locked().await {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<LoginAttemptDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+        Ok(locked) => build_resp(json!(locked).to_string(), StatusCode::OK),
+    }
+}
+
+//#[instrument]
+pub async fn unlock_user(
+    _req: &Request,
+    _c: &Context,
+    _config: &Config,
+    user_service: &UsersService,
+    login_attempt_service: &LoginAttemptService,
+    id: &String,
+) -> ResultE<Response<String>> {
+    let user = match user_service.get_by_id(id).await {
+        Err(e) => {
+            return if let Some(m) = e.downcast_ref::<UserDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else if let Some(m) = e.downcast_ref::<UserNoExistsError>() {
+                build_resp(m.to_string(), StatusCode::NO_CONTENT)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            };
+        }
+        Ok(user) => user,
+    };
+    let email = match user.email() {
+        None => return build_resp("".to_string(), StatusCode::OK),
+        Some(email) => email.clone(),
+    };
+
+    match login_attempt_service.unlock(&email).await {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<LoginAttemptDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+        Ok(_) => build_resp("".to_string(), StatusCode::OK),
+    }
+}
@@ -1,55 +1,182 @@
-use lambda_http::{http::Method, http::StatusCode, IntoResponse, Request, RequestExt};
+use lambda_http::{
+    http::Method, http::StatusCode, lambda_runtime::Context, IntoResponse, Request, RequestExt,
+    Response,
+};
 use lib_config::config::Config;
 use lib_config::result::ResultE;
+use lib_engage::services::account_deletion::AccountDeletionService;
+use lib_licenses::services::assets::AssetService;
+use lib_users::models::audit::AuditEntry;
+use lib_users::models::permission::Permission;
+use lib_users::services::audit::{AuditManipulation, AuditService};
+use lib_users::services::login_attempts::LoginAttemptService;
 use lib_users::services::users::UsersService;
+use lib_util_jwt::auth::AuthOutcome;
 use lib_util_jwt::build::build_resp;
-use lib_util_jwt::jwt::check_jwt_token_as_admin;
+use lib_util_jwt::jwt::TokenVerifier;
+use self::asset_status::{disable_asset, enable_asset};
+use self::audit::{audited_action, complete, snapshot};
+use self::delete_user::delete_user;
+use self::get_audit::get_audit;
 use self::get_user_by_id::get_user_by_id;
 use self::get_users::get_users;
+use self::locked_users::{get_locked_users, unlock_user};
 use self::password_update_user::password_update_user;
 use self::promote_user::{downgrade_user, promote_user};
 use self::update_user::update_user;
+use self::user_roles::update_user_roles;
 use matchit::Router;
 
+mod asset_status;
+mod audit;
+mod delete_user;
 pub mod error;
+mod get_audit;
 mod get_user_by_id;
 mod get_users;
+mod locked_users;
 mod password_update_user;
 mod promote_user;
 mod update_user;
+mod user_roles;
+
+// what every admin route asks for, checked before dispatching
+fn route_permission(method: &Method, route: &str) -> Option<Permission> {
+    match (method, route) {
+        (&Method::GET, "1") | (&Method::GET, "2") => Some(Permission::ViewPii),
+        (&Method::PUT, "2") | (&Method::DELETE, "2") | (&Method::POST, "3") => {
+            Some(Permission::ManageUsers)
+        }
+        (&Method::POST, "4") | (&Method::POST, "5") | (&Method::PUT, "8") => {
+            Some(Permission::ManageRoles)
+        }
+        (&Method::GET, "6") | (&Method::POST, "7") => Some(Permission::UnlockUsers),
+        (&Method::POST, "9") | (&Method::POST, "10") => Some(Permission::DisableAsset),
+        (&Method::GET, "11") => Some(Permission::ViewAudit),
+        _ => None,
+    }
+}
 
 //#[instrument]
+#[allow(clippy::too_many_arguments)]
 pub async fn function_handler(
     config: &Config,
     user_service: &UsersService,
+    login_attempt_service: &LoginAttemptService,
+    asset_service: &AssetService,
+    account_deletion_service: &AccountDeletionService,
+    audit_service: &AuditService,
+    verifier: &TokenVerifier,
     req: Request,
 ) -> ResultE<impl IntoResponse> {
     let context = req.lambda_context();
     //let query_string = req.query_string_parameters().to_owned();
     //request.uri().path()
 
-    match check_jwt_token_as_admin(&req, config) {
-        Err(e) => {
-            return build_resp(e.to_string(), StatusCode::UNAUTHORIZED);
-        }
-        Ok(value) => match value {
-            false => {
-                return build_resp(
-                    "you aren't admin, please login as admin".to_string(),
-                    StatusCode::UNAUTHORIZED,
-                );
-            }
-            _ => {}
-        },
-    }
-
     let mut router = Router::new();
     router.insert("/admin/users", Some("1"))?;
     router.insert("/admin/users/:id", Some("2"))?;
     router.insert("/admin/users/password_update/:id", Some("3"))?;
     router.insert("/admin/users/upgrade/:id", Some("4"))?;
     router.insert("/admin/users/downgrade/:id", Some("5"))?;
+    router.insert("/admin/users/locked", Some("6"))?;
+    router.insert("/admin/users/unlock/:id", Some("7"))?;
+    router.insert("/admin/users/roles/:id", Some("8"))?;
+    router.insert("/admin/assets/disable/:id", Some("9"))?;
+    router.insert("/admin/assets/enable/:id", Some("10"))?;
+    router.insert("/admin/audit", Some("11"))?;
+
+    let route = match router.at(req.uri().path()) {
+        Err(_) => "",
+        Ok(matched) => matched.value.unwrap(),
+    };
+    let actor_id = match route_permission(req.method(), route) {
+        None => {
+            return build_resp(
+                "method not allowed".to_string(),
+                StatusCode::METHOD_NOT_ALLOWED,
+            );
+        }
+        Some(permission) => {
+            let auth = AuthOutcome::extract(&req, config, verifier).await;
+            match auth.require_permission(&permission) {
+                Err(e) => return Ok(e),
+                Ok(auth) => auth.user_id().clone(),
+            }
+        }
+    };
+
+    // changes are recorded here, around the handlers, so none of them can skip the audit log
+    let audited = audited_action(req.method(), route).and_then(|action| {
+        let matched = router.at(req.uri().path()).ok()?;
+        let target_id = matched.params.get("id")?.to_string();
+        Some((action, target_id))
+    });
+    // the entry goes in before the handler runs, even when the target can't be read: an action
+    // that couldn't be audited mustn't happen
+    let mut entry = None;
+    if let Some((action, target_id)) = &audited {
+        let mut new_entry = AuditEntry::new();
+        new_entry.set_actor_id(&actor_id);
+        new_entry.set_action(action);
+        new_entry.set_target_id(target_id);
+        new_entry.set_before(&snapshot(action, target_id, user_service, asset_service).await);
+        new_entry.set_request_id(&context.request_id);
+        if let Err(e) = audit_service.record(&new_entry).await {
+            log::error!(
+                "audit log unavailable, {} on {} refused: {}",
+                action,
+                target_id,
+                e
+            );
+            return build_resp(
+                "audit log unavailable, nothing was changed".to_string(),
+                StatusCode::SERVICE_UNAVAILABLE,
+            );
+        }
+        entry = Some(new_entry);
+    }
+
+    let resp = dispatch(
+        &req,
+        &context,
+        config,
+        &router,
+        user_service,
+        login_attempt_service,
+        asset_service,
+        account_deletion_service,
+        audit_service,
+    )
+    .await?;
+
+    if let Some(mut entry) = entry {
+        let after = snapshot(
+            entry.action(),
+            entry.target_id(),
+            user_service,
+            asset_service,
+        )
+        .await;
+        entry.set_after(&after);
+        entry.set_status(&Some(resp.status().as_u16()));
+        complete(audit_service, &entry).await;
+    }
+    Ok(resp)
+}
 
+#[allow(clippy::too_many_arguments)]
+async fn dispatch(
+    req: &Request,
+    context: &Context,
+    config: &Config,
+    router: &Router<Option<&str>>,
+    user_service: &UsersService,
+    login_attempt_service: &LoginAttemptService,
+    asset_service: &AssetService,
+    account_deletion_service: &AccountDeletionService,
+    audit_service: &AuditService,
+) -> ResultE<Response<String>> {
     //info!("{}",req.uri().path());
     match req.method() {
         &Method::GET => match router.at(req.uri().path()) {
@@ -58,11 +185,13 @@ pub async fn function_handler(
                 StatusCode::METHOD_NOT_ALLOWED,
             ),
             Ok(matched) => match matched.value.unwrap() {
-                "1" => get_users(&req, &context, config, user_service).await,
+                "1" => get_users(req, context, config, user_service).await,
                 "2" => {
                     let id = matched.params.get("id").unwrap().to_string();
-                    return get_user_by_id(&req, &context, config, user_service, &id).await;
+                    return get_user_by_id(req, context, config, user_service, &id).await;
                 }
+                "6" => get_locked_users(req, context, config, login_attempt_service).await,
+                "11" => get_audit(req, context, config, audit_service).await,
                 _ => build_resp(
                     "method not allowed".to_string(),
                     StatusCode::METHOD_NOT_ALLOWED,
@@ -77,15 +206,27 @@ pub async fn function_handler(
             Ok(matched) => match matched.value.unwrap() {
                 "3" => {
                     let id = matched.params.get("id").unwrap().to_string();
-                    return password_update_user(&req, &context, config, user_service, &id).await;
+                    return password_update_user(req, context, config, user_service, &id).await;
                 }
                 "4" => {
                     let id = matched.params.get("id").unwrap().to_string();
-                    return promote_user(&req, &context, config, user_service, &id).await;
+                    return promote_user(req, context, config, user_service, &id).await;
                 }
                 "5" => {
                     let id = matched.params.get("id").unwrap().to_string();
-                    return downgrade_user(&req, &context, config, user_service, &id).await;
+                    return downgrade_user(req, context, config, user_service, &id).await;
+                }
+                "7" => {
+                    let id = matched.params.get("id").unwrap().to_string();
+                    return unlock_user(req, context, config, user_service, login_attempt_service, &id).await;
+                }
+                "9" => {
+                    let id = matched.params.get("id").unwrap().to_string();
+                    return disable_asset(req, context, config, asset_service, &id).await;
+                }
+                "10" => {
+                    let id = matched.params.get("id").unwrap().to_string();
+                    return enable_asset(req, context, config, asset_service, &id).await;
                 }
                 _ => build_resp(
                     "method not allowed".to_string(),
@@ -101,7 +242,27 @@ pub async fn function_handler(
             Ok(matched) => match matched.value.unwrap() {
                 "2" => {
                     let id = matched.params.get("id").unwrap().to_string();
-                    update_user(&req, &context, config, user_service, &id).await
+                    update_user(req, context, config, user_service, &id).await
+                }
+                "8" => {
+                    let id = matched.params.get("id").unwrap().to_string();
+                    update_user_roles(req, context, config, user_service, &id).await
+                }
+                &_ => build_resp(
+                    "method not allowed".to_string(),
+                    StatusCode::METHOD_NOT_ALLOWED,
+                ),
+            },
+        },
+        &Method::DELETE => match router.at(req.uri().path()) {
+            Err(_) => build_resp(
+                "method not allowed".to_string(),
+                StatusCode::METHOD_NOT_ALLOWED,
+            ),
+            Ok(matched) => match matched.value.unwrap() {
+                "2" => {
+                    let id = matched.params.get("id").unwrap().to_string();
+                    delete_user(req, context, config, account_deletion_service, &id).await
                 }
                 &_ => build_resp(
                     "method not allowed".to_string(),
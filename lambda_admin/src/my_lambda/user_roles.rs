@@ -0,0 +1,51 @@
+use lambda_http::RequestPayloadExt;
+use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
+use lib_config::config::Config;
+use lib_config::result::ResultE;
+use lib_users::errors::users::{UserDynamoDBError, UserNoExistsError};
+use lib_users::models::user::UserRoles;
+use lib_users::services::users::{UserManipulation, UsersService};
+use serde::Deserialize;
+
+use super::build_resp;
+
+#[derive(Debug, Deserialize)]
+pub struct UserRolesPayload {
+    // Admin, Moderator, Support, Partner, VerifiedPress; Basic is always kept
+    pub roles: Vec<String>,
+}
+
+//#[instrument]
+pub async fn update_user_roles(
+    req: &Request,
+    _c: &Context,
+    _config: &Config,
+    user_service: &UsersService,
+    id: &String,
+) -> ResultE<Response<String>> {
+    let payload = match req.payload::<UserRolesPayload>() {
+        Err(e) => return build_resp(e.to_string(), StatusCode::BAD_REQUEST),
+        Ok(None) => return build_resp("no payload found".to_string(), StatusCode::BAD_REQUEST),
+        Ok(Some(payload)) => payload,
+    };
+    let mut roles = Vec::new();
+    for role in payload.roles.iter() {
+        match UserRoles::deserialize(role) {
+            None => return build_resp(format!("unknown role {}", role), StatusCode::BAD_REQUEST),
+            Some(role) => roles.push(role),
+        }
+    }
+
+    match user_service.update_roles(id, &roles).await {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<UserDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else if let Some(m) = e.downcast_ref::<UserNoExistsError>() {
+                build_resp(m.to_string(), StatusCode::NO_CONTENT)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+        Ok(_) => build_resp("".to_string(), StatusCode::OK),
+    }
+}
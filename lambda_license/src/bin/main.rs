@@ -5,9 +5,14 @@ use lib_config::config::Config;
 use lib_config::logs::setup_log;
 //use lib_config::traces::setup_tracing_level;
 use lib_engage::repositories::sender::SenderEmailsRepo;
+use lib_licenses::repositories::license_grants::LicenseGrantRepo;
+use lib_licenses::repositories::license_requests::LicenseRequestRepo;
+use lib_licenses::repositories::licenses::LicenseRepo;
 use lib_licenses::repositories::owners::OwnerRepo;
 use lib_licenses::repositories::shorter::ShorterRepo;
 use lib_licenses::services::assets::AssetService;
+use lib_licenses::services::license_requests::LicenseRequestService;
+use lib_licenses::services::licenses::LicenseService;
 use lib_licenses::services::owners::OwnerService;
 use lib_licenses::services::video::VideoService;
 use lib_licenses::repositories::assets::AssetRepo;
@@ -15,6 +20,7 @@ use lib_users::repositories::users::UsersRepo;
 use lib_users::services::users::UsersService;
 use lib_engage::repositories::subscription::SubscriptionRepo;
 use lib_engage::services::subscription::SubscriptionService;
+use lib_util_jwt::jwt::{TokenVerifier, AUDIENCE_LICENSE};
 
 
 #[tokio::main]
@@ -44,6 +50,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
     let subscription_repo = SubscriptionRepo::new(&config);
     let sender_repo = SenderEmailsRepo::new(&config);
     let subscription_service = SubscriptionService::new(subscription_repo, sender_repo);
+
+    let license_service = LicenseService::new(LicenseRepo::new(&config), AssetRepo::new(&config));
+
+    let license_request_service = LicenseRequestService::new(
+        LicenseRequestRepo::new(&config),
+        LicenseGrantRepo::new(&config),
+        LicenseRepo::new(&config),
+        AssetRepo::new(&config),
+        OwnerRepo::new(&config),
+    );
+    let license_sender_repo = SenderEmailsRepo::new(&config);
+
+    let verifier = TokenVerifier::new(&config, AUDIENCE_LICENSE);
     
 
     log::info!("bootstrapping dependencies: completed. Lambda ready.");
@@ -54,7 +73,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
             &owners_service,
             &user_service,
             &video_service,
+            &license_service,
             &subscription_service,
+            &license_request_service,
+            &license_sender_repo,
+            &verifier,
             event,
         )
     }))
@@ -5,49 +5,80 @@ use std::str::FromStr;
 use lambda_http::{http::Method, http::StatusCode, IntoResponse, Request, RequestExt};
 use lib_config::config::Config;
 use lib_engage::{
-    repositories::subscription::SubscriptionRepo, services::subscription::SubscriptionService,
+    repositories::{sender::SenderEmailsRepo, subscription::SubscriptionRepo},
+    services::subscription::SubscriptionService,
 };
-use lib_licenses::services::{assets::AssetService, owners::OwnerService, video::VideoService};
+use lib_licenses::services::{
+    assets::AssetService, license_requests::LicenseRequestService, licenses::LicenseService,
+    owners::OwnerService, video::VideoService,
+};
+use lib_users::models::api_key::ApiKeyScope;
+use lib_users::models::permission::Permission;
 use lib_users::services::users::UsersService;
-use lib_util_jwt::build::{build_resp, jwt_mandatory};
+use lib_util_jwt::auth::AuthOutcome;
+use lib_util_jwt::build::build_resp;
+use lib_util_jwt::jwt::TokenVerifier;
 use matchit::Router;
 use url::Url;
 use uuid::Uuid;
 
 use crate::my_lambda::{
     assets::{
+        asset_owners::{get_asset_owners, update_asset_owners},
         create_asset::create_asset,
         get_asset::{get_asset_by_id, get_asset_by_url},
         get_similar_assets::{get_similar_assets_by_id, get_similar_assets_by_url},
     },
+    licenses::license_requests::{
+        approve_license_request, create_license_request, get_asset_license_requests,
+        get_license_request, get_my_license_grants, get_my_license_requests,
+        reject_license_request,
+    },
+    licenses::get_effective_license::get_effective_license,
+    licenses::verify_license::verify_license,
     subscribe::subscribe::{confirm_subscription, create_intent, remove_subscription},
     video::async_create_my_hash::async_create_my_hash_similars_sns,
 };
 
 //#[tracing::instrument]
+#[allow(clippy::too_many_arguments)]
 pub async fn function_handler(
     config: &Config,
     asset_service: &AssetService,
     owners_service: &OwnerService,
     user_service: &UsersService,
     video_service: &VideoService,
-    //_license_service: &LicenseService,
+    license_service: &LicenseService,
     subscription_service: &SubscriptionService<SubscriptionRepo>,
+    license_request_service: &LicenseRequestService,
+    sender_repo: &SenderEmailsRepo,
+    verifier: &TokenVerifier,
     req: Request,
 ) -> Result<impl IntoResponse, Box<dyn std::error::Error + Send + Sync>> {
     log::info!("income new request");
     let context = req.lambda_context();
     let user_id;
+    let auth = AuthOutcome::extract(&req, config, verifier).await;
 
     let mut router = Router::new();
     router.insert("/api/asset", Some("1"))?;
     router.insert("/api/asset/:id", Some("2"))?;
     router.insert("/api/hash", Some("88"))?;
+    router.insert("/api/hash/reprocess", Some("89"))?;
     router.insert("/api/similar/:id", Some("99"))?;
     router.insert("/api/similar", Some("999"))?;
     router.insert("/api/subscribe", Some("1000"))?;
     router.insert("/api/subscribe/confirmation/:id", Some("1001"))?;
     router.insert("/api/subscribe/:id", Some("1002"))?;
+    router.insert("/api/license/request", Some("2000"))?;
+    router.insert("/api/license/request/:id", Some("2001"))?;
+    router.insert("/api/license/request/:id/approve", Some("2002"))?;
+    router.insert("/api/license/request/:id/reject", Some("2003"))?;
+    router.insert("/api/asset/:id/license/request", Some("2004"))?;
+    router.insert("/api/license/grant", Some("2005"))?;
+    router.insert("/api/license/verify", Some("2006"))?;
+    router.insert("/api/asset/:id/license/effective", Some("2007"))?;
+    router.insert("/api/asset/:id/owners", Some("2008"))?;
 
     let query_pairs: Vec<(String, String)> = req
         .uri()
@@ -132,6 +163,175 @@ pub async fn function_handler(
                     .await;
                 }
 
+                "2000" => {
+                    match auth.require_scope(&ApiKeyScope::LicensesRead) {
+                        Err(e) => {
+                            return Ok(e);
+                        }
+                        Ok(ctx) => user_id = ctx.user_id().clone(),
+                    };
+                    return get_my_license_requests(
+                        &req,
+                        &context,
+                        config,
+                        license_request_service,
+                        &user_id,
+                    )
+                    .await;
+                }
+
+                "2001" => {
+                    match auth.require_scope(&ApiKeyScope::LicensesRead) {
+                        Err(e) => {
+                            return Ok(e);
+                        }
+                        Ok(ctx) => user_id = ctx.user_id().clone(),
+                    };
+                    let id = matched.params.get("id").unwrap().to_string();
+                    if let Ok(request_id) = Uuid::from_str(id.as_str()) {
+                        return get_license_request(
+                            &req,
+                            &context,
+                            config,
+                            owners_service,
+                            license_request_service,
+                            &request_id,
+                            &user_id,
+                        )
+                        .await;
+                    } else {
+                        build_resp(
+                            "id param must be UUID".to_string(),
+                            StatusCode::NOT_ACCEPTABLE,
+                        )
+                    }
+                }
+
+                "2004" => {
+                    match auth.require_scope(&ApiKeyScope::LicensesRead) {
+                        Err(e) => {
+                            return Ok(e);
+                        }
+                        Ok(ctx) => user_id = ctx.user_id().clone(),
+                    };
+                    let id = matched.params.get("id").unwrap().to_string();
+                    if let Ok(asset_id) = Uuid::from_str(id.as_str()) {
+                        return get_asset_license_requests(
+                            &req,
+                            &context,
+                            config,
+                            license_request_service,
+                            &asset_id,
+                            &user_id,
+                        )
+                        .await;
+                    } else {
+                        build_resp(
+                            "id param must be UUID".to_string(),
+                            StatusCode::NOT_ACCEPTABLE,
+                        )
+                    }
+                }
+
+                "2005" => {
+                    match auth.require_scope(&ApiKeyScope::LicensesRead) {
+                        Err(e) => {
+                            return Ok(e);
+                        }
+                        Ok(ctx) => user_id = ctx.user_id().clone(),
+                    };
+                    return get_my_license_grants(
+                        &req,
+                        &context,
+                        config,
+                        license_request_service,
+                        &user_id,
+                    )
+                    .await;
+                }
+
+                "2006" => {
+                    // public, not required jwt token
+                    let asset_op = query_pairs
+                        .iter()
+                        .find(|(key, _)| key == "asset")
+                        .map(|(_, value)| value.clone());
+                    let grantee_op = query_pairs
+                        .iter()
+                        .find(|(key, _)| key == "grantee")
+                        .map(|(_, value)| value.clone());
+
+                    match (asset_op, grantee_op) {
+                        (Some(asset), Some(grantee)) => match Uuid::from_str(asset.as_str()) {
+                            Ok(asset_id) => {
+                                return verify_license(
+                                    &req,
+                                    &context,
+                                    config,
+                                    license_request_service,
+                                    &asset_id,
+                                    &grantee,
+                                )
+                                .await;
+                            }
+                            Err(_) => build_resp(
+                                "asset param must be UUID".to_string(),
+                                StatusCode::NOT_ACCEPTABLE,
+                            ),
+                        },
+                        _ => build_resp(
+                            "asset and grantee not found in query string".to_string(),
+                            StatusCode::BAD_REQUEST,
+                        ),
+                    }
+                }
+
+                "2007" => {
+                    // public, not required jwt token
+                    let id = matched.params.get("id").unwrap().to_string();
+                    if let Ok(asset_id) = Uuid::from_str(id.as_str()) {
+                        return get_effective_license(
+                            &req,
+                            &context,
+                            config,
+                            license_service,
+                            &asset_id,
+                        )
+                        .await;
+                    } else {
+                        build_resp(
+                            "id param must be UUID".to_string(),
+                            StatusCode::NOT_ACCEPTABLE,
+                        )
+                    }
+                }
+
+                "2008" => {
+                    match auth.require_scope(&ApiKeyScope::AssetsRead) {
+                        Err(e) => {
+                            return Ok(e);
+                        }
+                        Ok(ctx) => user_id = ctx.user_id().clone(),
+                    };
+                    let id = matched.params.get("id").unwrap().to_string();
+                    if let Ok(asset_id) = Uuid::from_str(id.as_str()) {
+                        return get_asset_owners(
+                            &req,
+                            &context,
+                            config,
+                            owners_service,
+                            &asset_id,
+                            &user_id,
+                        )
+                        .await;
+                    } else {
+                        build_resp(
+                            "id param must be UUID".to_string(),
+                            StatusCode::NOT_ACCEPTABLE,
+                        )
+                    }
+                }
+
                 _ => build_resp(
                     "GET method not allowed".to_string(),
                     StatusCode::METHOD_NOT_ALLOWED,
@@ -145,19 +345,42 @@ pub async fn function_handler(
             ),
             Ok(matched) => match matched.value.unwrap() {
                 "1" => {
-                    let ussrr = match jwt_mandatory(&req, config) {
-                        Err(_) => None,
-                        Ok(user) => Some(user),
+                    // anonymous uploads are fine, a bad token or key is refused
+                    let ussrr = match auth.optional_scope(&ApiKeyScope::AssetsWrite) {
+                        Err(e) => {
+                            return Ok(e);
+                        }
+                        Ok(ctx) => ctx.map(|ctx| ctx.user_id().clone()),
                     };
                     create_asset(&req, &context, config, asset_service, video_service, ussrr).await
                 }
 
                 "88" => {
-                    match jwt_mandatory(&req, config) {
+                    match auth.require_scope(&ApiKeyScope::SimilarRead) {
                         Err(e) => {
                             return Ok(e);
                         }
-                        Ok(user) => user_id = user,
+                        Ok(ctx) => user_id = ctx.user_id().clone(),
+                    };
+
+                    return async_create_my_hash_similars_sns(
+                        &req,
+                        &context,
+                        config,
+                        asset_service,
+                        video_service,
+                        &user_id,
+                    )
+                    .await;
+                }
+
+                // staff send a failed hash job through the pipeline again
+                "89" => {
+                    match auth.require_permission(&Permission::ReprocessJobs) {
+                        Err(e) => {
+                            return Ok(e);
+                        }
+                        Ok(ctx) => user_id = ctx.user_id().clone(),
                     };
 
                     return async_create_my_hash_similars_sns(
@@ -214,6 +437,100 @@ pub async fn function_handler(
                     }
                 }
 
+                "2000" => {
+                    match auth.require_scope(&ApiKeyScope::LicensesWrite) {
+                        Err(e) => {
+                            return Ok(e);
+                        }
+                        Ok(ctx) => user_id = ctx.user_id().clone(),
+                    };
+                    return create_license_request(
+                        &req,
+                        &context,
+                        config,
+                        asset_service,
+                        owners_service,
+                        user_service,
+                        license_request_service,
+                        sender_repo,
+                        &user_id,
+                    )
+                    .await;
+                }
+
+                "2002" | "2003" => {
+                    match auth.require_permission(&Permission::GrantLicenses) {
+                        Err(e) => {
+                            return Ok(e);
+                        }
+                        Ok(ctx) => user_id = ctx.user_id().clone(),
+                    };
+                    let id = matched.params.get("id").unwrap().to_string();
+                    let request_id = match Uuid::from_str(id.as_str()) {
+                        Err(_) => {
+                            return build_resp(
+                                "id param must be UUID".to_string(),
+                                StatusCode::NOT_ACCEPTABLE,
+                            )
+                        }
+                        Ok(val) => val,
+                    };
+                    if matched.value.unwrap() == "2002" {
+                        return approve_license_request(
+                            &req,
+                            &context,
+                            config,
+                            asset_service,
+                            user_service,
+                            license_request_service,
+                            sender_repo,
+                            &request_id,
+                            &user_id,
+                        )
+                        .await;
+                    } else {
+                        return reject_license_request(
+                            &req,
+                            &context,
+                            config,
+                            asset_service,
+                            user_service,
+                            license_request_service,
+                            sender_repo,
+                            &request_id,
+                            &user_id,
+                        )
+                        .await;
+                    }
+                }
+
+                "2008" => {
+                    match auth.require_scope(&ApiKeyScope::AssetsWrite) {
+                        Err(e) => {
+                            return Ok(e);
+                        }
+                        Ok(ctx) => user_id = ctx.user_id().clone(),
+                    };
+                    let id = matched.params.get("id").unwrap().to_string();
+                    if let Ok(asset_id) = Uuid::from_str(id.as_str()) {
+                        return update_asset_owners(
+                            &req,
+                            &context,
+                            config,
+                            owners_service,
+                            user_service,
+                            &asset_id,
+                            &user_id,
+                        )
+                        .await;
+                    } else {
+                        build_resp(
+                            "id param must be UUID".to_string(),
+                            StatusCode::NOT_ACCEPTABLE,
+                        )
+                    }
+                }
+
                 &_ => build_resp(
                     "POST method not allowed here".to_string(),
                     StatusCode::METHOD_NOT_ALLOWED,
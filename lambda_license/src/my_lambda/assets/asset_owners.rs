@@ -0,0 +1,110 @@
+use lambda_http::RequestPayloadExt;
+use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
+use lib_config::config::Config;
+use lib_licenses::errors::owner::{OwnerDynamoDBError, OwnerNoExistsError, OwnerShareError};
+use lib_licenses::models::owner::{UpdatableFildsCoOwners, ANONYMOUS_OWNER};
+use lib_licenses::services::owners::{OwnerManipulation, OwnerService};
+use lib_users::errors::users::{UserDynamoDBError, UserNoExistsError};
+use lib_users::services::users::{UserManipulation, UsersService};
+use serde_json::json;
+use uuid::Uuid;
+use validator::{ValidationError, ValidationErrors};
+
+use lib_util_jwt::build::{build_resp, build_resp_env, build_resp_no_cache};
+
+fn build_resp_from_error(
+    config: &Config,
+    e: Box<dyn std::error::Error + Send + Sync>,
+) -> Result<Response<String>, Box<dyn std::error::Error + Send + Sync>> {
+    if let Some(m) = e.downcast_ref::<OwnerDynamoDBError>() {
+        build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+    } else if let Some(m) = e.downcast_ref::<OwnerNoExistsError>() {
+        build_resp(m.to_string(), StatusCode::FORBIDDEN)
+    } else if let Some(m) = e.downcast_ref::<OwnerShareError>() {
+        build_resp(m.to_string(), StatusCode::BAD_REQUEST)
+    } else if let Some(m) = e.downcast_ref::<UserDynamoDBError>() {
+        build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+    } else if let Some(m) = e.downcast_ref::<ValidationErrors>() {
+        build_resp(m.to_string(), StatusCode::BAD_REQUEST)
+    } else if let Some(m) = e.downcast_ref::<ValidationError>() {
+        build_resp(m.to_string(), StatusCode::BAD_REQUEST)
+    } else {
+        build_resp_env(
+            &config.env_vars().environment().unwrap(),
+            e,
+            StatusCode::INTERNAL_SERVER_ERROR,
+        )
+    }
+}
+
+// shares are visible to the co-owners only
+pub async fn get_asset_owners(
+    _req: &Request,
+    _c: &Context,
+    config: &Config,
+    owners_service: &OwnerService,
+    asset_id: &Uuid,
+    user_id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error + Send + Sync>> {
+    if let Err(e) = owners_service.get_by_user_asset_ids(asset_id, user_id).await {
+        return build_resp_from_error(config, e);
+    }
+    match owners_service.get_all_by_asset(asset_id).await {
+        Err(e) => build_resp_from_error(config, e),
+        Ok(owners) => build_resp_no_cache(json!(owners).to_string(), StatusCode::OK),
+    }
+}
+
+// 202 while the split waits for the other controlling owners to ask for it too
+pub async fn update_asset_owners(
+    req: &Request,
+    _c: &Context,
+    config: &Config,
+    owners_service: &OwnerService,
+    user_service: &UsersService,
+    asset_id: &Uuid,
+    user_id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let co_owners_fields;
+    match req.payload::<UpdatableFildsCoOwners>() {
+        Err(e) => {
+            return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+        }
+        Ok(op_payload) => match op_payload {
+            None => {
+                return build_resp("no payload found".to_string(), StatusCode::BAD_REQUEST);
+            }
+            Some(payload) => co_owners_fields = payload.clone(),
+        },
+    }
+
+    // shares of removed accounts are kept under the anonymous owner, which has no user behind
+    for co_owner in co_owners_fields.owners.iter() {
+        if co_owner.user_id == ANONYMOUS_OWNER {
+            continue;
+        }
+        if let Err(e) = user_service.get_by_id(&co_owner.user_id).await {
+            if e.is::<UserNoExistsError>() {
+                return build_resp(
+                    format!("co-owner {} doesn't exist", co_owner.user_id),
+                    StatusCode::BAD_REQUEST,
+                );
+            }
+            return build_resp_from_error(config, e);
+        }
+    }
+
+    let op_res = owners_service
+        .set_co_owners(asset_id, user_id, &co_owners_fields)
+        .await;
+    match op_res {
+        Err(e) => build_resp_from_error(config, e),
+        Ok(owners) => {
+            if owners.iter().any(|owner| owner.pending_split().is_some()) {
+                build_resp(json!(owners).to_string(), StatusCode::ACCEPTED)
+            } else {
+                build_resp(json!(owners).to_string(), StatusCode::OK)
+            }
+        }
+    }
+}
@@ -1,3 +1,4 @@
+pub mod asset_owners;
 pub mod get_asset;
 pub mod get_my_asset;
 pub mod get_similar_assets;
@@ -4,7 +4,7 @@ use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
 use lib_config::config::Config;
 use lib_licenses::errors::asset::AssetNoExistsError;
 use lib_licenses::errors::license::LicenseDynamoDBError;
-use lib_licenses::errors::owner::OwnerNoExistsError;
+use lib_licenses::errors::owner::{OwnerNoExistsError, OwnerShareError};
 use lib_licenses::models::license::CreatableFildsLicense;
 use lib_licenses::services::licenses::{LicenseManipulation, LicenseService};
 use log::info;
@@ -46,6 +46,8 @@ pub async fn create_my_license(
                 return build_resp(m.to_string(), StatusCode::BAD_REQUEST);
             } else if let Some(m) = e.downcast_ref::<OwnerNoExistsError>() {
                 return build_resp(m.to_string(), StatusCode::BAD_REQUEST);
+            } else if let Some(m) = e.downcast_ref::<OwnerShareError>() {
+                return build_resp(m.to_string(), StatusCode::FORBIDDEN);
             } else {
                 return build_resp_env(
                     &config.env_vars().environment().unwrap(),
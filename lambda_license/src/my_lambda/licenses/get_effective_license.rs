@@ -0,0 +1,45 @@
+use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
+use lib_config::config::Config;
+use lib_licenses::errors::asset::{AssetDynamoDBError, AssetNoExistsError};
+use lib_licenses::errors::license::LicenseDynamoDBError;
+use lib_licenses::errors::owner::OwnerDynamoDBError;
+use lib_licenses::services::licenses::{LicenseManipulation, LicenseService};
+use serde_json::json;
+use uuid::Uuid;
+
+use lib_util_jwt::build::{build_resp, build_resp_env, build_resp_no_cache};
+
+//#[instrument]
+pub async fn get_effective_license(
+    _req: &Request,
+    _c: &Context,
+    config: &Config,
+    lic_service: &LicenseService,
+    asset_id: &Uuid,
+) -> Result<Response<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let op_res = lic_service.get_effective_license(asset_id).await;
+    match op_res {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<LicenseDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else if let Some(m) = e.downcast_ref::<AssetDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else if let Some(m) = e.downcast_ref::<OwnerDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else if let Some(m) = e.downcast_ref::<AssetNoExistsError>() {
+                build_resp(m.to_string(), StatusCode::NOT_FOUND)
+            } else {
+                build_resp_env(
+                    &config.env_vars().environment().unwrap(),
+                    e,
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                )
+            }
+        }
+        Ok(None) => build_resp(
+            "no active license for this asset or its ancestors".to_string(),
+            StatusCode::NOT_FOUND,
+        ),
+        Ok(Some(effective)) => build_resp_no_cache(json!(effective).to_string(), StatusCode::OK),
+    }
+}
@@ -40,7 +40,7 @@ pub async fn get_licenses(
             }
         }
         Ok(asset) => {
-            let lic_op = lic_service.get_by_asset(asset.id()).await;
+            let lic_op = lic_service.get_active_by_asset(asset.id()).await;
             match lic_op {
                 Err(e) => {
                     if let Some(e) = e.downcast_ref::<LicenseDynamoDBError>() {
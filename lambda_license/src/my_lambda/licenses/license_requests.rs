@@ -0,0 +1,326 @@
+use lambda_http::RequestPayloadExt;
+use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
+use lib_config::config::Config;
+use lib_engage::repositories::sender::SenderEmailsRepo;
+use lib_licenses::errors::asset::{AssetDynamoDBError, AssetNoExistsError};
+use lib_licenses::errors::license::{LicenseDynamoDBError, LicenseNotFoundError};
+use lib_licenses::errors::license_request::{
+    LicenseGrantPeriodError, LicenseRequestDynamoDBError, LicenseRequestNoExistsError,
+    LicenseRequestStatusError,
+};
+use lib_licenses::errors::owner::{OwnerDynamoDBError, OwnerNoExistsError, OwnerShareError};
+use lib_licenses::models::license_grant::LicenseGrant;
+use lib_licenses::models::license_request::{
+    ApproveFildsLicenseRequest, CreatableFildsLicenseRequest, LicenseRequest,
+    RejectFildsLicenseRequest,
+};
+use lib_licenses::services::assets::{AssetManipulation, AssetService};
+use lib_licenses::services::license_requests::{
+    LicenseRequestManipulation, LicenseRequestService,
+};
+use lib_licenses::services::owners::{OwnerManipulation, OwnerService};
+use lib_users::services::users::{UserManipulation, UsersService};
+use serde_json::json;
+use uuid::Uuid;
+use validator::{ValidationError, ValidationErrors};
+
+use lib_util_jwt::build::{build_resp, build_resp_env, build_resp_no_cache};
+
+fn build_resp_from_error(
+    config: &Config,
+    e: Box<dyn std::error::Error + Send + Sync>,
+) -> Result<Response<String>, Box<dyn std::error::Error + Send + Sync>> {
+    if let Some(m) = e.downcast_ref::<LicenseRequestDynamoDBError>() {
+        return build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE);
+    } else if let Some(m) = e.downcast_ref::<LicenseDynamoDBError>() {
+        return build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE);
+    } else if let Some(m) = e.downcast_ref::<AssetDynamoDBError>() {
+        return build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE);
+    } else if let Some(m) = e.downcast_ref::<OwnerDynamoDBError>() {
+        return build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE);
+    } else if let Some(m) = e.downcast_ref::<LicenseRequestNoExistsError>() {
+        return build_resp(m.to_string(), StatusCode::NOT_FOUND);
+    } else if let Some(m) = e.downcast_ref::<LicenseNotFoundError>() {
+        return build_resp(m.to_string(), StatusCode::NOT_FOUND);
+    } else if let Some(m) = e.downcast_ref::<AssetNoExistsError>() {
+        return build_resp(m.to_string(), StatusCode::NOT_FOUND);
+    } else if let Some(m) = e.downcast_ref::<OwnerNoExistsError>() {
+        return build_resp(m.to_string(), StatusCode::FORBIDDEN);
+    } else if let Some(m) = e.downcast_ref::<OwnerShareError>() {
+        return build_resp(m.to_string(), StatusCode::FORBIDDEN);
+    } else if let Some(m) = e.downcast_ref::<LicenseRequestStatusError>() {
+        return build_resp(m.to_string(), StatusCode::CONFLICT);
+    } else if let Some(m) = e.downcast_ref::<LicenseGrantPeriodError>() {
+        return build_resp(m.to_string(), StatusCode::BAD_REQUEST);
+    } else if let Some(m) = e.downcast_ref::<ValidationErrors>() {
+        return build_resp(m.to_string(), StatusCode::BAD_REQUEST);
+    } else if let Some(m) = e.downcast_ref::<ValidationError>() {
+        return build_resp(m.to_string(), StatusCode::BAD_REQUEST);
+    } else {
+        return build_resp_env(
+            &config.env_vars().environment().unwrap(),
+            e,
+            StatusCode::INTERNAL_SERVER_ERROR,
+        );
+    }
+}
+
+// emails are best effort: a failure here must not undo the request workflow
+async fn notify_owner(
+    asset_service: &AssetService,
+    owners_service: &OwnerService,
+    user_service: &UsersService,
+    sender: &SenderEmailsRepo,
+    request: &LicenseRequest,
+) {
+    let owners = match owners_service.get_all_by_asset(request.asset_id()).await {
+        Err(e) => {
+            log::error!("owners not found for license request {}: {}", request.id(), e);
+            return;
+        }
+        Ok(owners) => owners,
+    };
+    let asset = match asset_service.get_by_id(request.asset_id()).await {
+        Err(e) => {
+            log::error!("asset {} not found: {}", request.asset_id(), e);
+            return;
+        }
+        Ok(asset) => asset,
+    };
+    // only the ones who can review the request are told about it
+    for owner in owners.iter().filter(|owner| owner.is_controlling()) {
+        let user = match user_service.get_by_id(owner.user_id()).await {
+            Err(e) => {
+                log::error!("user {} not found: {}", owner.user_id(), e);
+                continue;
+            }
+            Ok(user) => user,
+        };
+        if user.email().is_none() {
+            log::info!("owner {} has no email, skipping notification", user.user_id());
+            continue;
+        }
+        if let Err(e) = sender
+            .send_license_request_received(user, asset.clone(), request)
+            .await
+        {
+            log::error!("license request email couldn't be sent: {}", e);
+        }
+    }
+}
+
+async fn notify_requester(
+    asset_service: &AssetService,
+    user_service: &UsersService,
+    sender: &SenderEmailsRepo,
+    request: &LicenseRequest,
+    grant: Option<&LicenseGrant>,
+) {
+    let user = match user_service.get_by_id(request.requester_id()).await {
+        Err(e) => {
+            log::error!("user {} not found: {}", request.requester_id(), e);
+            return;
+        }
+        Ok(user) => user,
+    };
+    if user.email().is_none() {
+        log::info!("requester {} has no email, skipping notification", user.user_id());
+        return;
+    }
+    let asset = match asset_service.get_by_id(request.asset_id()).await {
+        Err(e) => {
+            log::error!("asset {} not found: {}", request.asset_id(), e);
+            return;
+        }
+        Ok(asset) => asset,
+    };
+    if let Err(e) = sender
+        .send_license_request_resolved(user, asset, request, grant)
+        .await
+    {
+        log::error!("license request resolution email couldn't be sent: {}", e);
+    }
+}
+
+//#[instrument]
+#[allow(clippy::too_many_arguments)]
+pub async fn create_license_request(
+    req: &Request,
+    _c: &Context,
+    config: &Config,
+    asset_service: &AssetService,
+    owners_service: &OwnerService,
+    user_service: &UsersService,
+    license_request_service: &LicenseRequestService,
+    sender: &SenderEmailsRepo,
+    user_id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let request_fields;
+    match req.payload::<CreatableFildsLicenseRequest>() {
+        Err(e) => {
+            return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+        }
+        Ok(op_payload) => match op_payload {
+            None => {
+                return build_resp("no payload found".to_string(), StatusCode::BAD_REQUEST);
+            }
+            Some(payload) => request_fields = payload.clone(),
+        },
+    }
+
+    let op_res = license_request_service
+        .request(&request_fields, user_id)
+        .await;
+    match op_res {
+        Err(e) => build_resp_from_error(config, e),
+        Ok(request) => {
+            notify_owner(
+                asset_service,
+                owners_service,
+                user_service,
+                sender,
+                &request,
+            )
+            .await;
+            build_resp(json!(request).to_string(), StatusCode::OK)
+        }
+    }
+}
+
+pub async fn get_my_license_requests(
+    _req: &Request,
+    _c: &Context,
+    config: &Config,
+    license_request_service: &LicenseRequestService,
+    user_id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let op_res = license_request_service.get_by_requester(user_id).await;
+    match op_res {
+        Err(e) => build_resp_from_error(config, e),
+        Ok(requests) => build_resp_no_cache(json!(requests).to_string(), StatusCode::OK),
+    }
+}
+
+pub async fn get_license_request(
+    _req: &Request,
+    _c: &Context,
+    config: &Config,
+    owners_service: &OwnerService,
+    license_request_service: &LicenseRequestService,
+    request_id: &Uuid,
+    user_id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let op_res = license_request_service.get_by_id(request_id).await;
+    match op_res {
+        Err(e) => build_resp_from_error(config, e),
+        Ok(request) => {
+            // visible only to whoever asked for it and to the asset's owner
+            if request.requester_id() != user_id {
+                if let Err(e) = owners_service
+                    .get_by_user_asset_ids(request.asset_id(), user_id)
+                    .await
+                {
+                    return build_resp_from_error(config, e);
+                }
+            }
+            build_resp_no_cache(json!(request).to_string(), StatusCode::OK)
+        }
+    }
+}
+
+pub async fn get_asset_license_requests(
+    _req: &Request,
+    _c: &Context,
+    config: &Config,
+    license_request_service: &LicenseRequestService,
+    asset_id: &Uuid,
+    user_id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let op_res = license_request_service.get_by_asset(asset_id, user_id).await;
+    match op_res {
+        Err(e) => build_resp_from_error(config, e),
+        Ok(requests) => build_resp_no_cache(json!(requests).to_string(), StatusCode::OK),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn approve_license_request(
+    req: &Request,
+    _c: &Context,
+    config: &Config,
+    asset_service: &AssetService,
+    user_service: &UsersService,
+    license_request_service: &LicenseRequestService,
+    sender: &SenderEmailsRepo,
+    request_id: &Uuid,
+    user_id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let approve_fields;
+    match req.payload::<ApproveFildsLicenseRequest>() {
+        Err(e) => {
+            return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+        }
+        Ok(op_payload) => match op_payload {
+            None => {
+                return build_resp("no payload found".to_string(), StatusCode::BAD_REQUEST);
+            }
+            Some(payload) => approve_fields = payload.clone(),
+        },
+    }
+
+    let op_res = license_request_service
+        .approve(request_id, user_id, &approve_fields)
+        .await;
+    match op_res {
+        Err(e) => build_resp_from_error(config, e),
+        Ok((request, grant)) => {
+            notify_requester(asset_service, user_service, sender, &request, Some(&grant)).await;
+            build_resp(json!(grant).to_string(), StatusCode::OK)
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn reject_license_request(
+    req: &Request,
+    _c: &Context,
+    config: &Config,
+    asset_service: &AssetService,
+    user_service: &UsersService,
+    license_request_service: &LicenseRequestService,
+    sender: &SenderEmailsRepo,
+    request_id: &Uuid,
+    user_id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let reject_fields = match req.payload::<RejectFildsLicenseRequest>() {
+        Err(e) => {
+            return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+        }
+        Ok(op_payload) => op_payload.unwrap_or(RejectFildsLicenseRequest { comment: None }),
+    };
+
+    let op_res = license_request_service
+        .reject(request_id, user_id, &reject_fields)
+        .await;
+    match op_res {
+        Err(e) => build_resp_from_error(config, e),
+        Ok(request) => {
+            notify_requester(asset_service, user_service, sender, &request, None).await;
+            build_resp(json!(request).to_string(), StatusCode::OK)
+        }
+    }
+}
+
+pub async fn get_my_license_grants(
+    _req: &Request,
+    _c: &Context,
+    config: &Config,
+    license_request_service: &LicenseRequestService,
+    user_id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let op_res = license_request_service.get_grants_by_grantee(user_id).await;
+    match op_res {
+        Err(e) => build_resp_from_error(config, e),
+        Ok(grants) => build_resp_no_cache(json!(grants).to_string(), StatusCode::OK),
+    }
+}
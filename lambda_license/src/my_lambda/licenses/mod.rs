@@ -1,3 +1,6 @@
 pub mod create_my_license;
+pub mod get_effective_license;
 pub mod get_licenses;
 pub mod get_my_license;
+pub mod license_requests;
+pub mod verify_license;
@@ -0,0 +1,37 @@
+use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
+use lib_config::config::Config;
+use lib_licenses::errors::license::LicenseDynamoDBError;
+use lib_licenses::errors::license_request::LicenseRequestDynamoDBError;
+use lib_licenses::services::license_requests::{LicenseRequestManipulation, LicenseRequestService};
+use serde_json::json;
+use uuid::Uuid;
+
+use lib_util_jwt::build::{build_resp, build_resp_env, build_resp_no_cache};
+
+//#[instrument]
+pub async fn verify_license(
+    _req: &Request,
+    _c: &Context,
+    config: &Config,
+    license_request_service: &LicenseRequestService,
+    asset_id: &Uuid,
+    grantee_id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let op_res = license_request_service.verify(asset_id, grantee_id).await;
+    match op_res {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<LicenseRequestDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else if let Some(m) = e.downcast_ref::<LicenseDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp_env(
+                    &config.env_vars().environment().unwrap(),
+                    e,
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                )
+            }
+        }
+        Ok(verification) => build_resp_no_cache(json!(verification).to_string(), StatusCode::OK),
+    }
+}
@@ -0,0 +1,24 @@
+use lambda_license_expiry::function_handler;
+use lambda_runtime::{run, service_fn, Error};
+
+use lib_config::{config::Config, logs::setup_log};
+use lib_licenses::{
+    repositories::{assets::AssetRepo, licenses::LicenseRepo},
+    services::licenses::LicenseService,
+};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    setup_log();
+
+    let mut config = Config::new();
+    config.setup_with_secrets().await;
+
+    log::info!("bootstrapping dependencies...");
+
+    let license_repo = LicenseRepo::new(&config);
+    let asset_repo = AssetRepo::new(&config);
+    let license_service = LicenseService::new(license_repo, asset_repo);
+
+    run(service_fn(|e| function_handler(e, &license_service))).await
+}
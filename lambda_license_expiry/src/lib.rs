@@ -0,0 +1,23 @@
+use aws_lambda_events::cloudwatch_events::CloudWatchEvent;
+use chrono::Utc;
+use lambda_runtime::LambdaEvent;
+use lib_licenses::services::licenses::{LicenseManipulation, LicenseService};
+use serde_json::Value;
+
+pub async fn function_handler(
+    _: LambdaEvent<CloudWatchEvent<Value>>,
+    license_service: &LicenseService,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let expired = license_service.expire_licenses(&Utc::now()).await?;
+
+    for license in expired.iter() {
+        log::info!(
+            "license {} of asset {} expired",
+            license.id(),
+            license.asset_id()
+        );
+    }
+    log::info!("{} licenses expired", expired.len());
+
+    Ok(())
+}
@@ -2,9 +2,26 @@
 use lambda_http::service_fn;
 use lib_config::{config::Config, //traces::setup_tracing_level, 
     logs::setup_log};
+use lib_engage::repositories::sender::SenderEmailsRepo;
+use lib_users::repositories::devices::DevicesRepo;
+use lib_users::repositories::identities::IdentitiesRepo;
+use lib_users::repositories::login_attempts::LoginAttemptsRepo;
+use lib_users::repositories::mfa::MfaRepo;
+use lib_users::repositories::one_time_tokens::OneTimeTokensRepo;
+use lib_users::repositories::sessions::SessionsRepo;
 use lib_users::repositories::users::UsersRepo;
+use lib_users::services::devices::DeviceService;
+use lib_users::services::login_attempts::LoginAttemptService;
+use lib_users::services::mfa::MfaService;
+use lib_users::services::oidc_login::OidcLoginService;
+use lib_users::services::one_time_tokens::OneTimeTokenService;
+use lib_users::services::sessions::SessionService;
 use lib_users::services::users::UsersService;
+use lib_users::services::wallet_login::{WalletLoginService, SIWE_CHAIN_ID, SIWE_DOMAIN, SIWE_URI};
 use lib_util_jwt::error::ApiLambdaError;
+use lib_util_jwt::jwt::{TokenVerifier, AUDIENCE_LOGIN, AUDIENCE_MFA};
+use lib_util_jwt::keys::SignerCache;
+use lib_util_jwt::oidc::load_oidc_providers;
 use my_lambda::function_handler;
 
 mod my_lambda;
@@ -24,9 +41,58 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let user_repo = UsersRepo::new(&config);
     let user_service = UsersService::new(user_repo);
 
+    let session_repo = SessionsRepo::new(&config);
+    let session_service = SessionService::new(session_repo.clone());
+    let oidc_service = OidcLoginService::new(
+        IdentitiesRepo::new(&config),
+        session_repo.clone(),
+        load_oidc_providers(&config).await,
+    );
+    let wallet_service =
+        WalletLoginService::new(session_repo, SIWE_DOMAIN, SIWE_URI, SIWE_CHAIN_ID);
+
+    let device_repo = DevicesRepo::new(&config);
+    let device_service = DeviceService::new(device_repo);
+
+    let login_attempt_repo = LoginAttemptsRepo::new(&config);
+    let login_attempt_service = LoginAttemptService::new(login_attempt_repo);
+
+    let mfa_repo = MfaRepo::new(&config);
+    let mfa_service = MfaService::new(mfa_repo);
+
+    let one_time_token_repo = OneTimeTokensRepo::new(&config);
+    let one_time_token_service = OneTimeTokenService::new(one_time_token_repo, &config);
+
+    let sender_repo = SenderEmailsRepo::new(&config);
+
+    let verifier = TokenVerifier::new(&config, AUDIENCE_LOGIN);
+    let mfa_verifier = TokenVerifier::new(&config, AUDIENCE_MFA);
+    let signers = match SignerCache::load(&config).await {
+        Ok(signers) => signers,
+        Err(e) => {
+            log::error!("{}", e);
+            return Err(ApiLambdaError { 0: e.to_string() }.into());
+        }
+    };
+
     log::info!("lambda ready, awaiting for events.");
     let resp = lambda_http::run(service_fn(|event| {
-        function_handler(&config, &user_service, event)
+        function_handler(
+            &config,
+            &user_service,
+            &session_service,
+            &one_time_token_service,
+            &wallet_service,
+            &oidc_service,
+            &device_service,
+            &login_attempt_service,
+            &mfa_service,
+            &sender_repo,
+            &verifier,
+            &mfa_verifier,
+            &signers,
+            event,
+        )
     }))
     .await;
 
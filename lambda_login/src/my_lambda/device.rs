@@ -0,0 +1,197 @@
+use crate::my_lambda::build_resp;
+use crate::my_lambda::session::start_session;
+use lambda_http::RequestPayloadExt;
+use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
+use lib_config::config::Config;
+use lib_users::errors::devices::{
+    DeviceAlreadyExistsError, DeviceDynamoDBError, DeviceKeyError, DeviceSignatureError,
+};
+use lib_users::errors::users::{
+    UserDynamoDBError, UserNoExistsError, UserNotVerifiedError, UserStatusError,
+};
+use lib_users::services::devices::{DeviceManipulation, DeviceService};
+use lib_users::services::login::LoginOps;
+use lib_users::services::mfa::MfaService;
+use lib_users::services::sessions::SessionService;
+use lib_users::services::users::{UserManipulation, UsersService};
+use lib_util_jwt::keys::JwtSigner;
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct DeviceChallengePayload {
+    #[validate(length(min = 1, max = 200))]
+    pub device: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct DevicePairPayload {
+    #[validate(length(min = 1, max = 200))]
+    pub device: String,
+    #[validate(length(min = 1, max = 20))]
+    pub algorithm: String,
+    // base64 DER public key, the private one never leaves the device
+    #[validate(length(min = 1, max = 200))]
+    pub public_key: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct DeviceLoginPayload {
+    #[validate(length(min = 1, max = 200))]
+    pub device: String,
+    #[validate(length(min = 1, max = 100))]
+    pub challenge: String,
+    // base64 signature over the challenge message, made with the device private key
+    #[validate(length(min = 1, max = 200))]
+    pub signature: String,
+    #[serde(default)]
+    pub admin: bool,
+}
+
+pub async fn device_challenge(
+    req: &Request,
+    _c: &Context,
+    _config: &Config,
+    device_service: &DeviceService,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let payload = match req.payload::<DeviceChallengePayload>() {
+        Err(e) => return build_resp(e.to_string(), StatusCode::BAD_REQUEST),
+        Ok(None) => {
+            return build_resp("device field is mandatory".to_string(), StatusCode::BAD_REQUEST)
+        }
+        Ok(Some(payload)) => payload,
+    };
+    if let Err(e) = payload.validate() {
+        return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+    }
+
+    match device_service.challenge(&payload.device).await {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<DeviceDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+        Ok(challenge) => build_resp(json!({ "challenge": challenge }).to_string(), StatusCode::OK),
+    }
+}
+
+// one time pairing for device-only accounts created before devices had keys
+pub async fn device_pair(
+    req: &Request,
+    _c: &Context,
+    _config: &Config,
+    user_service: &UsersService,
+    device_service: &DeviceService,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let payload = match req.payload::<DevicePairPayload>() {
+        Err(e) => return build_resp(e.to_string(), StatusCode::BAD_REQUEST),
+        Ok(None) => {
+            return build_resp(
+                "device, algorithm and public_key fields are mandatory".to_string(),
+                StatusCode::BAD_REQUEST,
+            )
+        }
+        Ok(Some(payload)) => payload,
+    };
+    if let Err(e) = payload.validate() {
+        return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+    }
+
+    let user = match user_service.get_by_device(&payload.device).await {
+        Err(e) => {
+            return if let Some(m) = e.downcast_ref::<UserNoExistsError>() {
+                build_resp(m.to_string(), StatusCode::NOT_FOUND)
+            } else if let Some(m) = e.downcast_ref::<UserDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            };
+        }
+        Ok(user) => user,
+    };
+
+    match device_service
+        .pair_legacy(
+            user.user_id(),
+            &payload.device,
+            &payload.algorithm,
+            &payload.public_key,
+        )
+        .await
+    {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<DeviceKeyError>() {
+                build_resp(m.to_string(), StatusCode::BAD_REQUEST)
+            } else if let Some(m) = e.downcast_ref::<DeviceAlreadyExistsError>() {
+                build_resp(m.to_string(), StatusCode::CONFLICT)
+            } else if let Some(m) = e.downcast_ref::<DeviceDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+        Ok(_) => build_resp("".to_string(), StatusCode::CREATED),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn device_login(
+    req: &Request,
+    _c: &Context,
+    config: &Config,
+    user_service: &UsersService,
+    session_service: &SessionService,
+    mfa_service: &MfaService,
+    device_service: &DeviceService,
+    signer: &JwtSigner,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let payload = match req.payload::<DeviceLoginPayload>() {
+        Err(e) => return build_resp(e.to_string(), StatusCode::BAD_REQUEST),
+        Ok(None) => {
+            return build_resp(
+                "device, challenge and signature fields are mandatory".to_string(),
+                StatusCode::BAD_REQUEST,
+            )
+        }
+        Ok(Some(payload)) => payload,
+    };
+    if let Err(e) = payload.validate() {
+        return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+    }
+
+    let device = match device_service
+        .verify(&payload.device, &payload.challenge, &payload.signature)
+        .await
+    {
+        Err(e) => {
+            return if let Some(m) = e.downcast_ref::<DeviceSignatureError>() {
+                build_resp(m.to_string(), StatusCode::UNAUTHORIZED)
+            } else if let Some(m) = e.downcast_ref::<DeviceDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            };
+        }
+        Ok(device) => device,
+    };
+
+    match user_service.login(&Some(device), &None, &None, &None).await {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<UserDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else if let Some(m) = e.downcast_ref::<UserNoExistsError>() {
+                build_resp(m.to_string(), StatusCode::NOT_FOUND)
+            } else if let Some(m) = e.downcast_ref::<UserStatusError>() {
+                build_resp(m.to_string(), StatusCode::FORBIDDEN)
+            } else if let Some(m) = e.downcast_ref::<UserNotVerifiedError>() {
+                build_resp(m.to_string(), StatusCode::FORBIDDEN)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+        Ok(log_inf) => start_session(config, session_service, mfa_service, signer, &log_inf, payload.admin).await,
+    }
+}
@@ -0,0 +1,26 @@
+use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
+use lib_config::config::Config;
+use lib_users::services::jwt_keys::JwtKeyManipulation;
+use lib_util_jwt::jwt::TokenVerifier;
+use lib_util_jwt::keys::jwk_set;
+use serde_json::json;
+
+use crate::my_lambda::build_resp;
+
+// public keys for anyone who wants to verify our tokens without asking us
+pub async fn get_jwks(
+    _req: &Request,
+    _c: &Context,
+    _config: &Config,
+    verifier: &TokenVerifier,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    match verifier.keys().get_verification_keys().await {
+        Err(e) => build_resp(e.to_string(), StatusCode::SERVICE_UNAVAILABLE),
+        Ok(keys) => {
+            let mut resp = build_resp(json!(jwk_set(&keys)).to_string(), StatusCode::OK)?;
+            resp.headers_mut()
+                .insert("cache-control", "max-age=300".parse().unwrap());
+            Ok(resp)
+        }
+    }
+}
@@ -1,14 +1,26 @@
 use lambda_http::RequestPayloadExt;
 use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
 use lib_config::config::Config;
-use lib_users::errors::users::{UserDynamoDBError, UserNoExistsError, UserStatusError};
+use lib_engage::repositories::sender::SenderEmailsRepo;
+use lib_users::errors::login_attempts::{
+    LoginAttemptDynamoDBError, LoginThrottledError, UserLockedError,
+};
+use lib_users::errors::users::{
+    UserDynamoDBError, UserNoExistsError, UserNotVerifiedError, UserStatusError,
+};
 use lib_users::services::login::LoginOps;
+use lib_users::services::login_attempts::{LoginAttemptManipulation, LoginAttemptService};
+use lib_users::services::one_time_tokens::OneTimeTokenService;
+use lib_users::services::mfa::{MfaManipulation, MfaService};
+use lib_users::services::sessions::SessionService;
 use lib_users::services::users::UsersService;
-use lib_util_jwt::jwt::create_jwt;
+use lib_util_jwt::auth::source_ip;
+use lib_util_jwt::keys::JwtSigner;
 use serde::Deserialize;
-use serde_json::json;
 
 use crate::my_lambda::build_resp;
+use crate::my_lambda::session::start_session;
+use crate::my_lambda::unlock::register_failed_login;
 use validator::{Validate, ValidationError};
 
 #[derive(Debug, Deserialize, Validate)]
@@ -19,54 +31,88 @@ pub struct LoginPayload {
     #[serde(default)]
     #[validate(length(min = 8, max = 50))]
     pub password: Option<String>,
+    // asks for a token for the admin api instead of the regular one
     #[serde(default)]
-    pub device: Option<String>,
+    pub admin: bool,
 }
 
 //#[tracing::instrument(skip(config), level )]
+#[allow(clippy::too_many_arguments)]
 pub async fn login(
     _req: &Request,
     _c: &Context,
     config: &Config,
     user_service: &UsersService,
+    session_service: &SessionService,
+    mfa_service: &MfaService,
+    login_attempt_service: &LoginAttemptService,
+    one_time_token_service: &OneTimeTokenService,
+    sender_repo: &SenderEmailsRepo,
+    signer: &JwtSigner,
 ) -> Result<Response<String>, Box<dyn std::error::Error>> {
     //let method_name = event.into_parts().0;
     let args = _req.payload::<LoginPayload>();
 
     match args{
-         Err(_) => build_resp("no correct payload attached to the request: either username and password, or device, are mandatories".to_string(), StatusCode::BAD_REQUEST ),
+         Err(_) => build_resp("no correct payload attached to the request: username and password are mandatories".to_string(), StatusCode::BAD_REQUEST ),
          Ok(user_pass) => {
              match user_pass {
-                 None => build_resp("either email/password or device fields are empty".to_string(), StatusCode::BAD_REQUEST),
+                 None => build_resp("email/password fields are empty".to_string(), StatusCode::BAD_REQUEST),
                  Some(payload) => {
                     match payload.validate(){
                         Err(e) => { return build_resp(e.to_string(), StatusCode::BAD_REQUEST); }
                         Ok(_) => {}
                     }
-                    let result = user_service.login(&payload.device, &None, &payload.email, &payload.password).await;
+                    let ip = source_ip(_req);
+                    if let Some(eml) = &payload.email {
+                        if let Err(e) = login_attempt_service.check(eml, &ip).await {
+                            return if let Some(m) = e.downcast_ref::<UserLockedError>() {
+                                build_resp(m.to_string(), StatusCode::FORBIDDEN)
+                            } else if let Some(m) = e.downcast_ref::<LoginThrottledError>() {
+                                build_resp(m.to_string(), StatusCode::TOO_MANY_REQUESTS)
+                            } else if let Some(m) = e.downcast_ref::<LoginAttemptDynamoDBError>() {
+                                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+                            } else {
+                                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+                            };
+                        }
+                    }
+                    let result = user_service.login(&None, &None, &payload.email, &payload.password).await;
                     match result {
                         Err(e) => {
                             if let Some(_) = e.downcast_ref::<UserDynamoDBError>() {
                                 build_resp(e.to_string(), StatusCode::SERVICE_UNAVAILABLE)
                             } else if let Some(e) = e.downcast_ref::<UserNoExistsError>() {
+                                if let Some(eml) = &payload.email {
+                                    if let Err(err) = register_failed_login(eml, &ip, user_service, login_attempt_service, one_time_token_service, sender_repo).await {
+                                        log::error!("failed login not registered for {}: {}", eml, err);
+                                    }
+                                }
                                 build_resp(e.to_string(), StatusCode::NOT_FOUND)
                             } else if let Some(m) = e.downcast_ref::<ValidationError>() {
                                 build_resp(m.to_string(), StatusCode::BAD_REQUEST)
                             }else if let Some(m) = e.downcast_ref::<UserStatusError>() {
                                 build_resp(m.to_string(), StatusCode::FORBIDDEN)
+                            }else if let Some(m) = e.downcast_ref::<UserNotVerifiedError>() {
+                                build_resp(m.to_string(), StatusCode::FORBIDDEN)
                             }else {
                                 build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
                             }
                         }
                         Ok(log_inf) => {
-                            let roles: Vec<String> = log_inf.roles.into_iter().map(|ur| ur.to_string()).collect();
-                            let token_secret = config.env_vars().jwt_token_base().unwrap();
-                            let exp_hours =  config.env_vars().jwt_token_time_exp_hours().unwrap().parse::<i64>().unwrap();
-                            let token_creation_ops = create_jwt(&log_inf.user_id, roles, &token_secret, exp_hours );
-                            match token_creation_ops {
-                                Err(_) => build_resp("".to_string(), StatusCode::INTERNAL_SERVER_ERROR),
-                                Ok(token) => build_resp (  json!({ "token": token }).to_string()  ,StatusCode::OK)
+                            // with mfa the counter waits until the code is right as well
+                            if let Some(eml) = &payload.email {
+                                match mfa_service.is_enabled(&log_inf.user_id).await {
+                                    Ok(true) => {}
+                                    Ok(false) => {
+                                        if let Err(e) = login_attempt_service.success(eml).await {
+                                            log::error!("{}", e);
+                                        }
+                                    }
+                                    Err(e) => log::error!("{}", e),
+                                }
                             }
+                            start_session(config, session_service, mfa_service, signer, &log_inf, payload.admin).await
                         }
                     }
                  }
@@ -0,0 +1,131 @@
+use crate::my_lambda::build_resp;
+use crate::my_lambda::session::open_session;
+use lambda_http::RequestPayloadExt;
+use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
+use lib_config::config::Config;
+use lib_users::errors::login_attempts::{
+    LoginAttemptDynamoDBError, LoginThrottledError, UserLockedError,
+};
+use lib_users::errors::mfa::{MfaCodeError, MfaDynamoDBError, MfaNotEnabledError};
+use lib_users::errors::users::{UserDynamoDBError, UserNoExistsError};
+use lib_users::services::login::LoginInfo;
+use lib_users::services::login_attempts::{LoginAttemptManipulation, LoginAttemptService};
+use lib_users::services::mfa::{MfaManipulation, MfaService};
+use lib_users::services::sessions::{SessionManipulation, SessionService};
+use lib_users::services::users::{UserManipulation, UsersService};
+use lib_util_jwt::auth::source_ip;
+use lib_util_jwt::jwt::{check_jwt_token, TokenVerifier, BEARER};
+use lib_util_jwt::keys::JwtSigner;
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct MfaLoginPayload {
+    #[validate(length(min = 1, max = 2000))]
+    pub mfa_token: String,
+    // the totp of the authenticator app or a recovery code
+    #[validate(length(min = 6, max = 20))]
+    pub code: String,
+    #[serde(default)]
+    pub admin: bool,
+}
+
+// second step of a login: the challenge from the first step plus a code
+#[allow(clippy::too_many_arguments)]
+pub async fn mfa_login(
+    req: &Request,
+    _c: &Context,
+    config: &Config,
+    user_service: &UsersService,
+    session_service: &SessionService,
+    mfa_service: &MfaService,
+    login_attempt_service: &LoginAttemptService,
+    mfa_verifier: &TokenVerifier,
+    signer: &JwtSigner,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let payload = match req.payload::<MfaLoginPayload>() {
+        Err(e) => return build_resp(e.to_string(), StatusCode::BAD_REQUEST),
+        Ok(None) => {
+            return build_resp("mfa_token and code fields are mandatory".to_string(), StatusCode::BAD_REQUEST)
+        }
+        Ok(Some(payload)) => payload,
+    };
+    if let Err(e) = payload.validate() {
+        return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+    }
+
+    let claims = match check_jwt_token(&format!("{}{}", BEARER, payload.mfa_token), mfa_verifier).await {
+        Err(e) => return build_resp(e.to_string(), StatusCode::UNAUTHORIZED),
+        Ok(claims) => claims,
+    };
+    // a new challenge per guess isn't enough, the password step doesn't count codes
+    let ip = source_ip(req);
+    if let Err(e) = login_attempt_service.check_mfa(&claims.uid, &ip).await {
+        return if let Some(m) = e.downcast_ref::<UserLockedError>() {
+            build_resp(m.to_string(), StatusCode::FORBIDDEN)
+        } else if let Some(m) = e.downcast_ref::<LoginThrottledError>() {
+            build_resp(m.to_string(), StatusCode::TOO_MANY_REQUESTS)
+        } else if let Some(m) = e.downcast_ref::<LoginAttemptDynamoDBError>() {
+            build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+        } else {
+            build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+        };
+    }
+
+    // one code per challenge: a wrong guess sends the user back to the first step
+    let expires_at = chrono::DateTime::from_timestamp(claims.exp as i64, 0).unwrap();
+    if let Err(e) = session_service
+        .logout(&claims.uid, &None, &claims.jti, &expires_at)
+        .await
+    {
+        return build_resp(e.to_string(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    if let Err(e) = mfa_service.verify(&claims.uid, &payload.code).await {
+        return if let Some(m) = e.downcast_ref::<MfaCodeError>() {
+            if let Err(err) = login_attempt_service.mfa_failure(&claims.uid, &ip).await {
+                log::error!("wrong mfa code not registered for {}: {}", claims.uid, err);
+            }
+            build_resp(m.to_string(), StatusCode::UNAUTHORIZED)
+        } else if let Some(m) = e.downcast_ref::<MfaNotEnabledError>() {
+            build_resp(m.to_string(), StatusCode::UNAUTHORIZED)
+        } else if let Some(m) = e.downcast_ref::<MfaDynamoDBError>() {
+            build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+        } else {
+            build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+        };
+    }
+
+    // roles and status are read again, they might have changed since the first step
+    let user = match user_service.get_by_id(&claims.uid).await {
+        Err(e) => {
+            return if let Some(m) = e.downcast_ref::<UserNoExistsError>() {
+                build_resp(m.to_string(), StatusCode::UNAUTHORIZED)
+            } else if let Some(m) = e.downcast_ref::<UserDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            };
+        }
+        Ok(user) => user,
+    };
+    if user.status().is_disabled() {
+        return build_resp("user has been disabled".to_string(), StatusCode::FORBIDDEN);
+    }
+
+    // both factors passed, the counters of the password step can go as well
+    if let Err(e) = login_attempt_service.mfa_success(&claims.uid).await {
+        log::error!("{}", e);
+    }
+    if let Some(email) = user.email() {
+        if let Err(e) = login_attempt_service.success(email).await {
+            log::error!("{}", e);
+        }
+    }
+
+    let log_inf = LoginInfo {
+        user_id: user.user_id().clone(),
+        roles: user.roles().clone(),
+    };
+    open_session(config, session_service, signer, &log_inf, payload.admin).await
+}
@@ -1,5 +1,14 @@
+mod device;
+mod jwks;
 mod login;
+mod mfa;
+mod oidc;
+mod password;
+mod session;
 mod signup;
+mod unlock;
+mod verify;
+mod wallet;
 
 use self::signup::create_basic_user;
 use lambda_http::{
@@ -7,17 +16,53 @@ use lambda_http::{
     Response,
 };
 use lib_config::{config::Config, stage::remove_stage_prefix};
+use lib_engage::repositories::sender::SenderEmailsRepo;
+use lib_users::services::devices::DeviceService;
+use lib_users::services::login_attempts::LoginAttemptService;
+use lib_users::services::mfa::MfaService;
+use lib_users::services::oidc_login::OidcLoginService;
+use lib_users::services::one_time_tokens::OneTimeTokenService;
+use lib_users::services::sessions::SessionService;
 use lib_users::services::users::UsersService;
+use lib_users::services::wallet_login::WalletLoginService;
 use lib_util_jwt::{error::ApiLambdaError, build::not_allowed};
+use lib_util_jwt::{jwt::TokenVerifier, keys::SignerCache};
+use device::{device_challenge, device_login, device_pair};
+use jwks::get_jwks;
 use login::login;
+use mfa::mfa_login;
+use oidc::{get_oidc_nonce, oidc_login};
+use password::{forgot_password, reset_password};
+use session::{logout, refresh};
+use unlock::unlock_account;
+use verify::{resend_verification, verify_email};
+use wallet::{get_wallet_nonce, wallet_login};
 
 //#[instrument]
+#[allow(clippy::too_many_arguments)]
 pub async fn function_handler(
     config: &Config,
     user_service: &UsersService,
+    session_service: &SessionService,
+    one_time_token_service: &OneTimeTokenService,
+    wallet_service: &WalletLoginService,
+    oidc_service: &OidcLoginService,
+    device_service: &DeviceService,
+    login_attempt_service: &LoginAttemptService,
+    mfa_service: &MfaService,
+    sender_repo: &SenderEmailsRepo,
+    verifier: &TokenVerifier,
+    mfa_verifier: &TokenVerifier,
+    signers: &SignerCache,
     req: Request,
 ) -> Result<impl IntoResponse, Box<dyn std::error::Error>> {
     let context = req.lambda_context();
+    // worked out again only when the cached one is due, so rotations reach warm lambdas
+    let signer = match signers.current().await {
+        Err(e) => return build_resp(e.to_string(), StatusCode::SERVICE_UNAVAILABLE),
+        Ok(signer) => signer,
+    };
+    let signer = &signer;
 
     let path = remove_stage_prefix( 
         req.uri().path().to_string(), 
@@ -25,8 +70,53 @@ pub async fn function_handler(
 
     match req.method() {
         &Method::POST => match path.as_str()  {
-            "/auth/login" => login(&req, &context, config, user_service).await,
-            "/auth/signup" => create_basic_user(&req, &context, config, user_service).await,
+            "/auth/login" => {
+                login(&req, &context, config, user_service, session_service, mfa_service, login_attempt_service, one_time_token_service, sender_repo, signer).await
+            }
+            "/auth/mfa/login" => {
+                mfa_login(&req, &context, config, user_service, session_service, mfa_service, login_attempt_service, mfa_verifier, signer).await
+            }
+            "/auth/refresh" => {
+                refresh(&req, &context, config, user_service, session_service, signer).await
+            }
+            "/auth/wallet/login" => {
+                wallet_login(&req, &context, config, user_service, session_service, mfa_service, wallet_service, signer).await
+            }
+            "/auth/oidc/login" => {
+                oidc_login(&req, &context, config, user_service, session_service, mfa_service, oidc_service, signer).await
+            }
+            "/auth/device/challenge" => device_challenge(&req, &context, config, device_service).await,
+            "/auth/device/pair" => {
+                device_pair(&req, &context, config, user_service, device_service).await
+            }
+            "/auth/device/login" => {
+                device_login(&req, &context, config, user_service, session_service, mfa_service, device_service, signer).await
+            }
+            "/auth/logout" => logout(&req, &context, config, session_service, verifier).await,
+            "/auth/signup" => {
+                create_basic_user(&req, &context, config, user_service, device_service, one_time_token_service, sender_repo).await
+            }
+            "/auth/verify" => {
+                verify_email(&req, &context, config, user_service, one_time_token_service).await
+            }
+            "/auth/verify/resend" => {
+                resend_verification(&req, &context, config, user_service, one_time_token_service, sender_repo).await
+            }
+            "/auth/unlock" => {
+                unlock_account(&req, &context, config, login_attempt_service, one_time_token_service).await
+            }
+            "/auth/password/forgot" => {
+                forgot_password(&req, &context, config, user_service, one_time_token_service, sender_repo).await
+            }
+            "/auth/password/reset" => {
+                reset_password(&req, &context, config, user_service, session_service, one_time_token_service).await
+            }
+            _ => not_allowed(&req, &context),
+        },
+        &Method::GET => match path.as_str() {
+            "/.well-known/jwks.json" => get_jwks(&req, &context, config, verifier).await,
+            "/auth/wallet/nonce" => get_wallet_nonce(&req, &context, config, wallet_service).await,
+            "/auth/oidc/nonce" => get_oidc_nonce(&req, &context, config, oidc_service).await,
             _ => not_allowed(&req, &context),
         },
         _ => not_allowed(&req, &context),
@@ -0,0 +1,114 @@
+use crate::my_lambda::build_resp;
+use crate::my_lambda::session::start_session;
+use lambda_http::RequestPayloadExt;
+use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
+use lib_config::config::Config;
+use lib_users::errors::identities::{IdentityDynamoDBError, OidcProviderError, OidcTokenError};
+use lib_users::errors::sessions::SessionDynamoDBError;
+use lib_users::errors::users::{UserDynamoDBError, UserNotVerifiedError, UserStatusError};
+use lib_users::services::mfa::MfaService;
+use lib_users::services::oidc_login::{OidcLoginManipulation, OidcLoginService};
+use lib_users::services::sessions::SessionService;
+use lib_users::services::users::UsersService;
+use lib_util_jwt::keys::JwtSigner;
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct OidcLoginPayload {
+    // as the provider's sdk returned it to the app
+    #[validate(length(min = 1, max = 8000))]
+    pub id_token: String,
+    // the one handed out by /auth/oidc/nonce, not its hash
+    #[validate(length(min = 1, max = 100))]
+    pub nonce: String,
+    #[serde(default)]
+    pub admin: bool,
+}
+
+pub async fn get_oidc_nonce(
+    _req: &Request,
+    _c: &Context,
+    _config: &Config,
+    oidc_service: &OidcLoginService,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    match oidc_service.nonce().await {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<SessionDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+        Ok(nonce) => build_resp(json!({ "nonce": nonce }).to_string(), StatusCode::OK),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn oidc_login(
+    req: &Request,
+    _c: &Context,
+    config: &Config,
+    user_service: &UsersService,
+    session_service: &SessionService,
+    mfa_service: &MfaService,
+    oidc_service: &OidcLoginService,
+    signer: &JwtSigner,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let payload = match req.payload::<OidcLoginPayload>() {
+        Err(e) => return build_resp(e.to_string(), StatusCode::BAD_REQUEST),
+        Ok(None) => {
+            return build_resp(
+                "id_token and nonce fields are mandatory".to_string(),
+                StatusCode::BAD_REQUEST,
+            )
+        }
+        Ok(Some(payload)) => payload,
+    };
+    if let Err(e) = payload.validate() {
+        return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+    }
+
+    let identity = match oidc_service.verify(&payload.id_token, &payload.nonce).await {
+        Err(e) => {
+            return if let Some(m) = e.downcast_ref::<OidcTokenError>() {
+                build_resp(m.to_string(), StatusCode::UNAUTHORIZED)
+            } else if let Some(m) = e.downcast_ref::<OidcProviderError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else if let Some(m) = e.downcast_ref::<SessionDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            };
+        }
+        Ok(identity) => identity,
+    };
+
+    match oidc_service.login(user_service, &identity).await {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<IdentityDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else if let Some(m) = e.downcast_ref::<UserDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else if let Some(m) = e.downcast_ref::<UserStatusError>() {
+                build_resp(m.to_string(), StatusCode::FORBIDDEN)
+            } else if let Some(m) = e.downcast_ref::<UserNotVerifiedError>() {
+                build_resp(m.to_string(), StatusCode::FORBIDDEN)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+        Ok(log_inf) => {
+            start_session(
+                config,
+                session_service,
+                mfa_service,
+                signer,
+                &log_inf,
+                payload.admin,
+            )
+            .await
+        }
+    }
+}
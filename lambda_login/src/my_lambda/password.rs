@@ -0,0 +1,158 @@
+use crate::my_lambda::build_resp;
+use lambda_http::RequestPayloadExt;
+use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
+use lib_config::config::Config;
+use lib_engage::repositories::sender::SenderEmailsRepo;
+use lib_users::errors::one_time_tokens::{
+    OneTimeTokenDynamoDBError, OneTimeTokenError, OneTimeTokenRateLimitError,
+};
+use lib_users::errors::users::{UserDynamoDBError, UserNoExistsError};
+use lib_users::models::one_time_token::OneTimeTokenPurpose;
+use lib_users::services::one_time_tokens::{
+    OneTimeTokenManipulation, OneTimeTokenService, PASSWORD_RESET_EXP_HOURS,
+};
+use lib_users::services::sessions::{SessionManipulation, SessionService};
+use lib_users::services::users::{UserManipulation, UsersService};
+use lib_users::validate_password;
+use serde::Deserialize;
+use validator::{Validate, ValidationError};
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ForgotPasswordPayload {
+    #[validate(email)]
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ResetPasswordPayload {
+    #[validate(length(min = 1, max = 200))]
+    pub token: String,
+    #[validate(length(min = 8, max = 50), custom = "validate_password")]
+    pub password: String,
+}
+
+// the answer is always the same, so nobody can probe which emails have an account
+pub async fn forgot_password(
+    req: &Request,
+    _c: &Context,
+    _config: &Config,
+    user_service: &UsersService,
+    one_time_token_service: &OneTimeTokenService,
+    sender_repo: &SenderEmailsRepo,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let payload = match req.payload::<ForgotPasswordPayload>() {
+        Err(e) => return build_resp(e.to_string(), StatusCode::BAD_REQUEST),
+        Ok(None) => return build_resp("email field is mandatory".to_string(), StatusCode::BAD_REQUEST),
+        Ok(Some(payload)) => payload,
+    };
+    if let Err(e) = payload.validate() {
+        return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+    }
+
+    let user = match user_service.get_by_email(&payload.email).await {
+        Err(e) => {
+            if e.downcast_ref::<UserNoExistsError>().is_none() {
+                log::error!("password reset lookup failed: {}", e);
+            }
+            return build_resp("".to_string(), StatusCode::ACCEPTED);
+        }
+        Ok(user) => user,
+    };
+
+    let issued = one_time_token_service
+        .issue(
+            user.user_id(),
+            &payload.email,
+            &OneTimeTokenPurpose::PasswordReset,
+            PASSWORD_RESET_EXP_HOURS,
+        )
+        .await;
+    match issued {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<OneTimeTokenRateLimitError>() {
+                log::warn!("password reset throttled for user {}: {}", user.user_id(), m);
+            } else {
+                log::error!("password reset token not issued for user {}: {}", user.user_id(), e);
+            }
+        }
+        Ok(token) => {
+            if let Err(e) = sender_repo.send_password_reset(payload.email.clone(), token).await {
+                log::error!("password reset email not sent to user {}: {}", user.user_id(), e);
+            }
+        }
+    }
+    build_resp("".to_string(), StatusCode::ACCEPTED)
+}
+
+pub async fn reset_password(
+    req: &Request,
+    _c: &Context,
+    _config: &Config,
+    user_service: &UsersService,
+    session_service: &SessionService,
+    one_time_token_service: &OneTimeTokenService,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let payload = match req.payload::<ResetPasswordPayload>() {
+        Err(e) => return build_resp(e.to_string(), StatusCode::BAD_REQUEST),
+        Ok(None) => {
+            return build_resp("token and password fields are mandatory".to_string(), StatusCode::BAD_REQUEST)
+        }
+        Ok(Some(payload)) => payload,
+    };
+    // a weak password must not burn the token
+    if let Err(e) = payload.validate() {
+        return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+    }
+
+    let token = match one_time_token_service
+        .consume(&payload.token, &OneTimeTokenPurpose::PasswordReset)
+        .await
+    {
+        Err(e) => {
+            return if let Some(m) = e.downcast_ref::<OneTimeTokenError>() {
+                build_resp(m.to_string(), StatusCode::BAD_REQUEST)
+            } else if let Some(m) = e.downcast_ref::<OneTimeTokenDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            };
+        }
+        Ok(token) => token,
+    };
+
+    // the link was mailed to an address the account no longer uses
+    let user = match user_service.get_by_id(token.user_id()).await {
+        Err(e) => {
+            return if e.downcast_ref::<UserNoExistsError>().is_some() {
+                build_resp(OneTimeTokenError("unknown token".to_string()).to_string(), StatusCode::BAD_REQUEST)
+            } else if let Some(m) = e.downcast_ref::<UserDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            };
+        }
+        Ok(user) => user,
+    };
+    if user.email().as_ref() != Some(token.email()) {
+        return build_resp(OneTimeTokenError("unknown token".to_string()).to_string(), StatusCode::BAD_REQUEST);
+    }
+
+    if let Err(e) = user_service.update_password(user.user_id(), &payload.password).await {
+        return if let Some(m) = e.downcast_ref::<ValidationError>() {
+            build_resp(m.to_string(), StatusCode::BAD_REQUEST)
+        } else if let Some(m) = e.downcast_ref::<UserDynamoDBError>() {
+            build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+        } else {
+            build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+        };
+    }
+
+    // whoever had the old password is logged out; reading the email also proves the address
+    if let Err(e) = session_service.revoke_all(user.user_id()).await {
+        log::error!("{}", e);
+    }
+    if let Err(e) = user_service.mark_email_verified(user.user_id(), token.email()).await {
+        log::error!("{}", e);
+    }
+    build_resp("".to_string(), StatusCode::OK)
+}
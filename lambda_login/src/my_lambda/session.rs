@@ -0,0 +1,262 @@
+use crate::my_lambda::build_resp;
+use lambda_http::RequestPayloadExt;
+use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
+use lib_config::config::Config;
+use lib_users::errors::mfa::MfaDynamoDBError;
+use lib_users::errors::sessions::{RefreshTokenError, SessionDynamoDBError};
+use lib_users::errors::users::{UserDynamoDBError, UserNoExistsError};
+use lib_users::models::user::UserRoles;
+use lib_users::services::login::LoginInfo;
+use lib_users::services::mfa::{mfa_required, MfaManipulation, MfaService, MFA_CHALLENGE_EXP_MINUTES};
+use lib_users::services::sessions::{SessionManipulation, SessionService};
+use lib_users::services::users::{UserManipulation, UsersService};
+use lib_util_jwt::jwt::{
+    admin_audience, create_jwt, default_audience, get_header_jwt, JWTSecurityError,
+    TokenVerifier, ACCESS_TOKEN_EXP_MINUTES, AUDIENCE_ADMIN, AUDIENCE_MFA,
+};
+use lib_util_jwt::keys::JwtSigner;
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct RefreshPayload {
+    #[validate(length(min = 1, max = 100))]
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Deserialize, Validate, Default)]
+pub struct LogoutPayload {
+    #[serde(default)]
+    #[validate(length(min = 1, max = 100))]
+    pub refresh_token: Option<String>,
+}
+
+// refresh tokens last as long as a whole login used to, jwt_token_time_exp_hours
+pub fn refresh_token_exp_hours(config: &Config) -> i64 {
+    config
+        .env_vars()
+        .jwt_token_time_exp_hours()
+        .unwrap()
+        .parse::<i64>()
+        .unwrap()
+}
+
+// admin tokens are only good for lambda_admin, regular ones for everything else;
+// every staff role gets them, what each one may do there is up to require_permission
+pub fn audience_for(admin: bool, roles: &Vec<UserRoles>) -> Option<Vec<String>> {
+    if !admin {
+        Some(default_audience())
+    } else if roles.iter().any(|role| role.is_staff()) {
+        Some(admin_audience())
+    } else {
+        None
+    }
+}
+
+pub fn create_access_token(
+    signer: &JwtSigner,
+    user_id: &String,
+    roles: &Vec<UserRoles>,
+    audience: &Vec<String>,
+) -> Result<String, JWTSecurityError> {
+    create_jwt(
+        user_id,
+        UserRoles::to_vec_str(roles),
+        audience,
+        signer,
+        ACCESS_TOKEN_EXP_MINUTES,
+    )
+}
+
+// shared by every login method: users with mfa get a challenge token instead of a session,
+// admin tokens are only handed out to admins who have mfa enabled
+pub async fn start_session(
+    config: &Config,
+    session_service: &SessionService,
+    mfa_service: &MfaService,
+    signer: &JwtSigner,
+    log_inf: &LoginInfo,
+    admin: bool,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let mfa_enabled = match mfa_service.is_enabled(&log_inf.user_id).await {
+        Err(e) => {
+            return if let Some(m) = e.downcast_ref::<MfaDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            };
+        }
+        Ok(enabled) => enabled,
+    };
+    if mfa_enabled {
+        let challenge = create_jwt(
+            &log_inf.user_id,
+            UserRoles::to_vec_str(&log_inf.roles),
+            &vec![AUDIENCE_MFA.to_string()],
+            signer,
+            MFA_CHALLENGE_EXP_MINUTES,
+        );
+        return match challenge {
+            Err(_) => build_resp("".to_string(), StatusCode::INTERNAL_SERVER_ERROR),
+            Ok(mfa_token) => build_resp(
+                json!({ "mfa_required": true, "mfa_token": mfa_token }).to_string(),
+                StatusCode::OK,
+            ),
+        };
+    }
+    if admin && mfa_required(&log_inf.roles) {
+        return build_resp(
+            "enable mfa in your account before asking for an admin token".to_string(),
+            StatusCode::FORBIDDEN,
+        );
+    }
+    open_session(config, session_service, signer, log_inf, admin).await
+}
+
+// access token plus the refresh token of a new session, once every factor has been checked
+pub async fn open_session(
+    config: &Config,
+    session_service: &SessionService,
+    signer: &JwtSigner,
+    log_inf: &LoginInfo,
+    admin: bool,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let audience = match audience_for(admin, &log_inf.roles) {
+        None => return build_resp("user is not a staff member".to_string(), StatusCode::FORBIDDEN),
+        Some(audience) => audience,
+    };
+    let token = match create_access_token(signer, &log_inf.user_id, &log_inf.roles, &audience) {
+        Err(_) => return build_resp("".to_string(), StatusCode::INTERNAL_SERVER_ERROR),
+        Ok(token) => token,
+    };
+    match session_service
+        .start(&log_inf.user_id, &audience, refresh_token_exp_hours(config))
+        .await
+    {
+        Err(e) => build_resp(e.to_string(), StatusCode::SERVICE_UNAVAILABLE),
+        Ok(refresh_token) => build_resp(
+            json!({ "token": token, "refresh_token": refresh_token }).to_string(),
+            StatusCode::OK,
+        ),
+    }
+}
+
+pub async fn refresh(
+    req: &Request,
+    _c: &Context,
+    config: &Config,
+    user_service: &UsersService,
+    session_service: &SessionService,
+    signer: &JwtSigner,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let payload = match req.payload::<RefreshPayload>() {
+        Err(e) => return build_resp(e.to_string(), StatusCode::BAD_REQUEST),
+        Ok(None) => {
+            return build_resp("refresh_token field is mandatory".to_string(), StatusCode::BAD_REQUEST)
+        }
+        Ok(Some(payload)) => payload,
+    };
+    if let Err(e) = payload.validate() {
+        return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+    }
+
+    let session = match session_service
+        .refresh(&payload.refresh_token, refresh_token_exp_hours(config))
+        .await
+    {
+        Err(e) => {
+            return if let Some(m) = e.downcast_ref::<RefreshTokenError>() {
+                build_resp(m.to_string(), StatusCode::UNAUTHORIZED)
+            } else if let Some(m) = e.downcast_ref::<SessionDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            };
+        }
+        Ok(session) => session,
+    };
+
+    let user = match user_service.get_by_id(&session.user_id).await {
+        Err(e) => {
+            return if let Some(m) = e.downcast_ref::<UserNoExistsError>() {
+                build_resp(m.to_string(), StatusCode::UNAUTHORIZED)
+            } else if let Some(m) = e.downcast_ref::<UserDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            };
+        }
+        Ok(user) => user,
+    };
+    if user.status().is_disabled() {
+        if let Err(e) = session_service.revoke_all(user.user_id()).await {
+            log::error!("{}", e);
+        }
+        return build_resp("user has been disabled".to_string(), StatusCode::FORBIDDEN);
+    }
+    // the email was changed after login and the new one isn't verified yet
+    if !user.is_verified() {
+        return build_resp("email not verified".to_string(), StatusCode::FORBIDDEN);
+    }
+
+    // the admin role might be gone since the session started
+    let admin = session.audience.iter().any(|aud| aud == AUDIENCE_ADMIN);
+    if audience_for(admin, user.roles()).is_none() {
+        if let Err(e) = session_service.revoke_all(user.user_id()).await {
+            log::error!("{}", e);
+        }
+        return build_resp("user is not a staff member".to_string(), StatusCode::FORBIDDEN);
+    }
+
+    match create_access_token(signer, user.user_id(), user.roles(), &session.audience) {
+        Err(_) => build_resp("".to_string(), StatusCode::INTERNAL_SERVER_ERROR),
+        Ok(token) => build_resp(
+            json!({ "token": token, "refresh_token": session.refresh_token }).to_string(),
+            StatusCode::OK,
+        ),
+    }
+}
+
+pub async fn logout(
+    req: &Request,
+    _c: &Context,
+    _config: &Config,
+    session_service: &SessionService,
+    verifier: &TokenVerifier,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let claims = match get_header_jwt(req.headers(), verifier).await {
+        Err(e) => return build_resp(e.to_string(), StatusCode::UNAUTHORIZED),
+        Ok(claims) => claims,
+    };
+
+    let payload = match req.payload::<LogoutPayload>() {
+        Err(e) => return build_resp(e.to_string(), StatusCode::BAD_REQUEST),
+        Ok(payload) => payload.unwrap_or_default(),
+    };
+    if let Err(e) = payload.validate() {
+        return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+    }
+
+    let access_expires_at = chrono::DateTime::from_timestamp(claims.exp as i64, 0).unwrap();
+    let op = session_service
+        .logout(
+            &claims.uid,
+            &payload.refresh_token,
+            &claims.jti,
+            &access_expires_at,
+        )
+        .await;
+    match op {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<RefreshTokenError>() {
+                build_resp(m.to_string(), StatusCode::BAD_REQUEST)
+            } else if let Some(m) = e.downcast_ref::<SessionDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+        Ok(_) => build_resp("".to_string(), StatusCode::OK),
+    }
+}
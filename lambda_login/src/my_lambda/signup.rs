@@ -1,9 +1,14 @@
 use crate::my_lambda::build_resp;
+use crate::my_lambda::verify::send_verification;
 use lambda_http::RequestPayloadExt;
 use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
 use lib_config::config::Config;
+use lib_engage::repositories::sender::SenderEmailsRepo;
+use lib_users::errors::devices::{DeviceAlreadyExistsError, DeviceKeyError};
 use lib_users::errors::users::{UserAlreadyExistsError, UserDynamoDBError, UserMismatchError};
 use lib_users::models::user::User;
+use lib_users::services::devices::{check_device_key, DeviceManipulation, DeviceService};
+use lib_users::services::one_time_tokens::OneTimeTokenService;
 use lib_users::services::users::{UserManipulation, UsersService};
 use lib_users::validate_password;
 use serde::{Deserialize, Serialize};
@@ -17,6 +22,9 @@ pub struct NewUser {
     #[validate(length(min = 8, max = 50), custom = "validate_password")]
     pub password: Option<String>,
     pub device: Option<String>,
+    // a device is only paired with its public key, see /auth/device/login
+    pub device_public_key: Option<String>,
+    pub device_algorithm: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -29,9 +37,13 @@ pub async fn create_basic_user(
     _c: &Context,
     _config: &Config,
     user_service: &UsersService,
+    device_service: &DeviceService,
+    one_time_token_service: &OneTimeTokenService,
+    sender_repo: &SenderEmailsRepo,
 ) -> Result<Response<String>, Box<dyn std::error::Error>> {
     let mut user = User::new();
     let new_password;
+    let mut device_key = None;
     match _req.payload::<NewUser>() {
         Err(e) => {
             return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
@@ -49,7 +61,21 @@ pub async fn create_basic_user(
                         user.set_email(eml);
                     }
                     if let Some(dvc) = &payload.device {
+                        let (algorithm, public_key) =
+                            match (&payload.device_algorithm, &payload.device_public_key) {
+                                (Some(alg), Some(key)) => (alg.clone(), key.clone()),
+                                _ => {
+                                    return build_resp(
+                                        "device_algorithm and device_public_key are mandatory with a device".to_string(),
+                                        StatusCode::BAD_REQUEST,
+                                    );
+                                }
+                            };
+                        if let Err(e) = check_device_key(&algorithm, &public_key) {
+                            return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+                        }
                         user.set_device(dvc);
+                        device_key = Some((dvc.clone(), algorithm, public_key));
                     }
                     new_password = payload.password
                 }
@@ -77,6 +103,24 @@ pub async fn create_basic_user(
             }
         }
         Ok(_) => {
+            if let Some((dvc, algorithm, public_key)) = &device_key {
+                if let Err(e) = device_service
+                    .register(user.user_id(), dvc, algorithm, public_key)
+                    .await
+                {
+                    return if let Some(m) = e.downcast_ref::<DeviceAlreadyExistsError>() {
+                        build_resp(m.to_string(), StatusCode::NOT_ACCEPTABLE)
+                    } else if let Some(m) = e.downcast_ref::<DeviceKeyError>() {
+                        build_resp(m.to_string(), StatusCode::BAD_REQUEST)
+                    } else {
+                        build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+                    };
+                }
+            }
+            // the account stays unverified until the link is clicked, it can be resent later
+            if let Err(e) = send_verification(&user, one_time_token_service, sender_repo).await {
+                log::error!("verification email not sent to user {}: {}", user.user_id(), e);
+            }
             build_resp("".to_string(), StatusCode::CREATED)
         }
     }
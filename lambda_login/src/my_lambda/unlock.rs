@@ -0,0 +1,93 @@
+use crate::my_lambda::build_resp;
+use lambda_http::RequestPayloadExt;
+use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
+use lib_config::config::Config;
+use lib_engage::repositories::sender::SenderEmailsRepo;
+use lib_users::errors::login_attempts::LoginAttemptDynamoDBError;
+use lib_users::errors::one_time_tokens::{OneTimeTokenDynamoDBError, OneTimeTokenError};
+use lib_users::models::one_time_token::OneTimeTokenPurpose;
+use lib_users::services::login_attempts::{LoginAttemptManipulation, LoginAttemptService};
+use lib_users::services::one_time_tokens::{
+    OneTimeTokenManipulation, OneTimeTokenService, ACCOUNT_UNLOCK_EXP_HOURS,
+};
+use lib_users::services::users::{UserManipulation, UsersService};
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UnlockPayload {
+    #[validate(length(min = 1, max = 200))]
+    pub token: String,
+}
+
+// counts a bad password and, when that locks the account, mails its owner an unlock link
+pub async fn register_failed_login(
+    email: &String,
+    ip: &Option<String>,
+    user_service: &UsersService,
+    login_attempt_service: &LoginAttemptService,
+    one_time_token_service: &OneTimeTokenService,
+    sender_repo: &SenderEmailsRepo,
+) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+    if !login_attempt_service.failure(email, ip).await? {
+        return Ok(());
+    }
+    // nobody to warn when the email has no account
+    let user = match user_service.get_by_email(email).await {
+        Err(_) => return Ok(()),
+        Ok(user) => user,
+    };
+    let token = one_time_token_service
+        .issue(
+            user.user_id(),
+            email,
+            &OneTimeTokenPurpose::AccountUnlock,
+            ACCOUNT_UNLOCK_EXP_HOURS,
+        )
+        .await?;
+    sender_repo.send_account_unlock(email.clone(), token).await
+}
+
+pub async fn unlock_account(
+    req: &Request,
+    _c: &Context,
+    _config: &Config,
+    login_attempt_service: &LoginAttemptService,
+    one_time_token_service: &OneTimeTokenService,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let payload = match req.payload::<UnlockPayload>() {
+        Err(e) => return build_resp(e.to_string(), StatusCode::BAD_REQUEST),
+        Ok(None) => return build_resp("token field is mandatory".to_string(), StatusCode::BAD_REQUEST),
+        Ok(Some(payload)) => payload,
+    };
+    if let Err(e) = payload.validate() {
+        return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+    }
+
+    let token = match one_time_token_service
+        .consume(&payload.token, &OneTimeTokenPurpose::AccountUnlock)
+        .await
+    {
+        Err(e) => {
+            return if let Some(m) = e.downcast_ref::<OneTimeTokenError>() {
+                build_resp(m.to_string(), StatusCode::BAD_REQUEST)
+            } else if let Some(m) = e.downcast_ref::<OneTimeTokenDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            };
+        }
+        Ok(token) => token,
+    };
+
+    match login_attempt_service.unlock(token.email()).await {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<LoginAttemptDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+        Ok(_) => build_resp("".to_string(), StatusCode::OK),
+    }
+}
@@ -0,0 +1,149 @@
+use crate::my_lambda::build_resp;
+use lambda_http::RequestPayloadExt;
+use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
+use lib_config::config::Config;
+use lib_engage::repositories::sender::SenderEmailsRepo;
+use lib_users::errors::one_time_tokens::{
+    OneTimeTokenDynamoDBError, OneTimeTokenError, OneTimeTokenRateLimitError,
+};
+use lib_users::errors::users::{UserDynamoDBError, UserNoExistsError, UserNotVerifiedError};
+use lib_users::models::one_time_token::OneTimeTokenPurpose;
+use lib_users::models::user::User;
+use lib_users::services::one_time_tokens::{
+    OneTimeTokenManipulation, OneTimeTokenService, EMAIL_VERIFICATION_EXP_HOURS,
+};
+use lib_users::services::users::{UserManipulation, UsersService};
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct VerifyPayload {
+    #[validate(length(min = 1, max = 200))]
+    pub token: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ResendPayload {
+    #[validate(email)]
+    pub email: String,
+}
+
+// mints a verification token for the current email of the user and mails the link
+pub async fn send_verification(
+    user: &User,
+    one_time_token_service: &OneTimeTokenService,
+    sender_repo: &SenderEmailsRepo,
+) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+    let email = match user.email() {
+        None => return Ok(()),
+        Some(email) => email.clone(),
+    };
+    let token = one_time_token_service
+        .issue(
+            user.user_id(),
+            &email,
+            &OneTimeTokenPurpose::EmailVerification,
+            EMAIL_VERIFICATION_EXP_HOURS,
+        )
+        .await?;
+    sender_repo.send_email_verification(email, token).await
+}
+
+pub async fn verify_email(
+    req: &Request,
+    _c: &Context,
+    _config: &Config,
+    user_service: &UsersService,
+    one_time_token_service: &OneTimeTokenService,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let payload = match req.payload::<VerifyPayload>() {
+        Err(e) => return build_resp(e.to_string(), StatusCode::BAD_REQUEST),
+        Ok(None) => return build_resp("token field is mandatory".to_string(), StatusCode::BAD_REQUEST),
+        Ok(Some(payload)) => payload,
+    };
+    if let Err(e) = payload.validate() {
+        return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+    }
+
+    let token = match one_time_token_service
+        .consume(&payload.token, &OneTimeTokenPurpose::EmailVerification)
+        .await
+    {
+        Err(e) => {
+            return if let Some(m) = e.downcast_ref::<OneTimeTokenError>() {
+                build_resp(m.to_string(), StatusCode::BAD_REQUEST)
+            } else if let Some(m) = e.downcast_ref::<OneTimeTokenDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            };
+        }
+        Ok(token) => token,
+    };
+
+    match user_service
+        .mark_email_verified(token.user_id(), token.email())
+        .await
+    {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<UserNotVerifiedError>() {
+                build_resp(m.to_string(), StatusCode::BAD_REQUEST)
+            } else if let Some(m) = e.downcast_ref::<UserNoExistsError>() {
+                build_resp(m.to_string(), StatusCode::NOT_FOUND)
+            } else if let Some(m) = e.downcast_ref::<UserDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+        Ok(_) => build_resp("".to_string(), StatusCode::OK),
+    }
+}
+
+// answers the same whether the email exists or is already verified
+pub async fn resend_verification(
+    req: &Request,
+    _c: &Context,
+    _config: &Config,
+    user_service: &UsersService,
+    one_time_token_service: &OneTimeTokenService,
+    sender_repo: &SenderEmailsRepo,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let payload = match req.payload::<ResendPayload>() {
+        Err(e) => return build_resp(e.to_string(), StatusCode::BAD_REQUEST),
+        Ok(None) => return build_resp("email field is mandatory".to_string(), StatusCode::BAD_REQUEST),
+        Ok(Some(payload)) => payload,
+    };
+    if let Err(e) = payload.validate() {
+        return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+    }
+
+    let user = match user_service.get_by_email(&payload.email).await {
+        Err(e) => {
+            return if e.downcast_ref::<UserNoExistsError>().is_some() {
+                build_resp("".to_string(), StatusCode::ACCEPTED)
+            } else if let Some(m) = e.downcast_ref::<UserDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            };
+        }
+        Ok(user) => user,
+    };
+    if user.is_verified() {
+        return build_resp("".to_string(), StatusCode::ACCEPTED);
+    }
+
+    match send_verification(&user, one_time_token_service, sender_repo).await {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<OneTimeTokenRateLimitError>() {
+                build_resp(m.to_string(), StatusCode::TOO_MANY_REQUESTS)
+            } else if let Some(m) = e.downcast_ref::<OneTimeTokenDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+        Ok(_) => build_resp("".to_string(), StatusCode::ACCEPTED),
+    }
+}
@@ -0,0 +1,100 @@
+use crate::my_lambda::build_resp;
+use crate::my_lambda::session::start_session;
+use lambda_http::RequestPayloadExt;
+use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
+use lib_config::config::Config;
+use lib_users::errors::sessions::{SessionDynamoDBError, WalletSignatureError};
+use lib_users::errors::users::{
+    UserDynamoDBError, UserNoExistsError, UserNotVerifiedError, UserStatusError,
+};
+use lib_users::services::login::LoginOps;
+use lib_users::services::mfa::MfaService;
+use lib_users::services::sessions::SessionService;
+use lib_users::services::users::UsersService;
+use lib_users::services::wallet_login::{WalletLoginManipulation, WalletLoginService};
+use lib_util_jwt::keys::JwtSigner;
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct WalletLoginPayload {
+    // the EIP-4361 message exactly as the wallet signed it
+    #[validate(length(min = 1, max = 2000))]
+    pub message: String,
+    #[validate(length(min = 130, max = 132))]
+    pub signature: String,
+    #[serde(default)]
+    pub admin: bool,
+}
+
+pub async fn get_wallet_nonce(
+    _req: &Request,
+    _c: &Context,
+    _config: &Config,
+    wallet_service: &WalletLoginService,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    match wallet_service.nonce().await {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<SessionDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+        Ok(nonce) => build_resp(json!({ "nonce": nonce }).to_string(), StatusCode::OK),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn wallet_login(
+    req: &Request,
+    _c: &Context,
+    config: &Config,
+    user_service: &UsersService,
+    session_service: &SessionService,
+    mfa_service: &MfaService,
+    wallet_service: &WalletLoginService,
+    signer: &JwtSigner,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let payload = match req.payload::<WalletLoginPayload>() {
+        Err(e) => return build_resp(e.to_string(), StatusCode::BAD_REQUEST),
+        Ok(None) => {
+            return build_resp("message and signature fields are mandatory".to_string(), StatusCode::BAD_REQUEST)
+        }
+        Ok(Some(payload)) => payload,
+    };
+    if let Err(e) = payload.validate() {
+        return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+    }
+
+    let wallet = match wallet_service.verify(&payload.message, &payload.signature).await {
+        Err(e) => {
+            return if let Some(m) = e.downcast_ref::<WalletSignatureError>() {
+                build_resp(m.to_string(), StatusCode::UNAUTHORIZED)
+            } else if let Some(m) = e.downcast_ref::<SessionDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            };
+        }
+        Ok(wallet) => wallet,
+    };
+
+    match user_service.login(&None, &Some(wallet), &None, &None).await {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<UserDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else if let Some(m) = e.downcast_ref::<UserNoExistsError>() {
+                build_resp(m.to_string(), StatusCode::NOT_FOUND)
+            } else if let Some(m) = e.downcast_ref::<UserStatusError>() {
+                build_resp(m.to_string(), StatusCode::FORBIDDEN)
+            } else if let Some(m) = e.downcast_ref::<UserNotVerifiedError>() {
+                build_resp(m.to_string(), StatusCode::FORBIDDEN)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+        Ok(log_inf) => start_session(config, session_service, mfa_service, signer, &log_inf, payload.admin).await,
+    }
+}
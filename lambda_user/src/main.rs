@@ -1,8 +1,29 @@
 use lambda_http::service_fn;
 use lib_config::{config::Config, logs::setup_log, //traces::setup_tracing_level
 };
+use lib_engage::services::account_deletion::AccountDeletionService;
+use lib_engage::services::account_merge::AccountMergeService;
+use lib_engage::repositories::sender::SenderEmailsRepo;
+use lib_engage::services::data_export::DataExportService;
+use lib_users::repositories::api_keys::ApiKeysRepo;
+use lib_users::repositories::devices::DevicesRepo;
+use lib_users::repositories::identities::IdentitiesRepo;
+use lib_users::repositories::login_attempts::LoginAttemptsRepo;
+use lib_users::repositories::mfa::MfaRepo;
+use lib_users::repositories::one_time_tokens::OneTimeTokensRepo;
+use lib_users::repositories::sessions::SessionsRepo;
 use lib_users::repositories::users::UsersRepo;
+use lib_users::services::api_keys::ApiKeyService;
+use lib_users::services::devices::DeviceService;
+use lib_users::services::login_attempts::LoginAttemptService;
+use lib_users::services::login_methods::LoginMethodService;
+use lib_users::services::mfa::MfaService;
+use lib_users::services::oidc_login::OidcLoginService;
+use lib_users::services::one_time_tokens::OneTimeTokenService;
 use lib_users::services::users::UsersService;
+use lib_users::services::wallet_login::{WalletLoginService, SIWE_CHAIN_ID, SIWE_DOMAIN, SIWE_URI};
+use lib_util_jwt::jwt::{TokenVerifier, AUDIENCE_USER};
+use lib_util_jwt::oidc::load_oidc_providers;
 use my_lambda::{error::ApiLambdaUserError, function_handler};
 
 mod my_lambda;
@@ -21,9 +42,65 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let user_repo = UsersRepo::new(&config);
     let user_service = UsersService::new(user_repo);
 
+    let device_repo = DevicesRepo::new(&config);
+    let device_service = DeviceService::new(device_repo);
+
+    let mfa_repo = MfaRepo::new(&config);
+    let mfa_service = MfaService::new(mfa_repo);
+
+    let api_key_repo = ApiKeysRepo::new(&config);
+    let api_key_service = ApiKeyService::new(api_key_repo);
+
+    let login_attempt_service = LoginAttemptService::new(LoginAttemptsRepo::new(&config));
+
+    let account_deletion_service = AccountDeletionService::new(&config);
+    let account_merge_service = AccountMergeService::new(&config);
+    let data_export_service = DataExportService::new(&config);
+
+    let login_method_service = LoginMethodService::new(
+        UsersRepo::new(&config),
+        IdentitiesRepo::new(&config),
+        DevicesRepo::new(&config),
+    );
+    let wallet_service = WalletLoginService::new(
+        SessionsRepo::new(&config),
+        SIWE_DOMAIN,
+        SIWE_URI,
+        SIWE_CHAIN_ID,
+    );
+    let oidc_service = OidcLoginService::new(
+        IdentitiesRepo::new(&config),
+        SessionsRepo::new(&config),
+        load_oidc_providers(&config).await,
+    );
+
+    let one_time_token_repo = OneTimeTokensRepo::new(&config);
+    let one_time_token_service = OneTimeTokenService::new(one_time_token_repo, &config);
+
+    let sender_repo = SenderEmailsRepo::new(&config);
+
+    let verifier = TokenVerifier::new(&config, AUDIENCE_USER);
+
     log::info!("lambda ready, awaiting for events.");
     let resp = lambda_http::run(service_fn(|event| {
-        function_handler(&config, &user_service, event)
+        function_handler(
+            &config,
+            &user_service,
+            &device_service,
+            &mfa_service,
+            &api_key_service,
+            &login_attempt_service,
+            &account_deletion_service,
+            &account_merge_service,
+            &data_export_service,
+            &login_method_service,
+            &wallet_service,
+            &oidc_service,
+            &one_time_token_service,
+            &sender_repo,
+            &verifier,
+            event,
+        )
     }))
     .await;
 
@@ -0,0 +1,118 @@
+use std::str::FromStr;
+
+use lambda_http::RequestPayloadExt;
+use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
+use lib_config::config::Config;
+use lib_users::errors::api_keys::{ApiKeyDynamoDBError, ApiKeyError, ApiKeyNoExistsError};
+use lib_users::models::api_key::{ApiKey, ApiKeyScope};
+use lib_users::services::api_keys::{ApiKeyManipulation, ApiKeyService};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::build_resp;
+
+#[derive(Serialize, Validate, Deserialize)]
+pub struct NewApiKey {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    // "assets:read", "assets:write", "similar:read", "licenses:read", "licenses:write"
+    #[validate(length(min = 1, max = 5))]
+    pub scopes: Vec<String>,
+}
+
+// the raw key only travels in this response, it can't be read back later
+#[derive(Serialize)]
+struct CreatedApiKey {
+    key: String,
+    #[serde(flatten)]
+    api_key: ApiKey,
+}
+
+//#[instrument]
+pub async fn get_my_api_keys(
+    _req: &Request,
+    _c: &Context,
+    _config: &Config,
+    api_key_service: &ApiKeyService,
+    id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    match api_key_service.get_by_user(id).await {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<ApiKeyDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+        Ok(keys) => {
+            let active: Vec<_> = keys.into_iter().filter(|k| !k.revoked()).collect();
+            build_resp(serde_json::to_string(&active)?, StatusCode::OK)
+        }
+    }
+}
+
+//#[instrument]
+pub async fn create_my_api_key(
+    req: &Request,
+    _c: &Context,
+    _config: &Config,
+    api_key_service: &ApiKeyService,
+    id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let payload = match req.payload::<NewApiKey>() {
+        Err(e) => return build_resp(e.to_string(), StatusCode::BAD_REQUEST),
+        Ok(None) => return build_resp("no payload found".to_string(), StatusCode::BAD_REQUEST),
+        Ok(Some(payload)) => payload,
+    };
+    if let Err(e) = payload.validate() {
+        return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+    }
+    let mut scopes = Vec::new();
+    for scope in payload.scopes.iter() {
+        match ApiKeyScope::from_str(scope) {
+            Err(_) => {
+                return build_resp(format!("unknown scope {}", scope), StatusCode::BAD_REQUEST)
+            }
+            Ok(scope) => scopes.push(scope),
+        }
+    }
+
+    match api_key_service.create(id, &payload.name, &scopes).await {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<ApiKeyDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else if let Some(m) = e.downcast_ref::<ApiKeyError>() {
+                build_resp(m.to_string(), StatusCode::NOT_ACCEPTABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+        Ok((api_key, key)) => build_resp(
+            serde_json::to_string(&CreatedApiKey { key, api_key })?,
+            StatusCode::CREATED,
+        ),
+    }
+}
+
+//#[instrument]
+pub async fn revoke_my_api_key(
+    _req: &Request,
+    _c: &Context,
+    _config: &Config,
+    api_key_service: &ApiKeyService,
+    id: &String,
+    key_id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    match api_key_service.revoke(id, key_id).await {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<ApiKeyDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else if let Some(m) = e.downcast_ref::<ApiKeyNoExistsError>() {
+                build_resp(m.to_string(), StatusCode::NOT_FOUND)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+        Ok(_) => build_resp("".to_string(), StatusCode::OK),
+    }
+}
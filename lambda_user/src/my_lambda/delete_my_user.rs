@@ -0,0 +1,139 @@
+use lambda_http::RequestPayloadExt;
+use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
+use lib_config::config::Config;
+use lib_engage::errors::account_deletion::AccountDeletionError;
+use lib_engage::services::account_deletion::{AccountDeletionService, AssetPolicy};
+use lib_licenses::errors::owner::{OwnerDynamoDBError, OwnerShareError};
+use lib_users::errors::login_attempts::{
+    LoginAttemptDynamoDBError, LoginThrottledError, UserLockedError,
+};
+use lib_users::errors::users::{UserDynamoDBError, UserNoExistsError};
+use lib_users::services::login_attempts::{LoginAttemptManipulation, LoginAttemptService};
+use lib_users::services::users::{UserManipulation, UsersService};
+use lib_util_jwt::auth::source_ip;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::build_resp;
+
+#[derive(Serialize, Validate, Deserialize)]
+pub struct DeleteMyUser {
+    // mandatory when the account has an email, wallet or device only accounts confirm with the token
+    #[validate(length(max = 50))]
+    pub password: Option<String>,
+    // anonymize, handing the assets to another user is up to an administrator
+    pub asset_policy: String,
+    pub transfer_to: Option<String>,
+}
+
+//#[instrument]
+pub async fn delete_my_user(
+    req: &Request,
+    _c: &Context,
+    _config: &Config,
+    user_service: &UsersService,
+    login_attempt_service: &LoginAttemptService,
+    account_deletion_service: &AccountDeletionService,
+    id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let payload = match req.payload::<DeleteMyUser>() {
+        Err(e) => {
+            return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+        }
+        Ok(None) => {
+            return build_resp("no payload found".to_string(), StatusCode::BAD_REQUEST);
+        }
+        Ok(Some(payload)) => match payload.validate() {
+            Err(e) => {
+                return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+            }
+            Ok(_) => payload,
+        },
+    };
+
+    let policy = match AssetPolicy::parse(&payload.asset_policy, &payload.transfer_to) {
+        Err(e) => {
+            return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+        }
+        Ok(policy) => policy,
+    };
+    // the heir would get the shares without a say, only staff and merges may do it
+    if let AssetPolicy::Transfer(_) = policy {
+        return build_resp(
+            AccountDeletionError::TransferNotAllowed.to_string(),
+            StatusCode::FORBIDDEN,
+        );
+    }
+
+    let user = match user_service.get_by_id(id).await {
+        Err(e) => return deletion_error(e),
+        Ok(user) => user,
+    };
+    if let Some(email) = user.email() {
+        let password = match &payload.password {
+            None => {
+                return build_resp(
+                    "password is mandatory to delete the account".to_string(),
+                    StatusCode::BAD_REQUEST,
+                );
+            }
+            Some(password) => password,
+        };
+        // the same throttling as a login, or this would be a way to guess the password
+        let ip = source_ip(req);
+        if let Err(e) = login_attempt_service.check(email, &ip).await {
+            return if let Some(m) = e.downcast_ref::<UserLockedError>() {
+                build_resp(m.to_string(), StatusCode::FORBIDDEN)
+            } else if let Some(m) = e.downcast_ref::<LoginThrottledError>() {
+                build_resp(m.to_string(), StatusCode::TOO_MANY_REQUESTS)
+            } else if let Some(m) = e.downcast_ref::<LoginAttemptDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            };
+        }
+        match user_service
+            .get_by_email_and_password(email, password)
+            .await
+        {
+            Err(e) => {
+                return if e.downcast_ref::<UserNoExistsError>().is_some() {
+                    if let Err(err) = login_attempt_service.failure(email, &ip).await {
+                        log::error!("failed password not registered for {}: {}", email, err);
+                    }
+                    build_resp("wrong password".to_string(), StatusCode::UNAUTHORIZED)
+                } else {
+                    deletion_error(e)
+                };
+            }
+            Ok(_) => {
+                if let Err(e) = login_attempt_service.success(email).await {
+                    log::error!("{}", e);
+                }
+            }
+        }
+    }
+
+    match account_deletion_service.delete(id, &policy).await {
+        Err(e) => deletion_error(e),
+        Ok(_) => build_resp("".to_string(), StatusCode::OK),
+    }
+}
+
+pub fn deletion_error(
+    e: Box<dyn std::error::Error + Sync + Send>,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    if let Some(m) = e.downcast_ref::<AccountDeletionError>() {
+        build_resp(m.to_string(), StatusCode::BAD_REQUEST)
+    } else if let Some(m) = e.downcast_ref::<UserNoExistsError>() {
+        build_resp(m.to_string(), StatusCode::NOT_FOUND)
+    } else if let Some(m) = e.downcast_ref::<OwnerShareError>() {
+        build_resp(m.to_string(), StatusCode::CONFLICT)
+    } else if let Some(m) = e.downcast_ref::<UserDynamoDBError>() {
+        build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+    } else if let Some(m) = e.downcast_ref::<OwnerDynamoDBError>() {
+        build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+    } else {
+        build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+    }
+}
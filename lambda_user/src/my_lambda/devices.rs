@@ -0,0 +1,103 @@
+use lambda_http::RequestPayloadExt;
+use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
+use lib_config::config::Config;
+use lib_users::errors::devices::{
+    DeviceAlreadyExistsError, DeviceDynamoDBError, DeviceKeyError, DeviceNoExistsError,
+};
+use lib_users::services::devices::{DeviceManipulation, DeviceService};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::build_resp;
+
+#[derive(Serialize, Validate, Deserialize)]
+pub struct NewDevice {
+    #[validate(length(min = 1, max = 200))]
+    pub device: String,
+    // base64 DER (SubjectPublicKeyInfo) of the device key
+    #[validate(length(min = 1, max = 500))]
+    pub public_key: String,
+    pub algorithm: String,
+}
+
+//#[instrument]
+pub async fn get_my_devices(
+    _req: &Request,
+    _c: &Context,
+    _config: &Config,
+    device_service: &DeviceService,
+    id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    match device_service.get_by_user(id).await {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<DeviceDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+        Ok(devices) => {
+            let active: Vec<_> = devices.into_iter().filter(|d| !d.revoked()).collect();
+            build_resp(serde_json::to_string(&active)?, StatusCode::OK)
+        }
+    }
+}
+
+//#[instrument]
+pub async fn register_my_device(
+    req: &Request,
+    _c: &Context,
+    _config: &Config,
+    device_service: &DeviceService,
+    id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let payload = match req.payload::<NewDevice>() {
+        Err(e) => return build_resp(e.to_string(), StatusCode::BAD_REQUEST),
+        Ok(None) => return build_resp("no payload found".to_string(), StatusCode::BAD_REQUEST),
+        Ok(Some(payload)) => payload,
+    };
+    if let Err(e) = payload.validate() {
+        return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+    }
+
+    let op_res = device_service
+        .register(id, &payload.device, &payload.algorithm, &payload.public_key)
+        .await;
+    match op_res {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<DeviceDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else if let Some(m) = e.downcast_ref::<DeviceKeyError>() {
+                build_resp(m.to_string(), StatusCode::BAD_REQUEST)
+            } else if let Some(m) = e.downcast_ref::<DeviceAlreadyExistsError>() {
+                build_resp(m.to_string(), StatusCode::NOT_ACCEPTABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+        Ok(device) => build_resp(serde_json::to_string(&device)?, StatusCode::CREATED),
+    }
+}
+
+//#[instrument]
+pub async fn revoke_my_device(
+    _req: &Request,
+    _c: &Context,
+    _config: &Config,
+    device_service: &DeviceService,
+    id: &String,
+    device_id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    match device_service.revoke(id, device_id).await {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<DeviceDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else if let Some(m) = e.downcast_ref::<DeviceNoExistsError>() {
+                build_resp(m.to_string(), StatusCode::NOT_FOUND)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+        Ok(_) => build_resp("".to_string(), StatusCode::OK),
+    }
+}
@@ -0,0 +1,36 @@
+use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
+use lib_config::config::Config;
+use lib_engage::services::data_export::DataExportService;
+use lib_licenses::errors::asset::AssetDynamoDBError;
+use lib_licenses::errors::owner::OwnerDynamoDBError;
+use lib_users::errors::users::{UserDynamoDBError, UserNoExistsError};
+
+use super::build_resp;
+
+// profile, login methods, assets with their licenses, subscriptions, alerts, license requests and
+// grants in a single json
+//#[instrument]
+pub async fn export_my_user(
+    _req: &Request,
+    _c: &Context,
+    _config: &Config,
+    data_export_service: &DataExportService,
+    id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    match data_export_service.export(id).await {
+        Ok(export) => build_resp(serde_json::to_string(&export)?, StatusCode::OK),
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<UserNoExistsError>() {
+                build_resp(m.to_string(), StatusCode::NOT_FOUND)
+            } else if let Some(m) = e.downcast_ref::<UserDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else if let Some(m) = e.downcast_ref::<OwnerDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else if let Some(m) = e.downcast_ref::<AssetDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+    }
+}
@@ -0,0 +1,365 @@
+use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
+use lambda_http::{RequestExt, RequestPayloadExt};
+use lib_config::config::Config;
+use lib_engage::repositories::sender::SenderEmailsRepo;
+use lib_users::errors::devices::{DeviceDynamoDBError, DeviceSignatureError};
+use lib_users::errors::identities::{IdentityDynamoDBError, OidcProviderError, OidcTokenError};
+use lib_users::errors::login_methods::{LoginMethodLastError, LoginMethodNotFoundError};
+use lib_users::errors::one_time_tokens::{
+    OneTimeTokenDynamoDBError, OneTimeTokenError, OneTimeTokenRateLimitError,
+};
+use lib_users::errors::sessions::{SessionDynamoDBError, WalletSignatureError};
+use lib_users::errors::users::{
+    UserAlreadyExistsError, UserDynamoDBError, UserNoExistsError, UserParamNotAccepted,
+};
+use lib_users::models::login_method::LoginMethodKind;
+use lib_users::models::one_time_token::OneTimeTokenPurpose;
+use lib_users::services::devices::{DeviceManipulation, DeviceService};
+use lib_users::services::login_methods::{LoginMethodManipulation, LoginMethodService};
+use lib_users::services::oidc_login::{OidcLoginManipulation, OidcLoginService};
+use lib_users::services::one_time_tokens::{
+    OneTimeTokenManipulation, OneTimeTokenService, EMAIL_LINK_EXP_HOURS,
+};
+use lib_users::services::wallet_login::{WalletLoginManipulation, WalletLoginService};
+use serde::Deserialize;
+use validator::Validate;
+
+use super::build_resp;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct NewEmailLogin {
+    #[validate(email)]
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ConfirmEmailLogin {
+    #[validate(length(min = 1, max = 200))]
+    pub token: String,
+}
+
+// a device paired at /api/user/devices answering a challenge from /auth/device/challenge
+#[derive(Debug, Deserialize, Validate)]
+pub struct NewDeviceLogin {
+    #[validate(length(min = 1, max = 200))]
+    pub device: String,
+    #[validate(length(min = 1, max = 100))]
+    pub challenge: String,
+    #[validate(length(min = 1, max = 200))]
+    pub signature: String,
+}
+
+// signed the same way as at /auth/wallet/login, with a nonce from /auth/wallet/nonce
+#[derive(Debug, Deserialize, Validate)]
+pub struct NewWalletLogin {
+    #[validate(length(min = 1, max = 2000))]
+    pub message: String,
+    #[validate(length(min = 130, max = 132))]
+    pub signature: String,
+}
+
+// an ID token issued with a nonce from /auth/oidc/nonce
+#[derive(Debug, Deserialize, Validate)]
+pub struct NewOidcLogin {
+    #[validate(length(min = 1, max = 8000))]
+    pub id_token: String,
+    #[validate(length(min = 1, max = 100))]
+    pub nonce: String,
+}
+
+fn login_method_error(
+    e: Box<dyn std::error::Error + Sync + Send>,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    if let Some(m) = e.downcast_ref::<UserAlreadyExistsError>() {
+        build_resp(m.to_string(), StatusCode::CONFLICT)
+    } else if let Some(m) = e.downcast_ref::<LoginMethodLastError>() {
+        build_resp(m.to_string(), StatusCode::CONFLICT)
+    } else if let Some(m) = e.downcast_ref::<LoginMethodNotFoundError>() {
+        build_resp(m.to_string(), StatusCode::NOT_FOUND)
+    } else if let Some(m) = e.downcast_ref::<UserNoExistsError>() {
+        build_resp(m.to_string(), StatusCode::NOT_FOUND)
+    } else if let Some(m) = e.downcast_ref::<UserParamNotAccepted>() {
+        build_resp(m.to_string(), StatusCode::BAD_REQUEST)
+    } else if let Some(m) = e.downcast_ref::<UserDynamoDBError>() {
+        build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+    } else if let Some(m) = e.downcast_ref::<IdentityDynamoDBError>() {
+        build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+    } else if let Some(m) = e.downcast_ref::<DeviceDynamoDBError>() {
+        build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+    } else {
+        build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+    }
+}
+
+//#[instrument]
+pub async fn get_my_logins(
+    _req: &Request,
+    _c: &Context,
+    _config: &Config,
+    login_method_service: &LoginMethodService,
+    id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    match login_method_service.get(id).await {
+        Err(e) => login_method_error(e),
+        Ok(methods) => build_resp(serde_json::to_string(&methods)?, StatusCode::OK),
+    }
+}
+
+// the email is linked once the link mailed to it is confirmed
+pub async fn request_my_email_login(
+    req: &Request,
+    _c: &Context,
+    _config: &Config,
+    login_method_service: &LoginMethodService,
+    one_time_token_service: &OneTimeTokenService,
+    sender_repo: &SenderEmailsRepo,
+    id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let payload = match req.payload::<NewEmailLogin>() {
+        Err(e) => return build_resp(e.to_string(), StatusCode::BAD_REQUEST),
+        Ok(None) => return build_resp("no payload found".to_string(), StatusCode::BAD_REQUEST),
+        Ok(Some(payload)) => payload,
+    };
+    if let Err(e) = payload.validate() {
+        return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+    }
+
+    if let Err(e) = login_method_service
+        .check_email_available(id, &payload.email)
+        .await
+    {
+        return login_method_error(e);
+    }
+
+    let token = match one_time_token_service
+        .issue(
+            id,
+            &payload.email,
+            &OneTimeTokenPurpose::EmailLink,
+            EMAIL_LINK_EXP_HOURS,
+        )
+        .await
+    {
+        Err(e) => {
+            return if let Some(m) = e.downcast_ref::<OneTimeTokenRateLimitError>() {
+                build_resp(m.to_string(), StatusCode::TOO_MANY_REQUESTS)
+            } else if let Some(m) = e.downcast_ref::<OneTimeTokenDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            };
+        }
+        Ok(token) => token,
+    };
+
+    match sender_repo.send_email_link(payload.email, token).await {
+        Err(e) => build_resp(e.to_string(), StatusCode::SERVICE_UNAVAILABLE),
+        Ok(_) => build_resp("".to_string(), StatusCode::ACCEPTED),
+    }
+}
+
+pub async fn confirm_my_email_login(
+    req: &Request,
+    _c: &Context,
+    _config: &Config,
+    login_method_service: &LoginMethodService,
+    one_time_token_service: &OneTimeTokenService,
+    id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let payload = match req.payload::<ConfirmEmailLogin>() {
+        Err(e) => return build_resp(e.to_string(), StatusCode::BAD_REQUEST),
+        Ok(None) => return build_resp("no payload found".to_string(), StatusCode::BAD_REQUEST),
+        Ok(Some(payload)) => payload,
+    };
+    if let Err(e) = payload.validate() {
+        return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+    }
+
+    let token = match one_time_token_service
+        .consume(&payload.token, &OneTimeTokenPurpose::EmailLink)
+        .await
+    {
+        Err(e) => {
+            return if let Some(m) = e.downcast_ref::<OneTimeTokenError>() {
+                build_resp(m.to_string(), StatusCode::BAD_REQUEST)
+            } else if let Some(m) = e.downcast_ref::<OneTimeTokenDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            };
+        }
+        Ok(token) => token,
+    };
+    // the link only works for the account that asked for it
+    if token.user_id() != id {
+        return build_resp(
+            "the link was sent for another account".to_string(),
+            StatusCode::FORBIDDEN,
+        );
+    }
+
+    match login_method_service.link_email(id, token.email()).await {
+        Err(e) => login_method_error(e),
+        Ok(_) => build_resp("".to_string(), StatusCode::OK),
+    }
+}
+
+pub async fn add_my_device_login(
+    req: &Request,
+    _c: &Context,
+    _config: &Config,
+    login_method_service: &LoginMethodService,
+    device_service: &DeviceService,
+    id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let payload = match req.payload::<NewDeviceLogin>() {
+        Err(e) => return build_resp(e.to_string(), StatusCode::BAD_REQUEST),
+        Ok(None) => return build_resp("no payload found".to_string(), StatusCode::BAD_REQUEST),
+        Ok(Some(payload)) => payload,
+    };
+    if let Err(e) = payload.validate() {
+        return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+    }
+
+    let device = match device_service
+        .verify(&payload.device, &payload.challenge, &payload.signature)
+        .await
+    {
+        Err(e) => {
+            return if let Some(m) = e.downcast_ref::<DeviceSignatureError>() {
+                build_resp(m.to_string(), StatusCode::UNAUTHORIZED)
+            } else if let Some(m) = e.downcast_ref::<DeviceDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            };
+        }
+        Ok(device) => device,
+    };
+
+    match login_method_service.link_device(id, &device).await {
+        Err(e) => login_method_error(e),
+        Ok(_) => build_resp("".to_string(), StatusCode::CREATED),
+    }
+}
+
+pub async fn add_my_wallet_login(
+    req: &Request,
+    _c: &Context,
+    _config: &Config,
+    login_method_service: &LoginMethodService,
+    wallet_service: &WalletLoginService,
+    id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let payload = match req.payload::<NewWalletLogin>() {
+        Err(e) => return build_resp(e.to_string(), StatusCode::BAD_REQUEST),
+        Ok(None) => return build_resp("no payload found".to_string(), StatusCode::BAD_REQUEST),
+        Ok(Some(payload)) => payload,
+    };
+    if let Err(e) = payload.validate() {
+        return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+    }
+
+    let wallet = match wallet_service
+        .verify(&payload.message, &payload.signature)
+        .await
+    {
+        Err(e) => {
+            return if let Some(m) = e.downcast_ref::<WalletSignatureError>() {
+                build_resp(m.to_string(), StatusCode::UNAUTHORIZED)
+            } else if let Some(m) = e.downcast_ref::<SessionDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            };
+        }
+        Ok(wallet) => wallet,
+    };
+
+    match login_method_service.link_wallet(id, &wallet).await {
+        Err(e) => login_method_error(e),
+        Ok(_) => build_resp("".to_string(), StatusCode::CREATED),
+    }
+}
+
+pub async fn add_my_oidc_login(
+    req: &Request,
+    _c: &Context,
+    _config: &Config,
+    login_method_service: &LoginMethodService,
+    oidc_service: &OidcLoginService,
+    id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let payload = match req.payload::<NewOidcLogin>() {
+        Err(e) => return build_resp(e.to_string(), StatusCode::BAD_REQUEST),
+        Ok(None) => return build_resp("no payload found".to_string(), StatusCode::BAD_REQUEST),
+        Ok(Some(payload)) => payload,
+    };
+    if let Err(e) = payload.validate() {
+        return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+    }
+
+    let identity = match oidc_service.verify(&payload.id_token, &payload.nonce).await {
+        Err(e) => {
+            return if let Some(m) = e.downcast_ref::<OidcTokenError>() {
+                build_resp(m.to_string(), StatusCode::UNAUTHORIZED)
+            } else if let Some(m) = e.downcast_ref::<OidcProviderError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else if let Some(m) = e.downcast_ref::<SessionDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            };
+        }
+        Ok(identity) => identity,
+    };
+
+    match login_method_service.link_identity(id, &identity).await {
+        Err(e) => login_method_error(e),
+        Ok(_) => build_resp("".to_string(), StatusCode::CREATED),
+    }
+}
+
+// /api/user/logins/{email|device|wallet}, or oidc along with ?issuer=&subject=
+pub async fn remove_my_login(
+    req: &Request,
+    _c: &Context,
+    _config: &Config,
+    login_method_service: &LoginMethodService,
+    id: &String,
+    kind: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let kind = match kind.parse::<LoginMethodKind>() {
+        Err(_) => {
+            return build_resp(
+                "email, device, wallet or oidc".to_string(),
+                StatusCode::BAD_REQUEST,
+            )
+        }
+        Ok(kind) => kind,
+    };
+
+    let op_res = match kind {
+        LoginMethodKind::Oidc => {
+            let params = req.query_string_parameters();
+            match (params.first("issuer"), params.first("subject")) {
+                (Some(issuer), Some(subject)) => {
+                    login_method_service
+                        .unlink_identity(id, &issuer.to_string(), &subject.to_string())
+                        .await
+                }
+                _ => {
+                    return build_resp(
+                        "issuer and subject are mandatory".to_string(),
+                        StatusCode::BAD_REQUEST,
+                    )
+                }
+            }
+        }
+        _ => login_method_service.unlink(id, &kind).await,
+    };
+    match op_res {
+        Err(e) => login_method_error(e),
+        Ok(_) => build_resp("".to_string(), StatusCode::OK),
+    }
+}
@@ -0,0 +1,157 @@
+use lambda_http::RequestPayloadExt;
+use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
+use lib_config::config::Config;
+use lib_engage::errors::account_merge::AccountMergeError;
+use lib_engage::repositories::sender::SenderEmailsRepo;
+use lib_engage::services::account_merge::AccountMergeService;
+use lib_licenses::errors::license_request::LicenseRequestDynamoDBError;
+use lib_users::errors::one_time_tokens::{
+    OneTimeTokenDynamoDBError, OneTimeTokenError, OneTimeTokenRateLimitError,
+};
+use lib_users::errors::users::UserNoExistsError;
+use lib_users::models::one_time_token::OneTimeTokenPurpose;
+use lib_users::services::one_time_tokens::{
+    OneTimeTokenManipulation, OneTimeTokenService, ACCOUNT_MERGE_EXP_HOURS,
+};
+use lib_users::services::users::{UserManipulation, UsersService};
+use serde::Deserialize;
+use validator::Validate;
+
+use super::build_resp;
+use super::delete_my_user::deletion_error;
+
+// the email of the full account that will keep everything
+#[derive(Debug, Deserialize, Validate)]
+pub struct NewMerge {
+    #[validate(email)]
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ConfirmMerge {
+    #[validate(length(min = 1, max = 200))]
+    pub token: String,
+}
+
+// Asked from the device account. The answer is the same whether the email has an account
+// or not, so nobody can use it to find out who is registered.
+#[allow(clippy::too_many_arguments)]
+pub async fn request_my_merge(
+    req: &Request,
+    _c: &Context,
+    _config: &Config,
+    user_service: &UsersService,
+    account_merge_service: &AccountMergeService,
+    one_time_token_service: &OneTimeTokenService,
+    sender_repo: &SenderEmailsRepo,
+    id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let payload = match req.payload::<NewMerge>() {
+        Err(e) => return build_resp(e.to_string(), StatusCode::BAD_REQUEST),
+        Ok(None) => return build_resp("no payload found".to_string(), StatusCode::BAD_REQUEST),
+        Ok(Some(payload)) => payload,
+    };
+    if let Err(e) = payload.validate() {
+        return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+    }
+
+    let into = match user_service.get_by_email(&payload.email).await {
+        Err(e) if e.downcast_ref::<UserNoExistsError>().is_some() => {
+            return build_resp("".to_string(), StatusCode::ACCEPTED);
+        }
+        Err(e) => return merge_error(e),
+        Ok(into) => into,
+    };
+    if let Err(e) = account_merge_service.check(id, into.user_id()).await {
+        return merge_error(e);
+    }
+
+    // throttled or failed sends answer the same as the rest, only the logs tell them apart
+    match one_time_token_service
+        .issue(
+            id,
+            &payload.email,
+            &OneTimeTokenPurpose::AccountMerge,
+            ACCOUNT_MERGE_EXP_HOURS,
+        )
+        .await
+    {
+        Err(e) => {
+            if let Some(m) = e.downcast_ref::<OneTimeTokenRateLimitError>() {
+                log::warn!("account merge throttled for user {}: {}", id, m);
+            } else {
+                log::error!("account merge token not issued for user {}: {}", id, e);
+            }
+        }
+        Ok(token) => {
+            if let Err(e) = sender_repo.send_account_merge(payload.email, token).await {
+                log::error!("account merge email not sent for user {}: {}", id, e);
+            }
+        }
+    }
+    build_resp("".to_string(), StatusCode::ACCEPTED)
+}
+
+// Confirmed from the full account, signed in with the email the link was sent to.
+pub async fn confirm_my_merge(
+    req: &Request,
+    _c: &Context,
+    _config: &Config,
+    user_service: &UsersService,
+    account_merge_service: &AccountMergeService,
+    one_time_token_service: &OneTimeTokenService,
+    id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let payload = match req.payload::<ConfirmMerge>() {
+        Err(e) => return build_resp(e.to_string(), StatusCode::BAD_REQUEST),
+        Ok(None) => return build_resp("no payload found".to_string(), StatusCode::BAD_REQUEST),
+        Ok(Some(payload)) => payload,
+    };
+    if let Err(e) = payload.validate() {
+        return build_resp(e.to_string(), StatusCode::BAD_REQUEST);
+    }
+
+    let user = match user_service.get_by_id(id).await {
+        Err(e) => return merge_error(e),
+        Ok(user) => user,
+    };
+
+    let token = match one_time_token_service
+        .consume(&payload.token, &OneTimeTokenPurpose::AccountMerge)
+        .await
+    {
+        Err(e) => {
+            return if let Some(m) = e.downcast_ref::<OneTimeTokenError>() {
+                build_resp(m.to_string(), StatusCode::BAD_REQUEST)
+            } else if let Some(m) = e.downcast_ref::<OneTimeTokenDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            };
+        }
+        Ok(token) => token,
+    };
+    if user.email().as_ref() != Some(token.email()) {
+        return build_resp(
+            "the link was sent to another account".to_string(),
+            StatusCode::FORBIDDEN,
+        );
+    }
+
+    match account_merge_service.merge(token.user_id(), id).await {
+        Err(e) => merge_error(e),
+        Ok(_) => build_resp("".to_string(), StatusCode::OK),
+    }
+}
+
+fn merge_error(
+    e: Box<dyn std::error::Error + Sync + Send>,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    if let Some(m) = e.downcast_ref::<AccountMergeError>() {
+        build_resp(m.to_string(), StatusCode::CONFLICT)
+    } else if let Some(m) = e.downcast_ref::<LicenseRequestDynamoDBError>() {
+        build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+    } else {
+        deletion_error(e)
+    }
+}
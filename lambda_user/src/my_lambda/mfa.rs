@@ -0,0 +1,111 @@
+use lambda_http::RequestPayloadExt;
+use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
+use lib_config::config::Config;
+use lib_users::errors::mfa::{
+    MfaAlreadyEnabledError, MfaCodeError, MfaDynamoDBError, MfaNotEnabledError,
+};
+use lib_users::errors::users::{UserDynamoDBError, UserNoExistsError};
+use lib_users::services::mfa::{MfaManipulation, MfaService};
+use lib_users::services::users::{UserManipulation, UsersService};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::build_resp;
+
+#[derive(Serialize, Validate, Deserialize)]
+pub struct MfaCode {
+    // the totp of the authenticator app or, to disable it, a recovery code as well
+    #[validate(length(min = 6, max = 20))]
+    pub code: String,
+}
+
+fn mfa_error(e: Box<dyn std::error::Error + Sync + Send>) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    if let Some(m) = e.downcast_ref::<MfaCodeError>() {
+        build_resp(m.to_string(), StatusCode::BAD_REQUEST)
+    } else if let Some(m) = e.downcast_ref::<MfaAlreadyEnabledError>() {
+        build_resp(m.to_string(), StatusCode::NOT_ACCEPTABLE)
+    } else if let Some(m) = e.downcast_ref::<MfaNotEnabledError>() {
+        build_resp(m.to_string(), StatusCode::NOT_FOUND)
+    } else if let Some(m) = e.downcast_ref::<MfaDynamoDBError>() {
+        build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+    } else {
+        build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+    }
+}
+
+fn code_payload(req: &Request) -> Result<MfaCode, Result<Response<String>, Box<dyn std::error::Error>>> {
+    match req.payload::<MfaCode>() {
+        Err(e) => Err(build_resp(e.to_string(), StatusCode::BAD_REQUEST)),
+        Ok(None) => Err(build_resp("code field is mandatory".to_string(), StatusCode::BAD_REQUEST)),
+        Ok(Some(payload)) => match payload.validate() {
+            Err(e) => Err(build_resp(e.to_string(), StatusCode::BAD_REQUEST)),
+            Ok(_) => Ok(payload),
+        },
+    }
+}
+
+// secret, otpauth uri for the QR code and recovery codes, all shown only this once
+//#[instrument]
+pub async fn enroll_my_mfa(
+    _req: &Request,
+    _c: &Context,
+    _config: &Config,
+    user_service: &UsersService,
+    mfa_service: &MfaService,
+    id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let user = match user_service.get_by_id(id).await {
+        Err(e) => {
+            return if let Some(m) = e.downcast_ref::<UserDynamoDBError>() {
+                build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE)
+            } else if let Some(m) = e.downcast_ref::<UserNoExistsError>() {
+                build_resp(m.to_string(), StatusCode::NO_CONTENT)
+            } else {
+                build_resp(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+            };
+        }
+        Ok(user) => user,
+    };
+    let account = user.email().clone().unwrap_or_else(|| user.user_id().clone());
+
+    match mfa_service.enroll(id, &account).await {
+        Err(e) => mfa_error(e),
+        Ok(enrollment) => build_resp(serde_json::to_string(&enrollment)?, StatusCode::CREATED),
+    }
+}
+
+//#[instrument]
+pub async fn confirm_my_mfa(
+    req: &Request,
+    _c: &Context,
+    _config: &Config,
+    mfa_service: &MfaService,
+    id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let payload = match code_payload(req) {
+        Err(resp) => return resp,
+        Ok(payload) => payload,
+    };
+    match mfa_service.confirm(id, &payload.code).await {
+        Err(e) => mfa_error(e),
+        Ok(_) => build_resp("".to_string(), StatusCode::OK),
+    }
+}
+
+//#[instrument]
+pub async fn disable_my_mfa(
+    req: &Request,
+    _c: &Context,
+    _config: &Config,
+    mfa_service: &MfaService,
+    id: &String,
+) -> Result<Response<String>, Box<dyn std::error::Error>> {
+    let payload = match code_payload(req) {
+        Err(resp) => return resp,
+        Ok(payload) => payload,
+    };
+    match mfa_service.disable(id, &payload.code).await {
+        Err(e) => mfa_error(e),
+        Ok(_) => build_resp("".to_string(), StatusCode::OK),
+    }
+}
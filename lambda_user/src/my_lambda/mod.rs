@@ -1,37 +1,94 @@
+mod api_keys;
+mod delete_my_user;
+mod devices;
+mod export_my_user;
 pub mod error;
 mod get_my_user;
+mod logins;
+mod merge;
+mod mfa;
 mod update_my_password;
 mod update_my_user;
 
+use self::api_keys::{create_my_api_key, get_my_api_keys, revoke_my_api_key};
+use self::delete_my_user::delete_my_user;
+use self::devices::{get_my_devices, register_my_device, revoke_my_device};
 use self::error::ApiLambdaUserError;
+use self::export_my_user::export_my_user;
 use self::get_my_user::get_my_user;
+use self::logins::{
+    add_my_device_login, add_my_oidc_login, add_my_wallet_login, confirm_my_email_login,
+    get_my_logins, remove_my_login, request_my_email_login,
+};
+use self::merge::{confirm_my_merge, request_my_merge};
+use self::mfa::{confirm_my_mfa, disable_my_mfa, enroll_my_mfa};
 use self::update_my_password::password_update_my_user;
 use self::update_my_user::update_my_user;
 use lambda_http::{http::Method, http::StatusCode, IntoResponse, Request, RequestExt, Response};
 use lib_config::config::Config;
+use lib_engage::services::account_deletion::AccountDeletionService;
+use lib_engage::services::account_merge::AccountMergeService;
+use lib_engage::repositories::sender::SenderEmailsRepo;
+use lib_engage::services::data_export::DataExportService;
+use lib_users::services::api_keys::ApiKeyService;
+use lib_users::services::devices::DeviceService;
+use lib_users::services::login_attempts::LoginAttemptService;
+use lib_users::services::login_methods::LoginMethodService;
+use lib_users::services::mfa::MfaService;
+use lib_users::services::oidc_login::OidcLoginService;
+use lib_users::services::one_time_tokens::OneTimeTokenService;
 use lib_users::services::users::UsersService;
-use lib_util_jwt::jwt::{get_header_jwt, JWTSecurityError};
+use lib_users::services::wallet_login::WalletLoginService;
+use lib_util_jwt::auth::AuthOutcome;
+use lib_util_jwt::jwt::TokenVerifier;
 
 //#[instrument]
+#[allow(clippy::too_many_arguments)]
 pub async fn function_handler(
     config: &Config,
     user_service: &UsersService,
+    device_service: &DeviceService,
+    mfa_service: &MfaService,
+    api_key_service: &ApiKeyService,
+    login_attempt_service: &LoginAttemptService,
+    account_deletion_service: &AccountDeletionService,
+    account_merge_service: &AccountMergeService,
+    data_export_service: &DataExportService,
+    login_method_service: &LoginMethodService,
+    wallet_service: &WalletLoginService,
+    oidc_service: &OidcLoginService,
+    one_time_token_service: &OneTimeTokenService,
+    sender_repo: &SenderEmailsRepo,
+    verifier: &TokenVerifier,
     req: Request,
 ) -> Result<impl IntoResponse, Box<dyn std::error::Error>> {
     let context = req.lambda_context();
     //let query_string = req.query_string_parameters().to_owned();
     //request.uri().path()
     let user_id;
-    match check_jwt_token_as_user_logged(&req, config) {
+    let auth = AuthOutcome::extract(&req, config, verifier).await;
+    match auth.required() {
         Err(e) => {
-            return build_resp(e.to_string(), StatusCode::UNAUTHORIZED);
+            return Ok(e);
         }
-        Ok(id) => user_id = id,
+        Ok(auth) => user_id = auth.user_id().clone(),
     }
 
     match req.method() {
         &Method::GET => match req.uri().path() {
             "/api/user" => get_my_user(&req, &context, config, user_service, &user_id).await,
+            "/api/user/devices" => {
+                get_my_devices(&req, &context, config, device_service, &user_id).await
+            }
+            "/api/user/export" => {
+                export_my_user(&req, &context, config, data_export_service, &user_id).await
+            }
+            "/api/user/keys" => {
+                get_my_api_keys(&req, &context, config, api_key_service, &user_id).await
+            }
+            "/api/user/logins" => {
+                get_my_logins(&req, &context, config, login_method_service, &user_id).await
+            }
             &_ => build_resp(
                 "method not allowed".to_string(),
                 StatusCode::METHOD_NOT_ALLOWED,
@@ -47,6 +104,82 @@ pub async fn function_handler(
                 StatusCode::METHOD_NOT_ALLOWED,
             ),
         },
+        &Method::POST => match req.uri().path() {
+            "/api/user/devices" => {
+                register_my_device(&req, &context, config, device_service, &user_id).await
+            }
+            "/api/user/keys" => {
+                create_my_api_key(&req, &context, config, api_key_service, &user_id).await
+            }
+            "/api/user/mfa" => {
+                enroll_my_mfa(&req, &context, config, user_service, mfa_service, &user_id).await
+            }
+            "/api/user/mfa/confirm" => {
+                confirm_my_mfa(&req, &context, config, mfa_service, &user_id).await
+            }
+            "/api/user/logins/email" => {
+                request_my_email_login(&req, &context, config, login_method_service, one_time_token_service, sender_repo, &user_id).await
+            }
+            "/api/user/logins/email/confirm" => {
+                confirm_my_email_login(&req, &context, config, login_method_service, one_time_token_service, &user_id).await
+            }
+            "/api/user/logins/device" => {
+                add_my_device_login(&req, &context, config, login_method_service, device_service, &user_id).await
+            }
+            "/api/user/logins/wallet" => {
+                add_my_wallet_login(&req, &context, config, login_method_service, wallet_service, &user_id).await
+            }
+            "/api/user/logins/oidc" => {
+                add_my_oidc_login(&req, &context, config, login_method_service, oidc_service, &user_id).await
+            }
+            "/api/user/merge" => {
+                request_my_merge(&req, &context, config, user_service, account_merge_service, one_time_token_service, sender_repo, &user_id).await
+            }
+            "/api/user/merge/confirm" => {
+                confirm_my_merge(&req, &context, config, user_service, account_merge_service, one_time_token_service, &user_id).await
+            }
+            &_ => build_resp(
+                "method not allowed".to_string(),
+                StatusCode::METHOD_NOT_ALLOWED,
+            ),
+        },
+        &Method::DELETE if req.uri().path() == "/api/user" => {
+            delete_my_user(&req, &context, config, user_service, login_attempt_service, account_deletion_service, &user_id).await
+        }
+        &Method::DELETE if req.uri().path() == "/api/user/mfa" => {
+            disable_my_mfa(&req, &context, config, mfa_service, &user_id).await
+        }
+        &Method::DELETE if req.uri().path().starts_with("/api/user/keys/") => {
+            match req.uri().path().strip_prefix("/api/user/keys/") {
+                Some(key_id) if !key_id.is_empty() => {
+                    revoke_my_api_key(&req, &context, config, api_key_service, &user_id, &key_id.to_string()).await
+                }
+                _ => build_resp(
+                    "method not allowed".to_string(),
+                    StatusCode::METHOD_NOT_ALLOWED,
+                ),
+            }
+        }
+        &Method::DELETE if req.uri().path().starts_with("/api/user/logins/") => {
+            match req.uri().path().strip_prefix("/api/user/logins/") {
+                Some(kind) if !kind.is_empty() => {
+                    remove_my_login(&req, &context, config, login_method_service, &user_id, &kind.to_string()).await
+                }
+                _ => build_resp(
+                    "method not allowed".to_string(),
+                    StatusCode::METHOD_NOT_ALLOWED,
+                ),
+            }
+        }
+        &Method::DELETE => match req.uri().path().strip_prefix("/api/user/devices/") {
+            Some(device_id) if !device_id.is_empty() => {
+                revoke_my_device(&req, &context, config, device_service, &user_id, &device_id.to_string()).await
+            }
+            _ => build_resp(
+                "method not allowed".to_string(),
+                StatusCode::METHOD_NOT_ALLOWED,
+            ),
+        },
         _ => build_resp(
             "http verb doesn't use it here".to_string(),
             StatusCode::METHOD_NOT_ALLOWED,
@@ -70,24 +203,3 @@ fn build_resp(
     }
     //Ok(res)
 }
-
-fn check_jwt_token_as_user_logged(
-    req: &Request,
-    config: &Config,
-) -> Result<String, JWTSecurityError> {
-    let user_id;
-    let req_headers = req.headers();
-
-    let jwt_secret = config.env_vars().jwt_token_base().unwrap();
-    let claim_ops = get_header_jwt(req_headers, &jwt_secret);
-
-    match claim_ops {
-        Ok(clm) => {
-            user_id = clm.uid;
-        }
-        Err(e) => {
-            return Err(e);
-        }
-    }
-    Ok(user_id)
-}
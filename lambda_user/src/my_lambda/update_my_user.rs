@@ -1,7 +1,7 @@
 use lambda_http::RequestPayloadExt;
 use lambda_http::{http::StatusCode, lambda_runtime::Context, Request, Response};
 use lib_config::config::Config;
-use lib_users::errors::users::{UserDynamoDBError, UserNoExistsError};
+use lib_users::errors::users::{UserAlreadyExistsError, UserDynamoDBError, UserNoExistsError};
 use lib_users::services::users::{UpdatableFildsUser, UserManipulation, UsersService};
 use serde::{Deserialize, Serialize};
 use validator::{Validate, ValidationError};
@@ -55,6 +55,8 @@ pub async fn update_my_user(
                 return build_resp(m.to_string(), StatusCode::SERVICE_UNAVAILABLE);
             } else if let Some(m) = e.downcast_ref::<UserNoExistsError>() {
                 return build_resp(m.to_string(), StatusCode::NO_CONTENT);
+            } else if let Some(m) = e.downcast_ref::<UserAlreadyExistsError>() {
+                return build_resp(m.to_string(), StatusCode::CONFLICT);
             } else if let Some(m) = e.downcast_ref::<ValidationError>() {
                 return build_resp(m.to_string(), StatusCode::BAD_REQUEST);
             } else {
@@ -0,0 +1,17 @@
+#[derive(Debug, thiserror::Error)]
+pub enum AccountDeletionError {
+    #[error("User {0} can't inherit the assets, it doesn't exist")]
+    HeirNotFound(String),
+
+    #[error("Transferring assets needs the user that receives them")]
+    HeirMissing,
+
+    #[error("Assets can't be transferred to the account being deleted")]
+    HeirIsSameUser,
+
+    #[error("Only an administrator can hand the assets to another user")]
+    TransferNotAllowed,
+
+    #[error("Unknown asset policy {0}, use anonymize or transfer")]
+    UnknownAssetPolicy(String),
+}
@@ -0,0 +1,11 @@
+#[derive(Debug, thiserror::Error)]
+pub enum AccountMergeError {
+    #[error("An account can't be merged into itself")]
+    SameUser,
+
+    #[error("User {0} signs in with an email, only device accounts can be merged")]
+    SourceNotAnonymous(String),
+
+    #[error("User {0} has no email, accounts are merged into a full account")]
+    TargetWithoutEmail(String),
+}
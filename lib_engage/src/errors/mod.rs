@@ -1,2 +1,4 @@
+pub mod account_deletion;
+pub mod account_merge;
 pub mod alert_similar;
 pub mod subscription;
\ No newline at end of file
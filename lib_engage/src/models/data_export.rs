@@ -0,0 +1,45 @@
+use crate::models::alert_similar::AlertSimilar;
+use crate::models::subscription::Subscription;
+use chrono::{DateTime, Utc};
+use lib_licenses::models::asset::Asset;
+use lib_licenses::models::license::License;
+use lib_licenses::models::license_grant::LicenseGrant;
+use lib_licenses::models::license_request::LicenseRequest;
+use lib_licenses::models::owner::Owner;
+use lib_users::models::api_key::ApiKey;
+use lib_users::models::device::DeviceCredential;
+use lib_users::models::identity::LinkedIdentity;
+use lib_users::models::user::User;
+use serde::Serialize;
+
+// Everything we keep about a user, handed over on a personal data request.
+#[derive(Debug, Serialize)]
+pub struct UserDataExport {
+    pub generated_at: DateTime<Utc>,
+    // email, wallet and device login are part of it
+    pub profile: User,
+    pub login_methods: LoginMethods,
+    pub assets: Vec<OwnedAsset>,
+    pub subscriptions: Vec<Subscription>,
+    pub alerts: Vec<AlertSimilar>,
+    // the ones the user asked for, as a requester
+    pub license_requests: Vec<LicenseRequest>,
+    pub license_grants: Vec<LicenseGrant>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LoginMethods {
+    pub devices: Vec<DeviceCredential>,
+    // hashes are never serialized
+    pub api_keys: Vec<ApiKey>,
+    // google, apple or other oidc accounts
+    pub identities: Vec<LinkedIdentity>,
+    pub mfa_enabled: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OwnedAsset {
+    pub asset: Asset,
+    pub ownership: Owner,
+    pub licenses: Vec<License>,
+}
@@ -1,2 +1,3 @@
 pub mod alert_similar;
+pub mod data_export;
 pub mod subscription;
\ No newline at end of file
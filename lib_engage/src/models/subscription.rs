@@ -1,9 +1,10 @@
 use uuid::Uuid;
 use chrono::prelude::*;
+use serde::Serialize;
 use std::fmt;
 use std::str::FromStr;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum ConfirmedStatus {
     Enabled,
     Disabled,
@@ -30,7 +31,7 @@ impl fmt::Display for ConfirmedStatus {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Subscription {
     pub id: Uuid,
     pub user_id: String,
@@ -9,7 +9,10 @@ use chrono::{Utc, Local};
 use lib_config::pagination::{pagination_encode_token, pagination_decode_token };
 use lib_config::pagination::AttributeValueWrapper;
 
-use super::schema_alert_similar::{ALERT_SIMILARS_TABLE_NAME, ALERT_SIMILAR_ID_FIELD_PK, CREATION_TIME, TIME_INDEX_NAME};
+use super::schema_alert_similar::{
+    ALERT_SIMILARS_TABLE_NAME, ALERT_SIMILAR_ID_FIELD_PK, CREATION_TIME, TIME_INDEX_NAME,
+    ORIGIN_ASSET_ID, ORIGIN_ASSET_INDEX_NAME, SIMILAR_ASSET_ID, SIMILAR_ASSET_INDEX_NAME,
+};
 
 pub const LAST_UPDATE_TIME: &str = "last_update_time";
 pub const SOURCE_TYPE: &str = "source_type";
@@ -18,10 +21,8 @@ pub const ORIGIN_HASH_TYPE: &str = "origin_hash_type";
 pub const ORIGIN_FRAME_ID: &str = "origin_frame_id";
 pub const ORIGIN_FRAME_SECOND: &str = "origin_frame_second";
 pub const ORIGIN_FRAME_URL: &str = "origin_frame_url";
-pub const ORIGIN_ASSET_ID: &str = "origin_asset_id";
 pub const SIMILAR_FRAME_ID: &str = "similar_frame_id";
 pub const SIMILAR_FRAME_SECOND: &str = "similar_frame_second";
-pub const SIMILAR_ASSET_ID: &str = "similar_asset_id";
 pub const SIMILAR_FRAME_URL: &str = "similar_frame_url";
 
 
@@ -34,6 +35,7 @@ pub trait AlertSimilarRepository{
     async fn check_if_exists(&self, id:Uuid) -> ResultE<bool>;
     async fn get_all_by_time(&self, starting_at: SystemTime, window:Duration, token: Option<String>, limit: Option<u32> ) -> ResultE<(Vec<AlertSimilar>, Option<String> )>;
     async fn get_all(&self, token: Option<String>, limit: Option<u32> ) -> ResultE<(Vec<AlertSimilar>, Option<String> )>;
+    async fn get_by_asset(&self, asset_id: &Uuid) -> ResultE<Vec<AlertSimilar>>;
 
 }
 
@@ -54,6 +56,32 @@ impl AlertSimilarRepo {
         }
     }
 
+    async fn get_by_asset_index(&self, index_name: &str, field: &str, asset_id: &Uuid) -> ResultE<Vec<AlertSimilar>> {
+        let mut alerts = Vec::new();
+        let mut start_key: Option<HashMap<String, AttributeValue>> = None;
+        loop {
+            let response = self
+                .client
+                .query()
+                .table_name(ALERT_SIMILARS_TABLE_NAME.as_str())
+                .index_name(index_name)
+                .key_condition_expression("#asset_id = :asset_id")
+                .expression_attribute_names("#asset_id", field)
+                .expression_attribute_values(":asset_id", AttributeValue::S(asset_id.to_string()))
+                .set_exclusive_start_key(start_key)
+                .send()
+                .await?;
+            for item in response.items() {
+                alerts.push(mapping_from_doc(item)?);
+            }
+            match response.last_evaluated_key() {
+                Some(key) => start_key = Some(key.clone()),
+                None => break,
+            }
+        }
+        Ok(alerts)
+    }
+
     async fn add_or_update(&self, alert: &AlertSimilar) -> ResultE<()> {
         
         let id_av = AttributeValue::S(alert.id().to_string());
@@ -278,6 +306,22 @@ impl AlertSimilarRepository for AlertSimilarRepo {
 
     }
 
+    async fn get_by_asset(&self, asset_id: &Uuid) -> ResultE<Vec<AlertSimilar>> {
+        let mut alerts = self
+            .get_by_asset_index(ORIGIN_ASSET_INDEX_NAME, ORIGIN_ASSET_ID, asset_id)
+            .await?;
+        // an asset that is similar to itself would show up on both sides
+        for alert in self
+            .get_by_asset_index(SIMILAR_ASSET_INDEX_NAME, SIMILAR_ASSET_ID, asset_id)
+            .await?
+        {
+            if !alerts.iter().any(|existing| existing.id() == alert.id()) {
+                alerts.push(alert);
+            }
+        }
+        Ok(alerts)
+    }
+
     async fn get_all(&self, token: Option<String>, page_size: Option<u32> ) -> ResultE<(Vec<AlertSimilar>, Option<String> )>{
 
         let limit = page_size.unwrap_or(self.default_page_size.unwrap());
@@ -3,7 +3,9 @@ use aws_sdk_dynamodb::types::{
     builders::StreamSpecificationBuilder, AttributeDefinition, BillingMode,
     KeySchemaElement, KeyType, ScalarAttributeType, StreamViewType,
     Tag, Projection, ProjectionType, GlobalSecondaryIndex,
+    CreateGlobalSecondaryIndexAction, GlobalSecondaryIndexUpdate, IndexStatus,
 };
+use std::time::Duration;
 use lib_config::{
     config::Config,
     constants::{API_DOMAIN, TAG_ENVIRONMENT, TAG_PROJECT, TAG_SERVICE, VALUE_PROJECT},
@@ -19,14 +21,99 @@ lazy_static! {
 pub const ALERT_SIMILAR_ID_FIELD_PK: &str = "alert_id";
 pub const CREATION_TIME: &str = "creation_time";
 pub const TIME_INDEX_NAME: &str = "time_index";
+// an alert touches two assets, each one gets its index so an asset's alerts are a query away
+pub const ORIGIN_ASSET_ID: &str = "origin_asset_id";
+pub const ORIGIN_ASSET_INDEX_NAME: &str = "origin_asset_index";
+pub const SIMILAR_ASSET_ID: &str = "similar_asset_id";
+pub const SIMILAR_ASSET_INDEX_NAME: &str = "similar_asset_index";
+
+fn asset_index(index_name: &str, field: &str) -> (GlobalSecondaryIndex, AttributeDefinition) {
+    let index = GlobalSecondaryIndex::builder()
+        .index_name(index_name)
+        .key_schema(
+            KeySchemaElement::builder()
+                .attribute_name(field)
+                .key_type(KeyType::Hash)
+                .build().unwrap(),
+        )
+        .projection(
+            Projection::builder()
+                .projection_type(ProjectionType::All)
+                .build(),
+        )
+        .build().unwrap();
+    let attribute = AttributeDefinition::builder()
+        .attribute_name(field)
+        .attribute_type(ScalarAttributeType::S)
+        .build().unwrap();
+    (index, attribute)
+}
+
+// Tables created before an index existed get it here, dynamodb backfills it by itself. Only one
+// index can be built at a time, so this waits until it's ready.
+async fn add_index_if_missing(
+    config: &Config,
+    index: GlobalSecondaryIndex,
+    attribute: AttributeDefinition,
+) -> ResultE<()> {
+    let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+    let index_status = |description: &aws_sdk_dynamodb::operation::describe_table::DescribeTableOutput| {
+        description.table().and_then(|table| {
+            table
+                .global_secondary_indexes()
+                .iter()
+                .find(|gsi| gsi.index_name() == Some(index.index_name()))
+                .map(|gsi| gsi.index_status().cloned())
+        })
+    };
+
+    let description = client
+        .describe_table()
+        .table_name(ALERT_SIMILARS_TABLE_NAME.clone())
+        .send()
+        .await?;
+    if index_status(&description).is_none() {
+        let action = CreateGlobalSecondaryIndexAction::builder()
+            .index_name(index.index_name())
+            .set_key_schema(Some(index.key_schema().to_vec()))
+            .set_projection(index.projection().cloned())
+            .build()?;
+        client
+            .update_table()
+            .table_name(ALERT_SIMILARS_TABLE_NAME.clone())
+            .attribute_definitions(attribute)
+            .global_secondary_index_updates(
+                GlobalSecondaryIndexUpdate::builder().create(action).build(),
+            )
+            .send()
+            .await?;
+    }
+
+    loop {
+        let description = client
+            .describe_table()
+            .table_name(ALERT_SIMILARS_TABLE_NAME.clone())
+            .send()
+            .await?;
+        if let Some(Some(IndexStatus::Active)) = index_status(&description) {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_secs(5)).await;
+    }
+}
 
 pub struct AlertSimilarSchema;
 
 #[async_trait]
 impl Schema for AlertSimilarSchema {
     async fn create_schema(config: &Config) -> ResultE<()> {
+        let (origin_index, origin_attr) = asset_index(ORIGIN_ASSET_INDEX_NAME, ORIGIN_ASSET_ID);
+        let (similar_index, similar_attr) = asset_index(SIMILAR_ASSET_INDEX_NAME, SIMILAR_ASSET_ID);
+
         let exist = schema_exists(config, ALERT_SIMILARS_TABLE_NAME.as_str()).await?;
         if exist {
+            add_index_if_missing(config, origin_index, origin_attr).await?;
+            add_index_if_missing(config, similar_index, similar_attr).await?;
             return Ok(());
         }
 
@@ -72,9 +159,13 @@ impl Schema for AlertSimilarSchema {
             .table_name(ALERT_SIMILARS_TABLE_NAME.clone())
             .attribute_definitions(notification_pk_attr )
             .attribute_definitions(creation_time_attr )
+            .attribute_definitions(origin_attr)
+            .attribute_definitions(similar_attr)
             .key_schema(key_schema1)
             //.key_schema(key_schema2)
             .global_secondary_indexes(second_index)
+            .global_secondary_indexes(origin_index)
+            .global_secondary_indexes(similar_index)
             .billing_mode(BillingMode::PayPerRequest)
             .stream_specification(
                 StreamSpecificationBuilder::default()
@@ -1,12 +1,22 @@
 use crate::models::subscription::Subscription;
+use crate::template::account_merge::get_account_merge_message;
+use crate::template::account_unlock::get_account_unlock_message;
+use crate::template::email_link::get_email_link_message;
+use crate::template::email_verification::get_email_verification_message;
 use crate::template::intent::get_intent_message;
+use crate::template::license_request::{
+    get_license_request_received_message, get_license_request_resolved_message,
+};
 use crate::template::new_content_found::get_similar_content_found_message;
+use crate::template::password_reset::get_password_reset_message;
 use lettre::message::{Mailbox, MultiPart};
 use lettre::transport::smtp::authentication::Credentials;
 use lettre::{Address, Message, SmtpTransport, Transport};
 use lib_config::config::Config;
 use lib_config::result::ResultE;
 use lib_licenses::models::asset::Asset;
+use lib_licenses::models::license_grant::LicenseGrant;
+use lib_licenses::models::license_request::LicenseRequest;
 use lib_users::models::user::User;
 use url::Url;
 use uuid::Uuid;
@@ -116,4 +126,80 @@ impl SenderEmailsRepo {
 
         self.send(email, subject, body_flat_text, body_html).await
     }
+
+    pub async fn send_license_request_received(
+        &self,
+        owner: User,
+        asset: Asset,
+        request: &LicenseRequest,
+    ) -> ResultE<()> {
+        log::info!("Sending license request email to: {}", owner);
+
+        let email = owner.email().clone().unwrap();
+        let url = asset.url().clone().unwrap();
+
+        let (subject, body_flat_text, body_html) =
+            get_license_request_received_message(email.clone(), url, request);
+
+        self.send(email, subject, body_flat_text, body_html).await
+    }
+
+    pub async fn send_license_request_resolved(
+        &self,
+        requester: User,
+        asset: Asset,
+        request: &LicenseRequest,
+        grant: Option<&LicenseGrant>,
+    ) -> ResultE<()> {
+        log::info!("Sending license request resolution email to: {}", requester);
+
+        let email = requester.email().clone().unwrap();
+        let url = asset.url().clone().unwrap();
+
+        let (subject, body_flat_text, body_html) =
+            get_license_request_resolved_message(email.clone(), url, request, grant);
+
+        self.send(email, subject, body_flat_text, body_html).await
+    }
+
+    pub async fn send_email_verification(&self, email: String, token: String) -> ResultE<()> {
+        log::info!("Sending email verification to: {}", email);
+
+        let (subject, body_flat_text, body_html) =
+            get_email_verification_message(email.clone(), token);
+
+        self.send(email, subject, body_flat_text, body_html).await
+    }
+
+    pub async fn send_email_link(&self, email: String, token: String) -> ResultE<()> {
+        log::info!("Sending email link confirmation to: {}", email);
+
+        let (subject, body_flat_text, body_html) = get_email_link_message(email.clone(), token);
+
+        self.send(email, subject, body_flat_text, body_html).await
+    }
+
+    pub async fn send_account_merge(&self, email: String, token: String) -> ResultE<()> {
+        log::info!("Sending account merge confirmation to: {}", email);
+
+        let (subject, body_flat_text, body_html) = get_account_merge_message(email.clone(), token);
+
+        self.send(email, subject, body_flat_text, body_html).await
+    }
+
+    pub async fn send_password_reset(&self, email: String, token: String) -> ResultE<()> {
+        log::info!("Sending password reset to: {}", email);
+
+        let (subject, body_flat_text, body_html) = get_password_reset_message(email.clone(), token);
+
+        self.send(email, subject, body_flat_text, body_html).await
+    }
+
+    pub async fn send_account_unlock(&self, email: String, token: String) -> ResultE<()> {
+        log::info!("Sending account unlock to: {}", email);
+
+        let (subject, body_flat_text, body_html) = get_account_unlock_message(email.clone(), token);
+
+        self.send(email, subject, body_flat_text, body_html).await
+    }
 }
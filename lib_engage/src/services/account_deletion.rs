@@ -0,0 +1,148 @@
+use crate::errors::account_deletion::AccountDeletionError;
+use crate::repositories::sender::SenderEmailsRepo;
+use crate::repositories::subscription::SubscriptionRepo;
+use crate::services::subscription::SubscriptionService;
+use lib_config::config::Config;
+use lib_config::result::ResultE;
+use lib_licenses::models::owner::ANONYMOUS_OWNER;
+use lib_licenses::repositories::assets::AssetRepo;
+use lib_licenses::repositories::license_grants::LicenseGrantRepo;
+use lib_licenses::repositories::license_requests::LicenseRequestRepo;
+use lib_licenses::repositories::licenses::LicenseRepo;
+use lib_licenses::repositories::owners::OwnerRepo;
+use lib_licenses::services::license_requests::{LicenseRequestManipulation, LicenseRequestService};
+use lib_licenses::services::owners::{OwnerManipulation, OwnerService};
+use lib_users::repositories::api_keys::ApiKeysRepo;
+use lib_users::repositories::devices::DevicesRepo;
+use lib_users::repositories::identities::IdentitiesRepo;
+use lib_users::repositories::login_attempts::LoginAttemptsRepo;
+use lib_users::repositories::mfa::MfaRepo;
+use lib_users::repositories::sessions::SessionsRepo;
+use lib_users::repositories::users::UsersRepo;
+use lib_users::services::api_keys::{ApiKeyManipulation, ApiKeyService};
+use lib_users::services::devices::{DeviceManipulation, DeviceService};
+use lib_users::services::login_attempts::{LoginAttemptManipulation, LoginAttemptService};
+use lib_users::services::mfa::{MfaManipulation, MfaService};
+use lib_users::services::oidc_login::{OidcLoginManipulation, OidcLoginService};
+use lib_users::services::sessions::{SessionManipulation, SessionService};
+use lib_users::services::users::{UserManipulation, UsersService};
+
+pub const SERVICE: &str = "account_deletion";
+
+// What happens to the assets of a removed account. Licenses hang from the asset, so they follow it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AssetPolicy {
+    // shares go to another user, only for administrators and merges: the heir isn't asked
+    Transfer(String),
+    // assets stay online, owned by nobody
+    Anonymize,
+}
+
+impl AssetPolicy {
+    // "anonymize", or "transfer" along with the user receiving the shares
+    pub fn parse(
+        policy: &str,
+        transfer_to: &Option<String>,
+    ) -> Result<AssetPolicy, AccountDeletionError> {
+        match (policy, transfer_to) {
+            ("anonymize", _) => Ok(AssetPolicy::Anonymize),
+            ("transfer", Some(heir)) => Ok(AssetPolicy::Transfer(heir.clone())),
+            ("transfer", None) => Err(AccountDeletionError::HeirMissing),
+            (other, _) => Err(AccountDeletionError::UnknownAssetPolicy(other.to_string())),
+        }
+    }
+}
+
+// Removes a user and everything hanging from it across users, assets, license requests and
+// subscriptions.
+pub struct AccountDeletionService {
+    user_service: UsersService,
+    session_service: SessionService,
+    device_service: DeviceService,
+    mfa_service: MfaService,
+    api_key_service: ApiKeyService,
+    oidc_service: OidcLoginService,
+    login_attempt_service: LoginAttemptService,
+    owner_service: OwnerService,
+    license_request_service: LicenseRequestService,
+    subscription_service: SubscriptionService<SubscriptionRepo>,
+}
+
+impl AccountDeletionService {
+    pub fn new(conf: &Config) -> AccountDeletionService {
+        AccountDeletionService {
+            user_service: UsersService::new(UsersRepo::new(conf)),
+            session_service: SessionService::new(SessionsRepo::new(conf)),
+            device_service: DeviceService::new(DevicesRepo::new(conf)),
+            mfa_service: MfaService::new(MfaRepo::new(conf)),
+            api_key_service: ApiKeyService::new(ApiKeysRepo::new(conf)),
+            // only unlinks, no provider is needed
+            oidc_service: OidcLoginService::new(
+                IdentitiesRepo::new(conf),
+                SessionsRepo::new(conf),
+                Vec::new(),
+            ),
+            login_attempt_service: LoginAttemptService::new(LoginAttemptsRepo::new(conf)),
+            owner_service: OwnerService::new(OwnerRepo::new(conf)),
+            license_request_service: LicenseRequestService::new(
+                LicenseRequestRepo::new(conf),
+                LicenseGrantRepo::new(conf),
+                LicenseRepo::new(conf),
+                AssetRepo::new(conf),
+                OwnerRepo::new(conf),
+            ),
+            subscription_service: SubscriptionService::new(
+                SubscriptionRepo::new(conf),
+                SenderEmailsRepo::new(conf),
+            ),
+        }
+    }
+
+    // The user row goes last: if anything fails halfway, the same call can be repeated.
+    pub async fn delete(&self, user_id: &String, policy: &AssetPolicy) -> ResultE<()> {
+        let user = self.user_service.get_by_id(user_id).await?;
+
+        let heir = match policy {
+            AssetPolicy::Anonymize => ANONYMOUS_OWNER.to_string(),
+            AssetPolicy::Transfer(heir) => {
+                if heir == user_id {
+                    return Err(AccountDeletionError::HeirIsSameUser.into());
+                }
+                if self.user_service.get_by_id(heir).await.is_err() {
+                    return Err(AccountDeletionError::HeirNotFound(heir.clone()).into());
+                }
+                heir.clone()
+            }
+        };
+        let released = self.owner_service.release_by_user(user_id, &heir).await?;
+        log::info!(
+            "user {} removed, {} assets handed to {}",
+            user_id,
+            released.len(),
+            heir
+        );
+
+        // a merge has moved them already, nothing is left to close then
+        let closed = self.license_request_service.close_by_user(user_id).await?;
+        log::info!(
+            "user {} removed, {} license requests and grants closed",
+            user_id,
+            closed
+        );
+
+        self.subscription_service
+            .remove_by_user(user_id.clone())
+            .await?;
+
+        self.session_service.revoke_all(user_id).await?;
+        self.device_service.revoke_all(user_id).await?;
+        self.api_key_service.revoke_all(user_id).await?;
+        self.oidc_service.unlink_all(user_id).await?;
+        self.mfa_service.reset(user_id).await?;
+        if let Some(email) = user.email() {
+            self.login_attempt_service.unlock(email).await?;
+        }
+
+        self.user_service.remove_by_id(user_id).await
+    }
+}
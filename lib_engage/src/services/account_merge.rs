@@ -0,0 +1,101 @@
+use crate::errors::account_merge::AccountMergeError;
+use crate::repositories::sender::SenderEmailsRepo;
+use crate::repositories::subscription::SubscriptionRepo;
+use crate::services::account_deletion::{AccountDeletionService, AssetPolicy};
+use crate::services::subscription::SubscriptionService;
+use lib_config::config::Config;
+use lib_config::result::ResultE;
+use lib_licenses::repositories::assets::AssetRepo;
+use lib_licenses::repositories::license_grants::LicenseGrantRepo;
+use lib_licenses::repositories::license_requests::LicenseRequestRepo;
+use lib_licenses::repositories::licenses::LicenseRepo;
+use lib_licenses::repositories::owners::OwnerRepo;
+use lib_licenses::services::license_requests::{LicenseRequestManipulation, LicenseRequestService};
+use lib_users::repositories::devices::DevicesRepo;
+use lib_users::repositories::identities::IdentitiesRepo;
+use lib_users::repositories::users::UsersRepo;
+use lib_users::services::login_methods::{LoginMethodManipulation, LoginMethodService};
+use lib_users::services::users::{UserManipulation, UsersService};
+
+pub const SERVICE: &str = "account_merge";
+
+// Folds a device-only account into a full one: subscriptions, license requests and grants,
+// ways in and assets move to the full account, then the device account is removed.
+pub struct AccountMergeService {
+    user_service: UsersService,
+    login_method_service: LoginMethodService,
+    license_request_service: LicenseRequestService,
+    subscription_service: SubscriptionService<SubscriptionRepo>,
+    account_deletion_service: AccountDeletionService,
+}
+
+impl AccountMergeService {
+    pub fn new(conf: &Config) -> AccountMergeService {
+        AccountMergeService {
+            user_service: UsersService::new(UsersRepo::new(conf)),
+            login_method_service: LoginMethodService::new(
+                UsersRepo::new(conf),
+                IdentitiesRepo::new(conf),
+                DevicesRepo::new(conf),
+            ),
+            license_request_service: LicenseRequestService::new(
+                LicenseRequestRepo::new(conf),
+                LicenseGrantRepo::new(conf),
+                LicenseRepo::new(conf),
+                AssetRepo::new(conf),
+                OwnerRepo::new(conf),
+            ),
+            subscription_service: SubscriptionService::new(
+                SubscriptionRepo::new(conf),
+                SenderEmailsRepo::new(conf),
+            ),
+            account_deletion_service: AccountDeletionService::new(conf),
+        }
+    }
+
+    // before mailing the confirmation, so nobody gets a link that can't work
+    pub async fn check(&self, user_id: &String, into_user_id: &String) -> ResultE<()> {
+        if user_id == into_user_id {
+            return Err(AccountMergeError::SameUser.into());
+        }
+        let user = self.user_service.get_by_id(user_id).await?;
+        if user.email().is_some() {
+            return Err(AccountMergeError::SourceNotAnonymous(user_id.clone()).into());
+        }
+        let into = self.user_service.get_by_id(into_user_id).await?;
+        if into.email().is_none() {
+            return Err(AccountMergeError::TargetWithoutEmail(into_user_id.clone()).into());
+        }
+        Ok(())
+    }
+
+    // The device account goes last, through the deletion that hands its assets over: if
+    // anything fails halfway, the same call can be repeated.
+    pub async fn merge(&self, user_id: &String, into_user_id: &String) -> ResultE<()> {
+        self.check(user_id, into_user_id).await?;
+
+        let subscriptions = self
+            .subscription_service
+            .transfer_by_user(user_id.clone(), into_user_id.clone())
+            .await?;
+        let licenses = self
+            .license_request_service
+            .transfer_by_user(user_id, into_user_id)
+            .await?;
+        self.login_method_service
+            .take_over(into_user_id, user_id)
+            .await?;
+
+        self.account_deletion_service
+            .delete(user_id, &AssetPolicy::Transfer(into_user_id.clone()))
+            .await?;
+        log::info!(
+            "user {} merged into {}, {} subscriptions and {} license requests and grants moved",
+            user_id,
+            into_user_id,
+            subscriptions,
+            licenses
+        );
+        Ok(())
+    }
+}
@@ -54,4 +54,8 @@ impl<T: AlertSimilarRepository> AlertSimilarService<T> {
         self.repo.get_all(token, page_size).await
     }
 
+    pub async fn get_by_asset(&self, asset_id: &Uuid) -> ResultE<Vec<AlertSimilar>> {
+        self.repo.get_by_asset(asset_id).await
+    }
+
 }
@@ -0,0 +1,161 @@
+use crate::models::data_export::{LoginMethods, OwnedAsset, UserDataExport};
+use crate::repositories::alert_similar::AlertSimilarRepo;
+use crate::repositories::sender::SenderEmailsRepo;
+use crate::repositories::subscription::SubscriptionRepo;
+use crate::services::alert_similar::AlertSimilarService;
+use crate::services::subscription::SubscriptionService;
+use chrono::Utc;
+use lib_config::config::Config;
+use lib_config::result::ResultE;
+use lib_licenses::errors::asset::AssetNoExistsError;
+use lib_licenses::repositories::assets::AssetRepo;
+use lib_licenses::repositories::license_grants::LicenseGrantRepo;
+use lib_licenses::repositories::license_requests::LicenseRequestRepo;
+use lib_licenses::repositories::licenses::LicenseRepo;
+use lib_licenses::repositories::owners::OwnerRepo;
+use lib_licenses::repositories::shorter::ShorterRepo;
+use lib_licenses::services::assets::{AssetManipulation, AssetService};
+use lib_licenses::services::license_requests::{LicenseRequestManipulation, LicenseRequestService};
+use lib_licenses::services::licenses::{LicenseManipulation, LicenseService};
+use lib_licenses::services::owners::{OwnerManipulation, OwnerService};
+use lib_users::repositories::api_keys::ApiKeysRepo;
+use lib_users::repositories::devices::DevicesRepo;
+use lib_users::repositories::identities::IdentitiesRepo;
+use lib_users::repositories::mfa::MfaRepo;
+use lib_users::repositories::sessions::SessionsRepo;
+use lib_users::repositories::users::UsersRepo;
+use lib_users::services::api_keys::{ApiKeyManipulation, ApiKeyService};
+use lib_users::services::devices::{DeviceManipulation, DeviceService};
+use lib_users::services::mfa::{MfaManipulation, MfaService};
+use lib_users::services::oidc_login::{OidcLoginManipulation, OidcLoginService};
+use lib_users::services::users::{UserManipulation, UsersService};
+use std::collections::HashSet;
+
+pub const SERVICE: &str = "data_export";
+
+// Gathers what users, licenses and engage hold about a user, for personal data requests.
+pub struct DataExportService {
+    user_service: UsersService,
+    device_service: DeviceService,
+    mfa_service: MfaService,
+    api_key_service: ApiKeyService,
+    oidc_service: OidcLoginService,
+    owner_service: OwnerService,
+    asset_service: AssetService,
+    license_service: LicenseService,
+    license_request_service: LicenseRequestService,
+    subscription_service: SubscriptionService<SubscriptionRepo>,
+    alert_service: AlertSimilarService<AlertSimilarRepo>,
+}
+
+impl DataExportService {
+    pub fn new(conf: &Config) -> DataExportService {
+        DataExportService {
+            user_service: UsersService::new(UsersRepo::new(conf)),
+            device_service: DeviceService::new(DevicesRepo::new(conf)),
+            mfa_service: MfaService::new(MfaRepo::new(conf)),
+            api_key_service: ApiKeyService::new(ApiKeysRepo::new(conf)),
+            oidc_service: OidcLoginService::new(
+                IdentitiesRepo::new(conf),
+                SessionsRepo::new(conf),
+                Vec::new(),
+            ),
+            owner_service: OwnerService::new(OwnerRepo::new(conf)),
+            asset_service: AssetService::new(AssetRepo::new(conf), ShorterRepo::new(conf)),
+            license_service: LicenseService::new(LicenseRepo::new(conf), AssetRepo::new(conf)),
+            license_request_service: LicenseRequestService::new(
+                LicenseRequestRepo::new(conf),
+                LicenseGrantRepo::new(conf),
+                LicenseRepo::new(conf),
+                AssetRepo::new(conf),
+                OwnerRepo::new(conf),
+            ),
+            subscription_service: SubscriptionService::new(
+                SubscriptionRepo::new(conf),
+                SenderEmailsRepo::new(conf),
+            ),
+            alert_service: AlertSimilarService::new(AlertSimilarRepo::new(conf)),
+        }
+    }
+
+    pub async fn export(&self, user_id: &String) -> ResultE<UserDataExport> {
+        let profile = self.user_service.get_by_id(user_id).await?;
+
+        let login_methods = LoginMethods {
+            devices: self.device_service.get_by_user(user_id).await?,
+            api_keys: self.api_key_service.get_by_user(user_id).await?,
+            identities: self.oidc_service.get_by_user(user_id).await?,
+            mfa_enabled: self.mfa_service.is_enabled(user_id).await?,
+        };
+
+        let mut assets = Vec::new();
+        for ownership in self.owner_service.get_by_user(user_id).await? {
+            // an ownership row can outlive its asset, the rest of the export is still owed
+            let asset = match self.asset_service.get_by_id(ownership.asset_id()).await {
+                Ok(asset) => asset,
+                Err(e) if e.is::<AssetNoExistsError>() => {
+                    log::warn!(
+                        "export of user {}: asset {} not found, skipped",
+                        user_id,
+                        ownership.asset_id()
+                    );
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            let licenses = self
+                .license_service
+                .get_by_asset(ownership.asset_id())
+                .await?;
+            assets.push(OwnedAsset {
+                asset,
+                ownership,
+                licenses,
+            });
+        }
+
+        let subscriptions = self
+            .subscription_service
+            .find_assets_subscribed_to(user_id.clone())
+            .await?;
+
+        // alerts aren't kept per user: the pending ones touching an owned or followed asset are what
+        // the user has received or is about to
+        let mut watched = HashSet::new();
+        watched.extend(assets.iter().map(|owned| *owned.ownership.asset_id()));
+        watched.extend(
+            subscriptions
+                .iter()
+                .map(|subscription| subscription.asset_id),
+        );
+        let mut seen = HashSet::new();
+        let mut alerts = Vec::new();
+        for asset_id in &watched {
+            for alert in self.alert_service.get_by_asset(asset_id).await? {
+                if seen.insert(*alert.id()) {
+                    alerts.push(alert);
+                }
+            }
+        }
+
+        let license_requests = self
+            .license_request_service
+            .get_by_requester(user_id)
+            .await?;
+        let license_grants = self
+            .license_request_service
+            .get_grants_by_grantee(user_id)
+            .await?;
+
+        Ok(UserDataExport {
+            generated_at: Utc::now(),
+            profile,
+            login_methods,
+            assets,
+            subscriptions,
+            alerts,
+            license_requests,
+            license_grants,
+        })
+    }
+}
@@ -2,6 +2,7 @@ use crate::errors::subscription::SubscriptionError;
 use crate::models::subscription::{ConfirmedStatus, Subscription};
 use crate::repositories::sender::SenderEmailsRepo;
 use crate::repositories::subscription::SubscriptionRepository;
+use chrono::Utc;
 use lib_config::result::ResultE;
 use lib_licenses::models::asset::Asset;
 use lib_users::models::user::User;
@@ -95,4 +96,34 @@ impl<T: SubscriptionRepository>  SubscriptionService<T> {
     pub async fn check_if_exists(&self, user_id: String, asset_id: Uuid) -> ResultE<Option<Uuid>> {
         self.subscription_repo.check_exists(user_id, asset_id).await
     }
+
+    pub async fn remove_by_user(&self, user_id: String) -> ResultE<()> {
+        let subscriptions = self.subscription_repo.find_by_user(user_id).await?;
+        for subscription in subscriptions {
+            self.subscription_repo.delete(subscription.id).await?;
+        }
+        Ok(())
+    }
+
+    // the heir keeps its own subscription when both follow the same asset
+    pub async fn transfer_by_user(&self, user_id: String, heir: String) -> ResultE<usize> {
+        let subscriptions = self.subscription_repo.find_by_user(user_id).await?;
+        let mut moved = 0;
+        for mut subscription in subscriptions {
+            let duplicated = self
+                .subscription_repo
+                .check_exists(heir.clone(), subscription.asset_id)
+                .await?;
+            if duplicated.is_some() {
+                self.subscription_repo.delete(subscription.id).await?;
+                continue;
+            }
+            // same id, the row is overwritten
+            subscription.user_id = heir.clone();
+            subscription.last_update_time = Utc::now();
+            self.subscription_repo.add(subscription).await?;
+            moved += 1;
+        }
+        Ok(moved)
+    }
 }
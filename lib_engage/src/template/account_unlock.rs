@@ -0,0 +1,51 @@
+//#[instrument]
+pub fn get_account_unlock_message(email: String, token: String) -> (String, String, String) {
+    let subject = "Truly.video your account has been locked".to_string();
+
+    let body_flat_text = format!(
+        r#"
+        Hi {email},
+
+        There have been too many failed attempts to log in to your truly.video account, so we've locked it for a while.
+        If it was you, click on the following link to unlock it right now: https://www.truly.video/unlock?token={token}
+
+        The link expires in 24 hours and works only once. If it wasn't you, someone may be guessing your password: consider resetting it.
+
+        If you've got any doubts, please, don't hesitate to contact us by our Discord channel: https://disboard.org/server/1164515811390664735 
+        We really appreciate your feedback.
+
+        Joan from truly.video
+        "#,
+        email = email,
+        token = token
+    );
+
+    let body_html = format!(
+        r#"
+        <html>
+            <head></head>
+            <body>
+                <p>Hi {email},</p>
+
+                <p>There have been too many failed attempts to log in to your truly.video account, so we've locked it for a while.</p>
+                <p>If it was you, click on the following link to unlock it right now: 
+                <a href="https://www.truly.video/unlock?token={token}">Unlock account</a>
+                </p>
+
+                <p>The link expires in 24 hours and works only once. If it wasn't you, someone may be guessing your password: consider resetting it.</p>
+
+                <p>If you have any doubts, please, don't hesitate to contact us via our 
+                <a href="https://disboard.org/server/1164515811390664735">Discord channel</a>. 
+                We really appreciate your feedback.
+                </p>
+
+                <p>Joan from truly.video</p>
+            </body>
+        </html>
+        "#,
+        email = email,
+        token = token
+    );
+
+    (subject, body_flat_text, body_html)
+}
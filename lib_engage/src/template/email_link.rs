@@ -0,0 +1,51 @@
+//#[instrument]
+pub fn get_email_link_message(email: String, token: String) -> (String, String, String) {
+    let subject = "Truly.video confirm your new login email".to_string();
+
+    let body_flat_text = format!(
+        r#"
+        Hi {email},
+
+        We've received a request to sign in to a truly.video account with this email address.
+        Please click on the following link to confirm it: https://www.truly.video/user/logins/email?token={token}
+
+        The link expires in 24 hours and works only once. If you didn't ask for it, you can safely ignore this email, nothing will change.
+
+        If you've got any doubts, please, don't hesitate to contact us by our Discord channel: https://disboard.org/server/1164515811390664735
+        We really appreciate your feedback.
+
+        Joan from truly.video
+        "#,
+        email = email,
+        token = token
+    );
+
+    let body_html = format!(
+        r#"
+        <html>
+            <head></head>
+            <body>
+                <p>Hi {email},</p>
+
+                <p>We've received a request to sign in to a truly.video account with this email address.</p>
+                <p>Please click on the following link to confirm it:
+                <a href="https://www.truly.video/user/logins/email?token={token}">Confirm email</a>
+                </p>
+
+                <p>The link expires in 24 hours and works only once. If you didn't ask for it, you can safely ignore this email, nothing will change.</p>
+
+                <p>If you have any doubts, please, don't hesitate to contact us via our
+                <a href="https://disboard.org/server/1164515811390664735">Discord channel</a>.
+                We really appreciate your feedback.
+                </p>
+
+                <p>Joan from truly.video</p>
+            </body>
+        </html>
+        "#,
+        email = email,
+        token = token
+    );
+
+    (subject, body_flat_text, body_html)
+}
@@ -0,0 +1,51 @@
+//#[instrument]
+pub fn get_email_verification_message(email: String, token: String) -> (String, String, String) {
+    let subject = "Truly.video please verify your email".to_string();
+
+    let body_flat_text = format!(
+        r#"
+        Hi {email},
+
+        Thanks for signing up at truly.video!
+        Please click on the following link to verify your email address: https://www.truly.video/verify?token={token}
+
+        The link expires in 24 hours. If you didn't create an account, you can safely ignore this email.
+
+        If you've got any doubts, please, don't hesitate to contact us by our Discord channel: https://disboard.org/server/1164515811390664735 
+        We really appreciate your feedback.
+
+        Joan from truly.video
+        "#,
+        email = email,
+        token = token
+    );
+
+    let body_html = format!(
+        r#"
+        <html>
+            <head></head>
+            <body>
+                <p>Hi {email},</p>
+
+                <p>Thanks for signing up at truly.video!</p>
+                <p>Please click on the following link to verify your email address: 
+                <a href="https://www.truly.video/verify?token={token}">Verify email</a>
+                </p>
+
+                <p>The link expires in 24 hours. If you didn't create an account, you can safely ignore this email.</p>
+
+                <p>If you have any doubts, please, don't hesitate to contact us via our 
+                <a href="https://disboard.org/server/1164515811390664735">Discord channel</a>. 
+                We really appreciate your feedback.
+                </p>
+
+                <p>Joan from truly.video</p>
+            </body>
+        </html>
+        "#,
+        email = email,
+        token = token
+    );
+
+    (subject, body_flat_text, body_html)
+}
@@ -0,0 +1,136 @@
+use lib_licenses::models::license_grant::LicenseGrant;
+use lib_licenses::models::license_request::LicenseRequest;
+use url::Url;
+
+pub fn get_license_request_received_message(
+    email: String,
+    asset: Url,
+    request: &LicenseRequest,
+) -> (String, String, String) {
+    let subject = "Truly.video new license request".to_string();
+
+    let body_flat_text = format!(
+        r#"
+        Hi {email},
+
+        Somebody has requested a license to use your video {asset_url}.
+
+        Intended use: {intended_use}
+        Territory: {territory}
+
+        Please click on the following link to review the request: https://www.truly.video/licenses/requests/{request_id}
+
+        If you've got any doubts, please, don't hesitate to contact us by our Discord channel: https://disboard.org/server/1164515811390664735
+        We really appreciate your feedback.
+
+        Joan from truly.video
+        "#,
+        email = email,
+        asset_url = asset,
+        intended_use = request.intended_use(),
+        territory = request.territory(),
+        request_id = request.id()
+    );
+
+    let body_html = format!(
+        r#"
+        <html>
+            <head></head>
+            <body>
+                <p>Hi {email},</p>
+
+                <p>Somebody has requested a license to use your video <a href="{asset_url}">{asset_url}</a>.</p>
+                <ul>
+                    <li>Intended use: {intended_use}</li>
+                    <li>Territory: {territory}</li>
+                </ul>
+                <p>Please click on the following link to review the request:
+                <a href="https://www.truly.video/licenses/requests/{request_id}">Review request</a>
+                </p>
+
+                <p>If you have any doubts, please, don't hesitate to contact us via our
+                <a href="https://disboard.org/server/1164515811390664735">Discord channel</a>.
+                We really appreciate your feedback.
+                </p>
+
+                <p>Joan from truly.video</p>
+            </body>
+        </html>
+        "#,
+        email = email,
+        asset_url = asset,
+        intended_use = request.intended_use(),
+        territory = request.territory(),
+        request_id = request.id()
+    );
+
+    (subject, body_flat_text, body_html)
+}
+
+pub fn get_license_request_resolved_message(
+    email: String,
+    asset: Url,
+    request: &LicenseRequest,
+    grant: Option<&LicenseGrant>,
+) -> (String, String, String) {
+    let (subject, outcome) = match grant {
+        Some(gr) => (
+            "Truly.video license request approved".to_string(),
+            format!(
+                "has been approved. You can use it from {} until {}",
+                gr.valid_from().format("%Y-%m-%d"),
+                gr.valid_until().format("%Y-%m-%d")
+            ),
+        ),
+        None => (
+            "Truly.video license request rejected".to_string(),
+            "has been rejected".to_string(),
+        ),
+    };
+    let comment = request.review_comment().clone().unwrap_or_default();
+
+    let body_flat_text = format!(
+        r#"
+        Hi {email},
+
+        Your license request to use the video {asset_url} {outcome}.
+        {comment}
+
+        If you've got any doubts, please, don't hesitate to contact us by our Discord channel: https://disboard.org/server/1164515811390664735
+        We really appreciate your feedback.
+
+        Joan from truly.video
+        "#,
+        email = email,
+        asset_url = asset,
+        outcome = outcome,
+        comment = comment
+    );
+
+    let body_html = format!(
+        r#"
+        <html>
+            <head></head>
+            <body>
+                <p>Hi {email},</p>
+
+                <p>Your license request to use the video <a href="{asset_url}">{asset_url}</a> {outcome}.</p>
+                <p>{comment}</p>
+
+                <p>If you have any doubts, please, don't hesitate to contact us via our
+                <a href="https://disboard.org/server/1164515811390664735">Discord channel</a>.
+                We really appreciate your feedback.
+                </p>
+
+                <p>Joan from truly.video</p>
+            </body>
+        </html>
+        "#,
+        email = email,
+        asset_url = asset,
+        outcome = outcome,
+        comment = comment
+    );
+
+    (subject, body_flat_text, body_html)
+}
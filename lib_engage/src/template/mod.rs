@@ -1,3 +1,8 @@
-
+pub mod account_merge;
+pub mod account_unlock;
+pub mod email_link;
+pub mod email_verification;
 pub mod intent;
-pub mod new_content_found;
\ No newline at end of file
+pub mod license_request;
+pub mod new_content_found;
+pub mod password_reset;
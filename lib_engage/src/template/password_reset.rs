@@ -0,0 +1,51 @@
+//#[instrument]
+pub fn get_password_reset_message(email: String, token: String) -> (String, String, String) {
+    let subject = "Truly.video reset your password".to_string();
+
+    let body_flat_text = format!(
+        r#"
+        Hi {email},
+
+        We've received a request to reset the password of your truly.video account.
+        Please click on the following link to choose a new one: https://www.truly.video/password/reset?token={token}
+
+        The link expires in 1 hour and works only once. If you didn't ask for it, you can safely ignore this email, your password won't change.
+
+        If you've got any doubts, please, don't hesitate to contact us by our Discord channel: https://disboard.org/server/1164515811390664735 
+        We really appreciate your feedback.
+
+        Joan from truly.video
+        "#,
+        email = email,
+        token = token
+    );
+
+    let body_html = format!(
+        r#"
+        <html>
+            <head></head>
+            <body>
+                <p>Hi {email},</p>
+
+                <p>We've received a request to reset the password of your truly.video account.</p>
+                <p>Please click on the following link to choose a new one: 
+                <a href="https://www.truly.video/password/reset?token={token}">Reset password</a>
+                </p>
+
+                <p>The link expires in 1 hour and works only once. If you didn't ask for it, you can safely ignore this email, your password won't change.</p>
+
+                <p>If you have any doubts, please, don't hesitate to contact us via our 
+                <a href="https://disboard.org/server/1164515811390664735">Discord channel</a>. 
+                We really appreciate your feedback.
+                </p>
+
+                <p>Joan from truly.video</p>
+            </body>
+        </html>
+        "#,
+        email = email,
+        token = token
+    );
+
+    (subject, body_flat_text, body_html)
+}
@@ -0,0 +1,121 @@
+use base64::{engine::general_purpose, Engine as _};
+use ed25519_dalek::pkcs8::EncodePublicKey;
+use ed25519_dalek::{Signer, SigningKey};
+use lib_config::config::Config;
+use lib_config::environment::{DEV_ENV, ENV_VAR_ENVIRONMENT};
+use lib_config::infra::build_local_stack_connection;
+use lib_config::result::ResultE;
+use lib_config::schema::Schema;
+use lib_engage::models::subscription::{ConfirmedStatus, Subscription};
+use lib_engage::repositories::schema_subscription::SubscriptionSchema;
+use lib_engage::repositories::sender::SMTP_TEST_SERVER;
+use lib_engage::repositories::subscription::{SubscriptionRepo, SubscriptionRepository};
+use lib_engage::services::account_merge::AccountMergeService;
+use lib_licenses::repositories::schema_license_requests::LicenseRequestAllSchema;
+use lib_licenses::repositories::schema_owners::OwnerSchema;
+use lib_users::models::device::device_challenge_message;
+use lib_users::models::user::User;
+use lib_users::repositories::devices::DevicesRepo;
+use lib_users::repositories::schema_user::UserAllSchema;
+use lib_users::repositories::users::UsersRepo;
+use lib_users::services::devices::{DeviceManipulation, DeviceService};
+use lib_users::services::login::LoginOps;
+use lib_users::services::users::{UserManipulation, UsersService};
+use std::env;
+use testcontainers::*;
+use uuid::Uuid;
+
+fn sign(key: &SigningKey, device: &String, challenge: &String) -> String {
+    let signature = key.sign(device_challenge_message(device, challenge).as_bytes());
+    general_purpose::STANDARD.encode(signature.to_bytes())
+}
+
+#[tokio::test]
+async fn merge_device_account_test() -> ResultE<()> {
+    env::set_var("RUST_LOG", "debug");
+    env::set_var("AWS_REGION", "eu-central-1");
+    env::set_var(ENV_VAR_ENVIRONMENT, DEV_ENV);
+    env::set_var("SMTP_USER", "test@test.com");
+    env::set_var("SMTP_HOST", SMTP_TEST_SERVER);
+    env::set_var("SMTP_PASSW", "test1");
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let docker = clients::Cli::default();
+
+    let mut local_stack = images::local_stack::LocalStack::default();
+    local_stack.set_services("dynamodb");
+    let node = docker.run(local_stack);
+    let host_port = node.get_host_port_ipv4(4566);
+
+    let shared_config = build_local_stack_connection(host_port).await;
+
+    let mut conf = Config::new();
+    conf.setup().await;
+    conf.set_aws_config(&shared_config);
+
+    UserAllSchema::create_schema(&conf).await?;
+    OwnerSchema::create_schema(&conf).await?;
+    LicenseRequestAllSchema::create_schema(&conf).await?;
+    SubscriptionSchema::create_schema(&conf).await?;
+
+    let user_service = UsersService::new(UsersRepo::new(&conf));
+    let device_service = DeviceService::new(DevicesRepo::new(&conf));
+    let subscription_repo = SubscriptionRepo::new(&conf);
+    let merge_service = AccountMergeService::new(&conf);
+
+    let mut full_user = User::new();
+    full_user.set_email(&"pepe@test.cat.io".to_string());
+    let full_id = user_service
+        .add(&mut full_user, &Some("123456789aA$%^@2".to_string()))
+        .await?;
+
+    let device = "device-anonymous".to_string();
+    let key = SigningKey::from_bytes(&[7u8; 32]);
+    let public_key =
+        general_purpose::STANDARD.encode(key.verifying_key().to_public_key_der()?.as_bytes());
+    let mut device_user = User::new();
+    device_user.set_device(&device);
+    let device_id = user_service.add(&mut device_user, &None).await?;
+    device_service
+        .register(&device_id, &device, &"EdDSA".to_string(), &public_key)
+        .await?;
+
+    let asset_id = Uuid::new_v4();
+    subscription_repo
+        .add(Subscription::new(
+            device_id.clone(),
+            asset_id,
+            ConfirmedStatus::Enabled,
+        ))
+        .await?;
+
+    // only device accounts can be folded into a full one
+    assert!(merge_service.check(&full_id, &device_id).await.is_err());
+    assert!(merge_service.check(&device_id, &device_id).await.is_err());
+
+    merge_service.merge(&device_id, &full_id).await?;
+
+    assert!(user_service.get_by_id(&device_id).await.is_err());
+    let subscriptions = subscription_repo.find_by_user(full_id.clone()).await?;
+    assert_eq!(subscriptions.len(), 1);
+    assert_eq!(subscriptions[0].asset_id, asset_id);
+
+    // the app keeps signing in with its paired key, now into the full account
+    let devices = device_service.get_by_user(&full_id).await?;
+    assert_eq!(devices.len(), 1);
+    assert!(!devices[0].revoked());
+
+    let challenge = device_service.challenge(&device).await?;
+    let signature = sign(&key, &device, &challenge);
+    let verified = device_service
+        .verify(&device, &challenge, &signature)
+        .await?;
+    assert_eq!(verified.user_id(), &full_id);
+
+    let res = user_service
+        .login(&Some(verified), &None, &None, &None)
+        .await?;
+    assert_eq!(res.user_id, full_id);
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use lib_config::config::Config;
+use lib_config::environment::{DEV_ENV, ENV_VAR_ENVIRONMENT};
+use lib_config::infra::build_local_stack_connection;
+use lib_config::result::ResultE;
+use lib_config::schema::Schema;
+use lib_engage::models::alert_similar::AlertSimilarBuilder;
+use lib_engage::repositories::alert_similar::AlertSimilarRepo;
+use lib_engage::repositories::schema_alert_similar::AlertSimilarSchema;
+use lib_engage::services::alert_similar::AlertSimilarService;
+use std::env;
+use testcontainers::*;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn alerts_by_asset_test() -> ResultE<()> {
+    env::set_var("RUST_LOG", "debug");
+    env::set_var("AWS_REGION", "eu-central-1");
+    env::set_var(ENV_VAR_ENVIRONMENT, DEV_ENV);
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let docker = clients::Cli::default();
+
+    let mut local_stack = images::local_stack::LocalStack::default();
+    local_stack.set_services("dynamodb");
+    let node = docker.run(local_stack);
+    let host_port = node.get_host_port_ipv4(4566);
+
+    let shared_config = build_local_stack_connection(host_port).await;
+
+    let mut conf = Config::new();
+    conf.setup().await;
+    conf.set_aws_config(&shared_config);
+
+    AlertSimilarSchema::create_schema(&conf).await?;
+    // a second run finds the indexes in place and leaves them alone
+    AlertSimilarSchema::create_schema(&conf).await?;
+
+    let service = AlertSimilarService::new(AlertSimilarRepo::new(&conf));
+
+    let mine = Uuid::new_v4();
+    let other = Uuid::new_v4();
+    let unrelated = Uuid::new_v4();
+
+    let as_origin = service
+        .add(
+            AlertSimilarBuilder::default()
+                .origin_asset_id(Some(mine))
+                .similar_asset_id(Some(other)),
+        )
+        .await?;
+    let as_similar = service
+        .add(
+            AlertSimilarBuilder::default()
+                .origin_asset_id(Some(other))
+                .similar_asset_id(Some(mine)),
+        )
+        .await?;
+    let itself = service
+        .add(
+            AlertSimilarBuilder::default()
+                .origin_asset_id(Some(mine))
+                .similar_asset_id(Some(mine)),
+        )
+        .await?;
+    service
+        .add(
+            AlertSimilarBuilder::default()
+                .origin_asset_id(Some(other))
+                .similar_asset_id(Some(unrelated)),
+        )
+        .await?;
+
+    // both sides are looked up and an alert against itself comes back once
+    let mut found: Vec<Uuid> = service
+        .get_by_asset(&mine)
+        .await?
+        .iter()
+        .map(|alert| *alert.id())
+        .collect();
+    found.sort();
+    let mut expected = vec![*as_origin.id(), *as_similar.id(), *itself.id()];
+    expected.sort();
+    assert_eq!(found, expected);
+
+    assert_eq!(service.get_by_asset(&unrelated).await?.len(), 1);
+    assert!(service.get_by_asset(&Uuid::new_v4()).await?.is_empty());
+
+    Ok(())
+}
@@ -1,2 +1,3 @@
-
+mod account_merge_test;
+mod alert_similar_test;
 mod subscription_test;
\ No newline at end of file
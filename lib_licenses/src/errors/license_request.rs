@@ -0,0 +1,56 @@
+use std::fmt::Display;
+
+#[derive(Debug)]
+pub struct LicenseRequestNoExistsError(pub String);
+
+impl std::error::Error for LicenseRequestNoExistsError {}
+
+impl Display for LicenseRequestNoExistsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "license request doesn't exists in database: {}", self.0)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LicenseRequestDynamoDBError(pub String);
+
+impl std::error::Error for LicenseRequestDynamoDBError {}
+
+impl Display for LicenseRequestDynamoDBError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "license request database error: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct LicenseRequestStatusError(pub String);
+
+impl std::error::Error for LicenseRequestStatusError {}
+
+impl Display for LicenseRequestStatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "license request status doesn't allow this operation: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct LicenseGrantNoExistsError(pub String);
+
+impl std::error::Error for LicenseGrantNoExistsError {}
+
+impl Display for LicenseGrantNoExistsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "license grant doesn't exists in database: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct LicenseGrantPeriodError(pub String);
+
+impl std::error::Error for LicenseGrantPeriodError {}
+
+impl Display for LicenseGrantPeriodError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "license grant validity period not accepted: {}", self.0)
+    }
+}
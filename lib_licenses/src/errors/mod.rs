@@ -1,4 +1,5 @@
 pub mod asset;
 pub mod license;
+pub mod license_request;
 pub mod owner;
 pub mod video;
@@ -32,3 +32,14 @@ impl Display for OwnerNoExistsError {
         write!(f, "owner doesn't exists in database: {}", self.0)
     }
 }
+
+#[derive(Debug)]
+pub struct OwnerShareError(pub String);
+
+impl std::error::Error for OwnerShareError {}
+
+impl Display for OwnerShareError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "owner shares error: {}", self.0)
+    }
+}
@@ -24,6 +24,9 @@ pub struct License {
     rights: Vec<Royalty>,
 
     status: LicenseStatus,
+
+    valid_from: Option<DateTime<Utc>>,
+    valid_until: Option<DateTime<Utc>>,
 }
 
 impl License {
@@ -47,6 +50,9 @@ impl License {
             rights: Vec::new(),
 
             status: LicenseStatus::Enabled,
+
+            valid_from: None,
+            valid_until: None,
         }
     }
 
@@ -178,6 +184,59 @@ impl License {
     pub fn set_status(&mut self, new_status: LicenseStatus) {
         self.status = new_status;
     }
+
+    // Getter for valid_from
+    pub fn valid_from(&self) -> &Option<DateTime<Utc>> {
+        &self.valid_from
+    }
+
+    // Setter for valid_from
+    pub fn set_valid_from(&mut self, valid_from: Option<DateTime<Utc>>) {
+        self.valid_from = valid_from;
+    }
+
+    // Getter for valid_until
+    pub fn valid_until(&self) -> &Option<DateTime<Utc>> {
+        &self.valid_until
+    }
+
+    // Setter for valid_until
+    pub fn set_valid_until(&mut self, valid_until: Option<DateTime<Utc>>) {
+        self.valid_until = valid_until;
+    }
+
+    // a license without window is valid forever while it's enabled
+    pub fn is_active_at(&self, at: &DateTime<Utc>) -> bool {
+        self.status == LicenseStatus::Enabled && in_window(&self.valid_from, &self.valid_until, at)
+    }
+
+    pub fn is_expired_at(&self, at: &DateTime<Utc>) -> bool {
+        match self.valid_until {
+            None => false,
+            Some(until) => until <= *at,
+        }
+    }
+
+    // drops the royalties out of their own window, used by public responses
+    pub fn retain_active_rights(&mut self, at: &DateTime<Utc>) {
+        self.rights.retain(|royalty| royalty.is_active_at(at));
+    }
+}
+
+fn in_window(
+    valid_from: &Option<DateTime<Utc>>,
+    valid_until: &Option<DateTime<Utc>>,
+    at: &DateTime<Utc>,
+) -> bool {
+    let started = match valid_from {
+        None => true,
+        Some(from) => *from <= *at,
+    };
+    let not_finished = match valid_until {
+        None => true,
+        Some(until) => *at < *until,
+    };
+    started && not_finished
 }
 
 impl Default for License {
@@ -196,6 +255,7 @@ impl fmt::Display for License {
 pub enum LicenseStatus {
     Enabled,
     Disabled,
+    Expired,
 }
 
 impl fmt::Display for LicenseStatus {
@@ -203,6 +263,7 @@ impl fmt::Display for LicenseStatus {
         match self {
             LicenseStatus::Enabled => write!(f, "Enabled"),
             LicenseStatus::Disabled => write!(f, "Disabled"),
+            LicenseStatus::Expired => write!(f, "Expired"),
         }
     }
 }
@@ -214,6 +275,7 @@ impl FromStr for LicenseStatus {
         match input {
             "Enabled" => Ok(LicenseStatus::Enabled),
             "Disabled" => Ok(LicenseStatus::Disabled),
+            "Expired" => Ok(LicenseStatus::Expired),
             _ => Err(ParseLicenseStatusError),
         }
     }
@@ -223,6 +285,16 @@ impl FromStr for LicenseStatus {
 pub struct Royalty {
     pub price: f32,
     pub location: String,
+    #[serde(default)]
+    pub valid_from: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub valid_until: Option<DateTime<Utc>>,
+}
+
+impl Royalty {
+    pub fn is_active_at(&self, at: &DateTime<Utc>) -> bool {
+        in_window(&self.valid_from, &self.valid_until, at)
+    }
 }
 
 impl PartialEq for License {
@@ -240,14 +312,27 @@ impl PartialEq for License {
             && self.right_to_use_press_media == other.right_to_use_press_media
             && self.rights == other.rights
             && self.status == other.status
+            && self.valid_from == other.valid_from
+            && self.valid_until == other.valid_until
     }
 }
 
 impl PartialEq for Royalty {
     fn eq(&self, other: &Self) -> bool {
-        self.price == other.price && self.location == other.location
+        self.price == other.price
+            && self.location == other.location
+            && self.valid_from == other.valid_from
+            && self.valid_until == other.valid_until
     }
 }
+// license ruling an asset: its own one, or the closest ancestor's when it's a derivative
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct EffectiveLicense {
+    pub asset_id: Uuid,
+    pub inherited_from: Option<Uuid>,
+    pub license: License,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CreatableFildsLicense {
     pub asset_id: Uuid,
@@ -258,6 +343,10 @@ pub struct CreatableFildsLicense {
     pub right_to_use_broadcast_media: bool,
     pub right_to_use_press_media: bool,
     pub rights: Vec<Royalty>,
+    #[serde(default)]
+    pub valid_from: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub valid_until: Option<DateTime<Utc>>,
 }
 
 impl CreatableFildsLicense {
@@ -279,6 +368,8 @@ impl CreatableFildsLicense {
         license.set_rights(self.rights.clone());
 
         license.set_status(LicenseStatus::Enabled);
+        license.set_valid_from(self.valid_from);
+        license.set_valid_until(self.valid_until);
 
         license
     }
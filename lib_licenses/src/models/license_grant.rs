@@ -0,0 +1,237 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{fmt, str::FromStr};
+use uuid::Uuid;
+
+use crate::models::owner::{Owner, FULL_OWNERSHIP};
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct LicenseGrant {
+    id: Uuid,
+    creation_time: DateTime<Utc>,
+    last_update_time: DateTime<Utc>,
+    request_id: Uuid,
+    license_id: Uuid,
+    license_version: u8,
+    asset_id: Uuid,
+    grantee_id: String,
+    granted_by: String,
+    valid_from: DateTime<Utc>,
+    valid_until: DateTime<Utc>,
+    status: LicenseGrantStatus,
+    price: Option<f32>,
+    royalty_splits: Vec<RoyaltySplit>,
+}
+
+impl LicenseGrant {
+    pub fn new() -> LicenseGrant {
+        LicenseGrant {
+            id: Uuid::new_v4(),
+            creation_time: Utc::now(),
+            last_update_time: Utc::now(),
+            request_id: Uuid::nil(),
+            license_id: Uuid::nil(),
+            license_version: 0,
+            asset_id: Uuid::nil(),
+            grantee_id: String::new(),
+            granted_by: String::new(),
+            valid_from: Utc::now(),
+            valid_until: Utc::now(),
+            status: LicenseGrantStatus::Active,
+            price: None,
+            royalty_splits: Vec::new(),
+        }
+    }
+
+    pub fn id(&self) -> &Uuid {
+        &self.id
+    }
+    pub fn set_id(&mut self, val: &Uuid) {
+        self.id = val.clone()
+    }
+    pub fn creation_time(&self) -> &DateTime<Utc> {
+        &self.creation_time
+    }
+    pub fn set_creation_time(&mut self, val: &DateTime<Utc>) {
+        self.creation_time = val.clone()
+    }
+    pub fn last_update_time(&self) -> &DateTime<Utc> {
+        &self.last_update_time
+    }
+    pub fn set_last_update_time(&mut self, val: &DateTime<Utc>) {
+        self.last_update_time = val.clone()
+    }
+    pub fn request_id(&self) -> &Uuid {
+        &self.request_id
+    }
+    pub fn set_request_id(&mut self, val: &Uuid) {
+        self.request_id = val.clone()
+    }
+    pub fn license_id(&self) -> &Uuid {
+        &self.license_id
+    }
+    pub fn set_license_id(&mut self, val: &Uuid) {
+        self.license_id = val.clone()
+    }
+    pub fn license_version(&self) -> u8 {
+        self.license_version
+    }
+    pub fn set_license_version(&mut self, val: u8) {
+        self.license_version = val
+    }
+    pub fn asset_id(&self) -> &Uuid {
+        &self.asset_id
+    }
+    pub fn set_asset_id(&mut self, val: &Uuid) {
+        self.asset_id = val.clone()
+    }
+    pub fn grantee_id(&self) -> &String {
+        &self.grantee_id
+    }
+    pub fn set_grantee_id(&mut self, val: &String) {
+        self.grantee_id = val.clone()
+    }
+    pub fn granted_by(&self) -> &String {
+        &self.granted_by
+    }
+    pub fn set_granted_by(&mut self, val: &String) {
+        self.granted_by = val.clone()
+    }
+    pub fn valid_from(&self) -> &DateTime<Utc> {
+        &self.valid_from
+    }
+    pub fn set_valid_from(&mut self, val: &DateTime<Utc>) {
+        self.valid_from = val.clone()
+    }
+    pub fn valid_until(&self) -> &DateTime<Utc> {
+        &self.valid_until
+    }
+    pub fn set_valid_until(&mut self, val: &DateTime<Utc>) {
+        self.valid_until = val.clone()
+    }
+    pub fn status(&self) -> &LicenseGrantStatus {
+        &self.status
+    }
+    pub fn set_status(&mut self, val: &LicenseGrantStatus) {
+        self.status = val.clone()
+    }
+    pub fn price(&self) -> &Option<f32> {
+        &self.price
+    }
+    pub fn set_price(&mut self, val: &Option<f32>) {
+        self.price = *val
+    }
+    pub fn royalty_splits(&self) -> &Vec<RoyaltySplit> {
+        &self.royalty_splits
+    }
+    pub fn set_royalty_splits(&mut self, val: &Vec<RoyaltySplit>) {
+        self.royalty_splits = val.clone()
+    }
+
+    // a grant is valid when it's active and `at` falls inside its validity period
+    pub fn is_valid_at(&self, at: &DateTime<Utc>) -> bool {
+        self.status == LicenseGrantStatus::Active
+            && self.valid_from <= *at
+            && *at < self.valid_until
+    }
+}
+
+impl Default for LicenseGrant {
+    fn default() -> LicenseGrant {
+        LicenseGrant::new()
+    }
+}
+
+impl fmt::Display for LicenseGrant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", json!(self).to_string())
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum LicenseGrantStatus {
+    Active,
+    Revoked,
+}
+
+impl fmt::Display for LicenseGrantStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LicenseGrantStatus::Active => write!(f, "Active"),
+            LicenseGrantStatus::Revoked => write!(f, "Revoked"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseLicenseGrantStatusError;
+impl FromStr for LicenseGrantStatus {
+    type Err = ParseLicenseGrantStatusError;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "Active" => Ok(LicenseGrantStatus::Active),
+            "Revoked" => Ok(LicenseGrantStatus::Revoked),
+            _ => Err(ParseLicenseGrantStatusError),
+        }
+    }
+}
+
+// public answer: says if the grantee can use the asset, never how much it was paid
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LicenseVerification {
+    pub asset_id: Uuid,
+    pub grantee_id: String,
+    pub valid: bool,
+    pub license_id: Option<Uuid>,
+    pub license_version: Option<u8>,
+    pub valid_until: Option<DateTime<Utc>>,
+}
+
+impl LicenseVerification {
+    pub fn new(asset_id: &Uuid, grantee_id: &String) -> LicenseVerification {
+        LicenseVerification {
+            asset_id: asset_id.clone(),
+            grantee_id: grantee_id.clone(),
+            valid: false,
+            license_id: None,
+            license_version: None,
+            valid_until: None,
+        }
+    }
+}
+
+// how much of a grant's price goes to each co-owner of the asset
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct RoyaltySplit {
+    pub user_id: String,
+    pub share: f32,
+    pub amount: f32,
+}
+
+// amounts are rounded to cents; the rounding leftover goes to the biggest holder so
+// the splits always add up to the price
+pub fn split_royalty(price: f32, owners: &[Owner]) -> Vec<RoyaltySplit> {
+    let price_cents = (price * 100.0).round() as i64;
+    let mut splits: Vec<RoyaltySplit> = owners
+        .iter()
+        .map(|owner| RoyaltySplit {
+            user_id: owner.user_id().clone(),
+            share: owner.share(),
+            amount: ((price_cents as f32 * owner.share() / FULL_OWNERSHIP).floor()) / 100.0,
+        })
+        .collect();
+
+    let assigned: i64 = splits
+        .iter()
+        .map(|split| (split.amount * 100.0).round() as i64)
+        .sum();
+    let leftover = price_cents - assigned;
+    if let Some(biggest) = splits
+        .iter_mut()
+        .max_by(|a, b| a.share.partial_cmp(&b.share).unwrap_or(std::cmp::Ordering::Equal))
+    {
+        biggest.amount = ((biggest.amount * 100.0).round() as i64 + leftover) as f32 / 100.0;
+    }
+    splits
+}
@@ -0,0 +1,188 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{fmt, str::FromStr};
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct LicenseRequest {
+    id: Uuid,
+    creation_time: DateTime<Utc>,
+    last_update_time: DateTime<Utc>,
+    license_id: Uuid,
+    asset_id: Uuid,
+    requester_id: String,
+    intended_use: String,
+    territory: String,
+    status: LicenseRequestStatus,
+    reviewer_id: Option<String>,
+    review_comment: Option<String>,
+}
+
+impl LicenseRequest {
+    pub fn new() -> LicenseRequest {
+        LicenseRequest {
+            id: Uuid::new_v4(),
+            creation_time: Utc::now(),
+            last_update_time: Utc::now(),
+            license_id: Uuid::nil(),
+            asset_id: Uuid::nil(),
+            requester_id: String::new(),
+            intended_use: String::new(),
+            territory: String::new(),
+            status: LicenseRequestStatus::Pending,
+            reviewer_id: None,
+            review_comment: None,
+        }
+    }
+
+    pub fn id(&self) -> &Uuid {
+        &self.id
+    }
+    pub fn set_id(&mut self, val: &Uuid) {
+        self.id = val.clone()
+    }
+    pub fn creation_time(&self) -> &DateTime<Utc> {
+        &self.creation_time
+    }
+    pub fn set_creation_time(&mut self, val: &DateTime<Utc>) {
+        self.creation_time = val.clone()
+    }
+    pub fn last_update_time(&self) -> &DateTime<Utc> {
+        &self.last_update_time
+    }
+    pub fn set_last_update_time(&mut self, val: &DateTime<Utc>) {
+        self.last_update_time = val.clone()
+    }
+    pub fn license_id(&self) -> &Uuid {
+        &self.license_id
+    }
+    pub fn set_license_id(&mut self, val: &Uuid) {
+        self.license_id = val.clone()
+    }
+    pub fn asset_id(&self) -> &Uuid {
+        &self.asset_id
+    }
+    pub fn set_asset_id(&mut self, val: &Uuid) {
+        self.asset_id = val.clone()
+    }
+    pub fn requester_id(&self) -> &String {
+        &self.requester_id
+    }
+    pub fn set_requester_id(&mut self, val: &String) {
+        self.requester_id = val.clone()
+    }
+    pub fn intended_use(&self) -> &String {
+        &self.intended_use
+    }
+    pub fn set_intended_use(&mut self, val: &String) {
+        self.intended_use = val.clone()
+    }
+    pub fn territory(&self) -> &String {
+        &self.territory
+    }
+    pub fn set_territory(&mut self, val: &String) {
+        self.territory = val.clone()
+    }
+    pub fn status(&self) -> &LicenseRequestStatus {
+        &self.status
+    }
+    pub fn set_status(&mut self, val: &LicenseRequestStatus) {
+        self.status = val.clone()
+    }
+    pub fn reviewer_id(&self) -> &Option<String> {
+        &self.reviewer_id
+    }
+    pub fn set_reviewer_id(&mut self, val: &Option<String>) {
+        self.reviewer_id = val.clone()
+    }
+    pub fn review_comment(&self) -> &Option<String> {
+        &self.review_comment
+    }
+    pub fn set_review_comment(&mut self, val: &Option<String>) {
+        self.review_comment = val.clone()
+    }
+}
+
+impl Default for LicenseRequest {
+    fn default() -> LicenseRequest {
+        LicenseRequest::new()
+    }
+}
+
+impl fmt::Display for LicenseRequest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", json!(self).to_string())
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum LicenseRequestStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl fmt::Display for LicenseRequestStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LicenseRequestStatus::Pending => write!(f, "Pending"),
+            LicenseRequestStatus::Approved => write!(f, "Approved"),
+            LicenseRequestStatus::Rejected => write!(f, "Rejected"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseLicenseRequestStatusError;
+impl FromStr for LicenseRequestStatus {
+    type Err = ParseLicenseRequestStatusError;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "Pending" => Ok(LicenseRequestStatus::Pending),
+            "Approved" => Ok(LicenseRequestStatus::Approved),
+            "Rejected" => Ok(LicenseRequestStatus::Rejected),
+            _ => Err(ParseLicenseRequestStatusError),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+pub struct CreatableFildsLicenseRequest {
+    pub license_id: Uuid,
+    #[validate(length(min = 1, max = 1000))]
+    pub intended_use: String,
+    #[validate(length(min = 2, max = 100))]
+    pub territory: String,
+}
+
+impl CreatableFildsLicenseRequest {
+    pub fn to_license_request(&self, asset_id: &Uuid, requester_id: &String) -> LicenseRequest {
+        let mut request = LicenseRequest::new();
+        request.set_license_id(&self.license_id);
+        request.set_asset_id(asset_id);
+        request.set_requester_id(requester_id);
+        request.set_intended_use(&self.intended_use);
+        request.set_territory(&self.territory);
+        request
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+pub struct ApproveFildsLicenseRequest {
+    pub valid_from: Option<DateTime<Utc>>,
+    pub valid_until: DateTime<Utc>,
+    #[validate(length(max = 1000))]
+    pub comment: Option<String>,
+    // when missing, the royalty of the license for the requested territory is applied
+    #[serde(default)]
+    #[validate(range(min = 0.0))]
+    pub price: Option<f32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+pub struct RejectFildsLicenseRequest {
+    #[validate(length(max = 1000))]
+    pub comment: Option<String>,
+}
@@ -1,5 +1,7 @@
 pub mod asset;
 pub mod license;
+pub mod license_grant;
+pub mod license_request;
 pub mod owner;
 pub mod hash;
 pub mod video;
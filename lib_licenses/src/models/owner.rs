@@ -1,10 +1,21 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::HashSet;
 use std::fmt;
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::errors::owner::OwnerShareError;
+
+pub const FULL_OWNERSHIP: f32 = 100.0;
+// owners holding at least this share manage the asset: its licenses, requests and co-owners.
+// Changing the co-owners takes more than this on its own, a tie needs every controlling owner.
+pub const CONTROLLING_SHARE: f32 = 50.0;
+const SHARE_TOLERANCE: f32 = 0.01;
+// holds the shares of removed accounts whose assets are kept but not handed to anybody
+pub const ANONYMOUS_OWNER: &str = "anonymous";
+
 #[derive(Clone, Serialize, Validate, Deserialize, Debug)]
 pub struct Owner {
     asset_id: Uuid,
@@ -12,6 +23,10 @@ pub struct Owner {
     user_id: String,
     creation_time: DateTime<Utc>,
     last_update_time: DateTime<Utc>,
+    share: f32,
+    // split of the asset this owner has agreed to, waiting for the rest of the controlling owners
+    #[serde(default)]
+    pending_split: Option<String>,
 }
 
 impl fmt::Display for Owner {
@@ -27,6 +42,8 @@ impl Owner {
             creation_time: Utc::now(),
             last_update_time: Utc::now(),
             user_id: String::new(),
+            share: FULL_OWNERSHIP,
+            pending_split: None,
         }
     }
 
@@ -54,4 +71,93 @@ impl Owner {
     pub fn set_last_update_time(&mut self, val: &DateTime<Utc>) {
         self.last_update_time = val.clone()
     }
+    pub fn share(&self) -> f32 {
+        self.share
+    }
+    pub fn set_share(&mut self, val: f32) {
+        self.share = val
+    }
+    pub fn is_controlling(&self) -> bool {
+        self.share >= CONTROLLING_SHARE
+    }
+    pub fn has_majority(&self) -> bool {
+        self.share > CONTROLLING_SHARE
+    }
+    pub fn pending_split(&self) -> &Option<String> {
+        &self.pending_split
+    }
+    pub fn set_pending_split(&mut self, val: &Option<String>) {
+        self.pending_split = val.clone()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+pub struct CoOwnerShare {
+    #[validate(length(min = 1, max = 100))]
+    pub user_id: String,
+    #[validate(range(min = 0.01, max = 100.0))]
+    pub share: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+pub struct UpdatableFildsCoOwners {
+    #[validate(length(min = 1, max = 20))]
+    pub owners: Vec<CoOwnerShare>,
+}
+
+impl UpdatableFildsCoOwners {
+    // shares must add up to 100 and somebody must keep the control of the asset
+    pub fn check_shares(&self) -> Result<(), OwnerShareError> {
+        let mut users = HashSet::new();
+        for owner in self.owners.iter() {
+            if !users.insert(owner.user_id.clone()) {
+                return Err(OwnerShareError(format!(
+                    "user {} is listed more than once",
+                    owner.user_id
+                )));
+            }
+        }
+        let total: f32 = self.owners.iter().map(|owner| owner.share).sum();
+        if (total - FULL_OWNERSHIP).abs() > SHARE_TOLERANCE {
+            return Err(OwnerShareError(format!(
+                "shares must sum {}, but they sum {}",
+                FULL_OWNERSHIP, total
+            )));
+        }
+        if !self
+            .owners
+            .iter()
+            .any(|owner| owner.share >= CONTROLLING_SHARE)
+        {
+            return Err(OwnerShareError(format!(
+                "at least one owner must hold {}% or more",
+                CONTROLLING_SHARE
+            )));
+        }
+        Ok(())
+    }
+
+    // same owners and shares give the same text whatever order they were sent in
+    pub fn split(&self) -> String {
+        let mut owners: Vec<String> = self
+            .owners
+            .iter()
+            .map(|owner| format!("{}={}", owner.user_id, owner.share))
+            .collect();
+        owners.sort();
+        owners.join(";")
+    }
+
+    pub fn to_owners(&self, asset_id: &Uuid) -> Vec<Owner> {
+        self.owners
+            .iter()
+            .map(|co_owner| {
+                let mut owner = Owner::new();
+                owner.set_asset_id(asset_id);
+                owner.set_user_id(&co_owner.user_id);
+                owner.set_share(co_owner.share);
+                owner
+            })
+            .collect()
+    }
 }
@@ -13,14 +13,16 @@ use crate::errors::owner::{OwnerDynamoDBError, OwnerNoExistsError};
 use crate::models::asset::{
     Asset, AssetStatus, HashProcessStatus, SourceType, VideoLicensingStatus,
 };
-use crate::models::owner::Owner;
+use crate::models::owner::{Owner, FULL_OWNERSHIP};
 use async_trait::async_trait;
 use aws_sdk_dynamodb::types::{AttributeValue, Put, Select, TransactWriteItem};
 use aws_sdk_dynamodb::Client;
 use chrono::Local;
 use lib_config::config::Config;
 
-use super::owners::mapping_from_doc_to_owner;
+use super::owners::{
+    mapping_from_doc_to_owner, SHARE_FIELD_NAME as OWNER_SHARE_FIELD_NAME,
+};
 use super::schema_asset::{
     ASSETS_TABLE_NAME, ASSET_ID_FIELD_PK, ASSET_TREE_FATHER_ID_FIELD_PK, ASSET_TREE_FATHER_INDEX,
     ASSET_TREE_SON_ID_FIELD_PK, ASSET_TREE_TABLE_NAME, URL_FIELD_NAME, URL_INDEX_NAME,
@@ -61,6 +63,11 @@ pub trait AssetRepository {
     async fn get_all(&self, page_number: u32, page_size: u32) -> ResultE<Vec<Asset>>;
     async fn get_by_user_id(&self, user_id: &String) -> ResultE<Vec<Asset>>;
     async fn get_by_user_asset_id(&self, asset_id: &Uuid, user_id: &String) -> ResultE<Asset>;
+    async fn get_owner_by_user_asset_id(
+        &self,
+        asset_id: &Uuid,
+        user_id: &String,
+    ) -> ResultE<Owner>;
 }
 
 #[derive(Clone, Debug)]
@@ -217,6 +224,10 @@ impl AssetRepository for AssetRepo {
                         Put::builder()
                             .item(OWNER_USER_ID_FIELD_PK, user_id_av.clone())
                             .item(OWNER_ASSET_ID_FIELD_PK, asset_id_av.clone())
+                            .item(
+                                OWNER_SHARE_FIELD_NAME,
+                                AttributeValue::N(FULL_OWNERSHIP.to_string()),
+                            )
                             .table_name(OWNERS_TABLE_NAME.clone())
                             .build()
                             .unwrap(),
@@ -503,6 +514,16 @@ impl AssetRepository for AssetRepo {
     }
 
     async fn get_by_user_asset_id(&self, asset_id: &Uuid, user_id: &String) -> ResultE<Asset> {
+        let own = self.get_owner_by_user_asset_id(asset_id, user_id).await?;
+        let asset = self.get_by_id(own.asset_id()).await?;
+        Ok(asset)
+    }
+
+    async fn get_owner_by_user_asset_id(
+        &self,
+        asset_id: &Uuid,
+        user_id: &String,
+    ) -> ResultE<Owner> {
         let user_id_av = AttributeValue::S(user_id.to_string());
         let asset_id_av = AttributeValue::S(asset_id.to_string());
 
@@ -541,11 +562,7 @@ impl AssetRepository for AssetRepo {
                 mapping_from_doc_to_owner(&aux, &mut own);
             }
         }
-        //let res = self._get_by_id(own.asset_id()).await?;
-        //let mut asset = Asset::new();
-        //mapping_from_doc_to_asset(&res, &mut asset);
-        let asset = self.get_by_id(own.asset_id()).await?;
-        Ok(asset)
+        Ok(own)
     }
 
     async fn get_father(&self, son_id: &Uuid) -> ResultE<Option<Uuid>> {
@@ -0,0 +1,380 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use aws_sdk_dynamodb::types::Select;
+use lib_config::timing::{from_iso8601, iso8601};
+use uuid::Uuid;
+
+use crate::errors::license_request::{LicenseGrantNoExistsError, LicenseRequestDynamoDBError};
+use crate::models::license_grant::{LicenseGrant, LicenseGrantStatus, RoyaltySplit};
+use async_trait::async_trait;
+use aws_sdk_dynamodb::{types::AttributeValue, Client};
+use chrono::Local;
+use lib_config::config::Config;
+
+use super::schema_license_requests::{
+    LICENSE_GRANTS_ASSET_GRANTEE_INDEX, LICENSE_GRANTS_GRANTEE_ID_INDEX, LICENSE_GRANTS_TABLE_NAME,
+    LICENSE_GRANT_ASSET_ID_FIELD, LICENSE_GRANT_GRANTEE_ID_FIELD, LICENSE_GRANT_ID_FIELD_PK,
+};
+pub const CREATION_TIME_FIELD_NAME: &str = "creationTime";
+pub const LAST_UPDATE_TIME_FIELD_NAME: &str = "lastUpdateTime";
+pub const REQUEST_ID_FIELD_NAME: &str = "requestId";
+pub const LICENSE_ID_FIELD_NAME: &str = "licenseId";
+pub const LICENSE_VERSION_FIELD_NAME: &str = "licenseVersion";
+pub const GRANTED_BY_FIELD_NAME: &str = "grantedBy";
+pub const VALID_FROM_FIELD_NAME: &str = "validFrom";
+pub const VALID_UNTIL_FIELD_NAME: &str = "validUntil";
+pub const STATUS_FIELD_NAME: &str = "grantStatus";
+pub const PRICE_FIELD_NAME: &str = "price";
+pub const ROYALTY_SPLITS_FIELD_NAME: &str = "royaltySplits";
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+#[async_trait]
+pub trait LicenseGrantRepository {
+    async fn add(&self, grant: &LicenseGrant) -> ResultE<()>;
+    async fn update(&self, grant: &LicenseGrant) -> ResultE<()>;
+    async fn get_by_id(&self, grant_id: &Uuid) -> ResultE<LicenseGrant>;
+    async fn get_by_asset_grantee(
+        &self,
+        asset_id: &Uuid,
+        grantee_id: &String,
+    ) -> ResultE<Vec<LicenseGrant>>;
+    async fn get_by_grantee(&self, grantee_id: &String) -> ResultE<Vec<LicenseGrant>>;
+}
+
+#[derive(Clone, Debug)]
+pub struct LicenseGrantRepo {
+    client: Client,
+}
+
+impl LicenseGrantRepo {
+    pub fn new(conf: &Config) -> LicenseGrantRepo {
+        LicenseGrantRepo {
+            client: Client::new(conf.aws_config()),
+        }
+    }
+
+    async fn get_by_filter(
+        &self,
+        filter: &String,
+        values: HashMap<String, AttributeValue>,
+        index_name: &str,
+    ) -> ResultE<Vec<LicenseGrant>> {
+        let mut queried = Vec::new();
+
+        let request = self
+            .client
+            .query()
+            .table_name(LICENSE_GRANTS_TABLE_NAME.clone())
+            .index_name(index_name)
+            .key_condition_expression(filter)
+            .set_expression_attribute_values(Some(values))
+            .select(Select::AllProjectedAttributes);
+
+        let results = request.send().await;
+        match results {
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                return Err(LicenseRequestDynamoDBError(e.to_string()).into());
+            }
+            Ok(data) => {
+                let items = data.items();
+                for doc in items {
+                    let mut grant = LicenseGrant::new();
+                    mapping_from_doc_to_license_grant(doc, &mut grant);
+                    queried.push(grant.clone());
+                }
+            }
+        }
+
+        Ok(queried)
+    }
+
+    async fn put(&self, grant: &LicenseGrant) -> ResultE<()> {
+        let request = self
+            .client
+            .put_item()
+            .table_name(LICENSE_GRANTS_TABLE_NAME.clone())
+            .set_item(Some(mapping_from_license_grant_to_doc(grant)));
+
+        match request.send().await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                return Err(LicenseRequestDynamoDBError(e.to_string()).into());
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl LicenseGrantRepository for LicenseGrantRepo {
+    async fn add(&self, grant: &LicenseGrant) -> ResultE<()> {
+        self.put(grant).await
+    }
+
+    async fn update(&self, grant: &LicenseGrant) -> ResultE<()> {
+        self.put(grant).await
+    }
+
+    async fn get_by_id(&self, grant_id: &Uuid) -> ResultE<LicenseGrant> {
+        let id_av = AttributeValue::S(grant_id.to_string());
+
+        let request = self
+            .client
+            .get_item()
+            .table_name(LICENSE_GRANTS_TABLE_NAME.clone())
+            .key(LICENSE_GRANT_ID_FIELD_PK, id_av);
+
+        let results = request.send().await;
+        match results {
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                return Err(LicenseRequestDynamoDBError(e.to_string()).into());
+            }
+            Ok(res) => match res.item {
+                None => Err(LicenseGrantNoExistsError(grant_id.to_string()).into()),
+                Some(doc) => {
+                    let mut grant = LicenseGrant::new();
+                    mapping_from_doc_to_license_grant(&doc, &mut grant);
+                    Ok(grant)
+                }
+            },
+        }
+    }
+
+    async fn get_by_asset_grantee(
+        &self,
+        asset_id: &Uuid,
+        grantee_id: &String,
+    ) -> ResultE<Vec<LicenseGrant>> {
+        let filter = format!(
+            "{} = :asset AND {} = :grantee",
+            LICENSE_GRANT_ASSET_ID_FIELD, LICENSE_GRANT_GRANTEE_ID_FIELD
+        );
+        let mut values = HashMap::new();
+        values.insert(
+            ":asset".to_string(),
+            AttributeValue::S(asset_id.to_string()),
+        );
+        values.insert(
+            ":grantee".to_string(),
+            AttributeValue::S(grantee_id.clone()),
+        );
+
+        self.get_by_filter(&filter, values, LICENSE_GRANTS_ASSET_GRANTEE_INDEX)
+            .await
+    }
+
+    async fn get_by_grantee(&self, grantee_id: &String) -> ResultE<Vec<LicenseGrant>> {
+        let filter = format!("{} = :value", LICENSE_GRANT_GRANTEE_ID_FIELD);
+        let mut values = HashMap::new();
+        values.insert(":value".to_string(), AttributeValue::S(grantee_id.clone()));
+
+        self.get_by_filter(&filter, values, LICENSE_GRANTS_GRANTEE_ID_INDEX)
+            .await
+    }
+}
+
+fn mapping_from_doc_to_license_grant(doc: &HashMap<String, AttributeValue>, grant: &mut LicenseGrant) {
+    if let Some(attr) = doc.get(LICENSE_GRANT_ID_FIELD_PK) {
+        if let Ok(id) = attr.as_s() {
+            if let Ok(uuid) = Uuid::parse_str(id) {
+                grant.set_id(&uuid);
+            }
+        }
+    }
+
+    if let Some(attr) = doc.get(CREATION_TIME_FIELD_NAME) {
+        if let Ok(val) = attr.as_s() {
+            grant.set_creation_time(&from_iso8601(val));
+        }
+    }
+
+    if let Some(attr) = doc.get(LAST_UPDATE_TIME_FIELD_NAME) {
+        if let Ok(val) = attr.as_s() {
+            grant.set_last_update_time(&from_iso8601(val));
+        }
+    }
+
+    if let Some(attr) = doc.get(REQUEST_ID_FIELD_NAME) {
+        if let Ok(id) = attr.as_s() {
+            if let Ok(uuid) = Uuid::parse_str(id) {
+                grant.set_request_id(&uuid);
+            }
+        }
+    }
+
+    if let Some(attr) = doc.get(LICENSE_ID_FIELD_NAME) {
+        if let Ok(id) = attr.as_s() {
+            if let Ok(uuid) = Uuid::parse_str(id) {
+                grant.set_license_id(&uuid);
+            }
+        }
+    }
+
+    if let Some(attr) = doc.get(LICENSE_VERSION_FIELD_NAME) {
+        if let Ok(version) = attr.as_n() {
+            if let Ok(v) = version.parse::<u8>() {
+                grant.set_license_version(v);
+            }
+        }
+    }
+
+    if let Some(attr) = doc.get(LICENSE_GRANT_ASSET_ID_FIELD) {
+        if let Ok(id) = attr.as_s() {
+            if let Ok(uuid) = Uuid::parse_str(id) {
+                grant.set_asset_id(&uuid);
+            }
+        }
+    }
+
+    if let Some(attr) = doc.get(LICENSE_GRANT_GRANTEE_ID_FIELD) {
+        if let Ok(val) = attr.as_s() {
+            grant.set_grantee_id(val);
+        }
+    }
+
+    if let Some(attr) = doc.get(GRANTED_BY_FIELD_NAME) {
+        if let Ok(val) = attr.as_s() {
+            grant.set_granted_by(val);
+        }
+    }
+
+    if let Some(attr) = doc.get(VALID_FROM_FIELD_NAME) {
+        if let Ok(val) = attr.as_s() {
+            grant.set_valid_from(&from_iso8601(val));
+        }
+    }
+
+    if let Some(attr) = doc.get(VALID_UNTIL_FIELD_NAME) {
+        if let Ok(val) = attr.as_s() {
+            grant.set_valid_until(&from_iso8601(val));
+        }
+    }
+
+    if let Some(attr) = doc.get(STATUS_FIELD_NAME) {
+        if let Ok(val) = attr.as_s() {
+            if let Ok(st) = LicenseGrantStatus::from_str(val) {
+                grant.set_status(&st);
+            }
+        }
+    }
+
+    if let Some(attr) = doc.get(PRICE_FIELD_NAME) {
+        if let Ok(val) = attr.as_n() {
+            grant.set_price(&f32::from_str(val).ok());
+        }
+    }
+
+    if let Some(attr) = doc.get(ROYALTY_SPLITS_FIELD_NAME) {
+        if let Ok(items) = attr.as_l() {
+            let splits: Vec<RoyaltySplit> = items
+                .iter()
+                .filter_map(mapping_from_attr_to_royalty_split)
+                .collect();
+            grant.set_royalty_splits(&splits);
+        }
+    }
+}
+
+fn mapping_from_attr_to_royalty_split(attr: &AttributeValue) -> Option<RoyaltySplit> {
+    let m = attr.as_m().ok()?;
+    let user_id = m.get("userId")?.as_s().ok()?.clone();
+    let share = f32::from_str(m.get("share")?.as_n().ok()?).ok()?;
+    let amount = f32::from_str(m.get("amount")?.as_n().ok()?).ok()?;
+    Some(RoyaltySplit {
+        user_id,
+        share,
+        amount,
+    })
+}
+
+// shared with the license request repository, which writes the grant along with the approval
+pub fn mapping_from_license_grant_to_doc(grant: &LicenseGrant) -> HashMap<String, AttributeValue> {
+    let mut splits_av = Vec::new();
+    for split in grant.royalty_splits() {
+        splits_av.push(AttributeValue::M(maplit::hashmap! {
+            "userId".to_string() => AttributeValue::S(split.user_id.clone()),
+            "share".to_string() => AttributeValue::N(split.share.to_string()),
+            "amount".to_string() => AttributeValue::N(split.amount.to_string()),
+        }));
+    }
+
+    let mut item = HashMap::new();
+    item.insert(
+        LICENSE_GRANT_ID_FIELD_PK.to_string(),
+        AttributeValue::S(grant.id().to_string()),
+    );
+    item.insert(
+        CREATION_TIME_FIELD_NAME.to_string(),
+        AttributeValue::S(iso8601(grant.creation_time())),
+    );
+    item.insert(
+        LAST_UPDATE_TIME_FIELD_NAME.to_string(),
+        AttributeValue::S(iso8601(grant.last_update_time())),
+    );
+    item.insert(
+        REQUEST_ID_FIELD_NAME.to_string(),
+        AttributeValue::S(grant.request_id().to_string()),
+    );
+    item.insert(
+        LICENSE_ID_FIELD_NAME.to_string(),
+        AttributeValue::S(grant.license_id().to_string()),
+    );
+    item.insert(
+        LICENSE_VERSION_FIELD_NAME.to_string(),
+        AttributeValue::N(grant.license_version().to_string()),
+    );
+    item.insert(
+        LICENSE_GRANT_ASSET_ID_FIELD.to_string(),
+        AttributeValue::S(grant.asset_id().to_string()),
+    );
+    item.insert(
+        LICENSE_GRANT_GRANTEE_ID_FIELD.to_string(),
+        AttributeValue::S(grant.grantee_id().clone()),
+    );
+    item.insert(
+        GRANTED_BY_FIELD_NAME.to_string(),
+        AttributeValue::S(grant.granted_by().clone()),
+    );
+    item.insert(
+        VALID_FROM_FIELD_NAME.to_string(),
+        AttributeValue::S(iso8601(grant.valid_from())),
+    );
+    item.insert(
+        VALID_UNTIL_FIELD_NAME.to_string(),
+        AttributeValue::S(iso8601(grant.valid_until())),
+    );
+    item.insert(
+        STATUS_FIELD_NAME.to_string(),
+        AttributeValue::S(grant.status().to_string()),
+    );
+    item.insert(
+        ROYALTY_SPLITS_FIELD_NAME.to_string(),
+        AttributeValue::L(splits_av),
+    );
+    if let Some(price) = grant.price() {
+        item.insert(
+            PRICE_FIELD_NAME.to_string(),
+            AttributeValue::N(price.to_string()),
+        );
+    }
+    item
+}
@@ -0,0 +1,392 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use aws_sdk_dynamodb::types::{Put, Select, TransactWriteItem};
+use lib_config::timing::{from_iso8601, iso8601};
+use uuid::Uuid;
+
+use crate::errors::license_request::{LicenseRequestDynamoDBError, LicenseRequestNoExistsError};
+use crate::models::license_grant::LicenseGrant;
+use crate::models::license_request::{LicenseRequest, LicenseRequestStatus};
+use async_trait::async_trait;
+use aws_sdk_dynamodb::{types::AttributeValue, Client};
+use chrono::Local;
+use lib_config::config::Config;
+
+use super::license_grants::mapping_from_license_grant_to_doc;
+use super::schema_license_requests::{
+    LICENSE_GRANTS_TABLE_NAME, LICENSE_REQUESTS_ASSET_ID_INDEX,
+    LICENSE_REQUESTS_REQUESTER_ID_INDEX, LICENSE_REQUESTS_TABLE_NAME,
+    LICENSE_REQUEST_ASSET_ID_FIELD, LICENSE_REQUEST_ID_FIELD_PK,
+    LICENSE_REQUEST_REQUESTER_ID_FIELD,
+};
+pub const CREATION_TIME_FIELD_NAME: &str = "creationTime";
+pub const LAST_UPDATE_TIME_FIELD_NAME: &str = "lastUpdateTime";
+pub const LICENSE_ID_FIELD_NAME: &str = "licenseId";
+pub const INTENDED_USE_FIELD_NAME: &str = "intendedUse";
+pub const TERRITORY_FIELD_NAME: &str = "territory";
+pub const STATUS_FIELD_NAME: &str = "requestStatus";
+pub const REVIEWER_ID_FIELD_NAME: &str = "reviewerId";
+pub const REVIEW_COMMENT_FIELD_NAME: &str = "reviewComment";
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+#[async_trait]
+pub trait LicenseRequestRepository {
+    async fn add(&self, request: &LicenseRequest) -> ResultE<()>;
+    async fn update(&self, request: &LicenseRequest) -> ResultE<()>;
+    async fn get_by_id(&self, request_id: &Uuid) -> ResultE<LicenseRequest>;
+    async fn get_by_asset(&self, asset_id: &Uuid) -> ResultE<Vec<LicenseRequest>>;
+    async fn get_by_requester(&self, requester_id: &String) -> ResultE<Vec<LicenseRequest>>;
+    // stores the reviewed request, and the grant when approved, in one transaction; false when
+    // the request wasn't pending anymore, so two reviews can't both succeed
+    async fn resolve(
+        &self,
+        request: &LicenseRequest,
+        grant: &Option<LicenseGrant>,
+    ) -> ResultE<bool>;
+}
+
+#[derive(Clone, Debug)]
+pub struct LicenseRequestRepo {
+    client: Client,
+}
+
+impl LicenseRequestRepo {
+    pub fn new(conf: &Config) -> LicenseRequestRepo {
+        LicenseRequestRepo {
+            client: Client::new(conf.aws_config()),
+        }
+    }
+
+    async fn get_by_filter(
+        &self,
+        filter: &String,
+        label: &String,
+        index_name: &str,
+        av: AttributeValue,
+    ) -> ResultE<Vec<LicenseRequest>> {
+        let mut queried = Vec::new();
+
+        let request = self
+            .client
+            .query()
+            .table_name(LICENSE_REQUESTS_TABLE_NAME.clone())
+            .index_name(index_name)
+            .key_condition_expression(filter)
+            .expression_attribute_values(label, av)
+            .select(Select::AllProjectedAttributes);
+
+        let results = request.send().await;
+        match results {
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                return Err(LicenseRequestDynamoDBError(e.to_string()).into());
+            }
+            Ok(data) => {
+                let items = data.items();
+                for doc in items {
+                    let mut license_request = LicenseRequest::new();
+                    mapping_from_doc_to_license_request(doc, &mut license_request);
+                    queried.push(license_request.clone());
+                }
+            }
+        }
+
+        Ok(queried)
+    }
+
+    async fn put(&self, request: &LicenseRequest) -> ResultE<()> {
+        let request = self
+            .client
+            .put_item()
+            .table_name(LICENSE_REQUESTS_TABLE_NAME.clone())
+            .set_item(Some(mapping_from_license_request_to_doc(request)));
+
+        match request.send().await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                return Err(LicenseRequestDynamoDBError(e.to_string()).into());
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl LicenseRequestRepository for LicenseRequestRepo {
+    async fn add(&self, request: &LicenseRequest) -> ResultE<()> {
+        self.put(request).await
+    }
+
+    async fn update(&self, request: &LicenseRequest) -> ResultE<()> {
+        self.put(request).await
+    }
+
+    async fn get_by_id(&self, request_id: &Uuid) -> ResultE<LicenseRequest> {
+        let id_av = AttributeValue::S(request_id.to_string());
+
+        let request = self
+            .client
+            .get_item()
+            .table_name(LICENSE_REQUESTS_TABLE_NAME.clone())
+            .key(LICENSE_REQUEST_ID_FIELD_PK, id_av);
+
+        let results = request.send().await;
+        match results {
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                return Err(LicenseRequestDynamoDBError(e.to_string()).into());
+            }
+            Ok(res) => match res.item {
+                None => Err(LicenseRequestNoExistsError(request_id.to_string()).into()),
+                Some(doc) => {
+                    let mut license_request = LicenseRequest::new();
+                    mapping_from_doc_to_license_request(&doc, &mut license_request);
+                    Ok(license_request)
+                }
+            },
+        }
+    }
+
+    async fn get_by_asset(&self, asset_id: &Uuid) -> ResultE<Vec<LicenseRequest>> {
+        let asset_id_av = AttributeValue::S(asset_id.to_string());
+
+        let mut filter = "".to_string();
+        filter.push_str(LICENSE_REQUEST_ASSET_ID_FIELD);
+        filter.push_str(" = :value");
+
+        let res = self
+            .get_by_filter(
+                &filter,
+                &":value".to_string(),
+                LICENSE_REQUESTS_ASSET_ID_INDEX,
+                asset_id_av,
+            )
+            .await?;
+
+        Ok(res)
+    }
+
+    async fn get_by_requester(&self, requester_id: &String) -> ResultE<Vec<LicenseRequest>> {
+        let requester_id_av = AttributeValue::S(requester_id.clone());
+
+        let mut filter = "".to_string();
+        filter.push_str(LICENSE_REQUEST_REQUESTER_ID_FIELD);
+        filter.push_str(" = :value");
+
+        let res = self
+            .get_by_filter(
+                &filter,
+                &":value".to_string(),
+                LICENSE_REQUESTS_REQUESTER_ID_INDEX,
+                requester_id_av,
+            )
+            .await?;
+
+        Ok(res)
+    }
+    async fn resolve(
+        &self,
+        request: &LicenseRequest,
+        grant: &Option<LicenseGrant>,
+    ) -> ResultE<bool> {
+        let mut transaction = self.client.transact_write_items().transact_items(
+            TransactWriteItem::builder()
+                .put(
+                    Put::builder()
+                        .table_name(LICENSE_REQUESTS_TABLE_NAME.clone())
+                        .set_item(Some(mapping_from_license_request_to_doc(request)))
+                        .condition_expression("#status = :pending")
+                        .expression_attribute_names("#status", STATUS_FIELD_NAME)
+                        .expression_attribute_values(
+                            ":pending",
+                            AttributeValue::S(LicenseRequestStatus::Pending.to_string()),
+                        )
+                        .build()
+                        .unwrap(),
+                )
+                .build(),
+        );
+        if let Some(grant) = grant {
+            transaction = transaction.transact_items(
+                TransactWriteItem::builder()
+                    .put(
+                        Put::builder()
+                            .table_name(LICENSE_GRANTS_TABLE_NAME.clone())
+                            .set_item(Some(mapping_from_license_grant_to_doc(grant)))
+                            .build()
+                            .unwrap(),
+                    )
+                    .build(),
+            );
+        }
+
+        match transaction.send().await {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                let service_error = e.into_service_error();
+                // a concurrent review either changed the status or is writing the same row
+                if service_error.is_transaction_canceled_exception() {
+                    return Ok(false);
+                }
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    service_error
+                );
+                log::error!("{}", mssag);
+                Err(LicenseRequestDynamoDBError(service_error.to_string()).into())
+            }
+        }
+    }
+}
+
+fn mapping_from_doc_to_license_request(
+    doc: &HashMap<String, AttributeValue>,
+    request: &mut LicenseRequest,
+) {
+    if let Some(attr) = doc.get(LICENSE_REQUEST_ID_FIELD_PK) {
+        if let Ok(id) = attr.as_s() {
+            if let Ok(uuid) = Uuid::parse_str(id) {
+                request.set_id(&uuid);
+            }
+        }
+    }
+
+    if let Some(attr) = doc.get(CREATION_TIME_FIELD_NAME) {
+        if let Ok(creation_time) = attr.as_s() {
+            request.set_creation_time(&from_iso8601(creation_time));
+        }
+    }
+
+    if let Some(attr) = doc.get(LAST_UPDATE_TIME_FIELD_NAME) {
+        if let Ok(last_update_time) = attr.as_s() {
+            request.set_last_update_time(&from_iso8601(last_update_time));
+        }
+    }
+
+    if let Some(attr) = doc.get(LICENSE_ID_FIELD_NAME) {
+        if let Ok(id) = attr.as_s() {
+            if let Ok(uuid) = Uuid::parse_str(id) {
+                request.set_license_id(&uuid);
+            }
+        }
+    }
+
+    if let Some(attr) = doc.get(LICENSE_REQUEST_ASSET_ID_FIELD) {
+        if let Ok(id) = attr.as_s() {
+            if let Ok(uuid) = Uuid::parse_str(id) {
+                request.set_asset_id(&uuid);
+            }
+        }
+    }
+
+    if let Some(attr) = doc.get(LICENSE_REQUEST_REQUESTER_ID_FIELD) {
+        if let Ok(requester_id) = attr.as_s() {
+            request.set_requester_id(requester_id);
+        }
+    }
+
+    if let Some(attr) = doc.get(INTENDED_USE_FIELD_NAME) {
+        if let Ok(intended_use) = attr.as_s() {
+            request.set_intended_use(intended_use);
+        }
+    }
+
+    if let Some(attr) = doc.get(TERRITORY_FIELD_NAME) {
+        if let Ok(territory) = attr.as_s() {
+            request.set_territory(territory);
+        }
+    }
+
+    if let Some(attr) = doc.get(STATUS_FIELD_NAME) {
+        if let Ok(status) = attr.as_s() {
+            if let Ok(st) = LicenseRequestStatus::from_str(status) {
+                request.set_status(&st);
+            }
+        }
+    }
+
+    if let Some(attr) = doc.get(REVIEWER_ID_FIELD_NAME) {
+        if let Ok(reviewer_id) = attr.as_s() {
+            request.set_reviewer_id(&Some(reviewer_id.clone()));
+        }
+    }
+
+    if let Some(attr) = doc.get(REVIEW_COMMENT_FIELD_NAME) {
+        if let Ok(comment) = attr.as_s() {
+            request.set_review_comment(&Some(comment.clone()));
+        }
+    }
+}
+
+fn mapping_from_license_request_to_doc(
+    request: &LicenseRequest,
+) -> HashMap<String, AttributeValue> {
+    let mut item = HashMap::new();
+    item.insert(
+        LICENSE_REQUEST_ID_FIELD_PK.to_string(),
+        AttributeValue::S(request.id().to_string()),
+    );
+    item.insert(
+        CREATION_TIME_FIELD_NAME.to_string(),
+        AttributeValue::S(iso8601(request.creation_time())),
+    );
+    item.insert(
+        LAST_UPDATE_TIME_FIELD_NAME.to_string(),
+        AttributeValue::S(iso8601(request.last_update_time())),
+    );
+    item.insert(
+        LICENSE_ID_FIELD_NAME.to_string(),
+        AttributeValue::S(request.license_id().to_string()),
+    );
+    item.insert(
+        LICENSE_REQUEST_ASSET_ID_FIELD.to_string(),
+        AttributeValue::S(request.asset_id().to_string()),
+    );
+    item.insert(
+        LICENSE_REQUEST_REQUESTER_ID_FIELD.to_string(),
+        AttributeValue::S(request.requester_id().clone()),
+    );
+    item.insert(
+        INTENDED_USE_FIELD_NAME.to_string(),
+        AttributeValue::S(request.intended_use().clone()),
+    );
+    item.insert(
+        TERRITORY_FIELD_NAME.to_string(),
+        AttributeValue::S(request.territory().clone()),
+    );
+    item.insert(
+        STATUS_FIELD_NAME.to_string(),
+        AttributeValue::S(request.status().to_string()),
+    );
+    if let Some(reviewer) = request.reviewer_id() {
+        item.insert(
+            REVIEWER_ID_FIELD_NAME.to_string(),
+            AttributeValue::S(reviewer.clone()),
+        );
+    }
+    if let Some(comment) = request.review_comment() {
+        item.insert(
+            REVIEW_COMMENT_FIELD_NAME.to_string(),
+            AttributeValue::S(comment.clone()),
+        );
+    }
+    item
+}
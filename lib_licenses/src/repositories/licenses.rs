@@ -26,6 +26,8 @@ pub const RIGHT_TO_USE_BROADCAST_MEDIA_FIELD: &str = "rightToUseBroadcastMedia";
 pub const RIGHT_TO_USE_PRESS_MEDIA_FIELD: &str = "rightToUsePressMedia";
 pub const LICENSE_STATUS_FIELD: &str = "status";
 pub const ROYALTIES_FIELD: &str = "royalties";
+pub const VALID_FROM_FIELD: &str = "validFrom";
+pub const VALID_UNTIL_FIELD: &str = "validUntil";
 
 type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
 
@@ -38,6 +40,7 @@ pub trait LicenseRepository {
     async fn get_all(&self, _page_number: u32, _page_size: u32) -> ResultE<Vec<License>>;
     async fn update(&self, license: &License) -> ResultE<()>;
     async fn delete(&self, license: &License) -> ResultE<()>;
+    async fn get_enabled_with_expiration(&self) -> ResultE<Vec<License>>;
 }
 
 #[derive(Clone, Debug)]
@@ -149,17 +152,26 @@ impl LicenseRepository for LicenseRepo {
 
         let mut rights_av = Vec::new();
         for royalty in license.rights() {
-            let royalty_av = AttributeValue::M(
-                maplit::hashmap! {
-                    "price".to_string() => AttributeValue::N(royalty.price.to_string()),
-                    "location".to_string() => AttributeValue::S(royalty.location.to_string()),
-                }
-                .into(),
-            );
-            rights_av.push(royalty_av);
+            let mut royalty_map = maplit::hashmap! {
+                "price".to_string() => AttributeValue::N(royalty.price.to_string()),
+                "location".to_string() => AttributeValue::S(royalty.location.to_string()),
+            };
+            if let Some(from) = royalty.valid_from {
+                royalty_map.insert(
+                    VALID_FROM_FIELD.to_string(),
+                    AttributeValue::S(iso8601(&from)),
+                );
+            }
+            if let Some(until) = royalty.valid_until {
+                royalty_map.insert(
+                    VALID_UNTIL_FIELD.to_string(),
+                    AttributeValue::S(iso8601(&until)),
+                );
+            }
+            rights_av.push(AttributeValue::M(royalty_map));
         }
 
-        let request = self
+        let mut request = self
             .client
             .put_item()
             .table_name(LICENSES_TABLE_NAME.clone())
@@ -183,6 +195,13 @@ impl LicenseRepository for LicenseRepo {
             .item(LICENSE_STATUS_FIELD, status_av)
             .item(ROYALTIES_FIELD, AttributeValue::L(rights_av));
 
+        if let Some(from) = license.valid_from() {
+            request = request.item(VALID_FROM_FIELD, AttributeValue::S(iso8601(from)));
+        }
+        if let Some(until) = license.valid_until() {
+            request = request.item(VALID_UNTIL_FIELD, AttributeValue::S(iso8601(until)));
+        }
+
         match request.send().await {
             Ok(_) => Ok(()),
             Err(e) => {
@@ -294,7 +313,9 @@ impl LicenseRepository for LicenseRepo {
 
     async fn update(&self, license: &License) -> ResultE<()> {
         let last_update_time_av = AttributeValue::S(iso8601(license.last_update_time()));
+        let status_av = AttributeValue::S(license.status().to_string());
 
+        // status is a reserved word at dynamodb expressions
         let request = self
             .client
             .update_item()
@@ -303,8 +324,17 @@ impl LicenseRepository for LicenseRepo {
                 LICENSE_ID_FIELD_PK,
                 AttributeValue::S(license.id().to_string()),
             )
-            .update_expression(format!("SET {} = :value", LAST_UPDATE_TIME_FIELD_NAME))
-            .expression_attribute_values(":value", last_update_time_av);
+            .key(
+                LICENSE_ASSET_ID_FIELD_PK,
+                AttributeValue::S(license.asset_id().to_string()),
+            )
+            .update_expression(format!(
+                "SET {} = :value, #status = :status",
+                LAST_UPDATE_TIME_FIELD_NAME
+            ))
+            .expression_attribute_names("#status", LICENSE_STATUS_FIELD)
+            .expression_attribute_values(":value", last_update_time_av)
+            .expression_attribute_values(":status", status_av);
 
         match request.send().await {
             Ok(_) => Ok(()),
@@ -346,6 +376,58 @@ impl LicenseRepository for LicenseRepo {
             }
         }
     }
+
+    async fn get_enabled_with_expiration(&self) -> ResultE<Vec<License>> {
+        let mut queried = Vec::new();
+        let mut last_key: Option<HashMap<String, AttributeValue>> = None;
+
+        loop {
+            let results = self
+                .client
+                .scan()
+                .table_name(LICENSES_TABLE_NAME.clone())
+                .filter_expression(format!(
+                    "#status = :status AND attribute_exists({})",
+                    VALID_UNTIL_FIELD
+                ))
+                .expression_attribute_names("#status", LICENSE_STATUS_FIELD)
+                .expression_attribute_values(
+                    ":status",
+                    AttributeValue::S(LicenseStatus::Enabled.to_string()),
+                )
+                .set_exclusive_start_key(last_key.clone())
+                .send()
+                .await;
+
+            match results {
+                Err(e) => {
+                    let mssag = format!(
+                        "Error at [{}] - {} ",
+                        Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                        e
+                    );
+                    log::error!("{}", mssag);
+                    return Err(LicenseDynamoDBError(e.to_string()).into());
+                }
+                Ok(result) => {
+                    if let Some(docs) = result.items {
+                        for doc in docs {
+                            let mut lic = License::new();
+                            mapping_from_doc_to_license(&doc, &mut lic);
+                            queried.push(lic);
+                        }
+                    }
+                    last_key = result.last_evaluated_key;
+                }
+            }
+
+            if last_key.is_none() {
+                break;
+            }
+        }
+
+        Ok(queried)
+    }
 }
 
 // fn iso8601(st: DateTime<Utc>) -> String {
@@ -370,7 +452,21 @@ fn mapping_from_attr_to_royalty(attr: &AttributeValue) -> Option<Royalty> {
 
         let location = m.get("location").unwrap().as_s().unwrap().clone();
 
-        Some(Royalty { price, location })
+        let valid_from = m
+            .get(VALID_FROM_FIELD)
+            .and_then(|v| v.as_s().ok())
+            .map(from_iso8601);
+        let valid_until = m
+            .get(VALID_UNTIL_FIELD)
+            .and_then(|v| v.as_s().ok())
+            .map(from_iso8601);
+
+        Some(Royalty {
+            price,
+            location,
+            valid_from,
+            valid_until,
+        })
     } else {
         None
     }
@@ -459,6 +555,18 @@ fn mapping_from_doc_to_license(doc: &HashMap<String, AttributeValue>, license: &
         }
     }
 
+    if let Some(valid_from_attr) = doc.get(VALID_FROM_FIELD) {
+        if let Ok(valid_from) = valid_from_attr.as_s() {
+            license.set_valid_from(Some(from_iso8601(valid_from)));
+        }
+    }
+
+    if let Some(valid_until_attr) = doc.get(VALID_UNTIL_FIELD) {
+        if let Ok(valid_until) = valid_until_attr.as_s() {
+            license.set_valid_until(Some(from_iso8601(valid_until)));
+        }
+    }
+
     if let Some(rights_attr) = doc.get(ROYALTIES_FIELD) {
         if let Ok(rights) = rights_attr.as_l() {
             let royalties = rights
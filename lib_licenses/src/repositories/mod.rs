@@ -1,7 +1,10 @@
 pub mod assets;
+pub mod license_grants;
+pub mod license_requests;
 pub mod licenses;
 pub mod owners;
 pub mod schema_asset;
+pub mod schema_license_requests;
 pub mod schema_licenses;
 pub mod schema_owners;
 pub mod shorter;
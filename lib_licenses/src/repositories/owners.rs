@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::str::FromStr;
 
@@ -6,8 +7,9 @@ use lib_config::timing::{from_iso8601, iso8601};
 use uuid::Uuid;
 
 use crate::errors::owner::{OwnerDynamoDBError, OwnerNoExistsError};
-use crate::models::owner::Owner;
+use crate::models::owner::{Owner, FULL_OWNERSHIP};
 use async_trait::async_trait;
+use aws_sdk_dynamodb::types::{Delete, Put, TransactWriteItem};
 use aws_sdk_dynamodb::{types::AttributeValue, Client};
 use chrono::{prelude::Utc, Local};
 use lib_config::config::Config;
@@ -18,6 +20,8 @@ use super::schema_owners::{
 };
 pub const CREATIONTIME_FIELD_NAME: &str = "creationTime";
 pub const LASTUPDATETIME_FIELD_NAME: &str = "lastUpdateTime";
+pub const SHARE_FIELD_NAME: &str = "share";
+pub const PENDING_SPLIT_FIELD_NAME: &str = "pendingSplit";
 
 type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
 
@@ -29,6 +33,14 @@ pub trait OwnerRepository {
     async fn get_by_user(&self, user_id: &String) -> ResultE<Vec<Owner>>;
     async fn get_by_user_asset(&self, asset_id: &Uuid, user_id: &String) -> ResultE<Owner>;
     async fn get_all(&self, page_number: u32, page_size: u32) -> ResultE<Vec<Owner>>;
+    async fn get_all_by_asset(&self, asset_id: &Uuid) -> ResultE<Vec<Owner>>;
+    async fn replace_by_asset(
+        &self,
+        asset_id: &Uuid,
+        current: &Vec<Owner>,
+        new_owners: &Vec<Owner>,
+    ) -> ResultE<()>;
+    async fn update_pending_split(&self, owner: &Owner) -> ResultE<()>;
 }
 
 #[derive(Clone, Debug)]
@@ -100,7 +112,8 @@ impl OwnerRepository for OwnerRepo {
             .item(OWNER_USER_ID_FIELD_PK, user_id_av)
             .item(OWNER_ASSET_ID_FIELD_PK, asset_id_av)
             .item(CREATIONTIME_FIELD_NAME, creation_time_av)
-            .item(LASTUPDATETIME_FIELD_NAME, update_time_av);
+            .item(LASTUPDATETIME_FIELD_NAME, update_time_av)
+            .item(SHARE_FIELD_NAME, AttributeValue::N(owner.share().to_string()));
 
         match request.send().await {
             Ok(_) => Ok(()),
@@ -184,11 +197,105 @@ impl OwnerRepository for OwnerRepo {
             )
             .await?;
 
-        if res.len() == 0 {
-            Err(OwnerNoExistsError("id doesn't exist".to_string()).into())
-        } else {
-            let first = res[0].to_owned();
-            Ok(first)
+        // co-owned assets answer with the biggest holder
+        match res
+            .into_iter()
+            .max_by(|a, b| a.share().partial_cmp(&b.share()).unwrap_or(Ordering::Equal))
+        {
+            None => Err(OwnerNoExistsError("id doesn't exist".to_string()).into()),
+            Some(first) => Ok(first),
+        }
+    }
+
+    async fn get_all_by_asset(&self, asset_id: &Uuid) -> ResultE<Vec<Owner>> {
+        let id_av = AttributeValue::S(asset_id.to_string());
+        let filter = format!("{} = :value", OWNER_ASSET_ID_FIELD_PK);
+
+        self.get_by_filter(&filter, &":value".to_string(), OWNERS_ASSET_ID_INDEX, id_av)
+            .await
+    }
+
+    // swaps the whole set of owners of an asset in one transaction
+    async fn replace_by_asset(
+        &self,
+        asset_id: &Uuid,
+        current: &Vec<Owner>,
+        new_owners: &Vec<Owner>,
+    ) -> ResultE<()> {
+        let asset_id_av = AttributeValue::S(asset_id.to_string());
+        let now_av = AttributeValue::S(iso8601(&Utc::now()));
+
+        let mut request = self.client.transact_write_items();
+
+        for old in current.iter() {
+            if new_owners
+                .iter()
+                .any(|owner| owner.user_id() == old.user_id())
+            {
+                continue;
+            }
+            request = request.transact_items(
+                TransactWriteItem::builder()
+                    .delete(
+                        Delete::builder()
+                            .key(
+                                OWNER_USER_ID_FIELD_PK,
+                                AttributeValue::S(old.user_id().clone()),
+                            )
+                            .key(OWNER_ASSET_ID_FIELD_PK, asset_id_av.clone())
+                            .table_name(OWNERS_TABLE_NAME.clone())
+                            .build()
+                            .unwrap(),
+                    )
+                    .build(),
+            );
+        }
+
+        for owner in new_owners.iter() {
+            let creation_time = match current
+                .iter()
+                .find(|old| old.user_id() == owner.user_id())
+            {
+                Some(old) => old.creation_time().clone(),
+                None => Utc::now(),
+            };
+            request = request.transact_items(
+                TransactWriteItem::builder()
+                    .put(
+                        Put::builder()
+                            .item(
+                                OWNER_USER_ID_FIELD_PK,
+                                AttributeValue::S(owner.user_id().clone()),
+                            )
+                            .item(OWNER_ASSET_ID_FIELD_PK, asset_id_av.clone())
+                            .item(
+                                CREATIONTIME_FIELD_NAME,
+                                AttributeValue::S(iso8601(&creation_time)),
+                            )
+                            .item(LASTUPDATETIME_FIELD_NAME, now_av.clone())
+                            .item(
+                                SHARE_FIELD_NAME,
+                                AttributeValue::N(owner.share().to_string()),
+                            )
+                            .table_name(OWNERS_TABLE_NAME.clone())
+                            .build()
+                            .unwrap(),
+                    )
+                    .build(),
+            );
+        }
+
+        match request.send().await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                return Err(OwnerDynamoDBError(e.to_string()).into());
+            }
         }
     }
 
@@ -261,6 +368,48 @@ impl OwnerRepository for OwnerRepo {
             }
         }
     }
+
+    // replace_by_asset writes the rows from scratch, so an applied split leaves nothing pending
+    async fn update_pending_split(&self, owner: &Owner) -> ResultE<()> {
+        let request = self
+            .client
+            .update_item()
+            .table_name(OWNERS_TABLE_NAME.clone())
+            .key(
+                OWNER_USER_ID_FIELD_PK,
+                AttributeValue::S(owner.user_id().clone()),
+            )
+            .key(
+                OWNER_ASSET_ID_FIELD_PK,
+                AttributeValue::S(owner.asset_id().to_string()),
+            )
+            .expression_attribute_values(":lastup", AttributeValue::S(iso8601(&Utc::now())));
+        let request = match owner.pending_split() {
+            Some(split) => request
+                .update_expression(format!(
+                    "set {} = :split, {} = :lastup",
+                    PENDING_SPLIT_FIELD_NAME, LASTUPDATETIME_FIELD_NAME
+                ))
+                .expression_attribute_values(":split", AttributeValue::S(split.clone())),
+            None => request.update_expression(format!(
+                "set {} = :lastup remove {}",
+                LASTUPDATETIME_FIELD_NAME, PENDING_SPLIT_FIELD_NAME
+            )),
+        };
+
+        match request.send().await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                return Err(OwnerDynamoDBError(e.to_string()).into());
+            }
+        }
+    }
 }
 
 // fn iso8601(st: &DateTime<Utc>) -> String {
@@ -298,4 +447,18 @@ pub fn mapping_from_doc_to_owner(doc: &HashMap<String, AttributeValue>, owner: &
             owner.set_last_update_time(&from_iso8601(last_update_time.as_s().unwrap()));
         }
     }
+
+    // owners stored before co-ownership hold the whole asset
+    let share = doc
+        .get(SHARE_FIELD_NAME)
+        .and_then(|v| v.as_n().ok())
+        .and_then(|v| f32::from_str(v).ok())
+        .unwrap_or(FULL_OWNERSHIP);
+    owner.set_share(share);
+
+    let pending_split = doc
+        .get(PENDING_SPLIT_FIELD_NAME)
+        .and_then(|v| v.as_s().ok())
+        .cloned();
+    owner.set_pending_split(&pending_split);
 }
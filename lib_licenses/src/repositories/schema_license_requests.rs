@@ -0,0 +1,288 @@
+use async_trait::async_trait;
+use aws_sdk_dynamodb::types::{
+    builders::StreamSpecificationBuilder, AttributeDefinition, BillingMode, GlobalSecondaryIndex,
+    KeySchemaElement, KeyType, Projection, ProjectionType, ScalarAttributeType, StreamViewType,
+    Tag,
+};
+use lib_config::{
+    config::Config,
+    environment::PROD_ENV,
+    result::ResultE,
+    schema::{Schema, schema_exists, wait_until_schema_is_active},
+    constants::{
+        VALUE_PROJECT, API_DOMAIN, TAG_PROJECT, TAG_SERVICE, TAG_ENVIRONMENT
+    }
+};
+
+lazy_static! {
+    pub static ref LICENSE_REQUESTS_TABLE_NAME: String = format!("{}_{}_license_requests", VALUE_PROJECT, API_DOMAIN );
+    pub static ref LICENSE_GRANTS_TABLE_NAME: String = format!("{}_{}_license_grants", VALUE_PROJECT, API_DOMAIN );
+}
+
+pub const LICENSE_REQUEST_ID_FIELD_PK: &str = "requestId";
+pub const LICENSE_REQUEST_ASSET_ID_FIELD: &str = "assetId";
+pub const LICENSE_REQUEST_REQUESTER_ID_FIELD: &str = "requesterId";
+pub const LICENSE_REQUESTS_ASSET_ID_INDEX: &str = "asset_id_index";
+pub const LICENSE_REQUESTS_REQUESTER_ID_INDEX: &str = "requester_id_index";
+
+pub const LICENSE_GRANT_ID_FIELD_PK: &str = "grantId";
+pub const LICENSE_GRANT_ASSET_ID_FIELD: &str = "assetId";
+pub const LICENSE_GRANT_GRANTEE_ID_FIELD: &str = "granteeId";
+pub const LICENSE_GRANTS_ASSET_GRANTEE_INDEX: &str = "asset_grantee_index";
+pub const LICENSE_GRANTS_GRANTEE_ID_INDEX: &str = "grantee_id_index";
+
+pub struct LicenseRequestSchema;
+#[async_trait]
+impl Schema for LicenseRequestSchema {
+    async fn create_schema(config: &Config) -> ResultE<()> {
+
+        let exist = schema_exists(config, LICENSE_REQUESTS_TABLE_NAME.as_str()).await?;
+        if exist{
+            return Ok(())
+        }
+
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+
+        let ad1 = AttributeDefinition::builder()
+            .attribute_name(LICENSE_REQUEST_ID_FIELD_PK)
+            .attribute_type(ScalarAttributeType::S)
+            .build().unwrap();
+        let ad2 = AttributeDefinition::builder()
+            .attribute_name(LICENSE_REQUEST_ASSET_ID_FIELD)
+            .attribute_type(ScalarAttributeType::S)
+            .build().unwrap();
+        let ad3 = AttributeDefinition::builder()
+            .attribute_name(LICENSE_REQUEST_REQUESTER_ID_FIELD)
+            .attribute_type(ScalarAttributeType::S)
+            .build().unwrap();
+
+        let ks1 = KeySchemaElement::builder()
+            .attribute_name(LICENSE_REQUEST_ID_FIELD_PK)
+            .key_type(KeyType::Hash)
+            .build().unwrap();
+
+        let second_index_by_asset = GlobalSecondaryIndex::builder()
+            .index_name(LICENSE_REQUESTS_ASSET_ID_INDEX)
+            .key_schema(
+                KeySchemaElement::builder()
+                    .attribute_name(LICENSE_REQUEST_ASSET_ID_FIELD)
+                    .key_type(KeyType::Hash)
+                    .build().unwrap(),
+            )
+            .projection(
+                Projection::builder()
+                    .projection_type(ProjectionType::All)
+                    .build(),
+            )
+            .build().unwrap();
+        let third_index_by_requester = GlobalSecondaryIndex::builder()
+            .index_name(LICENSE_REQUESTS_REQUESTER_ID_INDEX)
+            .key_schema(
+                KeySchemaElement::builder()
+                    .attribute_name(LICENSE_REQUEST_REQUESTER_ID_FIELD)
+                    .key_type(KeyType::Hash)
+                    .build().unwrap(),
+            )
+            .projection(
+                Projection::builder()
+                    .projection_type(ProjectionType::All)
+                    .build(),
+            )
+            .build().unwrap();
+        client
+            .create_table()
+            .table_name(LICENSE_REQUESTS_TABLE_NAME.clone())
+            .key_schema(ks1)
+            .global_secondary_indexes(second_index_by_asset)
+            .global_secondary_indexes(third_index_by_requester)
+            .attribute_definitions(ad1)
+            .attribute_definitions(ad2)
+            .attribute_definitions(ad3)
+            .billing_mode(BillingMode::PayPerRequest)
+            .stream_specification(
+                StreamSpecificationBuilder::default()
+                    .stream_enabled(true)
+                    .stream_view_type(StreamViewType::NewAndOldImages)
+                    .build().unwrap(),
+            )
+            .tags(
+                Tag::builder()
+                    .set_key(Some(TAG_ENVIRONMENT.to_string()))
+                    .set_value(Some(config.env_vars().environment().unwrap()))
+                    .build().unwrap(),
+            )
+            .tags(
+                Tag::builder()
+                    .set_key(Some(TAG_PROJECT.to_string()))
+                    .set_value(Some(VALUE_PROJECT.to_string()))
+                    .build().unwrap(),
+            )
+            .tags(
+                Tag::builder()
+                    .set_key(Some(TAG_SERVICE.to_string()))
+                    .set_value(Some(API_DOMAIN.to_string()))
+                    .build().unwrap(),
+            )
+            .deletion_protection_enabled(if config.env_vars().environment().unwrap() == PROD_ENV {
+                true
+            } else {
+                false
+            })
+            .send()
+            .await?;
+
+        wait_until_schema_is_active(config, LICENSE_REQUESTS_TABLE_NAME.as_str()).await?;
+
+        Ok(())
+    }
+
+    async fn delete_schema(config: &Config) -> ResultE<()> {
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+        client
+            .delete_table()
+            .table_name(LICENSE_REQUESTS_TABLE_NAME.clone())
+            .send()
+            .await?;
+
+        Ok(())
+    }
+}
+
+pub struct LicenseGrantSchema;
+#[async_trait]
+impl Schema for LicenseGrantSchema {
+    async fn create_schema(config: &Config) -> ResultE<()> {
+
+        let exist = schema_exists(config, LICENSE_GRANTS_TABLE_NAME.as_str()).await?;
+        if exist{
+            return Ok(())
+        }
+
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+
+        let ad1 = AttributeDefinition::builder()
+            .attribute_name(LICENSE_GRANT_ID_FIELD_PK)
+            .attribute_type(ScalarAttributeType::S)
+            .build().unwrap();
+        let ad2 = AttributeDefinition::builder()
+            .attribute_name(LICENSE_GRANT_ASSET_ID_FIELD)
+            .attribute_type(ScalarAttributeType::S)
+            .build().unwrap();
+        let ad3 = AttributeDefinition::builder()
+            .attribute_name(LICENSE_GRANT_GRANTEE_ID_FIELD)
+            .attribute_type(ScalarAttributeType::S)
+            .build().unwrap();
+
+        let ks1 = KeySchemaElement::builder()
+            .attribute_name(LICENSE_GRANT_ID_FIELD_PK)
+            .key_type(KeyType::Hash)
+            .build().unwrap();
+
+        let second_index_by_asset_grantee = GlobalSecondaryIndex::builder()
+            .index_name(LICENSE_GRANTS_ASSET_GRANTEE_INDEX)
+            .key_schema(
+                KeySchemaElement::builder()
+                    .attribute_name(LICENSE_GRANT_ASSET_ID_FIELD)
+                    .key_type(KeyType::Hash)
+                    .build().unwrap(),
+            )
+            .key_schema(
+                KeySchemaElement::builder()
+                    .attribute_name(LICENSE_GRANT_GRANTEE_ID_FIELD)
+                    .key_type(KeyType::Range)
+                    .build().unwrap(),
+            )
+            .projection(
+                Projection::builder()
+                    .projection_type(ProjectionType::All)
+                    .build(),
+            )
+            .build().unwrap();
+        let third_index_by_grantee = GlobalSecondaryIndex::builder()
+            .index_name(LICENSE_GRANTS_GRANTEE_ID_INDEX)
+            .key_schema(
+                KeySchemaElement::builder()
+                    .attribute_name(LICENSE_GRANT_GRANTEE_ID_FIELD)
+                    .key_type(KeyType::Hash)
+                    .build().unwrap(),
+            )
+            .projection(
+                Projection::builder()
+                    .projection_type(ProjectionType::All)
+                    .build(),
+            )
+            .build().unwrap();
+        client
+            .create_table()
+            .table_name(LICENSE_GRANTS_TABLE_NAME.clone())
+            .key_schema(ks1)
+            .global_secondary_indexes(second_index_by_asset_grantee)
+            .global_secondary_indexes(third_index_by_grantee)
+            .attribute_definitions(ad1)
+            .attribute_definitions(ad2)
+            .attribute_definitions(ad3)
+            .billing_mode(BillingMode::PayPerRequest)
+            .stream_specification(
+                StreamSpecificationBuilder::default()
+                    .stream_enabled(true)
+                    .stream_view_type(StreamViewType::NewAndOldImages)
+                    .build().unwrap(),
+            )
+            .tags(
+                Tag::builder()
+                    .set_key(Some(TAG_ENVIRONMENT.to_string()))
+                    .set_value(Some(config.env_vars().environment().unwrap()))
+                    .build().unwrap(),
+            )
+            .tags(
+                Tag::builder()
+                    .set_key(Some(TAG_PROJECT.to_string()))
+                    .set_value(Some(VALUE_PROJECT.to_string()))
+                    .build().unwrap(),
+            )
+            .tags(
+                Tag::builder()
+                    .set_key(Some(TAG_SERVICE.to_string()))
+                    .set_value(Some(API_DOMAIN.to_string()))
+                    .build().unwrap(),
+            )
+            .deletion_protection_enabled(if config.env_vars().environment().unwrap() == PROD_ENV {
+                true
+            } else {
+                false
+            })
+            .send()
+            .await?;
+
+        wait_until_schema_is_active(config, LICENSE_GRANTS_TABLE_NAME.as_str()).await?;
+
+        Ok(())
+    }
+
+    async fn delete_schema(config: &Config) -> ResultE<()> {
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+        client
+            .delete_table()
+            .table_name(LICENSE_GRANTS_TABLE_NAME.clone())
+            .send()
+            .await?;
+
+        Ok(())
+    }
+}
+
+pub struct LicenseRequestAllSchema;
+#[async_trait]
+impl Schema for LicenseRequestAllSchema {
+    async fn create_schema(config: &Config) -> ResultE<()> {
+        LicenseRequestSchema::create_schema(config).await?;
+        LicenseGrantSchema::create_schema(config).await?;
+        Ok(())
+    }
+
+    async fn delete_schema(config: &Config) -> ResultE<()> {
+        LicenseRequestSchema::delete_schema(config).await?;
+        LicenseGrantSchema::delete_schema(config).await?;
+        Ok(())
+    }
+}
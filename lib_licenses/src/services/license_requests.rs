@@ -0,0 +1,380 @@
+use crate::errors::license::LicenseNotFoundError;
+use crate::errors::license_request::{LicenseGrantPeriodError, LicenseRequestStatusError};
+use crate::errors::owner::OwnerShareError;
+use crate::models::license_grant::{
+    split_royalty, LicenseGrant, LicenseGrantStatus, LicenseVerification,
+};
+use crate::models::license_request::{
+    ApproveFildsLicenseRequest, CreatableFildsLicenseRequest, LicenseRequest,
+    LicenseRequestStatus, RejectFildsLicenseRequest,
+};
+use crate::repositories::assets::{AssetRepo, AssetRepository};
+use crate::repositories::license_grants::{LicenseGrantRepo, LicenseGrantRepository};
+use crate::repositories::license_requests::{LicenseRequestRepo, LicenseRequestRepository};
+use crate::repositories::licenses::{LicenseRepo, LicenseRepository};
+use crate::repositories::owners::{OwnerRepo, OwnerRepository};
+use async_trait::async_trait;
+use chrono::Utc;
+use uuid::Uuid;
+use validator::Validate;
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+pub const SERVICE: &str = "license_requests";
+
+#[async_trait]
+pub trait LicenseRequestManipulation {
+    async fn request(
+        &self,
+        fields: &CreatableFildsLicenseRequest,
+        requester_id: &String,
+    ) -> ResultE<LicenseRequest>;
+    async fn get_by_id(&self, request_id: &Uuid) -> ResultE<LicenseRequest>;
+    async fn get_by_requester(&self, requester_id: &String) -> ResultE<Vec<LicenseRequest>>;
+    async fn get_by_asset(&self, asset_id: &Uuid, owner_id: &String)
+        -> ResultE<Vec<LicenseRequest>>;
+    async fn approve(
+        &self,
+        request_id: &Uuid,
+        owner_id: &String,
+        fields: &ApproveFildsLicenseRequest,
+    ) -> ResultE<(LicenseRequest, LicenseGrant)>;
+    async fn reject(
+        &self,
+        request_id: &Uuid,
+        owner_id: &String,
+        fields: &RejectFildsLicenseRequest,
+    ) -> ResultE<LicenseRequest>;
+    async fn get_grants_by_grantee(&self, grantee_id: &String) -> ResultE<Vec<LicenseGrant>>;
+    async fn get_grants_by_asset_grantee(
+        &self,
+        asset_id: &Uuid,
+        grantee_id: &String,
+    ) -> ResultE<Vec<LicenseGrant>>;
+    // whether the grantee holds a grant in force over an active license of the asset
+    async fn verify(&self, asset_id: &Uuid, grantee_id: &String) -> ResultE<LicenseVerification>;
+    // an account merged into another one hands over the requests it made and the grants it holds
+    async fn transfer_by_user(&self, user_id: &String, heir: &String) -> ResultE<usize>;
+    // a removed account: its pending requests are rejected, what it wrote about the intended use
+    // is wiped and its grants are revoked, the rows stay for the owners' records
+    async fn close_by_user(&self, user_id: &String) -> ResultE<usize>;
+}
+
+#[derive(Debug)]
+pub struct LicenseRequestService {
+    repository: LicenseRequestRepo,
+    grant_repo: LicenseGrantRepo,
+    license_repo: LicenseRepo,
+    asset_repo: AssetRepo,
+    owner_repo: OwnerRepo,
+}
+
+impl LicenseRequestService {
+    pub fn new(
+        repo: LicenseRequestRepo,
+        grant_repo: LicenseGrantRepo,
+        license_repo: LicenseRepo,
+        asset_repo: AssetRepo,
+        owner_repo: OwnerRepo,
+    ) -> LicenseRequestService {
+        LicenseRequestService {
+            repository: repo,
+            grant_repo,
+            license_repo,
+            asset_repo,
+            owner_repo,
+        }
+    }
+
+    // every co-owner can follow the requests, only controlling owners review them
+    async fn check_ownership(
+        &self,
+        asset_id: &Uuid,
+        user_id: &String,
+        controlling: bool,
+    ) -> ResultE<bool> {
+        let owner = self
+            .asset_repo
+            .get_owner_by_user_asset_id(asset_id, user_id)
+            .await?;
+        if controlling && !owner.is_controlling() {
+            return Err(OwnerShareError(format!(
+                "user {} holds {}% of asset {}, not enough to review its requests",
+                user_id,
+                owner.share(),
+                asset_id
+            ))
+            .into());
+        }
+        Ok(true)
+    }
+
+    // only the owner of the asset can review a request, and only while it's pending
+    async fn get_pending_for_owner(
+        &self,
+        request_id: &Uuid,
+        owner_id: &String,
+    ) -> ResultE<LicenseRequest> {
+        let request = self.repository.get_by_id(request_id).await?;
+        self.check_ownership(request.asset_id(), owner_id, true)
+            .await?;
+        if *request.status() != LicenseRequestStatus::Pending {
+            return Err(LicenseRequestStatusError(format!(
+                "request {} is already {}",
+                request_id,
+                request.status()
+            ))
+            .into());
+        }
+        Ok(request)
+    }
+
+    // another review got there first, between the check and the write
+    fn already_reviewed(request_id: &Uuid) -> Box<dyn std::error::Error + Sync + Send> {
+        LicenseRequestStatusError(format!("request {} is already reviewed", request_id)).into()
+    }
+}
+
+#[async_trait]
+impl LicenseRequestManipulation for LicenseRequestService {
+    async fn request(
+        &self,
+        fields: &CreatableFildsLicenseRequest,
+        requester_id: &String,
+    ) -> ResultE<LicenseRequest> {
+        fields.validate()?;
+
+        let license = match self.license_repo.get_by_license_id(&fields.license_id).await? {
+            None => {
+                return Err(LicenseNotFoundError(fields.license_id.to_string()).into());
+            }
+            Some(lic) => lic,
+        };
+        if !license.is_active_at(&Utc::now()) {
+            return Err(LicenseNotFoundError(fields.license_id.to_string()).into());
+        }
+
+        let request = fields.to_license_request(license.asset_id(), requester_id);
+        self.repository.add(&request).await?;
+        Ok(request)
+    }
+
+    async fn get_by_id(&self, request_id: &Uuid) -> ResultE<LicenseRequest> {
+        self.repository.get_by_id(request_id).await
+    }
+
+    async fn get_by_requester(&self, requester_id: &String) -> ResultE<Vec<LicenseRequest>> {
+        self.repository.get_by_requester(requester_id).await
+    }
+
+    async fn get_by_asset(
+        &self,
+        asset_id: &Uuid,
+        owner_id: &String,
+    ) -> ResultE<Vec<LicenseRequest>> {
+        self.check_ownership(asset_id, owner_id, false).await?;
+        self.repository.get_by_asset(asset_id).await
+    }
+
+    async fn approve(
+        &self,
+        request_id: &Uuid,
+        owner_id: &String,
+        fields: &ApproveFildsLicenseRequest,
+    ) -> ResultE<(LicenseRequest, LicenseGrant)> {
+        fields.validate()?;
+
+        let valid_from = fields.valid_from.unwrap_or(Utc::now());
+        if fields.valid_until <= valid_from || fields.valid_until <= Utc::now() {
+            return Err(LicenseGrantPeriodError(
+                "valid_until must be in the future and after valid_from".to_string(),
+            )
+            .into());
+        }
+
+        let mut request = self.get_pending_for_owner(request_id, owner_id).await?;
+
+        let license = match self
+            .license_repo
+            .get_by_id(request.license_id(), request.asset_id())
+            .await?
+        {
+            None => {
+                return Err(LicenseNotFoundError(request.license_id().to_string()).into());
+            }
+            Some(lic) => lic,
+        };
+
+        let mut grant = LicenseGrant::new();
+        grant.set_request_id(request.id());
+        grant.set_license_id(license.id());
+        grant.set_license_version(license.version());
+        grant.set_asset_id(request.asset_id());
+        grant.set_grantee_id(request.requester_id());
+        grant.set_granted_by(owner_id);
+        grant.set_valid_from(&valid_from);
+        grant.set_valid_until(&fields.valid_until);
+
+        // priced grants are split among the co-owners by their shares
+        let price = fields.price.or_else(|| {
+            license
+                .rights()
+                .iter()
+                .find(|royalty| {
+                    royalty.location.eq_ignore_ascii_case(request.territory())
+                        && royalty.is_active_at(&valid_from)
+                })
+                .map(|royalty| royalty.price)
+        });
+        if let Some(amount) = price {
+            let owners = self.owner_repo.get_all_by_asset(request.asset_id()).await?;
+            grant.set_price(&Some(amount));
+            grant.set_royalty_splits(&split_royalty(amount, &owners));
+        }
+
+        request.set_status(&LicenseRequestStatus::Approved);
+        request.set_reviewer_id(&Some(owner_id.clone()));
+        request.set_review_comment(&fields.comment);
+        request.set_last_update_time(&Utc::now());
+        if !self
+            .repository
+            .resolve(&request, &Some(grant.clone()))
+            .await?
+        {
+            return Err(LicenseRequestService::already_reviewed(request_id));
+        }
+
+        Ok((request, grant))
+    }
+
+    async fn reject(
+        &self,
+        request_id: &Uuid,
+        owner_id: &String,
+        fields: &RejectFildsLicenseRequest,
+    ) -> ResultE<LicenseRequest> {
+        fields.validate()?;
+
+        let mut request = self.get_pending_for_owner(request_id, owner_id).await?;
+
+        request.set_status(&LicenseRequestStatus::Rejected);
+        request.set_reviewer_id(&Some(owner_id.clone()));
+        request.set_review_comment(&fields.comment);
+        request.set_last_update_time(&Utc::now());
+        if !self.repository.resolve(&request, &None).await? {
+            return Err(LicenseRequestService::already_reviewed(request_id));
+        }
+
+        Ok(request)
+    }
+
+    async fn get_grants_by_grantee(&self, grantee_id: &String) -> ResultE<Vec<LicenseGrant>> {
+        self.grant_repo.get_by_grantee(grantee_id).await
+    }
+
+    async fn get_grants_by_asset_grantee(
+        &self,
+        asset_id: &Uuid,
+        grantee_id: &String,
+    ) -> ResultE<Vec<LicenseGrant>> {
+        self.grant_repo
+            .get_by_asset_grantee(asset_id, grantee_id)
+            .await
+    }
+
+    async fn verify(&self, asset_id: &Uuid, grantee_id: &String) -> ResultE<LicenseVerification> {
+        let mut verification = LicenseVerification::new(asset_id, grantee_id);
+
+        // the grant lasting longer wins when several are valid at the same time
+        let now = Utc::now();
+        let mut grants = self
+            .grant_repo
+            .get_by_asset_grantee(asset_id, grantee_id)
+            .await?;
+        grants.retain(|grant| grant.is_valid_at(&now));
+        grants.sort_by(|a, b| b.valid_until().cmp(a.valid_until()));
+
+        for grant in grants.iter() {
+            let license = match self
+                .license_repo
+                .get_by_license_id(grant.license_id())
+                .await?
+            {
+                None => continue,
+                Some(lic) => lic,
+            };
+            if !license.is_active_at(&now) {
+                continue;
+            }
+            verification.valid = true;
+            verification.license_id = Some(license.id().clone());
+            verification.license_version = Some(grant.license_version());
+            verification.valid_until = Some(grant.valid_until().clone());
+            break;
+        }
+        Ok(verification)
+    }
+
+    async fn transfer_by_user(&self, user_id: &String, heir: &String) -> ResultE<usize> {
+        let mut moved = 0;
+        for mut request in self.repository.get_by_requester(user_id).await? {
+            request.set_requester_id(heir);
+            request.set_last_update_time(&Utc::now());
+            self.repository.update(&request).await?;
+            moved += 1;
+        }
+        for mut grant in self.grant_repo.get_by_grantee(user_id).await? {
+            grant.set_grantee_id(heir);
+            grant.set_last_update_time(&Utc::now());
+            self.grant_repo.update(&grant).await?;
+            moved += 1;
+        }
+        Ok(moved)
+    }
+
+    async fn close_by_user(&self, user_id: &String) -> ResultE<usize> {
+        let mut closed = 0;
+        for mut request in self.repository.get_by_requester(user_id).await? {
+            request.set_intended_use(&"".to_string());
+            request.set_last_update_time(&Utc::now());
+            if *request.status() == LicenseRequestStatus::Pending {
+                request.set_status(&LicenseRequestStatus::Rejected);
+                request.set_review_comment(&Some("requester account removed".to_string()));
+                // reviewed meanwhile, the text is wiped from what the review left and the grant
+                // is revoked below
+                if !self.repository.resolve(&request, &None).await? {
+                    let mut reviewed = self.repository.get_by_id(request.id()).await?;
+                    reviewed.set_intended_use(&"".to_string());
+                    self.repository.update(&reviewed).await?;
+                }
+            } else {
+                self.repository.update(&request).await?;
+            }
+            closed += 1;
+        }
+        for mut grant in self.grant_repo.get_by_grantee(user_id).await? {
+            if *grant.status() == LicenseGrantStatus::Revoked {
+                continue;
+            }
+            grant.set_status(&LicenseGrantStatus::Revoked);
+            grant.set_last_update_time(&Utc::now());
+            self.grant_repo.update(&grant).await?;
+            closed += 1;
+        }
+        Ok(closed)
+    }
+}
+
+impl Clone for LicenseRequestService {
+    fn clone(&self) -> LicenseRequestService {
+        let aux = LicenseRequestService {
+            repository: self.repository.clone(),
+            grant_repo: self.grant_repo.clone(),
+            license_repo: self.license_repo.clone(),
+            asset_repo: self.asset_repo.clone(),
+            owner_repo: self.owner_repo.clone(),
+        };
+        aux
+    }
+}
+
@@ -1,9 +1,13 @@
+use crate::errors::license::LicenseCreationError;
+use crate::errors::owner::OwnerShareError;
 use crate::models::license::CreatableFildsLicense;
-use crate::models::license::License;
+use crate::models::license::{EffectiveLicense, License, LicenseStatus};
 use crate::repositories::assets::AssetRepo;
 use crate::repositories::assets::AssetRepository;
 use crate::repositories::licenses::{LicenseRepo, LicenseRepository};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
 use uuid::Uuid;
 
 type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
@@ -23,6 +27,9 @@ pub trait LicenseManipulation {
     ) -> ResultE<Uuid>;
     async fn update(&self, license: &License) -> ResultE<()>;
     async fn delete(&self, license: &License) -> ResultE<()>;
+    async fn get_active_by_asset(&self, asset_id: &Uuid) -> ResultE<Vec<License>>;
+    async fn expire_licenses(&self, at: &DateTime<Utc>) -> ResultE<Vec<License>>;
+    async fn get_effective_license(&self, asset_id: &Uuid) -> ResultE<Option<EffectiveLicense>>;
 }
 
 #[derive(Debug)]
@@ -44,11 +51,21 @@ impl LicenseService {
         Ok(true)
     }
 
+    // licensing a co-owned asset is up to its controlling owners
     async fn check_ownership(&self, asset_id: &Uuid, user_id: &String) -> ResultE<bool> {
-        let _ = self
+        let owner = self
             .asset_repo
-            .get_by_user_asset_id(asset_id, user_id)
+            .get_owner_by_user_asset_id(asset_id, user_id)
             .await?;
+        if !owner.is_controlling() {
+            return Err(OwnerShareError(format!(
+                "user {} holds {}% of asset {}, not enough to license it",
+                user_id,
+                owner.share(),
+                asset_id
+            ))
+            .into());
+        }
         Ok(true)
     }
 }
@@ -78,6 +95,25 @@ impl LicenseManipulation for LicenseService {
         license_new: &CreatableFildsLicense,
         user_id: &Option<String>,
     ) -> ResultE<Uuid> {
+        if let (Some(from), Some(until)) = (license_new.valid_from, license_new.valid_until) {
+            if until <= from {
+                return Err(LicenseCreationError(
+                    "valid_until must be after valid_from".to_string(),
+                )
+                .into());
+            }
+        }
+        for royalty in license_new.rights.iter() {
+            if let (Some(from), Some(until)) = (royalty.valid_from, royalty.valid_until) {
+                if until <= from {
+                    return Err(LicenseCreationError(format!(
+                        "royalty at {}: valid_until must be after valid_from",
+                        royalty.location
+                    ))
+                    .into());
+                }
+            }
+        }
         self.check_if_asset_exist(&license_new.asset_id).await?;
         if let Some(user) = user_id {
             self.check_ownership(&license_new.asset_id, user).await?;
@@ -96,6 +132,63 @@ impl LicenseManipulation for LicenseService {
         self.repository.delete(license).await?;
         Ok(())
     }
+
+    // public view: only licenses and royalties inside their validity window
+    async fn get_active_by_asset(&self, asset_id: &Uuid) -> ResultE<Vec<License>> {
+        let now = Utc::now();
+        let mut res = self.repository.get_by_asset_id(asset_id).await?;
+        res.retain(|license| license.is_active_at(&now));
+        for license in res.iter_mut() {
+            license.retain_active_rights(&now);
+        }
+        Ok(res)
+    }
+
+    async fn expire_licenses(&self, at: &DateTime<Utc>) -> ResultE<Vec<License>> {
+        let candidates = self.repository.get_enabled_with_expiration().await?;
+        let mut expired = Vec::new();
+        for mut license in candidates {
+            if !license.is_expired_at(at) {
+                continue;
+            }
+            license.set_status(LicenseStatus::Expired);
+            license.set_last_update_time(Utc::now());
+            self.repository.update(&license).await?;
+            expired.push(license);
+        }
+        Ok(expired)
+    }
+
+    // an own active license overrides the inherited one, otherwise climb up the asset tree
+    async fn get_effective_license(&self, asset_id: &Uuid) -> ResultE<Option<EffectiveLicense>> {
+        self.check_if_asset_exist(asset_id).await?;
+
+        let mut visited = HashSet::new();
+        let mut current = *asset_id;
+        while visited.insert(current) {
+            let licenses = self.get_active_by_asset(&current).await?;
+            let newest = licenses
+                .into_iter()
+                .max_by(|a, b| a.creation_time().cmp(b.creation_time()));
+            if let Some(license) = newest {
+                let inherited_from = if current == *asset_id {
+                    None
+                } else {
+                    Some(current)
+                };
+                return Ok(Some(EffectiveLicense {
+                    asset_id: *asset_id,
+                    inherited_from,
+                    license,
+                }));
+            }
+            match self.asset_repo.get_father(&current).await? {
+                None => break,
+                Some(father) => current = father,
+            }
+        }
+        Ok(None)
+    }
 }
 
 impl Clone for LicenseService {
@@ -1,4 +1,5 @@
 pub mod assets;
+pub mod license_requests;
 pub mod licenses;
 pub mod owners;
 pub mod video;
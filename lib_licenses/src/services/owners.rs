@@ -1,4 +1,5 @@
-use crate::models::owner::Owner;
+use crate::errors::owner::OwnerShareError;
+use crate::models::owner::{Owner, UpdatableFildsCoOwners};
 use crate::repositories::owners::{OwnerRepo, OwnerRepository};
 use async_trait::async_trait;
 use uuid::Uuid;
@@ -15,6 +16,15 @@ pub trait OwnerManipulation {
     async fn get_by_user_asset_ids(&self, asset_id: &Uuid, user_id: &String) -> ResultE<Owner>;
     async fn add(&self, owner: &mut Owner) -> ResultE<()>;
     async fn update(&self, current: &Owner, new_owner: &UpdatableFildsOwner) -> ResultE<()>;
+    async fn get_all_by_asset(&self, asset_id: &Uuid) -> ResultE<Vec<Owner>>;
+    async fn set_co_owners(
+        &self,
+        asset_id: &Uuid,
+        user_id: &String,
+        fields: &UpdatableFildsCoOwners,
+    ) -> ResultE<Vec<Owner>>;
+    // hands every share of the user to the heir, returns the assets it touched
+    async fn release_by_user(&self, user_id: &String, heir: &String) -> ResultE<Vec<Uuid>>;
 }
 
 #[derive(Debug)]
@@ -74,6 +84,85 @@ impl OwnerManipulation for OwnerService {
         let res = self.repository.get_by_user_asset(asset_id, user_id).await?;
         Ok(res)
     }
+
+    async fn get_all_by_asset(&self, asset_id: &Uuid) -> ResultE<Vec<Owner>> {
+        let res = self.repository.get_all_by_asset(asset_id).await?;
+        Ok(res)
+    }
+
+    // An owner with the majority splits the asset alone. On a tie nobody has it, so the split is
+    // kept as pending until every controlling owner has asked for the same one; the returned
+    // owners then still carry it.
+    async fn set_co_owners(
+        &self,
+        asset_id: &Uuid,
+        user_id: &String,
+        fields: &UpdatableFildsCoOwners,
+    ) -> ResultE<Vec<Owner>> {
+        fields.validate()?;
+        for co_owner in fields.owners.iter() {
+            co_owner.validate()?;
+        }
+        fields.check_shares()?;
+
+        let mut caller = self.repository.get_by_user_asset(asset_id, user_id).await?;
+        if !caller.is_controlling() {
+            return Err(OwnerShareError(format!(
+                "user {} holds {}% of the asset, not enough to change its owners",
+                user_id,
+                caller.share()
+            ))
+            .into());
+        }
+
+        let split = Some(fields.split());
+        if !caller.has_majority() {
+            caller.set_pending_split(&split);
+            self.repository.update_pending_split(&caller).await?;
+        }
+
+        let current = self.repository.get_all_by_asset(asset_id).await?;
+        if !caller.has_majority()
+            && !current
+                .iter()
+                .filter(|owner| owner.is_controlling())
+                .all(|owner| *owner.pending_split() == split)
+        {
+            return Ok(current);
+        }
+        let new_owners = fields.to_owners(asset_id);
+        self.repository
+            .replace_by_asset(asset_id, &current, &new_owners)
+            .await?;
+        Ok(new_owners)
+    }
+
+    async fn release_by_user(&self, user_id: &String, heir: &String) -> ResultE<Vec<Uuid>> {
+        let owned = self.repository.get_by_user(user_id).await?;
+        let mut released = Vec::new();
+        for owned_share in owned.iter() {
+            let asset_id = owned_share.asset_id();
+            let current = self.repository.get_all_by_asset(asset_id).await?;
+
+            // an heir that already co-owns the asset just adds the share to its own
+            let mut new_owners: Vec<Owner> = Vec::new();
+            for owner in current.iter() {
+                let mut owner = owner.clone();
+                if owner.user_id() == user_id {
+                    owner.set_user_id(heir);
+                }
+                match new_owners.iter_mut().find(|o| o.user_id() == owner.user_id()) {
+                    Some(existing) => existing.set_share(existing.share() + owner.share()),
+                    None => new_owners.push(owner),
+                }
+            }
+            self.repository
+                .replace_by_asset(asset_id, &current, &new_owners)
+                .await?;
+            released.push(asset_id.clone());
+        }
+        Ok(released)
+    }
 }
 
 impl Clone for OwnerService {
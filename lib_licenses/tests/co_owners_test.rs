@@ -0,0 +1,255 @@
+use chrono::{Duration, Utc};
+use lib_config::config::Config;
+use lib_config::environment::{DEV_ENV, ENV_VAR_ENVIRONMENT};
+use lib_config::infra::build_local_stack_connection;
+use lib_config::schema::Schema;
+use lib_licenses::errors::owner::{OwnerNoExistsError, OwnerShareError};
+use lib_licenses::models::asset::AssetBuilder;
+use lib_licenses::models::license::{CreatableFildsLicense, Royalty};
+use lib_licenses::models::license_request::{
+    ApproveFildsLicenseRequest, CreatableFildsLicenseRequest,
+};
+use lib_licenses::models::owner::{CoOwnerShare, UpdatableFildsCoOwners};
+use lib_licenses::repositories::assets::{AssetRepo, AssetRepository};
+use lib_licenses::repositories::license_grants::LicenseGrantRepo;
+use lib_licenses::repositories::license_requests::LicenseRequestRepo;
+use lib_licenses::repositories::licenses::LicenseRepo;
+use lib_licenses::repositories::owners::OwnerRepo;
+use lib_licenses::repositories::schema_asset::AssetAllSchema;
+use lib_licenses::repositories::schema_license_requests::LicenseRequestAllSchema;
+use lib_licenses::repositories::schema_licenses::LicenseSchema;
+use lib_licenses::repositories::schema_owners::OwnerSchema;
+use lib_licenses::services::license_requests::{
+    LicenseRequestManipulation, LicenseRequestService,
+};
+use lib_licenses::services::licenses::{LicenseManipulation, LicenseService};
+use lib_licenses::services::owners::{OwnerManipulation, OwnerService};
+use std::env;
+use testcontainers::*;
+use url::Url;
+use uuid::Uuid;
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+fn shares(owners: Vec<(&String, f32)>) -> UpdatableFildsCoOwners {
+    UpdatableFildsCoOwners {
+        owners: owners
+            .into_iter()
+            .map(|(user_id, share)| CoOwnerShare {
+                user_id: user_id.clone(),
+                share,
+            })
+            .collect(),
+    }
+}
+
+#[tokio::test]
+async fn co_ownership() -> ResultE<()> {
+    env::set_var("RUST_LOG", "debug");
+    env::set_var(ENV_VAR_ENVIRONMENT, DEV_ENV);
+
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let docker = clients::Cli::default();
+    let node = docker.run(images::dynamodb_local::DynamoDb::default());
+    let host_port = node.get_host_port_ipv4(8000);
+
+    let shared_config = build_local_stack_connection(host_port).await;
+
+    let mut conf = Config::new();
+    conf.setup().await;
+    conf.set_aws_config(&shared_config);
+
+    OwnerSchema::create_schema(&conf).await?;
+    AssetAllSchema::create_schema(&conf).await?;
+    LicenseSchema::create_schema(&conf).await?;
+    LicenseRequestAllSchema::create_schema(&conf).await?;
+
+    let ass_repo = AssetRepo::new(&conf);
+    let main_owner = Uuid::new_v4().to_string();
+    let partner = Uuid::new_v4().to_string();
+    let third = Uuid::new_v4().to_string();
+    let requester = Uuid::new_v4().to_string();
+
+    let asset = AssetBuilder::default()
+        .id(Uuid::new_v4())
+        .url(Url::parse("http://a.xyz")?)
+        .hash("hash1234")
+        .hash_algorithm("MD5")
+        .build();
+    let asset_id = ass_repo.add(&asset, &Some(main_owner.clone())).await?;
+
+    let owner_service = OwnerService::new(OwnerRepo::new(&conf));
+    let owners = owner_service.get_all_by_asset(&asset_id).await?;
+    assert_eq!(owners.len(), 1);
+    assert_eq!(owners[0].share(), 100.0);
+
+    // outsiders can't split the asset
+    let res = owner_service
+        .set_co_owners(&asset_id, &partner, &shares(vec![(&partner, 100.0)]))
+        .await;
+    assert!(res.err().unwrap().is::<OwnerNoExistsError>());
+
+    let res = owner_service
+        .set_co_owners(
+            &asset_id,
+            &main_owner,
+            &shares(vec![(&main_owner, 60.0), (&partner, 30.0)]),
+        )
+        .await;
+    assert!(res.err().unwrap().is::<OwnerShareError>());
+
+    let res = owner_service
+        .set_co_owners(
+            &asset_id,
+            &main_owner,
+            &shares(vec![(&main_owner, 30.0), (&partner, 30.0), (&third, 40.0)]),
+        )
+        .await;
+    assert!(res.err().unwrap().is::<OwnerShareError>());
+
+    owner_service
+        .set_co_owners(
+            &asset_id,
+            &main_owner,
+            &shares(vec![(&main_owner, 60.0), (&partner, 40.0)]),
+        )
+        .await?;
+    let owners = owner_service.get_all_by_asset(&asset_id).await?;
+    assert_eq!(owners.len(), 2);
+    assert_eq!(owner_service.get_by_asset(&asset_id).await?.user_id(), &main_owner);
+
+    // a minority holder can't take decisions on its own
+    let res = owner_service
+        .set_co_owners(&asset_id, &partner, &shares(vec![(&partner, 100.0)]))
+        .await;
+    assert!(res.err().unwrap().is::<OwnerShareError>());
+
+    let license_service = LicenseService::new(LicenseRepo::new(&conf), ass_repo.clone());
+    let license_fields = CreatableFildsLicense {
+        asset_id,
+        right_to_free_distribute: false,
+        if_you_distribute_mention_me: true,
+        right_to_modify: false,
+        if_you_modify_mention_me: true,
+        right_to_use_broadcast_media: true,
+        right_to_use_press_media: true,
+        rights: vec![Royalty {
+            price: 99.99,
+            location: "ES".to_string(),
+            valid_from: None,
+            valid_until: None,
+        }],
+        valid_from: None,
+        valid_until: None,
+    };
+    let res = license_service
+        .create(&license_fields, &Some(partner.clone()))
+        .await;
+    assert!(res.err().unwrap().is::<OwnerShareError>());
+    let license_id = license_service
+        .create(&license_fields, &Some(main_owner.clone()))
+        .await?;
+
+    let request_service = LicenseRequestService::new(
+        LicenseRequestRepo::new(&conf),
+        LicenseGrantRepo::new(&conf),
+        LicenseRepo::new(&conf),
+        ass_repo,
+        OwnerRepo::new(&conf),
+    );
+    let request = request_service
+        .request(
+            &CreatableFildsLicenseRequest {
+                license_id,
+                intended_use: "documentary".to_string(),
+                territory: "es".to_string(),
+            },
+            &requester,
+        )
+        .await?;
+
+    // every co-owner follows the requests, only the controlling one reviews them
+    assert_eq!(
+        request_service
+            .get_by_asset(&asset_id, &partner)
+            .await?
+            .len(),
+        1
+    );
+    let approval = ApproveFildsLicenseRequest {
+        valid_from: None,
+        valid_until: Utc::now() + Duration::days(30),
+        comment: None,
+        price: None,
+    };
+    let res = request_service
+        .approve(request.id(), &partner, &approval)
+        .await;
+    assert!(res.err().unwrap().is::<OwnerShareError>());
+
+    let (_, grant) = request_service
+        .approve(request.id(), &main_owner, &approval)
+        .await?;
+    assert_eq!(*grant.price(), Some(99.99));
+    let splits = grant.royalty_splits();
+    assert_eq!(splits.len(), 2);
+    let main_split = splits.iter().find(|s| s.user_id == main_owner).unwrap();
+    let partner_split = splits.iter().find(|s| s.user_id == partner).unwrap();
+    assert_eq!(main_split.amount, 60.0);
+    assert_eq!(partner_split.amount, 39.99);
+
+    let stored = request_service
+        .get_grants_by_asset_grantee(&asset_id, &requester)
+        .await?;
+    assert_eq!(stored[0], grant);
+
+    // the majority owner splits alone, even into a tie
+    let owners = owner_service
+        .set_co_owners(
+            &asset_id,
+            &main_owner,
+            &shares(vec![(&main_owner, 50.0), (&partner, 50.0)]),
+        )
+        .await?;
+    assert!(owners.iter().all(|owner| owner.pending_split().is_none()));
+
+    // on a tie each controlling owner has to ask for the same split
+    let tie_break = vec![(&main_owner, 50.0), (&partner, 25.0), (&third, 25.0)];
+    let owners = owner_service
+        .set_co_owners(&asset_id, &main_owner, &shares(tie_break.clone()))
+        .await?;
+    assert_eq!(owners.len(), 2);
+    assert!(owners.iter().any(|owner| owner.pending_split().is_some()));
+
+    let owners = owner_service
+        .set_co_owners(
+            &asset_id,
+            &partner,
+            &shares(vec![(&main_owner, 25.0), (&partner, 50.0), (&third, 25.0)]),
+        )
+        .await?;
+    assert_eq!(owners.len(), 2);
+    assert_eq!(owner_service.get_all_by_asset(&asset_id).await?.len(), 2);
+
+    let owners = owner_service
+        .set_co_owners(
+            &asset_id,
+            &partner,
+            &shares(tie_break.into_iter().rev().collect()),
+        )
+        .await?;
+    assert_eq!(owners.len(), 3);
+    let stored = owner_service.get_all_by_asset(&asset_id).await?;
+    assert_eq!(stored.len(), 3);
+    assert!(stored.iter().all(|owner| owner.pending_split().is_none()));
+    assert_eq!(
+        owner_service
+            .get_by_user_asset_ids(&asset_id, &third)
+            .await?
+            .share(),
+        25.0
+    );
+
+    Ok(())
+}
@@ -1,5 +1,10 @@
 mod after_video_test;
 mod assets_test;
+mod co_owners_test;
 mod fathers_sons_test;
+mod license_expiry_test;
+mod license_inheritance_test;
+mod license_requests_test;
+mod license_verification_test;
 mod licenses_test;
-mod owners_test;
\ No newline at end of file
+mod owners_test;
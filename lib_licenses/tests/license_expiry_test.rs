@@ -0,0 +1,131 @@
+use chrono::{Duration, Utc};
+use lib_config::config::Config;
+use lib_config::environment::{DEV_ENV, ENV_VAR_ENVIRONMENT};
+use lib_config::infra::build_local_stack_connection;
+use lib_config::schema::Schema;
+use lib_licenses::errors::license::LicenseCreationError;
+use lib_licenses::models::asset::AssetBuilder;
+use lib_licenses::models::license::{CreatableFildsLicense, LicenseStatus, Royalty};
+use lib_licenses::repositories::assets::{AssetRepo, AssetRepository};
+use lib_licenses::repositories::licenses::LicenseRepo;
+use lib_licenses::repositories::schema_asset::AssetAllSchema;
+use lib_licenses::repositories::schema_licenses::LicenseSchema;
+use lib_licenses::repositories::schema_owners::OwnerSchema;
+use lib_licenses::services::licenses::{LicenseManipulation, LicenseService};
+use std::env;
+use testcontainers::*;
+use url::Url;
+use uuid::Uuid;
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+fn license_fields(asset_id: Uuid, rights: Vec<Royalty>) -> CreatableFildsLicense {
+    CreatableFildsLicense {
+        asset_id,
+        right_to_free_distribute: false,
+        if_you_distribute_mention_me: true,
+        right_to_modify: false,
+        if_you_modify_mention_me: true,
+        right_to_use_broadcast_media: true,
+        right_to_use_press_media: true,
+        rights,
+        valid_from: None,
+        valid_until: None,
+    }
+}
+
+#[tokio::test]
+async fn license_expiry() -> ResultE<()> {
+    env::set_var("RUST_LOG", "debug");
+    env::set_var(ENV_VAR_ENVIRONMENT, DEV_ENV);
+
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let docker = clients::Cli::default();
+    let node = docker.run(images::dynamodb_local::DynamoDb::default());
+    let host_port = node.get_host_port_ipv4(8000);
+
+    let shared_config = build_local_stack_connection(host_port).await;
+
+    let mut conf = Config::new();
+    conf.setup().await;
+    conf.set_aws_config(&shared_config);
+
+    OwnerSchema::create_schema(&conf).await?;
+    AssetAllSchema::create_schema(&conf).await?;
+    LicenseSchema::create_schema(&conf).await?;
+
+    let ass_repo = AssetRepo::new(&conf);
+    let user_id = Uuid::new_v4().to_string();
+
+    let asset = AssetBuilder::default()
+        .id(Uuid::new_v4())
+        .url(Url::parse("http://a.xyz")?)
+        .hash("hash1234")
+        .hash_algorithm("MD5")
+        .build();
+    let asset_id = ass_repo.add(&asset, &Some(user_id.clone())).await?;
+
+    let service = LicenseService::new(LicenseRepo::new(&conf), ass_repo);
+    let now = Utc::now();
+
+    // a window closing before it opens is rejected
+    let mut wrong = license_fields(asset_id, vec![]);
+    wrong.valid_from = Some(now);
+    wrong.valid_until = Some(now - Duration::days(1));
+    let wrong_op = service.create(&wrong, &Some(user_id.clone())).await;
+    assert!(wrong_op.err().unwrap().is::<LicenseCreationError>());
+
+    // open ended license, with one royalty already out of its window
+    let open = license_fields(
+        asset_id,
+        vec![
+            Royalty {
+                price: 100.0,
+                location: "ES".to_string(),
+                valid_from: None,
+                valid_until: None,
+            },
+            Royalty {
+                price: 50.0,
+                location: "FR".to_string(),
+                valid_from: Some(now - Duration::days(10)),
+                valid_until: Some(now - Duration::days(5)),
+            },
+        ],
+    );
+    let open_id = service.create(&open, &Some(user_id.clone())).await?;
+
+    let mut past = license_fields(asset_id, vec![]);
+    past.valid_from = Some(now - Duration::days(10));
+    past.valid_until = Some(now - Duration::days(1));
+    let past_id = service.create(&past, &Some(user_id.clone())).await?;
+
+    let mut future = license_fields(asset_id, vec![]);
+    future.valid_from = Some(now + Duration::days(1));
+    future.valid_until = Some(now + Duration::days(10));
+    let future_id = service.create(&future, &Some(user_id.clone())).await?;
+
+    assert_eq!(service.get_by_asset(&asset_id).await?.len(), 3);
+
+    let active = service.get_active_by_asset(&asset_id).await?;
+    assert_eq!(active.len(), 1);
+    assert_eq!(*active[0].id(), open_id);
+    assert_eq!(active[0].rights().len(), 1);
+    assert_eq!(active[0].rights()[0].location, "ES");
+
+    let expired = service.expire_licenses(&Utc::now()).await?;
+    assert_eq!(expired.len(), 1);
+    assert_eq!(*expired[0].id(), past_id);
+
+    let stored = service.get_by_id(&past_id, &asset_id).await?.unwrap();
+    assert_eq!(*stored.status(), LicenseStatus::Expired);
+    let stored = service.get_by_id(&future_id, &asset_id).await?.unwrap();
+    assert_eq!(*stored.status(), LicenseStatus::Enabled);
+
+    // running it again finds nothing new to expire
+    let expired = service.expire_licenses(&Utc::now()).await?;
+    assert!(expired.is_empty());
+
+    Ok(())
+}
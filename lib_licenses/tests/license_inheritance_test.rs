@@ -0,0 +1,120 @@
+use lib_config::config::Config;
+use lib_config::environment::{DEV_ENV, ENV_VAR_ENVIRONMENT};
+use lib_config::infra::build_local_stack_connection;
+use lib_config::schema::Schema;
+use lib_licenses::models::asset::AssetBuilder;
+use lib_licenses::models::license::CreatableFildsLicense;
+use lib_licenses::repositories::assets::{AssetRepo, AssetRepository};
+use lib_licenses::repositories::licenses::LicenseRepo;
+use lib_licenses::repositories::schema_asset::AssetAllSchema;
+use lib_licenses::repositories::schema_licenses::LicenseSchema;
+use lib_licenses::repositories::schema_owners::OwnerSchema;
+use lib_licenses::services::licenses::{LicenseManipulation, LicenseService};
+use std::env;
+use testcontainers::*;
+use url::Url;
+use uuid::Uuid;
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+fn license_fields(asset_id: Uuid, right_to_modify: bool) -> CreatableFildsLicense {
+    CreatableFildsLicense {
+        asset_id,
+        right_to_free_distribute: false,
+        if_you_distribute_mention_me: true,
+        right_to_modify,
+        if_you_modify_mention_me: true,
+        right_to_use_broadcast_media: true,
+        right_to_use_press_media: true,
+        rights: vec![],
+        valid_from: None,
+        valid_until: None,
+    }
+}
+
+#[tokio::test]
+async fn license_inheritance() -> ResultE<()> {
+    env::set_var("RUST_LOG", "debug");
+    env::set_var(ENV_VAR_ENVIRONMENT, DEV_ENV);
+
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let docker = clients::Cli::default();
+    let node = docker.run(images::dynamodb_local::DynamoDb::default());
+    let host_port = node.get_host_port_ipv4(8000);
+
+    let shared_config = build_local_stack_connection(host_port).await;
+
+    let mut conf = Config::new();
+    conf.setup().await;
+    conf.set_aws_config(&shared_config);
+
+    OwnerSchema::create_schema(&conf).await?;
+    AssetAllSchema::create_schema(&conf).await?;
+    LicenseSchema::create_schema(&conf).await?;
+
+    let ass_repo = AssetRepo::new(&conf);
+    let user_id = Uuid::new_v4().to_string();
+
+    // grandfather -> father -> son
+    let grandfather = AssetBuilder::default()
+        .id(Uuid::new_v4())
+        .url(Url::parse("http://a.xyz")?)
+        .hash("hash1")
+        .hash_algorithm("MD5")
+        .build();
+    let grandfather_id = ass_repo.add(&grandfather, &Some(user_id.clone())).await?;
+
+    let mut father = AssetBuilder::default()
+        .id(Uuid::new_v4())
+        .url(Url::parse("http://b.xyz")?)
+        .hash("hash2")
+        .hash_algorithm("MD5")
+        .build();
+    father.set_father(&Some(grandfather_id));
+    let father_id = ass_repo.add(&father, &Some(user_id.clone())).await?;
+
+    let mut son = AssetBuilder::default()
+        .id(Uuid::new_v4())
+        .url(Url::parse("http://c.xyz")?)
+        .hash("hash3")
+        .hash_algorithm("MD5")
+        .build();
+    son.set_father(&Some(father_id));
+    let son_id = ass_repo.add(&son, &Some(user_id.clone())).await?;
+
+    let service = LicenseService::new(LicenseRepo::new(&conf), ass_repo);
+
+    assert!(service.get_effective_license(&son_id).await?.is_none());
+
+    let grandfather_license = service
+        .create(&license_fields(grandfather_id, false), &Some(user_id.clone()))
+        .await?;
+
+    let effective = service.get_effective_license(&son_id).await?.unwrap();
+    assert_eq!(effective.asset_id, son_id);
+    assert_eq!(effective.inherited_from, Some(grandfather_id));
+    assert_eq!(*effective.license.id(), grandfather_license);
+
+    // the father overrides what comes from above
+    let father_license = service
+        .create(&license_fields(father_id, true), &Some(user_id.clone()))
+        .await?;
+    let effective = service.get_effective_license(&son_id).await?.unwrap();
+    assert_eq!(effective.inherited_from, Some(father_id));
+    assert_eq!(*effective.license.id(), father_license);
+
+    // and an own license overrides any inherited one
+    let son_license = service
+        .create(&license_fields(son_id, false), &Some(user_id.clone()))
+        .await?;
+    let effective = service.get_effective_license(&son_id).await?.unwrap();
+    assert_eq!(effective.inherited_from, None);
+    assert_eq!(*effective.license.id(), son_license);
+
+    let effective = service.get_effective_license(&grandfather_id).await?.unwrap();
+    assert_eq!(effective.inherited_from, None);
+    assert_eq!(*effective.license.id(), grandfather_license);
+
+    Ok(())
+}
@@ -0,0 +1,191 @@
+use chrono::{Duration, Utc};
+use lib_config::config::Config;
+use lib_config::environment::{DEV_ENV, ENV_VAR_ENVIRONMENT};
+use lib_config::infra::build_local_stack_connection;
+use lib_config::schema::Schema;
+use lib_licenses::errors::license_request::{LicenseGrantPeriodError, LicenseRequestStatusError};
+use lib_licenses::errors::owner::OwnerNoExistsError;
+use lib_licenses::models::asset::AssetBuilder;
+use lib_licenses::models::license::{CreatableFildsLicense, Royalty};
+use lib_licenses::models::license_request::{
+    ApproveFildsLicenseRequest, CreatableFildsLicenseRequest, LicenseRequestStatus,
+    RejectFildsLicenseRequest,
+};
+use lib_licenses::repositories::assets::{AssetRepo, AssetRepository};
+use lib_licenses::repositories::license_grants::LicenseGrantRepo;
+use lib_licenses::repositories::license_requests::LicenseRequestRepo;
+use lib_licenses::repositories::licenses::LicenseRepo;
+use lib_licenses::repositories::owners::OwnerRepo;
+use lib_licenses::repositories::schema_asset::AssetAllSchema;
+use lib_licenses::repositories::schema_license_requests::LicenseRequestAllSchema;
+use lib_licenses::repositories::schema_licenses::LicenseSchema;
+use lib_licenses::repositories::schema_owners::OwnerSchema;
+use lib_licenses::services::license_requests::{
+    LicenseRequestManipulation, LicenseRequestService,
+};
+use lib_licenses::services::licenses::{LicenseManipulation, LicenseService};
+use std::env;
+use testcontainers::*;
+use url::Url;
+use uuid::Uuid;
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+#[tokio::test]
+async fn license_request_workflow() -> ResultE<()> {
+    env::set_var("RUST_LOG", "debug");
+    env::set_var(ENV_VAR_ENVIRONMENT, DEV_ENV);
+
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let docker = clients::Cli::default();
+    let node = docker.run(images::dynamodb_local::DynamoDb::default());
+    let host_port = node.get_host_port_ipv4(8000);
+
+    let shared_config = build_local_stack_connection(host_port).await;
+
+    let mut conf = Config::new();
+    conf.setup().await;
+    conf.set_aws_config(&shared_config);
+
+    OwnerSchema::create_schema(&conf).await?;
+    AssetAllSchema::create_schema(&conf).await?;
+    LicenseSchema::create_schema(&conf).await?;
+    LicenseRequestAllSchema::create_schema(&conf).await?;
+
+    let ass_repo = AssetRepo::new(&conf);
+    let owner_id = Uuid::new_v4().to_string();
+    let requester_id = Uuid::new_v4().to_string();
+
+    let asset = AssetBuilder::default()
+        .id(Uuid::new_v4())
+        .url(Url::parse("http://a.xyz")?)
+        .hash("hash1234")
+        .hash_algorithm("MD5")
+        .build();
+    let asset_id = ass_repo.add(&asset, &Some(owner_id.clone())).await?;
+
+    let license_service = LicenseService::new(LicenseRepo::new(&conf), ass_repo.clone());
+    let license_id = license_service
+        .create(
+            &CreatableFildsLicense {
+                asset_id,
+                right_to_free_distribute: false,
+                if_you_distribute_mention_me: true,
+                right_to_modify: false,
+                if_you_modify_mention_me: true,
+                right_to_use_broadcast_media: true,
+                right_to_use_press_media: true,
+                rights: vec![Royalty {
+                    price: 100.0,
+                    location: "ES".to_string(),
+                    valid_from: None,
+                    valid_until: None,
+                }],
+                valid_from: None,
+                valid_until: None,
+            },
+            &Some(owner_id.clone()),
+        )
+        .await?;
+
+    let service = LicenseRequestService::new(
+        LicenseRequestRepo::new(&conf),
+        LicenseGrantRepo::new(&conf),
+        LicenseRepo::new(&conf),
+        ass_repo,
+        OwnerRepo::new(&conf),
+    );
+
+    let fields = CreatableFildsLicenseRequest {
+        license_id,
+        intended_use: "evening news".to_string(),
+        territory: "ES".to_string(),
+    };
+    let request1 = service.request(&fields, &requester_id).await?;
+    let request2 = service.request(&fields, &requester_id).await?;
+    assert_eq!(*request1.asset_id(), asset_id);
+    assert_eq!(*request1.status(), LicenseRequestStatus::Pending);
+
+    let mine = service.get_by_requester(&requester_id).await?;
+    assert_eq!(mine.len(), 2);
+
+    let by_asset = service.get_by_asset(&asset_id, &owner_id).await?;
+    assert_eq!(by_asset.len(), 2);
+
+    // only the owner reviews requests
+    let approval = ApproveFildsLicenseRequest {
+        valid_from: None,
+        valid_until: Utc::now() + Duration::days(30),
+        comment: Some("enjoy".to_string()),
+        price: None,
+    };
+    let not_owner = service
+        .approve(request1.id(), &requester_id, &approval)
+        .await;
+    assert!(not_owner.err().unwrap().is::<OwnerNoExistsError>());
+
+    let wrong_period = ApproveFildsLicenseRequest {
+        valid_from: None,
+        valid_until: Utc::now() - Duration::days(1),
+        comment: None,
+        price: None,
+    };
+    let wrong = service
+        .approve(request1.id(), &owner_id, &wrong_period)
+        .await;
+    assert!(wrong.err().unwrap().is::<LicenseGrantPeriodError>());
+
+    let (approved, grant) = service.approve(request1.id(), &owner_id, &approval).await?;
+    assert_eq!(*approved.status(), LicenseRequestStatus::Approved);
+    assert_eq!(*grant.grantee_id(), requester_id);
+    assert_eq!(*grant.license_id(), license_id);
+    assert_eq!(grant.license_version(), 1);
+    assert!(grant.is_valid_at(&Utc::now()));
+    // the royalty for the requested territory prices the grant, all for the single owner
+    assert_eq!(*grant.price(), Some(100.0));
+    assert_eq!(grant.royalty_splits().len(), 1);
+    assert_eq!(grant.royalty_splits()[0].user_id, owner_id);
+    assert_eq!(grant.royalty_splits()[0].amount, 100.0);
+
+    let again = service.approve(request1.id(), &owner_id, &approval).await;
+    assert!(again.err().unwrap().is::<LicenseRequestStatusError>());
+
+    let rejected = service
+        .reject(
+            request2.id(),
+            &owner_id,
+            &RejectFildsLicenseRequest { comment: None },
+        )
+        .await?;
+    assert_eq!(*rejected.status(), LicenseRequestStatus::Rejected);
+
+    // two owners' sessions approving at once: only one grant is written
+    let request3 = service.request(&fields, &requester_id).await?;
+    let (first, second) = tokio::join!(
+        service.approve(request3.id(), &owner_id, &approval),
+        service.approve(request3.id(), &owner_id, &approval)
+    );
+    assert!(first.is_ok() != second.is_ok());
+    let lost = if first.is_ok() { second } else { first };
+    assert!(lost.err().unwrap().is::<LicenseRequestStatusError>());
+
+    let grants = service
+        .get_grants_by_asset_grantee(&asset_id, &requester_id)
+        .await?;
+    assert_eq!(grants.len(), 2);
+    assert!(grants.contains(&grant));
+
+    // a removed requester: pending requests are rejected, the text is wiped, grants are revoked
+    let request4 = service.request(&fields, &requester_id).await?;
+    service.close_by_user(&requester_id).await?;
+    let closed = service.get_by_id(request4.id()).await?;
+    assert_eq!(*closed.status(), LicenseRequestStatus::Rejected);
+    let mine = service.get_by_requester(&requester_id).await?;
+    assert!(mine.iter().all(|r| r.intended_use().is_empty()));
+    let grants = service.get_grants_by_grantee(&requester_id).await?;
+    assert_eq!(grants.len(), 2);
+    assert!(grants.iter().all(|g| !g.is_valid_at(&Utc::now())));
+
+    Ok(())
+}
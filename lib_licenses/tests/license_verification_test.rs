@@ -0,0 +1,152 @@
+use chrono::{Duration, Utc};
+use lib_config::config::Config;
+use lib_config::environment::{DEV_ENV, ENV_VAR_ENVIRONMENT};
+use lib_config::infra::build_local_stack_connection;
+use lib_config::schema::Schema;
+use lib_licenses::models::asset::AssetBuilder;
+use lib_licenses::models::license::{CreatableFildsLicense, LicenseStatus, Royalty};
+use lib_licenses::models::license_request::{
+    ApproveFildsLicenseRequest, CreatableFildsLicenseRequest,
+};
+use lib_licenses::repositories::assets::{AssetRepo, AssetRepository};
+use lib_licenses::repositories::license_grants::LicenseGrantRepo;
+use lib_licenses::repositories::license_requests::LicenseRequestRepo;
+use lib_licenses::repositories::licenses::{LicenseRepo, LicenseRepository};
+use lib_licenses::repositories::owners::OwnerRepo;
+use lib_licenses::repositories::schema_asset::AssetAllSchema;
+use lib_licenses::repositories::schema_license_requests::LicenseRequestAllSchema;
+use lib_licenses::repositories::schema_licenses::LicenseSchema;
+use lib_licenses::repositories::schema_owners::OwnerSchema;
+use lib_licenses::services::license_requests::{
+    LicenseRequestManipulation, LicenseRequestService,
+};
+use lib_licenses::services::licenses::{LicenseManipulation, LicenseService};
+use serde_json::json;
+use std::env;
+use testcontainers::*;
+use url::Url;
+use uuid::Uuid;
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+#[tokio::test]
+async fn license_verification() -> ResultE<()> {
+    env::set_var("RUST_LOG", "debug");
+    env::set_var(ENV_VAR_ENVIRONMENT, DEV_ENV);
+
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let docker = clients::Cli::default();
+    let node = docker.run(images::dynamodb_local::DynamoDb::default());
+    let host_port = node.get_host_port_ipv4(8000);
+
+    let shared_config = build_local_stack_connection(host_port).await;
+
+    let mut conf = Config::new();
+    conf.setup().await;
+    conf.set_aws_config(&shared_config);
+
+    OwnerSchema::create_schema(&conf).await?;
+    AssetAllSchema::create_schema(&conf).await?;
+    LicenseSchema::create_schema(&conf).await?;
+    LicenseRequestAllSchema::create_schema(&conf).await?;
+
+    let ass_repo = AssetRepo::new(&conf);
+    let owner_id = Uuid::new_v4().to_string();
+    let grantee_id = Uuid::new_v4().to_string();
+    let removed_id = Uuid::new_v4().to_string();
+
+    let asset = AssetBuilder::default()
+        .id(Uuid::new_v4())
+        .url(Url::parse("http://a.xyz")?)
+        .hash("hash1234")
+        .hash_algorithm("MD5")
+        .build();
+    let asset_id = ass_repo.add(&asset, &Some(owner_id.clone())).await?;
+
+    let license_service = LicenseService::new(LicenseRepo::new(&conf), ass_repo.clone());
+    let license_id = license_service
+        .create(
+            &CreatableFildsLicense {
+                asset_id,
+                right_to_free_distribute: false,
+                if_you_distribute_mention_me: true,
+                right_to_modify: false,
+                if_you_modify_mention_me: true,
+                right_to_use_broadcast_media: true,
+                right_to_use_press_media: true,
+                rights: vec![Royalty {
+                    price: 100.0,
+                    location: "ES".to_string(),
+                    valid_from: None,
+                    valid_until: None,
+                }],
+                valid_from: None,
+                valid_until: None,
+            },
+            &Some(owner_id.clone()),
+        )
+        .await?;
+
+    let service = LicenseRequestService::new(
+        LicenseRequestRepo::new(&conf),
+        LicenseGrantRepo::new(&conf),
+        LicenseRepo::new(&conf),
+        ass_repo,
+        OwnerRepo::new(&conf),
+    );
+
+    // nothing granted yet
+    let verification = service.verify(&asset_id, &grantee_id).await?;
+    assert!(!verification.valid);
+    assert_eq!(verification.license_id, None);
+
+    let fields = CreatableFildsLicenseRequest {
+        license_id,
+        intended_use: "evening news".to_string(),
+        territory: "ES".to_string(),
+    };
+    let approval = ApproveFildsLicenseRequest {
+        valid_from: None,
+        valid_until: Utc::now() + Duration::days(30),
+        comment: None,
+        price: Some(250.0),
+    };
+    let request = service.request(&fields, &grantee_id).await?;
+    let (_, grant) = service.approve(request.id(), &owner_id, &approval).await?;
+    let request = service.request(&fields, &removed_id).await?;
+    service.approve(request.id(), &owner_id, &approval).await?;
+
+    // an active license with a grant in force
+    let verification = service.verify(&asset_id, &grantee_id).await?;
+    assert!(verification.valid);
+    assert_eq!(verification.asset_id, asset_id);
+    assert_eq!(verification.grantee_id, grantee_id);
+    assert_eq!(verification.license_id, Some(license_id));
+    assert_eq!(verification.license_version, Some(1));
+    assert_eq!(verification.valid_until, Some(grant.valid_until().clone()));
+
+    // the public answer never tells what was paid or how it was split
+    let body = json!(verification);
+    assert!(body.get("price").is_none());
+    assert!(body.get("royalty_splits").is_none());
+
+    // a revoked grant no longer counts
+    service.close_by_user(&removed_id).await?;
+    let verification = service.verify(&asset_id, &removed_id).await?;
+    assert!(!verification.valid);
+    assert_eq!(verification.license_id, None);
+    assert_eq!(verification.valid_until, None);
+
+    // nor does a grant over an expired license
+    let license_repo = LicenseRepo::new(&conf);
+    let mut license = license_repo.get_by_license_id(&license_id).await?.unwrap();
+    license.set_status(LicenseStatus::Expired);
+    license.set_last_update_time(Utc::now());
+    license_repo.update(&license).await?;
+    let verification = service.verify(&asset_id, &grantee_id).await?;
+    assert!(!verification.valid);
+    assert_eq!(verification.license_id, None);
+
+    Ok(())
+}
@@ -157,6 +157,8 @@ fn generate_random_license(asset_id: Uuid) -> CreatableFildsLicense {
         right_to_use_broadcast_media: rng.gen::<bool>(),
         right_to_use_press_media: rng.gen::<bool>(),
         rights,
+        valid_from: None,
+        valid_until: None,
     };
 
     license
@@ -168,7 +170,12 @@ fn generate_random_royalty() -> Royalty {
     let price = rng.gen_range(0.0..=1000.0);
     let location = generate_random_string();
 
-    Royalty { price, location }
+    Royalty {
+        price,
+        location,
+        valid_from: None,
+        valid_until: None,
+    }
 }
 
 fn generate_random_string() -> String {
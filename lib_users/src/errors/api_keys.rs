@@ -0,0 +1,45 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone)]
+pub struct ApiKeyDynamoDBError(pub String);
+
+impl std::error::Error for ApiKeyDynamoDBError {}
+
+impl Display for ApiKeyDynamoDBError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "api keys database error: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct ApiKeyNoExistsError(pub String);
+
+impl std::error::Error for ApiKeyNoExistsError {}
+
+impl Display for ApiKeyNoExistsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "api key doesn't exist: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct ApiKeyError(pub String);
+
+impl std::error::Error for ApiKeyError {}
+
+impl Display for ApiKeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "api key not valid: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct ApiKeyScopeError(pub String);
+
+impl std::error::Error for ApiKeyScopeError {}
+
+impl Display for ApiKeyScopeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "api key without scope: {}", self.0)
+    }
+}
@@ -0,0 +1,23 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone)]
+pub struct AuditDynamoDBError(pub String);
+
+impl std::error::Error for AuditDynamoDBError {}
+
+impl Display for AuditDynamoDBError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "audit log database error: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct AuditParamNotAccepted(pub String);
+
+impl std::error::Error for AuditParamNotAccepted {}
+
+impl Display for AuditParamNotAccepted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "audit search param not accepted: {}", self.0)
+    }
+}
@@ -0,0 +1,56 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone)]
+pub struct DeviceDynamoDBError(pub String);
+
+impl std::error::Error for DeviceDynamoDBError {}
+
+impl Display for DeviceDynamoDBError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "device database error: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct DeviceAlreadyExistsError(pub String);
+
+impl std::error::Error for DeviceAlreadyExistsError {}
+
+impl Display for DeviceAlreadyExistsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "device already registered: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct DeviceNoExistsError(pub String);
+
+impl std::error::Error for DeviceNoExistsError {}
+
+impl Display for DeviceNoExistsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "device not registered: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct DeviceKeyError(pub String);
+
+impl std::error::Error for DeviceKeyError {}
+
+impl Display for DeviceKeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "device key not valid: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct DeviceSignatureError(pub String);
+
+impl std::error::Error for DeviceSignatureError {}
+
+impl Display for DeviceSignatureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "device signature not valid: {}", self.0)
+    }
+}
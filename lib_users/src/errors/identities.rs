@@ -0,0 +1,35 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone)]
+pub struct IdentityDynamoDBError(pub String);
+
+impl std::error::Error for IdentityDynamoDBError {}
+
+impl Display for IdentityDynamoDBError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "login identities database error: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct OidcTokenError(pub String);
+
+impl std::error::Error for OidcTokenError {}
+
+impl Display for OidcTokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "id token not valid: {}", self.0)
+    }
+}
+
+// the provider's keys couldn't be fetched
+#[derive(Debug)]
+pub struct OidcProviderError(pub String);
+
+impl std::error::Error for OidcProviderError {}
+
+impl Display for OidcProviderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "identity provider unavailable: {}", self.0)
+    }
+}
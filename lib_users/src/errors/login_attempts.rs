@@ -0,0 +1,34 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone)]
+pub struct LoginAttemptDynamoDBError(pub String);
+
+impl std::error::Error for LoginAttemptDynamoDBError {}
+
+impl Display for LoginAttemptDynamoDBError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "login attempts database error: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct LoginThrottledError(pub String);
+
+impl std::error::Error for LoginThrottledError {}
+
+impl Display for LoginThrottledError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "too many failed logins: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct UserLockedError(pub String);
+
+impl std::error::Error for UserLockedError {}
+
+impl Display for UserLockedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "account locked: {}", self.0)
+    }
+}
@@ -0,0 +1,25 @@
+use std::fmt::Display;
+
+// the user doesn't have that login method
+#[derive(Debug)]
+pub struct LoginMethodNotFoundError(pub String);
+
+impl std::error::Error for LoginMethodNotFoundError {}
+
+impl Display for LoginMethodNotFoundError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "login method not found: {}", self.0)
+    }
+}
+
+// removing it would leave the account without any way to sign in
+#[derive(Debug)]
+pub struct LoginMethodLastError(pub String);
+
+impl std::error::Error for LoginMethodLastError {}
+
+impl Display for LoginMethodLastError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "last login method: {}", self.0)
+    }
+}
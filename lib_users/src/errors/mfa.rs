@@ -0,0 +1,45 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone)]
+pub struct MfaDynamoDBError(pub String);
+
+impl std::error::Error for MfaDynamoDBError {}
+
+impl Display for MfaDynamoDBError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "mfa database error: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct MfaCodeError(pub String);
+
+impl std::error::Error for MfaCodeError {}
+
+impl Display for MfaCodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "mfa code not valid: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct MfaAlreadyEnabledError(pub String);
+
+impl std::error::Error for MfaAlreadyEnabledError {}
+
+impl Display for MfaAlreadyEnabledError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "mfa already enabled: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct MfaNotEnabledError(pub String);
+
+impl std::error::Error for MfaNotEnabledError {}
+
+impl Display for MfaNotEnabledError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "mfa not enabled: {}", self.0)
+    }
+}
@@ -1 +1,10 @@
+pub mod api_keys;
+pub mod audit;
+pub mod devices;
+pub mod identities;
+pub mod login_attempts;
+pub mod login_methods;
+pub mod mfa;
+pub mod one_time_tokens;
+pub mod sessions;
 pub mod users;
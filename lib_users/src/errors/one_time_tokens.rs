@@ -0,0 +1,34 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone)]
+pub struct OneTimeTokenDynamoDBError(pub String);
+
+impl std::error::Error for OneTimeTokenDynamoDBError {}
+
+impl Display for OneTimeTokenDynamoDBError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "one-time token database error: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct OneTimeTokenError(pub String);
+
+impl std::error::Error for OneTimeTokenError {}
+
+impl Display for OneTimeTokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "token not valid: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct OneTimeTokenRateLimitError(pub String);
+
+impl std::error::Error for OneTimeTokenRateLimitError {}
+
+impl Display for OneTimeTokenRateLimitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "too many requests: {}", self.0)
+    }
+}
@@ -0,0 +1,34 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone)]
+pub struct SessionDynamoDBError(pub String);
+
+impl std::error::Error for SessionDynamoDBError {}
+
+impl Display for SessionDynamoDBError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "session database error: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct RefreshTokenError(pub String);
+
+impl std::error::Error for RefreshTokenError {}
+
+impl Display for RefreshTokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "refresh token not valid: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct WalletSignatureError(pub String);
+
+impl std::error::Error for WalletSignatureError {}
+
+impl Display for WalletSignatureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "wallet signature not valid: {}", self.0)
+    }
+}
@@ -85,3 +85,14 @@ impl Display for UserNoPasswordError {
         write!(f, "you can't create a user with email and without password")
     }
 }
+
+#[derive(Debug)]
+pub struct UserNotVerifiedError(pub String);
+
+impl std::error::Error for UserNotVerifiedError {}
+
+impl Display for UserNotVerifiedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "email not verified: {}", self.0)
+    }
+}
@@ -0,0 +1,170 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+
+use super::session::hash_refresh_token;
+
+pub const API_KEY_PREFIX: &str = "truly_";
+// chars of the key kept in clear, enough to tell the keys of a user apart
+pub const API_KEY_VISIBLE_CHARS: usize = 12;
+
+// A key for programmatic clients. Like refresh tokens only the hash is stored,
+// the raw key is shown once when it's created.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct ApiKey {
+    key_id: String,
+    #[serde(skip_serializing, default)]
+    key_hash: String,
+    user_id: String,
+    name: String,
+    prefix: String,
+    scopes: Vec<ApiKeyScope>,
+    creation_time: DateTime<Utc>,
+    last_used_time: Option<DateTime<Utc>>,
+    revoked: bool,
+}
+
+impl ApiKey {
+    pub fn new() -> ApiKey {
+        ApiKey {
+            key_id: Uuid::new_v4().to_string(),
+            key_hash: String::new(),
+            user_id: String::new(),
+            name: String::new(),
+            prefix: String::new(),
+            scopes: vec![],
+            creation_time: Utc::now(),
+            last_used_time: None,
+            revoked: false,
+        }
+    }
+
+    pub fn key_id(&self) -> &String {
+        &self.key_id
+    }
+    pub fn set_key_id(&mut self, val: &String) {
+        self.key_id = val.clone()
+    }
+    pub fn key_hash(&self) -> &String {
+        &self.key_hash
+    }
+    pub fn set_key_hash(&mut self, val: &String) {
+        self.key_hash = val.clone()
+    }
+    pub fn user_id(&self) -> &String {
+        &self.user_id
+    }
+    pub fn set_user_id(&mut self, val: &String) {
+        self.user_id = val.clone()
+    }
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+    pub fn set_name(&mut self, val: &String) {
+        self.name = val.clone()
+    }
+    pub fn prefix(&self) -> &String {
+        &self.prefix
+    }
+    pub fn set_prefix(&mut self, val: &String) {
+        self.prefix = val.clone()
+    }
+    pub fn scopes(&self) -> &Vec<ApiKeyScope> {
+        &self.scopes
+    }
+    pub fn set_scopes(&mut self, val: &Vec<ApiKeyScope>) {
+        self.scopes = val.clone()
+    }
+    pub fn creation_time(&self) -> &DateTime<Utc> {
+        &self.creation_time
+    }
+    pub fn set_creation_time(&mut self, val: &DateTime<Utc>) {
+        self.creation_time = val.clone()
+    }
+    pub fn last_used_time(&self) -> &Option<DateTime<Utc>> {
+        &self.last_used_time
+    }
+    pub fn set_last_used_time(&mut self, val: &Option<DateTime<Utc>>) {
+        self.last_used_time = val.clone()
+    }
+    pub fn revoked(&self) -> bool {
+        self.revoked
+    }
+    pub fn set_revoked(&mut self, val: bool) {
+        self.revoked = val
+    }
+
+    pub fn has_scope(&self, scope: &ApiKeyScope) -> bool {
+        self.scopes.contains(scope)
+    }
+}
+
+impl Default for ApiKey {
+    fn default() -> ApiKey {
+        ApiKey::new()
+    }
+}
+
+impl fmt::Display for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", json!(self).to_string())
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum ApiKeyScope {
+    #[serde(rename = "assets:read")]
+    AssetsRead,
+    #[serde(rename = "assets:write")]
+    AssetsWrite,
+    #[serde(rename = "similar:read")]
+    SimilarRead,
+    #[serde(rename = "licenses:read")]
+    LicensesRead,
+    #[serde(rename = "licenses:write")]
+    LicensesWrite,
+}
+
+impl fmt::Display for ApiKeyScope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ApiKeyScope::AssetsRead => write!(f, "assets:read"),
+            ApiKeyScope::AssetsWrite => write!(f, "assets:write"),
+            ApiKeyScope::SimilarRead => write!(f, "similar:read"),
+            ApiKeyScope::LicensesRead => write!(f, "licenses:read"),
+            ApiKeyScope::LicensesWrite => write!(f, "licenses:write"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseApiKeyScopeError;
+impl FromStr for ApiKeyScope {
+    type Err = ParseApiKeyScopeError;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "assets:read" => Ok(ApiKeyScope::AssetsRead),
+            "assets:write" => Ok(ApiKeyScope::AssetsWrite),
+            "similar:read" => Ok(ApiKeyScope::SimilarRead),
+            "licenses:read" => Ok(ApiKeyScope::LicensesRead),
+            "licenses:write" => Ok(ApiKeyScope::LicensesWrite),
+            _ => Err(ParseApiKeyScopeError),
+        }
+    }
+}
+
+// "truly_<32 hex chars>", the fixed prefix lets secret scanners spot leaked keys
+pub fn generate_api_key() -> String {
+    format!("{}{}", API_KEY_PREFIX, Uuid::new_v4().simple())
+}
+
+pub fn api_key_visible_prefix(raw: &str) -> String {
+    raw.chars().take(API_KEY_VISIBLE_CHARS).collect()
+}
+
+pub fn hash_api_key(raw: &str) -> String {
+    hash_refresh_token(raw)
+}
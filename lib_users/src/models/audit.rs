@@ -0,0 +1,227 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::user_search::SEARCH_PAGE_SIZE_MIN;
+
+// What an admin did. One variant per mutating route of lambda_admin.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum AuditAction {
+    UpdateUser,
+    ResetPassword,
+    PromoteUser,
+    DowngradeUser,
+    UnlockUser,
+    UpdateRoles,
+    DeleteUser,
+    DisableAsset,
+    EnableAsset,
+}
+
+impl fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AuditAction::UpdateUser => write!(f, "UpdateUser"),
+            AuditAction::ResetPassword => write!(f, "ResetPassword"),
+            AuditAction::PromoteUser => write!(f, "PromoteUser"),
+            AuditAction::DowngradeUser => write!(f, "DowngradeUser"),
+            AuditAction::UnlockUser => write!(f, "UnlockUser"),
+            AuditAction::UpdateRoles => write!(f, "UpdateRoles"),
+            AuditAction::DeleteUser => write!(f, "DeleteUser"),
+            AuditAction::DisableAsset => write!(f, "DisableAsset"),
+            AuditAction::EnableAsset => write!(f, "EnableAsset"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseAuditActionError;
+impl FromStr for AuditAction {
+    type Err = ParseAuditActionError;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "UpdateUser" => Ok(AuditAction::UpdateUser),
+            "ResetPassword" => Ok(AuditAction::ResetPassword),
+            "PromoteUser" => Ok(AuditAction::PromoteUser),
+            "DowngradeUser" => Ok(AuditAction::DowngradeUser),
+            "UnlockUser" => Ok(AuditAction::UnlockUser),
+            "UpdateRoles" => Ok(AuditAction::UpdateRoles),
+            "DeleteUser" => Ok(AuditAction::DeleteUser),
+            "DisableAsset" => Ok(AuditAction::DisableAsset),
+            "EnableAsset" => Ok(AuditAction::EnableAsset),
+            _ => Err(ParseAuditActionError),
+        }
+    }
+}
+
+// One admin action. The entry is written before the action runs and completed once with its
+// outcome; nothing else updates or deletes it.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct AuditEntry {
+    audit_id: String,
+    actor_id: String,
+    action: AuditAction,
+    // user id or asset id, depending on the action
+    target_id: String,
+    // snapshots of the target around the action, none when it didn't exist
+    before: Option<Value>,
+    after: Option<Value>,
+    // http status the action answered with, none when it started but never reported back
+    status: Option<u16>,
+    request_id: String,
+    creation_time: DateTime<Utc>,
+}
+
+impl AuditEntry {
+    pub fn new() -> AuditEntry {
+        AuditEntry {
+            audit_id: Uuid::new_v4().to_string(),
+            actor_id: String::new(),
+            action: AuditAction::UpdateUser,
+            target_id: String::new(),
+            before: None,
+            after: None,
+            status: None,
+            request_id: String::new(),
+            creation_time: Utc::now(),
+        }
+    }
+
+    pub fn audit_id(&self) -> &String {
+        &self.audit_id
+    }
+    pub fn set_audit_id(&mut self, val: &String) {
+        self.audit_id = val.clone()
+    }
+    pub fn actor_id(&self) -> &String {
+        &self.actor_id
+    }
+    pub fn set_actor_id(&mut self, val: &String) {
+        self.actor_id = val.clone()
+    }
+    pub fn action(&self) -> &AuditAction {
+        &self.action
+    }
+    pub fn set_action(&mut self, val: &AuditAction) {
+        self.action = *val
+    }
+    pub fn target_id(&self) -> &String {
+        &self.target_id
+    }
+    pub fn set_target_id(&mut self, val: &String) {
+        self.target_id = val.clone()
+    }
+    pub fn before(&self) -> &Option<Value> {
+        &self.before
+    }
+    pub fn set_before(&mut self, val: &Option<Value>) {
+        self.before = val.clone()
+    }
+    pub fn after(&self) -> &Option<Value> {
+        &self.after
+    }
+    pub fn set_after(&mut self, val: &Option<Value>) {
+        self.after = val.clone()
+    }
+    pub fn status(&self) -> &Option<u16> {
+        &self.status
+    }
+    pub fn set_status(&mut self, val: &Option<u16>) {
+        self.status = *val
+    }
+    pub fn request_id(&self) -> &String {
+        &self.request_id
+    }
+    pub fn set_request_id(&mut self, val: &String) {
+        self.request_id = val.clone()
+    }
+    pub fn creation_time(&self) -> &DateTime<Utc> {
+        &self.creation_time
+    }
+    pub fn set_creation_time(&mut self, val: &DateTime<Utc>) {
+        self.creation_time = *val
+    }
+    pub fn month(&self) -> String {
+        audit_month(&self.creation_time)
+    }
+}
+
+impl Default for AuditEntry {
+    fn default() -> AuditEntry {
+        AuditEntry::new()
+    }
+}
+
+// partition of the by-month index, "YYYY-MM"
+pub fn audit_month(at: &DateTime<Utc>) -> String {
+    format!("{:04}-{:02}", at.year(), at.month())
+}
+
+// Filters of GET /admin/audit. A search walks the target, actor or action index when one of
+// them is set, otherwise the months between from and to; the rest of the fields filter it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuditSearch {
+    pub actor_id: Option<String>,
+    pub target_id: Option<String>,
+    pub action: Option<AuditAction>,
+    // "YYYY-MM"
+    pub month: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub ascending: bool,
+    pub page_size: u32,
+    // next_cursor of the previous page
+    pub cursor: Option<String>,
+}
+
+impl AuditSearch {
+    pub fn new() -> AuditSearch {
+        AuditSearch {
+            actor_id: None,
+            target_id: None,
+            action: None,
+            month: None,
+            from: None,
+            to: None,
+            ascending: false,
+            page_size: SEARCH_PAGE_SIZE_MIN,
+            cursor: None,
+        }
+    }
+
+    // months to walk when no other index applies, in the order the entries come out.
+    // Without dates only the current month is read.
+    pub fn months(&self) -> Vec<String> {
+        if let Some(month) = &self.month {
+            return vec![month.clone()];
+        }
+        let last = self.to.unwrap_or_else(Utc::now);
+        let first = self.from.unwrap_or(last);
+        let mut months = Vec::new();
+        let mut current = NaiveDate::from_ymd_opt(first.year(), first.month(), 1).unwrap();
+        while current <= last.date_naive() {
+            months.push(format!("{:04}-{:02}", current.year(), current.month()));
+            current = (current + Duration::days(32)).with_day(1).unwrap();
+        }
+        if !self.ascending {
+            months.reverse();
+        }
+        months
+    }
+}
+
+impl Default for AuditSearch {
+    fn default() -> AuditSearch {
+        AuditSearch::new()
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct AuditPage {
+    pub entries: Vec<AuditEntry>,
+    // none on the last page
+    pub next_cursor: Option<String>,
+}
@@ -0,0 +1,201 @@
+use std::{fmt, str::FromStr};
+
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+// A device paired with an account: the app keeps the private key, we keep the
+// public one and the device logs in by signing a challenge with it.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct DeviceCredential {
+    device_id: String,
+    user_id: String,
+    algorithm: DeviceKeyAlgorithm,
+    // base64 of the DER SubjectPublicKeyInfo
+    public_key: String,
+    creation_time: DateTime<Utc>,
+    last_used_time: Option<DateTime<Utc>>,
+    revoked: bool,
+}
+
+impl DeviceCredential {
+    pub fn new() -> DeviceCredential {
+        DeviceCredential {
+            device_id: String::new(),
+            user_id: String::new(),
+            algorithm: DeviceKeyAlgorithm::EdDSA,
+            public_key: String::new(),
+            creation_time: Utc::now(),
+            last_used_time: None,
+            revoked: false,
+        }
+    }
+
+    pub fn device_id(&self) -> &String {
+        &self.device_id
+    }
+    pub fn set_device_id(&mut self, val: &String) {
+        self.device_id = val.clone()
+    }
+    pub fn user_id(&self) -> &String {
+        &self.user_id
+    }
+    pub fn set_user_id(&mut self, val: &String) {
+        self.user_id = val.clone()
+    }
+    pub fn algorithm(&self) -> &DeviceKeyAlgorithm {
+        &self.algorithm
+    }
+    pub fn set_algorithm(&mut self, val: &DeviceKeyAlgorithm) {
+        self.algorithm = val.clone()
+    }
+    pub fn public_key(&self) -> &String {
+        &self.public_key
+    }
+    pub fn set_public_key(&mut self, val: &String) {
+        self.public_key = val.clone()
+    }
+    pub fn creation_time(&self) -> &DateTime<Utc> {
+        &self.creation_time
+    }
+    pub fn set_creation_time(&mut self, val: &DateTime<Utc>) {
+        self.creation_time = val.clone()
+    }
+    pub fn last_used_time(&self) -> &Option<DateTime<Utc>> {
+        &self.last_used_time
+    }
+    pub fn set_last_used_time(&mut self, val: &Option<DateTime<Utc>>) {
+        self.last_used_time = val.clone()
+    }
+    pub fn revoked(&self) -> bool {
+        self.revoked
+    }
+    pub fn set_revoked(&mut self, val: bool) {
+        self.revoked = val
+    }
+
+    // signatures are base64, raw (r || s for ES256) or DER encoded for ES256
+    pub fn verify(&self, message: &str, signature: &str) -> bool {
+        let der = match general_purpose::STANDARD.decode(&self.public_key) {
+            Err(_) => return false,
+            Ok(der) => der,
+        };
+        let signature = match general_purpose::STANDARD.decode(signature) {
+            Err(_) => return false,
+            Ok(signature) => signature,
+        };
+        match self.algorithm {
+            DeviceKeyAlgorithm::EdDSA => {
+                use ed25519_dalek::pkcs8::DecodePublicKey;
+                use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+                match (
+                    VerifyingKey::from_public_key_der(&der),
+                    Signature::from_slice(&signature),
+                ) {
+                    (Ok(key), Ok(signature)) => key.verify(message.as_bytes(), &signature).is_ok(),
+                    _ => false,
+                }
+            }
+            DeviceKeyAlgorithm::ES256 => {
+                use p256::ecdsa::signature::Verifier;
+                use p256::ecdsa::{Signature, VerifyingKey};
+                use p256::pkcs8::DecodePublicKey;
+                let signature =
+                    Signature::from_slice(&signature).or_else(|_| Signature::from_der(&signature));
+                match (VerifyingKey::from_public_key_der(&der), signature) {
+                    (Ok(key), Ok(signature)) => key.verify(message.as_bytes(), &signature).is_ok(),
+                    _ => false,
+                }
+            }
+        }
+    }
+}
+
+impl Default for DeviceCredential {
+    fn default() -> DeviceCredential {
+        DeviceCredential::new()
+    }
+}
+
+impl fmt::Display for DeviceCredential {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", json!(self).to_string())
+    }
+}
+
+// EdDSA is Ed25519, ES256 is P-256 (the only curve of the iOS secure enclave)
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum DeviceKeyAlgorithm {
+    EdDSA,
+    ES256,
+}
+
+impl DeviceKeyAlgorithm {
+    pub fn is_valid_public_key(&self, public_key: &str) -> bool {
+        let der = match general_purpose::STANDARD.decode(public_key) {
+            Err(_) => return false,
+            Ok(der) => der,
+        };
+        match self {
+            DeviceKeyAlgorithm::EdDSA => {
+                use ed25519_dalek::pkcs8::DecodePublicKey;
+                ed25519_dalek::VerifyingKey::from_public_key_der(&der).is_ok()
+            }
+            DeviceKeyAlgorithm::ES256 => {
+                use p256::pkcs8::DecodePublicKey;
+                p256::ecdsa::VerifyingKey::from_public_key_der(&der).is_ok()
+            }
+        }
+    }
+}
+
+impl fmt::Display for DeviceKeyAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DeviceKeyAlgorithm::EdDSA => write!(f, "EdDSA"),
+            DeviceKeyAlgorithm::ES256 => write!(f, "ES256"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseDeviceKeyAlgorithmError;
+impl FromStr for DeviceKeyAlgorithm {
+    type Err = ParseDeviceKeyAlgorithmError;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "EdDSA" => Ok(DeviceKeyAlgorithm::EdDSA),
+            "ES256" => Ok(DeviceKeyAlgorithm::ES256),
+            _ => Err(ParseDeviceKeyAlgorithmError),
+        }
+    }
+}
+
+// what the device signs to log in, bound to the device so a challenge can't be replayed elsewhere
+pub fn device_challenge_message(device_id: &str, challenge: &str) -> String {
+    format!("truly.video device login\n{}\n{}", device_id, challenge)
+}
+
+// A device that has just answered its challenge. Only the device service can build one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VerifiedDevice {
+    device_id: String,
+    user_id: String,
+}
+
+impl VerifiedDevice {
+    pub(crate) fn new(credential: &DeviceCredential) -> VerifiedDevice {
+        VerifiedDevice {
+            device_id: credential.device_id().clone(),
+            user_id: credential.user_id().clone(),
+        }
+    }
+
+    pub fn device_id(&self) -> &String {
+        &self.device_id
+    }
+    pub fn user_id(&self) -> &String {
+        &self.user_id
+    }
+}
@@ -0,0 +1,116 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// An OpenID Connect provider whose ID tokens are trusted: Google, Apple or any other
+// compliant one. Tokens must be issued by `issuer` for one of `client_ids`.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct OidcProvider {
+    pub name: String,
+    pub issuer: String,
+    pub jwks_uri: String,
+    pub client_ids: Vec<String>,
+}
+
+// The account at a provider whose ID token has just been checked against the provider's
+// keys. Only the OIDC login service can build one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VerifiedIdentity {
+    issuer: String,
+    subject: String,
+    email: Option<String>,
+    email_verified: bool,
+}
+
+impl VerifiedIdentity {
+    pub(crate) fn new(
+        issuer: &String,
+        subject: &String,
+        email: &Option<String>,
+        email_verified: bool,
+    ) -> VerifiedIdentity {
+        VerifiedIdentity {
+            issuer: issuer.clone(),
+            subject: subject.clone(),
+            email: email.clone(),
+            email_verified,
+        }
+    }
+
+    pub fn issuer(&self) -> &String {
+        &self.issuer
+    }
+    pub fn subject(&self) -> &String {
+        &self.subject
+    }
+    pub fn email(&self) -> &Option<String> {
+        &self.email
+    }
+    // as the provider states it
+    pub fn email_verified(&self) -> bool {
+        self.email_verified
+    }
+}
+
+// A provider account linked to a user, it logs that user in.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct LinkedIdentity {
+    issuer: String,
+    subject: String,
+    user_id: String,
+    email: Option<String>,
+    creation_time: DateTime<Utc>,
+}
+
+impl LinkedIdentity {
+    pub fn new() -> LinkedIdentity {
+        LinkedIdentity {
+            issuer: String::new(),
+            subject: String::new(),
+            user_id: String::new(),
+            email: None,
+            creation_time: Utc::now(),
+        }
+    }
+
+    pub fn issuer(&self) -> &String {
+        &self.issuer
+    }
+    pub fn set_issuer(&mut self, val: &String) {
+        self.issuer = val.clone()
+    }
+    pub fn subject(&self) -> &String {
+        &self.subject
+    }
+    pub fn set_subject(&mut self, val: &String) {
+        self.subject = val.clone()
+    }
+    pub fn user_id(&self) -> &String {
+        &self.user_id
+    }
+    pub fn set_user_id(&mut self, val: &String) {
+        self.user_id = val.clone()
+    }
+    pub fn email(&self) -> &Option<String> {
+        &self.email
+    }
+    pub fn set_email(&mut self, val: &Option<String>) {
+        self.email = val.clone()
+    }
+    pub fn creation_time(&self) -> &DateTime<Utc> {
+        &self.creation_time
+    }
+    pub fn set_creation_time(&mut self, val: &DateTime<Utc>) {
+        self.creation_time = *val
+    }
+}
+
+impl Default for LinkedIdentity {
+    fn default() -> LinkedIdentity {
+        LinkedIdentity::new()
+    }
+}
+
+// subjects are only unique within their issuer
+pub fn identity_key(issuer: &String, subject: &String) -> String {
+    format!("{}#{}", issuer, subject)
+}
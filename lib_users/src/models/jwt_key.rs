@@ -0,0 +1,108 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+// Public half of a token signing key. The private half never lands here, it lives
+// in secrets manager and only the login lambda reads it.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct JwtKey {
+    kid: String,
+    algorithm: String,
+    jwk: String,
+    status: JwtKeyStatus,
+    creation_time: DateTime<Utc>,
+}
+
+impl JwtKey {
+    pub fn new() -> JwtKey {
+        JwtKey {
+            kid: String::new(),
+            algorithm: String::new(),
+            jwk: String::new(),
+            status: JwtKeyStatus::Current,
+            creation_time: Utc::now(),
+        }
+    }
+
+    pub fn kid(&self) -> &String {
+        &self.kid
+    }
+    pub fn set_kid(&mut self, val: &String) {
+        self.kid = val.clone()
+    }
+    pub fn algorithm(&self) -> &String {
+        &self.algorithm
+    }
+    pub fn set_algorithm(&mut self, val: &String) {
+        self.algorithm = val.clone()
+    }
+    pub fn jwk(&self) -> &String {
+        &self.jwk
+    }
+    pub fn set_jwk(&mut self, val: &String) {
+        self.jwk = val.clone()
+    }
+    pub fn status(&self) -> &JwtKeyStatus {
+        &self.status
+    }
+    pub fn set_status(&mut self, val: &JwtKeyStatus) {
+        self.status = val.clone()
+    }
+    pub fn creation_time(&self) -> &DateTime<Utc> {
+        &self.creation_time
+    }
+    pub fn set_creation_time(&mut self, val: &DateTime<Utc>) {
+        self.creation_time = val.clone()
+    }
+
+    pub fn can_verify(&self) -> bool {
+        self.status != JwtKeyStatus::Retired
+    }
+}
+
+impl Default for JwtKey {
+    fn default() -> JwtKey {
+        JwtKey::new()
+    }
+}
+
+impl fmt::Display for JwtKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", json!(self).to_string())
+    }
+}
+
+// Current signs new tokens, Previous keeps verifying tokens issued before the
+// last rotation, Retired is out of the jwks and rejected.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum JwtKeyStatus {
+    Current,
+    Previous,
+    Retired,
+}
+
+impl fmt::Display for JwtKeyStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JwtKeyStatus::Current => write!(f, "Current"),
+            JwtKeyStatus::Previous => write!(f, "Previous"),
+            JwtKeyStatus::Retired => write!(f, "Retired"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseJwtKeyStatusError;
+impl FromStr for JwtKeyStatus {
+    type Err = ParseJwtKeyStatusError;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "Current" => Ok(JwtKeyStatus::Current),
+            "Previous" => Ok(JwtKeyStatus::Previous),
+            "Retired" => Ok(JwtKeyStatus::Retired),
+            _ => Err(ParseJwtKeyStatusError),
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use std::fmt;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+static EMAIL_KEY_PREFIX: &str = "email#";
+static IP_KEY_PREFIX: &str = "ip#";
+static MFA_KEY_PREFIX: &str = "mfa#";
+
+// Failed logins counted against one email, one source ip or the second factor of one user.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct LoginAttempt {
+    key: String,
+    failures: u32,
+    last_failure: DateTime<Utc>,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+impl LoginAttempt {
+    pub fn new() -> LoginAttempt {
+        LoginAttempt {
+            key: String::new(),
+            failures: 0,
+            last_failure: Utc::now(),
+            locked_until: None,
+        }
+    }
+
+    pub fn key(&self) -> &String {
+        &self.key
+    }
+    pub fn set_key(&mut self, val: &String) {
+        self.key = val.clone()
+    }
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+    pub fn set_failures(&mut self, val: u32) {
+        self.failures = val
+    }
+    pub fn last_failure(&self) -> &DateTime<Utc> {
+        &self.last_failure
+    }
+    pub fn set_last_failure(&mut self, val: &DateTime<Utc>) {
+        self.last_failure = val.clone()
+    }
+    pub fn locked_until(&self) -> &Option<DateTime<Utc>> {
+        &self.locked_until
+    }
+    pub fn set_locked_until(&mut self, val: &Option<DateTime<Utc>>) {
+        self.locked_until = val.clone()
+    }
+
+    pub fn is_locked_at(&self, at: &DateTime<Utc>) -> bool {
+        match self.locked_until {
+            None => false,
+            Some(until) => until > *at,
+        }
+    }
+
+    // the email behind the counter, None when it counts an ip
+    pub fn email(&self) -> Option<&str> {
+        self.key.strip_prefix(EMAIL_KEY_PREFIX)
+    }
+
+    // the first `free` failures cost nothing, then every new one doubles the wait
+    pub fn retry_after(&self, free: u32, max_delay_seconds: i64) -> DateTime<Utc> {
+        if self.failures < free {
+            return self.last_failure;
+        }
+        let exp = (self.failures - free).min(16);
+        let delay = (1i64 << exp).min(max_delay_seconds);
+        self.last_failure + Duration::seconds(delay)
+    }
+}
+
+impl Default for LoginAttempt {
+    fn default() -> LoginAttempt {
+        LoginAttempt::new()
+    }
+}
+
+impl fmt::Display for LoginAttempt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", json!(self).to_string())
+    }
+}
+
+pub fn email_attempt_key(email: &str) -> String {
+    format!("{}{}", EMAIL_KEY_PREFIX, email.trim().to_lowercase())
+}
+
+pub fn ip_attempt_key(ip: &str) -> String {
+    format!("{}{}", IP_KEY_PREFIX, ip)
+}
+
+pub fn mfa_attempt_key(user_id: &str) -> String {
+    format!("{}{}", MFA_KEY_PREFIX, user_id)
+}
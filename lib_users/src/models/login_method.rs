@@ -0,0 +1,78 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use super::identity::LinkedIdentity;
+
+// The ways a user signs in. An account holds at most one email, device and wallet, each one
+// in its own login table, and any number of OpenID Connect identities.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum LoginMethodKind {
+    Email,
+    Device,
+    Wallet,
+    Oidc,
+}
+
+impl fmt::Display for LoginMethodKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoginMethodKind::Email => write!(f, "email"),
+            LoginMethodKind::Device => write!(f, "device"),
+            LoginMethodKind::Wallet => write!(f, "wallet"),
+            LoginMethodKind::Oidc => write!(f, "oidc"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseLoginMethodKindError;
+impl FromStr for LoginMethodKind {
+    type Err = ParseLoginMethodKindError;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "email" => Ok(LoginMethodKind::Email),
+            "device" => Ok(LoginMethodKind::Device),
+            "wallet" => Ok(LoginMethodKind::Wallet),
+            "oidc" => Ok(LoginMethodKind::Oidc),
+            _ => Err(ParseLoginMethodKindError),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Debug, PartialEq)]
+pub struct UserLoginMethods {
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub password: bool,
+    pub device: Option<String>,
+    // the device holds a key that isn't revoked, a bare device string doesn't sign in anymore
+    pub device_paired: bool,
+    pub wallet: Option<String>,
+    pub identities: Vec<LinkedIdentity>,
+}
+
+impl UserLoginMethods {
+    // an email signs in with its password, or gets one through a reset link once verified
+    pub fn email_usable(&self) -> bool {
+        self.email.is_some() && (self.password || self.email_verified)
+    }
+
+    pub fn device_usable(&self) -> bool {
+        self.device.is_some() && self.device_paired
+    }
+
+    pub fn count(&self) -> usize {
+        let mut count = self.identities.len();
+        if self.email_usable() {
+            count += 1;
+        }
+        if self.device_usable() {
+            count += 1;
+        }
+        if self.wallet.is_some() {
+            count += 1;
+        }
+        count
+    }
+}
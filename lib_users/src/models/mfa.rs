@@ -0,0 +1,166 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha1::Sha1;
+
+use super::session::hash_refresh_token;
+
+type HmacSha1 = Hmac<Sha1>;
+
+// RFC 6238 defaults, the ones every authenticator app understands
+pub const TOTP_DIGITS: usize = 6;
+pub const TOTP_PERIOD_SECONDS: i64 = 30;
+// codes from the step before and after are accepted too, phones drift
+pub const TOTP_SKEW_STEPS: i64 = 1;
+const TOTP_SECRET_BYTES: usize = 20;
+const RECOVERY_CODE_BYTES: usize = 5;
+
+// The TOTP secret of a user. It stays pending (enabled = false) until the
+// first code proves the authenticator app got it right.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct MfaCredential {
+    user_id: String,
+    // base32, as shown to the user
+    secret: String,
+    enabled: bool,
+    // sha256 of the recovery codes still unused
+    recovery_codes: Vec<String>,
+    creation_time: DateTime<Utc>,
+}
+
+impl MfaCredential {
+    pub fn new() -> MfaCredential {
+        MfaCredential {
+            user_id: String::new(),
+            secret: String::new(),
+            enabled: false,
+            recovery_codes: vec![],
+            creation_time: Utc::now(),
+        }
+    }
+
+    pub fn user_id(&self) -> &String {
+        &self.user_id
+    }
+    pub fn set_user_id(&mut self, val: &String) {
+        self.user_id = val.clone()
+    }
+    pub fn secret(&self) -> &String {
+        &self.secret
+    }
+    pub fn set_secret(&mut self, val: &String) {
+        self.secret = val.clone()
+    }
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+    pub fn set_enabled(&mut self, val: bool) {
+        self.enabled = val
+    }
+    pub fn recovery_codes(&self) -> &Vec<String> {
+        &self.recovery_codes
+    }
+    pub fn set_recovery_codes(&mut self, val: &Vec<String>) {
+        self.recovery_codes = val.clone()
+    }
+    pub fn creation_time(&self) -> &DateTime<Utc> {
+        &self.creation_time
+    }
+    pub fn set_creation_time(&mut self, val: &DateTime<Utc>) {
+        self.creation_time = val.clone()
+    }
+
+    // the time step the code belongs to, None when it matches none around `at`
+    pub fn check_code(&self, code: &str, at: &DateTime<Utc>) -> Option<i64> {
+        let key = BASE32_NOPAD.decode(self.secret.as_bytes()).ok()?;
+        let code = code.trim();
+        if code.len() != TOTP_DIGITS {
+            return None;
+        }
+        let current = at.timestamp() / TOTP_PERIOD_SECONDS;
+        (current - TOTP_SKEW_STEPS..=current + TOTP_SKEW_STEPS)
+            .find(|step| totp_code(&key, *step) == code)
+    }
+
+    // otpauth:// uri the authenticator apps read from a QR code
+    pub fn provisioning_uri(&self, issuer: &str, account: &str) -> String {
+        format!(
+            "otpauth://totp/{issuer}:{account}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={digits}&period={period}",
+            issuer = url_escape(issuer),
+            account = url_escape(account),
+            secret = self.secret,
+            digits = TOTP_DIGITS,
+            period = TOTP_PERIOD_SECONDS
+        )
+    }
+}
+
+impl Default for MfaCredential {
+    fn default() -> MfaCredential {
+        MfaCredential::new()
+    }
+}
+
+// never prints the secret nor the recovery codes
+impl fmt::Display for MfaCredential {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            json!({ "user_id": self.user_id, "enabled": self.enabled, "creation_time": self.creation_time })
+        )
+    }
+}
+
+pub fn totp_code(key: &[u8], step: i64) -> String {
+    let mut mac = HmacSha1::new_from_slice(key).unwrap();
+    mac.update(&(step as u64).to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = ((hash[offset] as u32 & 0x7f) << 24)
+        | ((hash[offset + 1] as u32) << 16)
+        | ((hash[offset + 2] as u32) << 8)
+        | hash[offset + 3] as u32;
+    format!("{:0width$}", binary % 10u32.pow(TOTP_DIGITS as u32), width = TOTP_DIGITS)
+}
+
+pub fn generate_totp_secret() -> String {
+    let mut key = [0u8; TOTP_SECRET_BYTES];
+    OsRng.fill_bytes(&mut key);
+    BASE32_NOPAD.encode(&key)
+}
+
+// "ABCD-EFGH", easy to type from a sheet of paper
+pub fn generate_recovery_code() -> String {
+    let mut raw = [0u8; RECOVERY_CODE_BYTES];
+    OsRng.fill_bytes(&mut raw);
+    let code = BASE32_NOPAD.encode(&raw);
+    format!("{}-{}", &code[..4], &code[4..])
+}
+
+// dashes, spaces and case don't matter when the user types the code back
+pub fn hash_recovery_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_uppercase();
+    hash_refresh_token(&normalized)
+}
+
+fn url_escape(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'@' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
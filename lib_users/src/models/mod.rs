@@ -1 +1,15 @@
+pub mod api_key;
+pub mod audit;
+pub mod device;
+pub mod identity;
+pub mod jwt_key;
+pub mod login_attempt;
+pub mod login_method;
+pub mod mfa;
+pub mod one_time_token;
+pub mod password_hash;
+pub mod permission;
+pub mod session;
 pub mod user;
+pub mod user_search;
+pub mod wallet;
@@ -0,0 +1,170 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::Sha256;
+use uuid::Uuid;
+
+use super::session::hash_refresh_token;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// Single-use tokens sent by email (verify an address, reset a password, unlock an account,
+// add an address as login, merge a device account).
+// Like refresh tokens, only the hash is stored; the raw value travels in the link.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct OneTimeToken {
+    token_hash: String,
+    user_id: String,
+    purpose: OneTimeTokenPurpose,
+    email: String,
+    creation_time: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+    used: bool,
+}
+
+impl OneTimeToken {
+    pub fn new() -> OneTimeToken {
+        OneTimeToken {
+            token_hash: String::new(),
+            user_id: String::new(),
+            purpose: OneTimeTokenPurpose::EmailVerification,
+            email: String::new(),
+            creation_time: Utc::now(),
+            expires_at: Utc::now(),
+            used: false,
+        }
+    }
+
+    pub fn token_hash(&self) -> &String {
+        &self.token_hash
+    }
+    pub fn set_token_hash(&mut self, val: &String) {
+        self.token_hash = val.clone()
+    }
+    pub fn user_id(&self) -> &String {
+        &self.user_id
+    }
+    pub fn set_user_id(&mut self, val: &String) {
+        self.user_id = val.clone()
+    }
+    pub fn purpose(&self) -> &OneTimeTokenPurpose {
+        &self.purpose
+    }
+    pub fn set_purpose(&mut self, val: &OneTimeTokenPurpose) {
+        self.purpose = val.clone()
+    }
+    pub fn email(&self) -> &String {
+        &self.email
+    }
+    pub fn set_email(&mut self, val: &String) {
+        self.email = val.clone()
+    }
+    pub fn creation_time(&self) -> &DateTime<Utc> {
+        &self.creation_time
+    }
+    pub fn set_creation_time(&mut self, val: &DateTime<Utc>) {
+        self.creation_time = val.clone()
+    }
+    pub fn expires_at(&self) -> &DateTime<Utc> {
+        &self.expires_at
+    }
+    pub fn set_expires_at(&mut self, val: &DateTime<Utc>) {
+        self.expires_at = val.clone()
+    }
+    pub fn used(&self) -> bool {
+        self.used
+    }
+    pub fn set_used(&mut self, val: bool) {
+        self.used = val
+    }
+
+    pub fn is_expired_at(&self, at: &DateTime<Utc>) -> bool {
+        self.expires_at <= *at
+    }
+}
+
+impl Default for OneTimeToken {
+    fn default() -> OneTimeToken {
+        OneTimeToken::new()
+    }
+}
+
+impl fmt::Display for OneTimeToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", json!(self).to_string())
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum OneTimeTokenPurpose {
+    EmailVerification,
+    PasswordReset,
+    AccountUnlock,
+    // the email is linked to the user only once the link is followed
+    EmailLink,
+    // issued to the device account, mailed to the full account it's merged into
+    AccountMerge,
+}
+
+impl fmt::Display for OneTimeTokenPurpose {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OneTimeTokenPurpose::EmailVerification => write!(f, "EmailVerification"),
+            OneTimeTokenPurpose::PasswordReset => write!(f, "PasswordReset"),
+            OneTimeTokenPurpose::AccountUnlock => write!(f, "AccountUnlock"),
+            OneTimeTokenPurpose::EmailLink => write!(f, "EmailLink"),
+            OneTimeTokenPurpose::AccountMerge => write!(f, "AccountMerge"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseOneTimeTokenPurposeError;
+impl FromStr for OneTimeTokenPurpose {
+    type Err = ParseOneTimeTokenPurposeError;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "EmailVerification" => Ok(OneTimeTokenPurpose::EmailVerification),
+            "PasswordReset" => Ok(OneTimeTokenPurpose::PasswordReset),
+            "AccountUnlock" => Ok(OneTimeTokenPurpose::AccountUnlock),
+            "EmailLink" => Ok(OneTimeTokenPurpose::EmailLink),
+            "AccountMerge" => Ok(OneTimeTokenPurpose::AccountMerge),
+            _ => Err(ParseOneTimeTokenPurposeError),
+        }
+    }
+}
+
+fn signature(nonce: &str, purpose: &OneTimeTokenPurpose, secret: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(format!("{}:{}", purpose, nonce).as_bytes());
+    mac
+}
+
+// "<nonce>.<hmac>", the signature binds the token to its purpose and to this deployment
+pub fn generate_one_time_token(purpose: &OneTimeTokenPurpose, secret: &str) -> String {
+    let nonce = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    let sign = signature(&nonce, purpose, secret).finalize().into_bytes();
+    format!("{}.{}", nonce, hex::encode(sign))
+}
+
+// rejects forged or tampered tokens before touching the database
+pub fn check_one_time_token_signature(
+    token: &str,
+    purpose: &OneTimeTokenPurpose,
+    secret: &str,
+) -> bool {
+    match token.split_once('.') {
+        None => false,
+        Some((nonce, sign)) => match hex::decode(sign) {
+            Err(_) => false,
+            Ok(sign) => signature(nonce, purpose, secret).verify_slice(&sign).is_ok(),
+        },
+    }
+}
+
+pub fn hash_one_time_token(raw: &str) -> String {
+    hash_refresh_token(raw)
+}
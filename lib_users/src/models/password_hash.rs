@@ -0,0 +1,81 @@
+use lib_config::environment::{PROD_ENV, STAGE_ENV};
+use rand::{rngs::OsRng, RngCore};
+
+use crate::errors::users::UserPasswordError;
+
+pub const PASSWORD_HASH_LENGTH: u32 = 32;
+pub const PASSWORD_SALT_LENGTH: usize = 16;
+
+// Argon2id cost. The values end up in the encoded hash, so a hash made with other
+// params is still verified and gets rehashed with the current ones on the next login.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PasswordHashParams {
+    // KiB
+    pub mem_cost: u32,
+    pub time_cost: u32,
+    pub lanes: u32,
+}
+
+impl PasswordHashParams {
+    pub fn for_environment(environment: &str) -> PasswordHashParams {
+        if environment == PROD_ENV || environment == STAGE_ENV {
+            PasswordHashParams {
+                mem_cost: 65536,
+                time_cost: 3,
+                lanes: 4,
+            }
+        } else {
+            // owasp's minimum, keeps local runs and tests fast
+            PasswordHashParams {
+                mem_cost: 19456,
+                time_cost: 2,
+                lanes: 1,
+            }
+        }
+    }
+
+    fn encoded_params(&self) -> String {
+        format!("m={},t={},p={}", self.mem_cost, self.time_cost, self.lanes)
+    }
+}
+
+// cpu bound for tens of milliseconds: async callers run it on the blocking pool
+pub fn hash_password(
+    password: &str,
+    secret: &str,
+    params: &PasswordHashParams,
+) -> Result<String, UserPasswordError> {
+    let mut salt = [0u8; PASSWORD_SALT_LENGTH];
+    OsRng.fill_bytes(&mut salt);
+    let config = argon2::Config {
+        variant: argon2::Variant::Argon2id,
+        version: argon2::Version::Version13,
+        mem_cost: params.mem_cost,
+        time_cost: params.time_cost,
+        lanes: params.lanes,
+        secret: secret.as_bytes(),
+        ad: &[],
+        hash_length: PASSWORD_HASH_LENGTH,
+    };
+    argon2::hash_encoded(password.as_bytes(), &salt, &config)
+        .map_err(|e| UserPasswordError(e.to_string()))
+}
+
+// any argon2 variant and params, they're read from the encoded hash
+pub fn verify_password(
+    password: &str,
+    encoded: &str,
+    secret: &str,
+) -> Result<bool, UserPasswordError> {
+    argon2::verify_encoded_ext(encoded, password.as_bytes(), secret.as_bytes(), &[])
+        .map_err(|e| UserPasswordError(e.to_string()))
+}
+
+// $argon2id$v=19$m=65536,t=3,p=4$<salt>$<hash>
+pub fn needs_rehash(encoded: &str, params: &PasswordHashParams) -> bool {
+    let parts: Vec<&str> = encoded.split('$').collect();
+    if parts.len() != 6 || parts[1] != "argon2id" || parts[2] != "v=19" {
+        return true;
+    }
+    parts[3] != params.encoded_params()
+}
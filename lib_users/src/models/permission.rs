@@ -0,0 +1,100 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use super::user::UserRoles;
+
+// Actions guarded beyond "being logged in". Routers ask for one of them with
+// require_permission instead of checking roles by hand.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum Permission {
+    ViewPii,
+    ManageUsers,
+    ManageRoles,
+    UnlockUsers,
+    DisableAsset,
+    ReprocessJobs,
+    GrantLicenses,
+    ViewAudit,
+}
+
+// The whole authorization policy in one place: a user may do whatever any of its roles may do.
+// Basic and VerifiedPress carry no extra permission, VerifiedPress is a trust badge shown on assets.
+pub static ROLE_PERMISSIONS: &[(UserRoles, &[Permission])] = &[
+    (UserRoles::Basic, &[]),
+    (
+        UserRoles::Admin,
+        &[
+            Permission::ViewPii,
+            Permission::ManageUsers,
+            Permission::ManageRoles,
+            Permission::UnlockUsers,
+            Permission::DisableAsset,
+            Permission::ReprocessJobs,
+            Permission::GrantLicenses,
+            Permission::ViewAudit,
+        ],
+    ),
+    (
+        UserRoles::Moderator,
+        &[Permission::DisableAsset, Permission::ReprocessJobs],
+    ),
+    (UserRoles::VerifiedPress, &[]),
+    (UserRoles::Partner, &[Permission::GrantLicenses]),
+    (
+        UserRoles::Support,
+        &[
+            Permission::ViewPii,
+            Permission::UnlockUsers,
+            Permission::ReprocessJobs,
+        ],
+    ),
+];
+
+pub fn role_permissions(role: &UserRoles) -> &'static [Permission] {
+    ROLE_PERMISSIONS
+        .iter()
+        .find(|(r, _)| r == role)
+        .map(|(_, permissions)| *permissions)
+        .unwrap_or(&[])
+}
+
+pub fn has_permission(roles: &Vec<UserRoles>, permission: &Permission) -> bool {
+    roles
+        .iter()
+        .any(|role| role_permissions(role).contains(permission))
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Permission::ViewPii => write!(f, "ViewPii"),
+            Permission::ManageUsers => write!(f, "ManageUsers"),
+            Permission::ManageRoles => write!(f, "ManageRoles"),
+            Permission::UnlockUsers => write!(f, "UnlockUsers"),
+            Permission::DisableAsset => write!(f, "DisableAsset"),
+            Permission::ReprocessJobs => write!(f, "ReprocessJobs"),
+            Permission::GrantLicenses => write!(f, "GrantLicenses"),
+            Permission::ViewAudit => write!(f, "ViewAudit"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParsePermissionError;
+impl FromStr for Permission {
+    type Err = ParsePermissionError;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "ViewPii" => Ok(Permission::ViewPii),
+            "ManageUsers" => Ok(Permission::ManageUsers),
+            "ManageRoles" => Ok(Permission::ManageRoles),
+            "UnlockUsers" => Ok(Permission::UnlockUsers),
+            "DisableAsset" => Ok(Permission::DisableAsset),
+            "ReprocessJobs" => Ok(Permission::ReprocessJobs),
+            "GrantLicenses" => Ok(Permission::GrantLicenses),
+            "ViewAudit" => Ok(Permission::ViewAudit),
+            _ => Err(ParsePermissionError),
+        }
+    }
+}
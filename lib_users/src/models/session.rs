@@ -0,0 +1,129 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+// Refresh tokens are only stored hashed, the raw value is handed once to the client.
+// Every rotation keeps the family, so a reused token can be traced back to its login, and the
+// end of the session, so rotating can't keep a login alive past it.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct RefreshToken {
+    token_hash: String,
+    user_id: String,
+    family_id: Uuid,
+    audience: Vec<String>,
+    creation_time: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+    session_expires_at: DateTime<Utc>,
+    revoked: bool,
+}
+
+impl RefreshToken {
+    pub fn new() -> RefreshToken {
+        RefreshToken {
+            token_hash: String::new(),
+            user_id: String::new(),
+            family_id: Uuid::new_v4(),
+            audience: Vec::new(),
+            creation_time: Utc::now(),
+            expires_at: Utc::now(),
+            session_expires_at: Utc::now(),
+            revoked: false,
+        }
+    }
+
+    pub fn token_hash(&self) -> &String {
+        &self.token_hash
+    }
+    pub fn set_token_hash(&mut self, val: &String) {
+        self.token_hash = val.clone()
+    }
+    pub fn user_id(&self) -> &String {
+        &self.user_id
+    }
+    pub fn set_user_id(&mut self, val: &String) {
+        self.user_id = val.clone()
+    }
+    pub fn family_id(&self) -> &Uuid {
+        &self.family_id
+    }
+    pub fn set_family_id(&mut self, val: &Uuid) {
+        self.family_id = val.clone()
+    }
+    pub fn audience(&self) -> &Vec<String> {
+        &self.audience
+    }
+    pub fn set_audience(&mut self, val: &Vec<String>) {
+        self.audience = val.clone()
+    }
+    pub fn creation_time(&self) -> &DateTime<Utc> {
+        &self.creation_time
+    }
+    pub fn set_creation_time(&mut self, val: &DateTime<Utc>) {
+        self.creation_time = val.clone()
+    }
+    pub fn expires_at(&self) -> &DateTime<Utc> {
+        &self.expires_at
+    }
+    pub fn set_expires_at(&mut self, val: &DateTime<Utc>) {
+        self.expires_at = val.clone()
+    }
+    pub fn session_expires_at(&self) -> &DateTime<Utc> {
+        &self.session_expires_at
+    }
+    pub fn set_session_expires_at(&mut self, val: &DateTime<Utc>) {
+        self.session_expires_at = val.clone()
+    }
+    pub fn revoked(&self) -> bool {
+        self.revoked
+    }
+    pub fn set_revoked(&mut self, val: bool) {
+        self.revoked = val
+    }
+
+    pub fn is_expired_at(&self, at: &DateTime<Utc>) -> bool {
+        self.expires_at <= *at || self.session_expires_at <= *at
+    }
+}
+
+impl Default for RefreshToken {
+    fn default() -> RefreshToken {
+        RefreshToken::new()
+    }
+}
+
+impl fmt::Display for RefreshToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", json!(self).to_string())
+    }
+}
+
+pub fn generate_refresh_token() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+pub fn hash_refresh_token(raw: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(raw.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+// Wallet and oidc logins keep their nonces in the same table; each one is only spent by the flow
+// that handed it out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NoncePurpose {
+    Wallet,
+    Oidc,
+}
+
+impl fmt::Display for NoncePurpose {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NoncePurpose::Wallet => write!(f, "Wallet"),
+            NoncePurpose::Oidc => write!(f, "Oidc"),
+        }
+    }
+}
@@ -12,10 +12,14 @@ pub trait Userer {
     fn downgrade_from_admin(&mut self);
 }
 
-#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub enum UserRoles {
     Basic,
     Admin,
+    Moderator,
+    VerifiedPress,
+    Partner,
+    Support,
 }
 
 impl UserRoles {
@@ -25,6 +29,13 @@ impl UserRoles {
             _ => false,
         }
     }
+    // staff roles work from lambda_admin, so they get admin tokens and need mfa
+    pub fn is_staff(&self) -> bool {
+        match *self {
+            UserRoles::Admin | UserRoles::Moderator | UserRoles::Support => true,
+            _ => false,
+        }
+    }
 
     pub fn to_vec_str(input: &Vec<UserRoles>) -> Vec<String> {
         let aux: Vec<String> = input.into_iter().map(|i| i.to_string()).collect();
@@ -44,6 +55,10 @@ impl UserRoles {
         match input {
             "Basic" => return Some(UserRoles::Basic),
             "Admin" => return Some(UserRoles::Admin),
+            "Moderator" => return Some(UserRoles::Moderator),
+            "VerifiedPress" => return Some(UserRoles::VerifiedPress),
+            "Partner" => return Some(UserRoles::Partner),
+            "Support" => return Some(UserRoles::Support),
             _ => return None,
         }
     }
@@ -54,6 +69,10 @@ impl fmt::Display for UserRoles {
         match self {
             UserRoles::Basic => write!(f, "Basic"),
             UserRoles::Admin => write!(f, "Admin"),
+            UserRoles::Moderator => write!(f, "Moderator"),
+            UserRoles::VerifiedPress => write!(f, "VerifiedPress"),
+            UserRoles::Partner => write!(f, "Partner"),
+            UserRoles::Support => write!(f, "Support"),
         }
     }
 }
@@ -102,6 +121,9 @@ pub struct User {
     roles: Vec<UserRoles>,
     #[builder(default = "UserStatus::Enabled")]
     status: UserStatus,
+    #[builder(default)]
+    #[serde(default)]
+    email_verified: bool,
 }
 
 impl User {
@@ -116,6 +138,7 @@ impl User {
             roles: Vec::new(),
             //password: String::new(),
             status: UserStatus::Enabled,
+            email_verified: false,
         }
     }
 
@@ -162,6 +185,9 @@ impl User {
     pub fn roles_add(&mut self, val: &UserRoles) {
         self.roles.push(val.clone());
     }
+    pub fn is_staff(&self) -> bool {
+        self.roles.iter().any(|r| r.is_staff())
+    }
     pub fn is_admin(&self) -> bool {
         let i = self.roles.iter().filter(|r| r.is_admin()).count();
         match i {
@@ -182,6 +208,16 @@ impl User {
     pub fn set_status(&mut self, val: &UserStatus) {
         self.status = val.clone()
     }
+    pub fn email_verified(&self) -> bool {
+        self.email_verified
+    }
+    pub fn set_email_verified(&mut self, val: bool) {
+        self.email_verified = val
+    }
+    // accounts without an email (device or wallet logins) have nothing to verify
+    pub fn is_verified(&self) -> bool {
+        self.email.is_none() || self.email_verified
+    }
 }
 
 impl fmt::Display for User {
@@ -200,7 +236,8 @@ impl fmt::Display for User {
             write!(f, "  Wallet Address: {},\n", wallet_address)?;
         }
         write!(f, "  Roles: {:?},\n", self.roles)?;
-        write!(f, "  Status: {}\n", self.status)?;
+        write!(f, "  Status: {},\n", self.status)?;
+        write!(f, "  Email Verified: {}\n", self.email_verified)?;
         write!(f, "}}")
     }
 }
@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::user::{User, UserRoles, UserStatus};
+
+pub const SEARCH_PAGE_SIZE_MIN: u32 = 5;
+pub const SEARCH_PAGE_SIZE_MAX: u32 = 50;
+
+// What support staff look users up by. Each search walks a single index: wallet, device,
+// email prefix, role or, when none of those is set, status; the rest of the fields filter it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UserSearch {
+    pub email_prefix: Option<String>,
+    pub wallet: Option<String>,
+    pub device: Option<String>,
+    pub role: Option<UserRoles>,
+    pub status: Option<UserStatus>,
+    pub created_from: Option<DateTime<Utc>>,
+    pub created_to: Option<DateTime<Utc>>,
+    // by creation time, or by email when searching by email prefix
+    pub ascending: bool,
+    pub page_size: u32,
+    // next_cursor of the previous page
+    pub cursor: Option<String>,
+}
+
+impl UserSearch {
+    pub fn new() -> UserSearch {
+        UserSearch {
+            email_prefix: None,
+            wallet: None,
+            device: None,
+            role: None,
+            status: None,
+            created_from: None,
+            created_to: None,
+            ascending: false,
+            page_size: SEARCH_PAGE_SIZE_MIN,
+            cursor: None,
+        }
+    }
+
+    // the criteria the index didn't cover
+    pub fn matches(&self, user: &User) -> bool {
+        if let Some(prefix) = &self.email_prefix {
+            match user.email() {
+                Some(email) if email.starts_with(prefix.as_str()) => {}
+                _ => return false,
+            }
+        }
+        if self.wallet.is_some() && user.wallet_address() != &self.wallet {
+            return false;
+        }
+        if self.device.is_some() && user.device() != &self.device {
+            return false;
+        }
+        if let Some(role) = &self.role {
+            if !user.roles().contains(role) {
+                return false;
+            }
+        }
+        if let Some(status) = &self.status {
+            if user.status() != status {
+                return false;
+            }
+        }
+        if let Some(from) = &self.created_from {
+            if user.creation_time() < from {
+                return false;
+            }
+        }
+        if let Some(to) = &self.created_to {
+            if user.creation_time() > to {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl Default for UserSearch {
+    fn default() -> UserSearch {
+        UserSearch::new()
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct UserPage {
+    pub users: Vec<User>,
+    // none on the last page
+    pub next_cursor: Option<String>,
+}
+
+// Where a search stopped: the last key read and, when a search spans several partitions
+// of the index, the one it was reading. Handed out as an opaque url-safe string.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct SearchCursor {
+    pub partition: Option<String>,
+    pub key: HashMap<String, String>,
+}
+
+impl SearchCursor {
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_string(self).unwrap();
+        general_purpose::URL_SAFE_NO_PAD.encode(json)
+    }
+
+    pub fn decode(cursor: &str) -> Option<SearchCursor> {
+        let json = general_purpose::URL_SAFE_NO_PAD.decode(cursor).ok()?;
+        serde_json::from_slice(&json).ok()
+    }
+}
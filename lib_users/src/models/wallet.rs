@@ -0,0 +1,23 @@
+use std::fmt;
+
+// A wallet address whose owner has just proved control of it by signing a
+// Sign-In With Ethereum message. Only the wallet login service can build one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VerifiedWallet(String);
+
+impl VerifiedWallet {
+    pub(crate) fn new(address: &String) -> VerifiedWallet {
+        VerifiedWallet(address.clone())
+    }
+
+    // EIP-55 checksummed
+    pub fn address(&self) -> &String {
+        &self.0
+    }
+}
+
+impl fmt::Display for VerifiedWallet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
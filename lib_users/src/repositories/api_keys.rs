@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use aws_sdk_dynamodb::{
+    types::{AttributeValue, Select},
+    Client,
+};
+use chrono::{
+    prelude::{DateTime, Utc},
+    Local,
+};
+use lib_config::config::Config;
+use lib_config::timing::{from_iso8601, iso8601};
+
+use crate::errors::api_keys::ApiKeyDynamoDBError;
+use crate::models::api_key::{ApiKey, ApiKeyScope};
+
+use super::schema_sessions::{API_KEYS_TABLE_NAME, API_KEYS_USER_INDEX, API_KEY_FIELD_NAME_PK};
+use super::schema_user::USERID_FIELD_NAME_PK;
+
+static KEY_ID_FIELD_NAME: &str = "keyId";
+static NAME_FIELD_NAME: &str = "name";
+static PREFIX_FIELD_NAME: &str = "prefix";
+static SCOPES_FIELD_NAME: &str = "scopes";
+static CREATIONTIME_FIELD_NAME: &str = "creationTime";
+static LASTUSEDTIME_FIELD_NAME: &str = "lastUsedTime";
+static REVOKED_FIELD_NAME: &str = "revoked";
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+#[async_trait]
+pub trait ApiKeyRepository {
+    async fn add(&self, key: &ApiKey) -> ResultE<()>;
+    async fn get(&self, key_hash: &String) -> ResultE<Option<ApiKey>>;
+    async fn get_by_user(&self, user_id: &String) -> ResultE<Vec<ApiKey>>;
+    // true only when this call moved the key from active to revoked
+    async fn revoke(&self, key_hash: &String) -> ResultE<bool>;
+    async fn touch(&self, key_hash: &String, at: &DateTime<Utc>) -> ResultE<()>;
+}
+
+#[derive(Clone, Debug)]
+pub struct ApiKeysRepo {
+    client: Client,
+}
+
+impl ApiKeysRepo {
+    pub fn new(conf: &Config) -> ApiKeysRepo {
+        ApiKeysRepo {
+            client: Client::new(conf.aws_config()),
+        }
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for ApiKeysRepo {
+    async fn add(&self, key: &ApiKey) -> ResultE<()> {
+        let scopes: Vec<String> = key.scopes().iter().map(|s| s.to_string()).collect();
+        let request = self
+            .client
+            .put_item()
+            .table_name(API_KEYS_TABLE_NAME.clone())
+            .item(
+                API_KEY_FIELD_NAME_PK,
+                AttributeValue::S(key.key_hash().clone()),
+            )
+            .item(KEY_ID_FIELD_NAME, AttributeValue::S(key.key_id().clone()))
+            .item(
+                USERID_FIELD_NAME_PK,
+                AttributeValue::S(key.user_id().clone()),
+            )
+            .item(NAME_FIELD_NAME, AttributeValue::S(key.name().clone()))
+            .item(PREFIX_FIELD_NAME, AttributeValue::S(key.prefix().clone()))
+            .item(
+                SCOPES_FIELD_NAME,
+                AttributeValue::L(scopes.into_iter().map(AttributeValue::S).collect()),
+            )
+            .item(
+                CREATIONTIME_FIELD_NAME,
+                AttributeValue::S(iso8601(key.creation_time())),
+            )
+            .item(REVOKED_FIELD_NAME, AttributeValue::Bool(key.revoked()));
+
+        match request.send().await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(ApiKeyDynamoDBError(e.to_string()).into())
+            }
+        }
+    }
+
+    async fn get(&self, key_hash: &String) -> ResultE<Option<ApiKey>> {
+        let request = self
+            .client
+            .get_item()
+            .table_name(API_KEYS_TABLE_NAME.clone())
+            .key(API_KEY_FIELD_NAME_PK, AttributeValue::S(key_hash.clone()));
+
+        match request.send().await {
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(ApiKeyDynamoDBError(e.to_string()).into())
+            }
+            Ok(data) => match data.item() {
+                None => Ok(None),
+                Some(doc) => {
+                    let mut key = ApiKey::new();
+                    mapping_from_doc_to_api_key(doc, &mut key);
+                    Ok(Some(key))
+                }
+            },
+        }
+    }
+
+    async fn get_by_user(&self, user_id: &String) -> ResultE<Vec<ApiKey>> {
+        let mut queried = Vec::new();
+        let filter = format!("{} = :value", USERID_FIELD_NAME_PK);
+
+        let request = self
+            .client
+            .query()
+            .table_name(API_KEYS_TABLE_NAME.clone())
+            .index_name(API_KEYS_USER_INDEX)
+            .key_condition_expression(filter)
+            .expression_attribute_values(":value".to_string(), AttributeValue::S(user_id.clone()))
+            .select(Select::AllProjectedAttributes);
+
+        match request.send().await {
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                return Err(ApiKeyDynamoDBError(e.to_string()).into());
+            }
+            Ok(data) => {
+                for doc in data.items() {
+                    let mut key = ApiKey::new();
+                    mapping_from_doc_to_api_key(doc, &mut key);
+                    queried.push(key);
+                }
+            }
+        }
+        Ok(queried)
+    }
+
+    async fn revoke(&self, key_hash: &String) -> ResultE<bool> {
+        let request = self
+            .client
+            .update_item()
+            .table_name(API_KEYS_TABLE_NAME.clone())
+            .key(API_KEY_FIELD_NAME_PK, AttributeValue::S(key_hash.clone()))
+            .update_expression("SET #revoked = :revoked")
+            .condition_expression("attribute_exists(#pk) AND #revoked = :active")
+            .expression_attribute_names("#pk", API_KEY_FIELD_NAME_PK)
+            .expression_attribute_names("#revoked", REVOKED_FIELD_NAME)
+            .expression_attribute_values(":revoked", AttributeValue::Bool(true))
+            .expression_attribute_values(":active", AttributeValue::Bool(false));
+
+        match request.send().await {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                let service_error = e.into_service_error();
+                if service_error.is_conditional_check_failed_exception() {
+                    return Ok(false);
+                }
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    service_error
+                );
+                log::error!("{}", mssag);
+                Err(ApiKeyDynamoDBError(service_error.to_string()).into())
+            }
+        }
+    }
+
+    async fn touch(&self, key_hash: &String, at: &DateTime<Utc>) -> ResultE<()> {
+        let request = self
+            .client
+            .update_item()
+            .table_name(API_KEYS_TABLE_NAME.clone())
+            .key(API_KEY_FIELD_NAME_PK, AttributeValue::S(key_hash.clone()))
+            .update_expression("SET #used = :used")
+            .condition_expression("attribute_exists(#pk)")
+            .expression_attribute_names("#pk", API_KEY_FIELD_NAME_PK)
+            .expression_attribute_names("#used", LASTUSEDTIME_FIELD_NAME)
+            .expression_attribute_values(":used", AttributeValue::S(iso8601(at)));
+
+        match request.send().await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(ApiKeyDynamoDBError(e.to_string()).into())
+            }
+        }
+    }
+}
+
+fn mapping_from_doc_to_api_key(doc: &HashMap<String, AttributeValue>, key: &mut ApiKey) {
+    if let Some(hash) = doc.get(API_KEY_FIELD_NAME_PK) {
+        key.set_key_hash(hash.as_s().unwrap());
+    }
+    if let Some(key_id) = doc.get(KEY_ID_FIELD_NAME) {
+        key.set_key_id(key_id.as_s().unwrap());
+    }
+    if let Some(user_id) = doc.get(USERID_FIELD_NAME_PK) {
+        key.set_user_id(user_id.as_s().unwrap());
+    }
+    if let Some(name) = doc.get(NAME_FIELD_NAME) {
+        key.set_name(name.as_s().unwrap());
+    }
+    if let Some(prefix) = doc.get(PREFIX_FIELD_NAME) {
+        key.set_prefix(prefix.as_s().unwrap());
+    }
+    if let Some(scopes) = doc.get(SCOPES_FIELD_NAME) {
+        let scopes: Vec<ApiKeyScope> = scopes
+            .as_l()
+            .unwrap()
+            .iter()
+            .filter_map(|s| ApiKeyScope::from_str(s.as_s().unwrap()).ok())
+            .collect();
+        key.set_scopes(&scopes);
+    }
+    if let Some(creation_time) = doc.get(CREATIONTIME_FIELD_NAME) {
+        key.set_creation_time(&from_iso8601(creation_time.as_s().unwrap()));
+    }
+    if let Some(last_used) = doc.get(LASTUSEDTIME_FIELD_NAME) {
+        key.set_last_used_time(&Some(from_iso8601(last_used.as_s().unwrap())));
+    }
+    if let Some(revoked) = doc.get(REVOKED_FIELD_NAME) {
+        key.set_revoked(*revoked.as_bool().unwrap());
+    }
+}
@@ -0,0 +1,378 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use aws_sdk_dynamodb::{
+    operation::query::builders::QueryFluentBuilder, types::AttributeValue, Client,
+};
+use chrono::Local;
+use lib_config::config::Config;
+use lib_config::timing::{from_iso8601, iso8601};
+
+use crate::errors::audit::AuditDynamoDBError;
+use crate::models::audit::{AuditAction, AuditEntry, AuditPage, AuditSearch};
+use crate::models::user_search::SearchCursor;
+
+use super::schema_sessions::{
+    AUDIT_ACTION_FIELD_NAME, AUDIT_ACTION_INDEX, AUDIT_ACTOR_FIELD_NAME, AUDIT_ACTOR_INDEX,
+    AUDIT_CREATION_TIME_FIELD_NAME, AUDIT_FIELD_NAME_PK, AUDIT_MONTH_FIELD_NAME, AUDIT_MONTH_INDEX,
+    AUDIT_TABLE_NAME, AUDIT_TARGET_FIELD_NAME, AUDIT_TARGET_INDEX,
+};
+
+static BEFORE_FIELD_NAME: &str = "before";
+static AFTER_FIELD_NAME: &str = "after";
+static REQUEST_ID_FIELD_NAME: &str = "requestID";
+static STATUS_FIELD_NAME: &str = "status";
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+#[async_trait]
+pub trait AuditRepository {
+    // there is no remove and the only update is the one-off completion: the log is append only
+    async fn add(&self, entry: &AuditEntry) -> ResultE<()>;
+    async fn complete(&self, entry: &AuditEntry) -> ResultE<()>;
+    async fn search(&self, filter: &AuditSearch) -> ResultE<AuditPage>;
+}
+
+#[derive(Clone, Debug)]
+pub struct AuditRepo {
+    client: Client,
+}
+
+impl AuditRepo {
+    pub fn new(conf: &Config) -> AuditRepo {
+        AuditRepo {
+            client: Client::new(conf.aws_config()),
+        }
+    }
+
+    async fn query_page(
+        &self,
+        request: QueryFluentBuilder,
+    ) -> ResultE<(
+        Vec<HashMap<String, AttributeValue>>,
+        Option<HashMap<String, String>>,
+    )> {
+        match request.send().await {
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(AuditDynamoDBError(e.to_string()).into())
+            }
+            Ok(result) => {
+                let last_key = result.last_evaluated_key().map(|key| {
+                    key.iter()
+                        .filter_map(|(name, value)| {
+                            value.as_s().ok().map(|value| (name.clone(), value.clone()))
+                        })
+                        .collect()
+                });
+                Ok((result.items().to_vec(), last_key))
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl AuditRepository for AuditRepo {
+    async fn add(&self, entry: &AuditEntry) -> ResultE<()> {
+        let mut request = self
+            .client
+            .put_item()
+            .table_name(AUDIT_TABLE_NAME.clone())
+            .item(
+                AUDIT_FIELD_NAME_PK,
+                AttributeValue::S(entry.audit_id().clone()),
+            )
+            .item(
+                AUDIT_ACTOR_FIELD_NAME,
+                AttributeValue::S(entry.actor_id().clone()),
+            )
+            .item(
+                AUDIT_TARGET_FIELD_NAME,
+                AttributeValue::S(entry.target_id().clone()),
+            )
+            .item(
+                AUDIT_ACTION_FIELD_NAME,
+                AttributeValue::S(entry.action().to_string()),
+            )
+            .item(AUDIT_MONTH_FIELD_NAME, AttributeValue::S(entry.month()))
+            .item(
+                AUDIT_CREATION_TIME_FIELD_NAME,
+                AttributeValue::S(iso8601(entry.creation_time())),
+            )
+            .item(
+                REQUEST_ID_FIELD_NAME,
+                AttributeValue::S(entry.request_id().clone()),
+            )
+            // an id clash must never overwrite an older entry
+            .condition_expression(format!("attribute_not_exists({})", AUDIT_FIELD_NAME_PK));
+        if let Some(before) = entry.before() {
+            request = request.item(BEFORE_FIELD_NAME, AttributeValue::S(before.to_string()));
+        }
+        if let Some(after) = entry.after() {
+            request = request.item(AFTER_FIELD_NAME, AttributeValue::S(after.to_string()));
+        }
+
+        match request.send().await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(AuditDynamoDBError(e.to_string()).into())
+            }
+        }
+    }
+
+    // sets the outcome of an entry already written, once
+    async fn complete(&self, entry: &AuditEntry) -> ResultE<()> {
+        // status and after are reserved words in dynamodb expressions
+        let mut update_express = "set #status = :status".to_string();
+        let mut request = self
+            .client
+            .update_item()
+            .table_name(AUDIT_TABLE_NAME.clone())
+            .key(
+                AUDIT_FIELD_NAME_PK,
+                AttributeValue::S(entry.audit_id().clone()),
+            )
+            .expression_attribute_names("#status", STATUS_FIELD_NAME)
+            .expression_attribute_values(
+                ":status",
+                AttributeValue::N(entry.status().unwrap_or_default().to_string()),
+            )
+            .condition_expression(format!(
+                "attribute_exists({}) AND attribute_not_exists(#status)",
+                AUDIT_FIELD_NAME_PK
+            ));
+        if let Some(after) = entry.after() {
+            update_express.push_str(", #after = :after");
+            request = request
+                .expression_attribute_names("#after", AFTER_FIELD_NAME)
+                .expression_attribute_values(":after", AttributeValue::S(after.to_string()));
+        }
+
+        match request.update_expression(update_express).send().await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(AuditDynamoDBError(e.to_string()).into())
+            }
+        }
+    }
+
+    async fn search(&self, filter: &AuditSearch) -> ResultE<AuditPage> {
+        let (index, partition_field, partitions) = if let Some(target) = &filter.target_id {
+            (
+                AUDIT_TARGET_INDEX,
+                AUDIT_TARGET_FIELD_NAME,
+                vec![target.clone()],
+            )
+        } else if let Some(actor) = &filter.actor_id {
+            (
+                AUDIT_ACTOR_INDEX,
+                AUDIT_ACTOR_FIELD_NAME,
+                vec![actor.clone()],
+            )
+        } else if let Some(action) = &filter.action {
+            (
+                AUDIT_ACTION_INDEX,
+                AUDIT_ACTION_FIELD_NAME,
+                vec![action.to_string()],
+            )
+        } else {
+            (AUDIT_MONTH_INDEX, AUDIT_MONTH_FIELD_NAME, filter.months())
+        };
+        if partitions.is_empty() {
+            return Ok(AuditPage {
+                entries: vec![],
+                next_cursor: None,
+            });
+        }
+
+        let cursor = filter.cursor.as_ref().and_then(|c| SearchCursor::decode(c));
+        let (mut current, mut start) = match cursor {
+            None => (0, None),
+            Some(c) => (
+                partitions
+                    .iter()
+                    .position(|partition| Some(partition) == c.partition.as_ref())
+                    .unwrap_or(0),
+                start_key(&c.key),
+            ),
+        };
+        let (filter_expression, filter_values) = filter_condition(filter, partition_field);
+
+        let mut entries = Vec::new();
+        loop {
+            let (range, mut values) = creation_time_condition(filter);
+            values.insert(
+                ":partition".to_string(),
+                AttributeValue::S(partitions[current].clone()),
+            );
+            values.extend(filter_values.clone());
+            let request = self
+                .client
+                .query()
+                .table_name(AUDIT_TABLE_NAME.as_str())
+                .index_name(index)
+                .key_condition_expression(format!("{} = :partition{}", partition_field, range))
+                .set_filter_expression(filter_expression.clone())
+                .set_expression_attribute_values(Some(values))
+                .scan_index_forward(filter.ascending)
+                .limit((filter.page_size as usize - entries.len()) as i32)
+                .set_exclusive_start_key(start);
+
+            let (docs, last_key) = self.query_page(request).await?;
+            for doc in docs {
+                let mut entry = AuditEntry::new();
+                mapping_from_doc_to_audit(&doc, &mut entry);
+                entries.push(entry);
+            }
+
+            if let Some(key) = last_key {
+                if entries.len() >= filter.page_size as usize {
+                    let partition = Some(partitions[current].clone());
+                    return Ok(AuditPage {
+                        entries,
+                        next_cursor: Some(SearchCursor { partition, key }.encode()),
+                    });
+                }
+                // the filter dropped part of the page, keep reading the same partition
+                start = start_key(&key);
+                continue;
+            }
+            current += 1;
+            start = None;
+            if current == partitions.len() {
+                return Ok(AuditPage {
+                    entries,
+                    next_cursor: None,
+                });
+            }
+            if entries.len() >= filter.page_size as usize {
+                let partition = Some(partitions[current].clone());
+                let key = HashMap::new();
+                return Ok(AuditPage {
+                    entries,
+                    next_cursor: Some(SearchCursor { partition, key }.encode()),
+                });
+            }
+        }
+    }
+}
+
+fn start_key(key: &HashMap<String, String>) -> Option<HashMap<String, AttributeValue>> {
+    if key.is_empty() {
+        return None;
+    }
+    Some(
+        key.iter()
+            .map(|(name, value)| (name.clone(), AttributeValue::S(value.clone())))
+            .collect(),
+    )
+}
+
+// sort key condition on the creation time, to append to the partition one
+fn creation_time_condition(filter: &AuditSearch) -> (String, HashMap<String, AttributeValue>) {
+    let mut values = HashMap::new();
+    let condition = match (&filter.from, &filter.to) {
+        (Some(from), Some(to)) => {
+            values.insert(":from".to_string(), AttributeValue::S(iso8601(from)));
+            values.insert(":to".to_string(), AttributeValue::S(iso8601(to)));
+            format!(
+                " AND {} BETWEEN :from AND :to",
+                AUDIT_CREATION_TIME_FIELD_NAME
+            )
+        }
+        (Some(from), None) => {
+            values.insert(":from".to_string(), AttributeValue::S(iso8601(from)));
+            format!(" AND {} >= :from", AUDIT_CREATION_TIME_FIELD_NAME)
+        }
+        (None, Some(to)) => {
+            values.insert(":to".to_string(), AttributeValue::S(iso8601(to)));
+            format!(" AND {} <= :to", AUDIT_CREATION_TIME_FIELD_NAME)
+        }
+        (None, None) => String::new(),
+    };
+    (condition, values)
+}
+
+// the criteria the walked index doesn't cover
+fn filter_condition(
+    filter: &AuditSearch,
+    partition_field: &str,
+) -> (Option<String>, HashMap<String, AttributeValue>) {
+    let mut conditions = Vec::new();
+    let mut values = HashMap::new();
+    let criteria = [
+        (AUDIT_ACTOR_FIELD_NAME, ":actor", filter.actor_id.clone()),
+        (
+            AUDIT_ACTION_FIELD_NAME,
+            ":action",
+            filter.action.map(|a| a.to_string()),
+        ),
+        (AUDIT_MONTH_FIELD_NAME, ":month", filter.month.clone()),
+    ];
+    for (field, placeholder, value) in criteria {
+        if field == partition_field {
+            continue;
+        }
+        if let Some(value) = value {
+            conditions.push(format!("{} = {}", field, placeholder));
+            values.insert(placeholder.to_string(), AttributeValue::S(value));
+        }
+    }
+    if conditions.is_empty() {
+        (None, values)
+    } else {
+        (Some(conditions.join(" AND ")), values)
+    }
+}
+
+fn mapping_from_doc_to_audit(doc: &HashMap<String, AttributeValue>, entry: &mut AuditEntry) {
+    let audit_id = doc.get(AUDIT_FIELD_NAME_PK).unwrap().as_s().unwrap();
+    entry.set_audit_id(audit_id);
+
+    let actor_id = doc.get(AUDIT_ACTOR_FIELD_NAME).unwrap().as_s().unwrap();
+    entry.set_actor_id(actor_id);
+
+    let target_id = doc.get(AUDIT_TARGET_FIELD_NAME).unwrap().as_s().unwrap();
+    entry.set_target_id(target_id);
+
+    let action = doc.get(AUDIT_ACTION_FIELD_NAME).unwrap().as_s().unwrap();
+    entry.set_action(&AuditAction::from_str(action).unwrap());
+
+    let creation_time = doc
+        .get(AUDIT_CREATION_TIME_FIELD_NAME)
+        .unwrap()
+        .as_s()
+        .unwrap();
+    entry.set_creation_time(&from_iso8601(creation_time));
+
+    if let Some(request_id) = doc.get(REQUEST_ID_FIELD_NAME) {
+        entry.set_request_id(request_id.as_s().unwrap());
+    }
+    if let Some(before) = doc.get(BEFORE_FIELD_NAME) {
+        entry.set_before(&serde_json::from_str(before.as_s().unwrap()).ok());
+    }
+    if let Some(after) = doc.get(AFTER_FIELD_NAME) {
+        entry.set_after(&serde_json::from_str(after.as_s().unwrap()).ok());
+    }
+    if let Some(status) = doc.get(STATUS_FIELD_NAME) {
+        entry.set_status(&status.as_n().ok().and_then(|n| u16::from_str(n).ok()));
+    }
+}
@@ -0,0 +1,375 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use aws_sdk_dynamodb::{
+    types::{AttributeValue, Delete, Select, TransactWriteItem, Update},
+    Client,
+};
+use chrono::{
+    prelude::{DateTime, Utc},
+    Local,
+};
+use lib_config::config::Config;
+use lib_config::timing::{from_iso8601, iso8601};
+
+use crate::errors::devices::{DeviceAlreadyExistsError, DeviceDynamoDBError};
+use crate::models::device::{DeviceCredential, DeviceKeyAlgorithm};
+
+use super::schema_sessions::{
+    DEVICES_TABLE_NAME, DEVICES_USER_INDEX, DEVICE_CHALLENGES_TABLE_NAME,
+    DEVICE_CHALLENGE_FIELD_NAME_PK, DEVICE_FIELD_NAME_PK, SESSION_TTL_FIELD_NAME,
+};
+use super::schema_user::USERID_FIELD_NAME_PK;
+
+static ALGORITHM_FIELD_NAME: &str = "algorithm";
+static PUBLIC_KEY_FIELD_NAME: &str = "publicKey";
+static CREATIONTIME_FIELD_NAME: &str = "creationTime";
+static LASTUSEDTIME_FIELD_NAME: &str = "lastUsedTime";
+static REVOKED_FIELD_NAME: &str = "revoked";
+static EXPIRES_AT_FIELD_NAME: &str = "expiresAt";
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+#[async_trait]
+pub trait DeviceRepository {
+    async fn add(&self, device: &DeviceCredential) -> ResultE<()>;
+    async fn get(&self, device_id: &String) -> ResultE<Option<DeviceCredential>>;
+    async fn get_by_user(&self, user_id: &String) -> ResultE<Vec<DeviceCredential>>;
+    // true only when this call moved the device from active to revoked
+    async fn revoke(&self, device_id: &String) -> ResultE<bool>;
+    // every challenge is a row of its own, pending ones live until they're used or expire
+    async fn add_challenge(
+        &self,
+        device_id: &String,
+        challenge: &String,
+        expires_at: &DateTime<Utc>,
+    ) -> ResultE<()>;
+    // true only when the challenge was issued to this device, not expired, the device is still
+    // active and this call spent it
+    async fn consume_challenge(&self, device_id: &String, challenge: &String) -> ResultE<bool>;
+    // hands an active device over to another user, true only when it still belonged to user_id
+    async fn reassign(&self, device_id: &String, user_id: &String, heir: &String) -> ResultE<bool>;
+}
+
+#[derive(Clone, Debug)]
+pub struct DevicesRepo {
+    client: Client,
+}
+
+impl DevicesRepo {
+    pub fn new(conf: &Config) -> DevicesRepo {
+        DevicesRepo {
+            client: Client::new(conf.aws_config()),
+        }
+    }
+}
+
+#[async_trait]
+impl DeviceRepository for DevicesRepo {
+    async fn add(&self, device: &DeviceCredential) -> ResultE<()> {
+        // a revoked device can be paired again, maybe with another account
+        let request = self
+            .client
+            .put_item()
+            .table_name(DEVICES_TABLE_NAME.clone())
+            .item(
+                DEVICE_FIELD_NAME_PK,
+                AttributeValue::S(device.device_id().clone()),
+            )
+            .item(USERID_FIELD_NAME_PK, AttributeValue::S(device.user_id().clone()))
+            .item(
+                ALGORITHM_FIELD_NAME,
+                AttributeValue::S(device.algorithm().to_string()),
+            )
+            .item(
+                PUBLIC_KEY_FIELD_NAME,
+                AttributeValue::S(device.public_key().clone()),
+            )
+            .item(
+                CREATIONTIME_FIELD_NAME,
+                AttributeValue::S(iso8601(device.creation_time())),
+            )
+            .item(REVOKED_FIELD_NAME, AttributeValue::Bool(device.revoked()))
+            .condition_expression("attribute_not_exists(#pk) OR #revoked = :revoked")
+            .expression_attribute_names("#pk", DEVICE_FIELD_NAME_PK)
+            .expression_attribute_names("#revoked", REVOKED_FIELD_NAME)
+            .expression_attribute_values(":revoked", AttributeValue::Bool(true));
+
+        match request.send().await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let service_error = e.into_service_error();
+                if service_error.is_conditional_check_failed_exception() {
+                    return Err(DeviceAlreadyExistsError(device.device_id().clone()).into());
+                }
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    service_error
+                );
+                log::error!("{}", mssag);
+                Err(DeviceDynamoDBError(service_error.to_string()).into())
+            }
+        }
+    }
+
+    async fn get(&self, device_id: &String) -> ResultE<Option<DeviceCredential>> {
+        let request = self
+            .client
+            .get_item()
+            .table_name(DEVICES_TABLE_NAME.clone())
+            .key(DEVICE_FIELD_NAME_PK, AttributeValue::S(device_id.clone()));
+
+        match request.send().await {
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(DeviceDynamoDBError(e.to_string()).into())
+            }
+            Ok(data) => match data.item() {
+                None => Ok(None),
+                Some(doc) => {
+                    let mut device = DeviceCredential::new();
+                    mapping_from_doc_to_device(doc, &mut device);
+                    Ok(Some(device))
+                }
+            },
+        }
+    }
+
+    async fn get_by_user(&self, user_id: &String) -> ResultE<Vec<DeviceCredential>> {
+        let mut queried = Vec::new();
+        let filter = format!("{} = :value", USERID_FIELD_NAME_PK);
+
+        let request = self
+            .client
+            .query()
+            .table_name(DEVICES_TABLE_NAME.clone())
+            .index_name(DEVICES_USER_INDEX)
+            .key_condition_expression(filter)
+            .expression_attribute_values(":value".to_string(), AttributeValue::S(user_id.clone()))
+            .select(Select::AllProjectedAttributes);
+
+        match request.send().await {
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                return Err(DeviceDynamoDBError(e.to_string()).into());
+            }
+            Ok(data) => {
+                for doc in data.items() {
+                    let mut device = DeviceCredential::new();
+                    mapping_from_doc_to_device(doc, &mut device);
+                    queried.push(device);
+                }
+            }
+        }
+        Ok(queried)
+    }
+
+    async fn revoke(&self, device_id: &String) -> ResultE<bool> {
+        let request = self
+            .client
+            .update_item()
+            .table_name(DEVICES_TABLE_NAME.clone())
+            .key(DEVICE_FIELD_NAME_PK, AttributeValue::S(device_id.clone()))
+            .update_expression("SET #revoked = :revoked")
+            .condition_expression("attribute_exists(#pk) AND #revoked = :active")
+            .expression_attribute_names("#pk", DEVICE_FIELD_NAME_PK)
+            .expression_attribute_names("#revoked", REVOKED_FIELD_NAME)
+            .expression_attribute_values(":revoked", AttributeValue::Bool(true))
+            .expression_attribute_values(":active", AttributeValue::Bool(false));
+
+        match request.send().await {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                let service_error = e.into_service_error();
+                if service_error.is_conditional_check_failed_exception() {
+                    return Ok(false);
+                }
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    service_error
+                );
+                log::error!("{}", mssag);
+                Err(DeviceDynamoDBError(service_error.to_string()).into())
+            }
+        }
+    }
+
+    async fn add_challenge(
+        &self,
+        device_id: &String,
+        challenge: &String,
+        expires_at: &DateTime<Utc>,
+    ) -> ResultE<()> {
+        let request = self
+            .client
+            .put_item()
+            .table_name(DEVICE_CHALLENGES_TABLE_NAME.clone())
+            .item(
+                DEVICE_CHALLENGE_FIELD_NAME_PK,
+                AttributeValue::S(challenge.clone()),
+            )
+            .item(DEVICE_FIELD_NAME_PK, AttributeValue::S(device_id.clone()))
+            .item(
+                EXPIRES_AT_FIELD_NAME,
+                AttributeValue::N(expires_at.timestamp().to_string()),
+            )
+            .item(
+                SESSION_TTL_FIELD_NAME,
+                AttributeValue::N(expires_at.timestamp().to_string()),
+            );
+
+        match request.send().await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(DeviceDynamoDBError(e.to_string()).into())
+            }
+        }
+    }
+
+    async fn consume_challenge(&self, device_id: &String, challenge: &String) -> ResultE<bool> {
+        // the ttl purge is lazy, so the expiration is checked here as well
+        let now = Utc::now();
+        let request = self
+            .client
+            .transact_write_items()
+            .transact_items(
+                TransactWriteItem::builder()
+                    .delete(
+                        Delete::builder()
+                            .table_name(DEVICE_CHALLENGES_TABLE_NAME.clone())
+                            .key(
+                                DEVICE_CHALLENGE_FIELD_NAME_PK,
+                                AttributeValue::S(challenge.clone()),
+                            )
+                            .condition_expression("#device = :device AND #expires > :now")
+                            .expression_attribute_names("#device", DEVICE_FIELD_NAME_PK)
+                            .expression_attribute_names("#expires", EXPIRES_AT_FIELD_NAME)
+                            .expression_attribute_values(
+                                ":device",
+                                AttributeValue::S(device_id.clone()),
+                            )
+                            .expression_attribute_values(
+                                ":now",
+                                AttributeValue::N(now.timestamp().to_string()),
+                            )
+                            .build()
+                            .unwrap(),
+                    )
+                    .build(),
+            )
+            .transact_items(
+                TransactWriteItem::builder()
+                    .update(
+                        Update::builder()
+                            .table_name(DEVICES_TABLE_NAME.clone())
+                            .key(DEVICE_FIELD_NAME_PK, AttributeValue::S(device_id.clone()))
+                            .update_expression("SET #last_used = :last_used")
+                            .condition_expression("#revoked = :active")
+                            .expression_attribute_names("#last_used", LASTUSEDTIME_FIELD_NAME)
+                            .expression_attribute_names("#revoked", REVOKED_FIELD_NAME)
+                            .expression_attribute_values(
+                                ":last_used",
+                                AttributeValue::S(iso8601(&now)),
+                            )
+                            .expression_attribute_values(":active", AttributeValue::Bool(false))
+                            .build()
+                            .unwrap(),
+                    )
+                    .build(),
+            );
+
+        match request.send().await {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                let service_error = e.into_service_error();
+                if service_error.is_transaction_canceled_exception() {
+                    return Ok(false);
+                }
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    service_error
+                );
+                log::error!("{}", mssag);
+                Err(DeviceDynamoDBError(service_error.to_string()).into())
+            }
+        }
+    }
+
+    async fn reassign(&self, device_id: &String, user_id: &String, heir: &String) -> ResultE<bool> {
+        let request = self
+            .client
+            .update_item()
+            .table_name(DEVICES_TABLE_NAME.clone())
+            .key(DEVICE_FIELD_NAME_PK, AttributeValue::S(device_id.clone()))
+            .update_expression("SET #user = :heir")
+            .condition_expression("#user = :user AND #revoked = :active")
+            .expression_attribute_names("#user", USERID_FIELD_NAME_PK)
+            .expression_attribute_names("#revoked", REVOKED_FIELD_NAME)
+            .expression_attribute_values(":heir", AttributeValue::S(heir.clone()))
+            .expression_attribute_values(":user", AttributeValue::S(user_id.clone()))
+            .expression_attribute_values(":active", AttributeValue::Bool(false));
+
+        match request.send().await {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                let service_error = e.into_service_error();
+                if service_error.is_conditional_check_failed_exception() {
+                    return Ok(false);
+                }
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    service_error
+                );
+                log::error!("{}", mssag);
+                Err(DeviceDynamoDBError(service_error.to_string()).into())
+            }
+        }
+    }
+}
+
+fn mapping_from_doc_to_device(doc: &HashMap<String, AttributeValue>, device: &mut DeviceCredential) {
+    if let Some(device_id) = doc.get(DEVICE_FIELD_NAME_PK) {
+        device.set_device_id(device_id.as_s().unwrap());
+    }
+    if let Some(user_id) = doc.get(USERID_FIELD_NAME_PK) {
+        device.set_user_id(user_id.as_s().unwrap());
+    }
+    if let Some(algorithm) = doc.get(ALGORITHM_FIELD_NAME) {
+        if let Ok(value) = DeviceKeyAlgorithm::from_str(algorithm.as_s().unwrap()) {
+            device.set_algorithm(&value);
+        }
+    }
+    if let Some(public_key) = doc.get(PUBLIC_KEY_FIELD_NAME) {
+        device.set_public_key(public_key.as_s().unwrap());
+    }
+    if let Some(creation_time) = doc.get(CREATIONTIME_FIELD_NAME) {
+        device.set_creation_time(&from_iso8601(creation_time.as_s().unwrap()));
+    }
+    if let Some(last_used_time) = doc.get(LASTUSEDTIME_FIELD_NAME) {
+        device.set_last_used_time(&Some(from_iso8601(last_used_time.as_s().unwrap())));
+    }
+    if let Some(revoked) = doc.get(REVOKED_FIELD_NAME) {
+        device.set_revoked(*revoked.as_bool().unwrap());
+    }
+}
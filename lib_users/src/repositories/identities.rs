@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use aws_sdk_dynamodb::{
+    types::{AttributeValue, Select},
+    Client,
+};
+use chrono::Local;
+use lib_config::config::Config;
+use lib_config::timing::{from_iso8601, iso8601};
+
+use crate::errors::identities::IdentityDynamoDBError;
+use crate::models::identity::{identity_key, LinkedIdentity};
+
+use super::schema_user::{
+    LOGIN_IDENTITIES_TABLE_NAME, LOGIN_IDENTITIES_USER_INDEX, LOGIN_IDENTITY_FIELD_NAME_PK,
+    USERID_FIELD_NAME_PK,
+};
+
+static ISSUER_FIELD_NAME: &str = "issuer";
+static SUBJECT_FIELD_NAME: &str = "subject";
+static EMAIL_FIELD_NAME: &str = "email";
+static CREATIONTIME_FIELD_NAME: &str = "creationTime";
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+#[async_trait]
+pub trait IdentityRepository {
+    // false when the identity is already linked, to this or another user
+    async fn add(&self, identity: &LinkedIdentity) -> ResultE<bool>;
+    async fn get(&self, issuer: &String, subject: &String) -> ResultE<Option<LinkedIdentity>>;
+    async fn get_by_user(&self, user_id: &String) -> ResultE<Vec<LinkedIdentity>>;
+    async fn remove(&self, issuer: &String, subject: &String) -> ResultE<()>;
+}
+
+#[derive(Clone, Debug)]
+pub struct IdentitiesRepo {
+    client: Client,
+}
+
+impl IdentitiesRepo {
+    pub fn new(conf: &Config) -> IdentitiesRepo {
+        IdentitiesRepo {
+            client: Client::new(conf.aws_config()),
+        }
+    }
+}
+
+#[async_trait]
+impl IdentityRepository for IdentitiesRepo {
+    async fn add(&self, identity: &LinkedIdentity) -> ResultE<bool> {
+        let mut request = self
+            .client
+            .put_item()
+            .table_name(LOGIN_IDENTITIES_TABLE_NAME.clone())
+            .item(
+                LOGIN_IDENTITY_FIELD_NAME_PK,
+                AttributeValue::S(identity_key(identity.issuer(), identity.subject())),
+            )
+            .item(
+                USERID_FIELD_NAME_PK,
+                AttributeValue::S(identity.user_id().clone()),
+            )
+            .item(
+                ISSUER_FIELD_NAME,
+                AttributeValue::S(identity.issuer().clone()),
+            )
+            .item(
+                SUBJECT_FIELD_NAME,
+                AttributeValue::S(identity.subject().clone()),
+            )
+            .item(
+                CREATIONTIME_FIELD_NAME,
+                AttributeValue::S(iso8601(identity.creation_time())),
+            )
+            .condition_expression("attribute_not_exists(#pk)")
+            .expression_attribute_names("#pk", LOGIN_IDENTITY_FIELD_NAME_PK);
+        if let Some(email) = identity.email() {
+            request = request.item(EMAIL_FIELD_NAME, AttributeValue::S(email.clone()));
+        }
+
+        match request.send().await {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                let service_error = e.into_service_error();
+                if service_error.is_conditional_check_failed_exception() {
+                    return Ok(false);
+                }
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    service_error
+                );
+                log::error!("{}", mssag);
+                Err(IdentityDynamoDBError(service_error.to_string()).into())
+            }
+        }
+    }
+
+    async fn get(&self, issuer: &String, subject: &String) -> ResultE<Option<LinkedIdentity>> {
+        let request = self
+            .client
+            .get_item()
+            .table_name(LOGIN_IDENTITIES_TABLE_NAME.clone())
+            .key(
+                LOGIN_IDENTITY_FIELD_NAME_PK,
+                AttributeValue::S(identity_key(issuer, subject)),
+            );
+
+        match request.send().await {
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(IdentityDynamoDBError(e.to_string()).into())
+            }
+            Ok(data) => match data.item() {
+                None => Ok(None),
+                Some(doc) => {
+                    let mut identity = LinkedIdentity::new();
+                    mapping_from_doc_to_identity(doc, &mut identity);
+                    Ok(Some(identity))
+                }
+            },
+        }
+    }
+
+    async fn get_by_user(&self, user_id: &String) -> ResultE<Vec<LinkedIdentity>> {
+        let mut queried = Vec::new();
+        let filter = format!("{} = :value", USERID_FIELD_NAME_PK);
+
+        let request = self
+            .client
+            .query()
+            .table_name(LOGIN_IDENTITIES_TABLE_NAME.clone())
+            .index_name(LOGIN_IDENTITIES_USER_INDEX)
+            .key_condition_expression(filter)
+            .expression_attribute_values(":value".to_string(), AttributeValue::S(user_id.clone()))
+            .select(Select::AllProjectedAttributes);
+
+        match request.send().await {
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                return Err(IdentityDynamoDBError(e.to_string()).into());
+            }
+            Ok(data) => {
+                for doc in data.items() {
+                    let mut identity = LinkedIdentity::new();
+                    mapping_from_doc_to_identity(doc, &mut identity);
+                    queried.push(identity);
+                }
+            }
+        }
+        Ok(queried)
+    }
+
+    async fn remove(&self, issuer: &String, subject: &String) -> ResultE<()> {
+        let request = self
+            .client
+            .delete_item()
+            .table_name(LOGIN_IDENTITIES_TABLE_NAME.clone())
+            .key(
+                LOGIN_IDENTITY_FIELD_NAME_PK,
+                AttributeValue::S(identity_key(issuer, subject)),
+            );
+
+        match request.send().await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(IdentityDynamoDBError(e.to_string()).into())
+            }
+        }
+    }
+}
+
+fn mapping_from_doc_to_identity(
+    doc: &HashMap<String, AttributeValue>,
+    identity: &mut LinkedIdentity,
+) {
+    if let Some(issuer) = doc.get(ISSUER_FIELD_NAME) {
+        identity.set_issuer(issuer.as_s().unwrap());
+    }
+    if let Some(subject) = doc.get(SUBJECT_FIELD_NAME) {
+        identity.set_subject(subject.as_s().unwrap());
+    }
+    if let Some(user_id) = doc.get(USERID_FIELD_NAME_PK) {
+        identity.set_user_id(user_id.as_s().unwrap());
+    }
+    if let Some(email) = doc.get(EMAIL_FIELD_NAME) {
+        identity.set_email(&Some(email.as_s().unwrap().clone()));
+    }
+    if let Some(creation_time) = doc.get(CREATIONTIME_FIELD_NAME) {
+        identity.set_creation_time(&from_iso8601(creation_time.as_s().unwrap()));
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use aws_sdk_dynamodb::{types::AttributeValue, Client};
+use chrono::Local;
+use lib_config::config::Config;
+use lib_config::timing::{from_iso8601, iso8601};
+
+use crate::errors::sessions::SessionDynamoDBError;
+use crate::models::jwt_key::{JwtKey, JwtKeyStatus};
+
+use super::schema_sessions::{JWT_KEYS_TABLE_NAME, JWT_KEY_KID_FIELD_NAME_PK};
+
+static ALGORITHM_FIELD_NAME: &str = "algorithm";
+static JWK_FIELD_NAME: &str = "jwk";
+static STATUS_FIELD_NAME: &str = "keyStatus";
+static CREATIONTIME_FIELD_NAME: &str = "creationTime";
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+#[async_trait]
+pub trait JwtKeyRepository {
+    async fn add(&self, key: &JwtKey) -> ResultE<()>;
+    async fn update_status(&self, kid: &String, status: &JwtKeyStatus) -> ResultE<()>;
+    async fn get_by_kid(&self, kid: &String) -> ResultE<Option<JwtKey>>;
+    async fn get_all(&self) -> ResultE<Vec<JwtKey>>;
+}
+
+#[derive(Clone, Debug)]
+pub struct JwtKeysRepo {
+    client: Client,
+}
+
+impl JwtKeysRepo {
+    pub fn new(conf: &Config) -> JwtKeysRepo {
+        JwtKeysRepo {
+            client: Client::new(conf.aws_config()),
+        }
+    }
+}
+
+#[async_trait]
+impl JwtKeyRepository for JwtKeysRepo {
+    async fn add(&self, key: &JwtKey) -> ResultE<()> {
+        let request = self
+            .client
+            .put_item()
+            .table_name(JWT_KEYS_TABLE_NAME.clone())
+            .item(JWT_KEY_KID_FIELD_NAME_PK, AttributeValue::S(key.kid().clone()))
+            .item(ALGORITHM_FIELD_NAME, AttributeValue::S(key.algorithm().clone()))
+            .item(JWK_FIELD_NAME, AttributeValue::S(key.jwk().clone()))
+            .item(STATUS_FIELD_NAME, AttributeValue::S(key.status().to_string()))
+            .item(
+                CREATIONTIME_FIELD_NAME,
+                AttributeValue::S(iso8601(key.creation_time())),
+            );
+
+        match request.send().await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(SessionDynamoDBError(e.to_string()).into())
+            }
+        }
+    }
+
+    async fn update_status(&self, kid: &String, status: &JwtKeyStatus) -> ResultE<()> {
+        let request = self
+            .client
+            .update_item()
+            .table_name(JWT_KEYS_TABLE_NAME.clone())
+            .key(JWT_KEY_KID_FIELD_NAME_PK, AttributeValue::S(kid.clone()))
+            .update_expression("SET #status = :status")
+            .expression_attribute_names("#status", STATUS_FIELD_NAME)
+            .expression_attribute_values(":status", AttributeValue::S(status.to_string()));
+
+        match request.send().await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(SessionDynamoDBError(e.to_string()).into())
+            }
+        }
+    }
+
+    async fn get_by_kid(&self, kid: &String) -> ResultE<Option<JwtKey>> {
+        let request = self
+            .client
+            .get_item()
+            .table_name(JWT_KEYS_TABLE_NAME.clone())
+            .key(JWT_KEY_KID_FIELD_NAME_PK, AttributeValue::S(kid.clone()));
+
+        match request.send().await {
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(SessionDynamoDBError(e.to_string()).into())
+            }
+            Ok(data) => match data.item() {
+                None => Ok(None),
+                Some(doc) => {
+                    let mut key = JwtKey::new();
+                    mapping_from_doc_to_jwt_key(doc, &mut key);
+                    Ok(Some(key))
+                }
+            },
+        }
+    }
+
+    // a handful of keys at most, a scan is fine
+    async fn get_all(&self) -> ResultE<Vec<JwtKey>> {
+        let mut queried = Vec::new();
+        let mut last_evaluated_key = None;
+        loop {
+            let request = self
+                .client
+                .scan()
+                .table_name(JWT_KEYS_TABLE_NAME.clone())
+                .set_exclusive_start_key(last_evaluated_key.clone());
+
+            match request.send().await {
+                Err(e) => {
+                    let mssag = format!(
+                        "Error at [{}] - {} ",
+                        Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                        e
+                    );
+                    log::error!("{}", mssag);
+                    return Err(SessionDynamoDBError(e.to_string()).into());
+                }
+                Ok(data) => {
+                    for doc in data.items() {
+                        let mut key = JwtKey::new();
+                        mapping_from_doc_to_jwt_key(doc, &mut key);
+                        queried.push(key);
+                    }
+                    last_evaluated_key = data.last_evaluated_key().cloned();
+                }
+            }
+            if last_evaluated_key.is_none() {
+                break;
+            }
+        }
+        Ok(queried)
+    }
+}
+
+fn mapping_from_doc_to_jwt_key(doc: &HashMap<String, AttributeValue>, key: &mut JwtKey) {
+    if let Some(kid) = doc.get(JWT_KEY_KID_FIELD_NAME_PK) {
+        key.set_kid(kid.as_s().unwrap());
+    }
+    if let Some(algorithm) = doc.get(ALGORITHM_FIELD_NAME) {
+        key.set_algorithm(algorithm.as_s().unwrap());
+    }
+    if let Some(jwk) = doc.get(JWK_FIELD_NAME) {
+        key.set_jwk(jwk.as_s().unwrap());
+    }
+    if let Some(status) = doc.get(STATUS_FIELD_NAME) {
+        key.set_status(&JwtKeyStatus::from_str(status.as_s().unwrap()).unwrap());
+    }
+    if let Some(creation_time) = doc.get(CREATIONTIME_FIELD_NAME) {
+        key.set_creation_time(&from_iso8601(creation_time.as_s().unwrap()));
+    }
+}
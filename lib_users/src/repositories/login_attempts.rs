@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use aws_sdk_dynamodb::{
+    types::{AttributeValue, ReturnValue},
+    Client,
+};
+use chrono::{
+    prelude::{DateTime, Utc},
+    Local, TimeZone,
+};
+use lib_config::config::Config;
+use lib_config::timing::{from_iso8601, iso8601};
+
+use crate::errors::login_attempts::LoginAttemptDynamoDBError;
+use crate::models::login_attempt::LoginAttempt;
+
+use super::schema_sessions::{
+    LOGIN_ATTEMPTS_TABLE_NAME, LOGIN_ATTEMPT_FIELD_NAME_PK, SESSION_TTL_FIELD_NAME,
+};
+
+static FAILURES_FIELD_NAME: &str = "failures";
+static LAST_FAILURE_FIELD_NAME: &str = "lastFailure";
+// epoch seconds, so the scan for locked rows can compare it with now
+static LOCKED_UNTIL_FIELD_NAME: &str = "lockedUntil";
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+#[async_trait]
+pub trait LoginAttemptRepository {
+    async fn get(&self, key: &String) -> ResultE<Option<LoginAttempt>>;
+    // adds one failure atomically and returns the counter as it is now
+    async fn add_failure(
+        &self,
+        key: &String,
+        at: &DateTime<Utc>,
+        expires_at: &DateTime<Utc>,
+    ) -> ResultE<LoginAttempt>;
+    async fn lock(&self, key: &String, until: &DateTime<Utc>) -> ResultE<()>;
+    async fn remove(&self, key: &String) -> ResultE<()>;
+    async fn get_locked(&self, at: &DateTime<Utc>) -> ResultE<Vec<LoginAttempt>>;
+}
+
+#[derive(Clone, Debug)]
+pub struct LoginAttemptsRepo {
+    client: Client,
+}
+
+impl LoginAttemptsRepo {
+    pub fn new(conf: &Config) -> LoginAttemptsRepo {
+        LoginAttemptsRepo {
+            client: Client::new(conf.aws_config()),
+        }
+    }
+}
+
+#[async_trait]
+impl LoginAttemptRepository for LoginAttemptsRepo {
+    async fn get(&self, key: &String) -> ResultE<Option<LoginAttempt>> {
+        let request = self
+            .client
+            .get_item()
+            .table_name(LOGIN_ATTEMPTS_TABLE_NAME.clone())
+            .key(LOGIN_ATTEMPT_FIELD_NAME_PK, AttributeValue::S(key.clone()));
+
+        match request.send().await {
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(LoginAttemptDynamoDBError(e.to_string()).into())
+            }
+            Ok(data) => match data.item() {
+                None => Ok(None),
+                Some(doc) => {
+                    let mut attempt = LoginAttempt::new();
+                    mapping_from_doc_to_login_attempt(doc, &mut attempt);
+                    Ok(Some(attempt))
+                }
+            },
+        }
+    }
+
+    async fn add_failure(
+        &self,
+        key: &String,
+        at: &DateTime<Utc>,
+        expires_at: &DateTime<Utc>,
+    ) -> ResultE<LoginAttempt> {
+        let request = self
+            .client
+            .update_item()
+            .table_name(LOGIN_ATTEMPTS_TABLE_NAME.clone())
+            .key(LOGIN_ATTEMPT_FIELD_NAME_PK, AttributeValue::S(key.clone()))
+            .update_expression("ADD #failures :one SET #last = :last, #ttl = :ttl")
+            .expression_attribute_names("#failures", FAILURES_FIELD_NAME)
+            .expression_attribute_names("#last", LAST_FAILURE_FIELD_NAME)
+            .expression_attribute_names("#ttl", SESSION_TTL_FIELD_NAME)
+            .expression_attribute_values(":one", AttributeValue::N("1".to_string()))
+            .expression_attribute_values(":last", AttributeValue::S(iso8601(at)))
+            .expression_attribute_values(
+                ":ttl",
+                AttributeValue::N(expires_at.timestamp().to_string()),
+            )
+            .return_values(ReturnValue::AllNew);
+
+        match request.send().await {
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(LoginAttemptDynamoDBError(e.to_string()).into())
+            }
+            Ok(data) => {
+                let mut attempt = LoginAttempt::new();
+                attempt.set_key(key);
+                if let Some(doc) = data.attributes() {
+                    mapping_from_doc_to_login_attempt(doc, &mut attempt);
+                }
+                Ok(attempt)
+            }
+        }
+    }
+
+    async fn lock(&self, key: &String, until: &DateTime<Utc>) -> ResultE<()> {
+        // the row must outlive the lock, otherwise dynamodb could purge it too early
+        let request = self
+            .client
+            .update_item()
+            .table_name(LOGIN_ATTEMPTS_TABLE_NAME.clone())
+            .key(LOGIN_ATTEMPT_FIELD_NAME_PK, AttributeValue::S(key.clone()))
+            .update_expression("SET #locked = :until, #ttl = :until")
+            .expression_attribute_names("#locked", LOCKED_UNTIL_FIELD_NAME)
+            .expression_attribute_names("#ttl", SESSION_TTL_FIELD_NAME)
+            .expression_attribute_values(":until", AttributeValue::N(until.timestamp().to_string()));
+
+        match request.send().await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(LoginAttemptDynamoDBError(e.to_string()).into())
+            }
+        }
+    }
+
+    async fn remove(&self, key: &String) -> ResultE<()> {
+        let request = self
+            .client
+            .delete_item()
+            .table_name(LOGIN_ATTEMPTS_TABLE_NAME.clone())
+            .key(LOGIN_ATTEMPT_FIELD_NAME_PK, AttributeValue::S(key.clone()));
+
+        match request.send().await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(LoginAttemptDynamoDBError(e.to_string()).into())
+            }
+        }
+    }
+
+    async fn get_locked(&self, at: &DateTime<Utc>) -> ResultE<Vec<LoginAttempt>> {
+        let mut queried = Vec::new();
+        let mut last_key = None;
+        loop {
+            let request = self
+                .client
+                .scan()
+                .table_name(LOGIN_ATTEMPTS_TABLE_NAME.clone())
+                .filter_expression("#locked > :now")
+                .expression_attribute_names("#locked", LOCKED_UNTIL_FIELD_NAME)
+                .expression_attribute_values(":now", AttributeValue::N(at.timestamp().to_string()))
+                .set_exclusive_start_key(last_key);
+
+            match request.send().await {
+                Err(e) => {
+                    let mssag = format!(
+                        "Error at [{}] - {} ",
+                        Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                        e
+                    );
+                    log::error!("{}", mssag);
+                    return Err(LoginAttemptDynamoDBError(e.to_string()).into());
+                }
+                Ok(data) => {
+                    for doc in data.items() {
+                        let mut attempt = LoginAttempt::new();
+                        mapping_from_doc_to_login_attempt(doc, &mut attempt);
+                        queried.push(attempt);
+                    }
+                    last_key = data.last_evaluated_key().cloned();
+                    if last_key.is_none() {
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(queried)
+    }
+}
+
+fn mapping_from_doc_to_login_attempt(
+    doc: &HashMap<String, AttributeValue>,
+    attempt: &mut LoginAttempt,
+) {
+    if let Some(key) = doc.get(LOGIN_ATTEMPT_FIELD_NAME_PK) {
+        attempt.set_key(key.as_s().unwrap());
+    }
+    if let Some(failures) = doc.get(FAILURES_FIELD_NAME) {
+        attempt.set_failures(failures.as_n().unwrap().parse::<u32>().unwrap_or(0));
+    }
+    if let Some(last) = doc.get(LAST_FAILURE_FIELD_NAME) {
+        attempt.set_last_failure(&from_iso8601(last.as_s().unwrap()));
+    }
+    if let Some(locked) = doc.get(LOCKED_UNTIL_FIELD_NAME) {
+        let secs = locked.as_n().unwrap().parse::<i64>().unwrap_or(0);
+        attempt.set_locked_until(&Utc.timestamp_opt(secs, 0).single());
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use aws_sdk_dynamodb::{types::AttributeValue, Client};
+use chrono::Local;
+use lib_config::config::Config;
+use lib_config::timing::{from_iso8601, iso8601};
+
+use crate::errors::mfa::{MfaAlreadyEnabledError, MfaDynamoDBError};
+use crate::models::mfa::MfaCredential;
+
+use super::schema_sessions::MFA_TABLE_NAME;
+use super::schema_user::USERID_FIELD_NAME_PK;
+
+static SECRET_FIELD_NAME: &str = "secret";
+static ENABLED_FIELD_NAME: &str = "enabled";
+static RECOVERY_CODES_FIELD_NAME: &str = "recoveryCodes";
+static CREATIONTIME_FIELD_NAME: &str = "creationTime";
+// last time step accepted, a code can't be used twice
+static LAST_STEP_FIELD_NAME: &str = "lastStep";
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+#[async_trait]
+pub trait MfaRepository {
+    // replaces a pending enrollment, never an enabled one
+    async fn add(&self, credential: &MfaCredential) -> ResultE<()>;
+    async fn get(&self, user_id: &String) -> ResultE<Option<MfaCredential>>;
+    // true only when this call moved the credential from pending to enabled
+    async fn enable(&self, user_id: &String) -> ResultE<bool>;
+    async fn remove(&self, user_id: &String) -> ResultE<()>;
+    // true only when `step` is newer than any accepted before
+    async fn use_step(&self, user_id: &String, step: i64) -> ResultE<bool>;
+    // true only when the recovery code was still there and this call spent it
+    async fn use_recovery_code(&self, user_id: &String, code_hash: &String) -> ResultE<bool>;
+}
+
+#[derive(Clone, Debug)]
+pub struct MfaRepo {
+    client: Client,
+}
+
+impl MfaRepo {
+    pub fn new(conf: &Config) -> MfaRepo {
+        MfaRepo {
+            client: Client::new(conf.aws_config()),
+        }
+    }
+
+    async fn conditional_update(
+        &self,
+        request: aws_sdk_dynamodb::operation::update_item::builders::UpdateItemFluentBuilder,
+    ) -> ResultE<bool> {
+        match request.send().await {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                let service_error = e.into_service_error();
+                if service_error.is_conditional_check_failed_exception() {
+                    return Ok(false);
+                }
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    service_error
+                );
+                log::error!("{}", mssag);
+                Err(MfaDynamoDBError(service_error.to_string()).into())
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl MfaRepository for MfaRepo {
+    async fn add(&self, credential: &MfaCredential) -> ResultE<()> {
+        let mut request = self
+            .client
+            .put_item()
+            .table_name(MFA_TABLE_NAME.clone())
+            .item(USERID_FIELD_NAME_PK, AttributeValue::S(credential.user_id().clone()))
+            .item(SECRET_FIELD_NAME, AttributeValue::S(credential.secret().clone()))
+            .item(ENABLED_FIELD_NAME, AttributeValue::Bool(credential.enabled()))
+            .item(
+                CREATIONTIME_FIELD_NAME,
+                AttributeValue::S(iso8601(credential.creation_time())),
+            )
+            .condition_expression("attribute_not_exists(#pk) OR #enabled = :pending")
+            .expression_attribute_names("#pk", USERID_FIELD_NAME_PK)
+            .expression_attribute_names("#enabled", ENABLED_FIELD_NAME)
+            .expression_attribute_values(":pending", AttributeValue::Bool(false));
+        // dynamodb refuses empty sets
+        if !credential.recovery_codes().is_empty() {
+            request = request.item(
+                RECOVERY_CODES_FIELD_NAME,
+                AttributeValue::Ss(credential.recovery_codes().clone()),
+            );
+        }
+
+        match request.send().await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let service_error = e.into_service_error();
+                if service_error.is_conditional_check_failed_exception() {
+                    return Err(MfaAlreadyEnabledError(credential.user_id().clone()).into());
+                }
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    service_error
+                );
+                log::error!("{}", mssag);
+                Err(MfaDynamoDBError(service_error.to_string()).into())
+            }
+        }
+    }
+
+    async fn get(&self, user_id: &String) -> ResultE<Option<MfaCredential>> {
+        let request = self
+            .client
+            .get_item()
+            .table_name(MFA_TABLE_NAME.clone())
+            .key(USERID_FIELD_NAME_PK, AttributeValue::S(user_id.clone()));
+
+        match request.send().await {
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(MfaDynamoDBError(e.to_string()).into())
+            }
+            Ok(data) => match data.item() {
+                None => Ok(None),
+                Some(doc) => {
+                    let mut credential = MfaCredential::new();
+                    mapping_from_doc_to_mfa(doc, &mut credential);
+                    Ok(Some(credential))
+                }
+            },
+        }
+    }
+
+    async fn enable(&self, user_id: &String) -> ResultE<bool> {
+        let request = self
+            .client
+            .update_item()
+            .table_name(MFA_TABLE_NAME.clone())
+            .key(USERID_FIELD_NAME_PK, AttributeValue::S(user_id.clone()))
+            .update_expression("SET #enabled = :enabled")
+            .condition_expression("attribute_exists(#pk) AND #enabled = :pending")
+            .expression_attribute_names("#pk", USERID_FIELD_NAME_PK)
+            .expression_attribute_names("#enabled", ENABLED_FIELD_NAME)
+            .expression_attribute_values(":enabled", AttributeValue::Bool(true))
+            .expression_attribute_values(":pending", AttributeValue::Bool(false));
+
+        self.conditional_update(request).await
+    }
+
+    async fn remove(&self, user_id: &String) -> ResultE<()> {
+        let request = self
+            .client
+            .delete_item()
+            .table_name(MFA_TABLE_NAME.clone())
+            .key(USERID_FIELD_NAME_PK, AttributeValue::S(user_id.clone()));
+
+        match request.send().await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(MfaDynamoDBError(e.to_string()).into())
+            }
+        }
+    }
+
+    async fn use_step(&self, user_id: &String, step: i64) -> ResultE<bool> {
+        let request = self
+            .client
+            .update_item()
+            .table_name(MFA_TABLE_NAME.clone())
+            .key(USERID_FIELD_NAME_PK, AttributeValue::S(user_id.clone()))
+            .update_expression("SET #step = :step")
+            .condition_expression(
+                "attribute_exists(#pk) AND (attribute_not_exists(#step) OR #step < :step)",
+            )
+            .expression_attribute_names("#pk", USERID_FIELD_NAME_PK)
+            .expression_attribute_names("#step", LAST_STEP_FIELD_NAME)
+            .expression_attribute_values(":step", AttributeValue::N(step.to_string()));
+
+        self.conditional_update(request).await
+    }
+
+    async fn use_recovery_code(&self, user_id: &String, code_hash: &String) -> ResultE<bool> {
+        let request = self
+            .client
+            .update_item()
+            .table_name(MFA_TABLE_NAME.clone())
+            .key(USERID_FIELD_NAME_PK, AttributeValue::S(user_id.clone()))
+            .update_expression("DELETE #codes :codes")
+            .condition_expression("contains(#codes, :code)")
+            .expression_attribute_names("#codes", RECOVERY_CODES_FIELD_NAME)
+            .expression_attribute_values(":codes", AttributeValue::Ss(vec![code_hash.clone()]))
+            .expression_attribute_values(":code", AttributeValue::S(code_hash.clone()));
+
+        self.conditional_update(request).await
+    }
+}
+
+fn mapping_from_doc_to_mfa(doc: &HashMap<String, AttributeValue>, credential: &mut MfaCredential) {
+    if let Some(user_id) = doc.get(USERID_FIELD_NAME_PK) {
+        credential.set_user_id(user_id.as_s().unwrap());
+    }
+    if let Some(secret) = doc.get(SECRET_FIELD_NAME) {
+        credential.set_secret(secret.as_s().unwrap());
+    }
+    if let Some(enabled) = doc.get(ENABLED_FIELD_NAME) {
+        credential.set_enabled(*enabled.as_bool().unwrap());
+    }
+    if let Some(codes) = doc.get(RECOVERY_CODES_FIELD_NAME) {
+        credential.set_recovery_codes(codes.as_ss().unwrap());
+    }
+    if let Some(creation_time) = doc.get(CREATIONTIME_FIELD_NAME) {
+        credential.set_creation_time(&from_iso8601(creation_time.as_s().unwrap()));
+    }
+}
@@ -1,2 +1,12 @@
+pub mod api_keys;
+pub mod audit;
+pub mod devices;
+pub mod identities;
+pub mod jwt_keys;
+pub mod login_attempts;
+pub mod mfa;
+pub mod one_time_tokens;
+pub mod schema_sessions;
 pub mod schema_user;
+pub mod sessions;
 pub mod users;
@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use aws_sdk_dynamodb::{
+    types::{AttributeValue, Select},
+    Client,
+};
+use chrono::Local;
+use lib_config::config::Config;
+use lib_config::timing::{from_iso8601, iso8601};
+
+use crate::errors::one_time_tokens::OneTimeTokenDynamoDBError;
+use crate::models::one_time_token::{OneTimeToken, OneTimeTokenPurpose};
+
+use super::schema_sessions::{
+    ONE_TIME_TOKENS_TABLE_NAME, ONE_TIME_TOKENS_USER_INDEX, ONE_TIME_TOKEN_FIELD_NAME_PK,
+    SESSION_TTL_FIELD_NAME,
+};
+use super::schema_user::USERID_FIELD_NAME_PK;
+
+static PURPOSE_FIELD_NAME: &str = "purpose";
+static EMAIL_FIELD_NAME: &str = "email";
+static CREATIONTIME_FIELD_NAME: &str = "creationTime";
+static EXPIRES_AT_FIELD_NAME: &str = "expiresAt";
+static USED_FIELD_NAME: &str = "used";
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+#[async_trait]
+pub trait OneTimeTokenRepository {
+    async fn add(&self, token: &OneTimeToken) -> ResultE<()>;
+    async fn get(&self, token_hash: &String) -> ResultE<Option<OneTimeToken>>;
+    async fn get_by_user(&self, user_id: &String) -> ResultE<Vec<OneTimeToken>>;
+    // true only when this call spent the token
+    async fn mark_used(&self, token_hash: &String) -> ResultE<bool>;
+}
+
+#[derive(Clone, Debug)]
+pub struct OneTimeTokensRepo {
+    client: Client,
+}
+
+impl OneTimeTokensRepo {
+    pub fn new(conf: &Config) -> OneTimeTokensRepo {
+        OneTimeTokensRepo {
+            client: Client::new(conf.aws_config()),
+        }
+    }
+}
+
+#[async_trait]
+impl OneTimeTokenRepository for OneTimeTokensRepo {
+    async fn add(&self, token: &OneTimeToken) -> ResultE<()> {
+        let request = self
+            .client
+            .put_item()
+            .table_name(ONE_TIME_TOKENS_TABLE_NAME.clone())
+            .item(
+                ONE_TIME_TOKEN_FIELD_NAME_PK,
+                AttributeValue::S(token.token_hash().clone()),
+            )
+            .item(USERID_FIELD_NAME_PK, AttributeValue::S(token.user_id().clone()))
+            .item(
+                PURPOSE_FIELD_NAME,
+                AttributeValue::S(token.purpose().to_string()),
+            )
+            .item(EMAIL_FIELD_NAME, AttributeValue::S(token.email().clone()))
+            .item(
+                CREATIONTIME_FIELD_NAME,
+                AttributeValue::S(iso8601(token.creation_time())),
+            )
+            .item(
+                EXPIRES_AT_FIELD_NAME,
+                AttributeValue::S(iso8601(token.expires_at())),
+            )
+            .item(
+                SESSION_TTL_FIELD_NAME,
+                AttributeValue::N(token.expires_at().timestamp().to_string()),
+            )
+            .item(USED_FIELD_NAME, AttributeValue::Bool(token.used()));
+
+        match request.send().await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(OneTimeTokenDynamoDBError(e.to_string()).into())
+            }
+        }
+    }
+
+    async fn get(&self, token_hash: &String) -> ResultE<Option<OneTimeToken>> {
+        let request = self
+            .client
+            .get_item()
+            .table_name(ONE_TIME_TOKENS_TABLE_NAME.clone())
+            .key(
+                ONE_TIME_TOKEN_FIELD_NAME_PK,
+                AttributeValue::S(token_hash.clone()),
+            );
+
+        match request.send().await {
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(OneTimeTokenDynamoDBError(e.to_string()).into())
+            }
+            Ok(data) => match data.item() {
+                None => Ok(None),
+                Some(doc) => {
+                    let mut token = OneTimeToken::new();
+                    mapping_from_doc_to_one_time_token(doc, &mut token);
+                    Ok(Some(token))
+                }
+            },
+        }
+    }
+
+    async fn get_by_user(&self, user_id: &String) -> ResultE<Vec<OneTimeToken>> {
+        let mut queried = Vec::new();
+        let filter = format!("{} = :value", USERID_FIELD_NAME_PK);
+
+        let request = self
+            .client
+            .query()
+            .table_name(ONE_TIME_TOKENS_TABLE_NAME.clone())
+            .index_name(ONE_TIME_TOKENS_USER_INDEX)
+            .key_condition_expression(filter)
+            .expression_attribute_values(":value".to_string(), AttributeValue::S(user_id.clone()))
+            .select(Select::AllProjectedAttributes);
+
+        match request.send().await {
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                return Err(OneTimeTokenDynamoDBError(e.to_string()).into());
+            }
+            Ok(data) => {
+                for doc in data.items() {
+                    let mut token = OneTimeToken::new();
+                    mapping_from_doc_to_one_time_token(doc, &mut token);
+                    queried.push(token);
+                }
+            }
+        }
+        Ok(queried)
+    }
+
+    async fn mark_used(&self, token_hash: &String) -> ResultE<bool> {
+        let request = self
+            .client
+            .update_item()
+            .table_name(ONE_TIME_TOKENS_TABLE_NAME.clone())
+            .key(
+                ONE_TIME_TOKEN_FIELD_NAME_PK,
+                AttributeValue::S(token_hash.clone()),
+            )
+            .update_expression("SET #used = :used")
+            .condition_expression("attribute_exists(#pk) AND #used = :unused")
+            .expression_attribute_names("#used", USED_FIELD_NAME)
+            .expression_attribute_names("#pk", ONE_TIME_TOKEN_FIELD_NAME_PK)
+            .expression_attribute_values(":used", AttributeValue::Bool(true))
+            .expression_attribute_values(":unused", AttributeValue::Bool(false));
+
+        match request.send().await {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                let service_error = e.into_service_error();
+                if service_error.is_conditional_check_failed_exception() {
+                    return Ok(false);
+                }
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    service_error
+                );
+                log::error!("{}", mssag);
+                Err(OneTimeTokenDynamoDBError(service_error.to_string()).into())
+            }
+        }
+    }
+}
+
+fn mapping_from_doc_to_one_time_token(
+    doc: &HashMap<String, AttributeValue>,
+    token: &mut OneTimeToken,
+) {
+    if let Some(hash) = doc.get(ONE_TIME_TOKEN_FIELD_NAME_PK) {
+        token.set_token_hash(hash.as_s().unwrap());
+    }
+    if let Some(user_id) = doc.get(USERID_FIELD_NAME_PK) {
+        token.set_user_id(user_id.as_s().unwrap());
+    }
+    if let Some(purpose) = doc.get(PURPOSE_FIELD_NAME) {
+        if let Ok(value) = OneTimeTokenPurpose::from_str(purpose.as_s().unwrap()) {
+            token.set_purpose(&value);
+        }
+    }
+    if let Some(email) = doc.get(EMAIL_FIELD_NAME) {
+        token.set_email(email.as_s().unwrap());
+    }
+    if let Some(creation_time) = doc.get(CREATIONTIME_FIELD_NAME) {
+        token.set_creation_time(&from_iso8601(creation_time.as_s().unwrap()));
+    }
+    if let Some(expires_at) = doc.get(EXPIRES_AT_FIELD_NAME) {
+        token.set_expires_at(&from_iso8601(expires_at.as_s().unwrap()));
+    }
+    if let Some(used) = doc.get(USED_FIELD_NAME) {
+        token.set_used(*used.as_bool().unwrap());
+    }
+}
@@ -0,0 +1,771 @@
+use crate::SERVICE;
+use async_trait::async_trait;
+use aws_sdk_dynamodb::types::{
+    AttributeDefinition, BillingMode, GlobalSecondaryIndex, KeySchemaElement, KeyType, Projection,
+    ProjectionType, ScalarAttributeType, Tag, TimeToLiveSpecification,
+};
+use lib_config::{
+    config::Config,
+    result::ResultE,
+    schema::{Schema, schema_exists, wait_until_schema_is_active},
+    constants::{
+        VALUE_PROJECT, API_DOMAIN, TAG_PROJECT, TAG_SERVICE, TAG_ENVIRONMENT
+    }
+};
+
+use super::schema_user::USERID_FIELD_NAME_PK;
+
+lazy_static! {
+    pub static ref REFRESH_TOKENS_TABLE_NAME: String = format!("{}_{}_{}_refresh_tokens", VALUE_PROJECT, API_DOMAIN, SERVICE);
+    pub static ref REVOKED_TOKENS_TABLE_NAME: String = format!("{}_{}_{}_revoked_tokens", VALUE_PROJECT, API_DOMAIN, SERVICE);
+    pub static ref JWT_KEYS_TABLE_NAME: String = format!("{}_{}_{}_jwt_keys", VALUE_PROJECT, API_DOMAIN, SERVICE);
+    pub static ref WALLET_NONCES_TABLE_NAME: String = format!("{}_{}_{}_wallet_nonces", VALUE_PROJECT, API_DOMAIN, SERVICE);
+    pub static ref DEVICES_TABLE_NAME: String = format!("{}_{}_{}_devices", VALUE_PROJECT, API_DOMAIN, SERVICE);
+    pub static ref DEVICE_CHALLENGES_TABLE_NAME: String = format!("{}_{}_{}_device_challenges", VALUE_PROJECT, API_DOMAIN, SERVICE);
+    pub static ref ONE_TIME_TOKENS_TABLE_NAME: String = format!("{}_{}_{}_one_time_tokens", VALUE_PROJECT, API_DOMAIN, SERVICE);
+    pub static ref LOGIN_ATTEMPTS_TABLE_NAME: String = format!("{}_{}_{}_login_attempts", VALUE_PROJECT, API_DOMAIN, SERVICE);
+    pub static ref MFA_TABLE_NAME: String = format!("{}_{}_{}_mfa", VALUE_PROJECT, API_DOMAIN, SERVICE);
+    pub static ref API_KEYS_TABLE_NAME: String = format!("{}_{}_{}_api_keys", VALUE_PROJECT, API_DOMAIN, SERVICE);
+    pub static ref AUDIT_TABLE_NAME: String = format!("{}_{}_{}_audit_log", VALUE_PROJECT, API_DOMAIN, SERVICE);
+}
+pub const REFRESH_TOKEN_FIELD_NAME_PK: &str = "tokenHash";
+pub const REFRESH_TOKENS_USER_INDEX: &str = "index_user";
+pub const REVOKED_TOKEN_JTI_FIELD_NAME_PK: &str = "jti";
+pub const JWT_KEY_KID_FIELD_NAME_PK: &str = "kid";
+pub const WALLET_NONCE_FIELD_NAME_PK: &str = "nonce";
+pub const DEVICE_FIELD_NAME_PK: &str = "deviceId";
+pub const DEVICES_USER_INDEX: &str = "index_user";
+pub const DEVICE_CHALLENGE_FIELD_NAME_PK: &str = "challenge";
+pub const ONE_TIME_TOKEN_FIELD_NAME_PK: &str = "tokenHash";
+pub const ONE_TIME_TOKENS_USER_INDEX: &str = "index_user";
+pub const LOGIN_ATTEMPT_FIELD_NAME_PK: &str = "attemptKey";
+pub const API_KEY_FIELD_NAME_PK: &str = "keyHash";
+pub const API_KEYS_USER_INDEX: &str = "index_user";
+pub const AUDIT_FIELD_NAME_PK: &str = "auditID";
+pub const AUDIT_ACTOR_FIELD_NAME: &str = "actorID";
+pub const AUDIT_TARGET_FIELD_NAME: &str = "targetID";
+pub const AUDIT_ACTION_FIELD_NAME: &str = "auditAction";
+pub const AUDIT_MONTH_FIELD_NAME: &str = "auditMonth";
+pub const AUDIT_CREATION_TIME_FIELD_NAME: &str = "creationTime";
+pub const AUDIT_ACTOR_INDEX: &str = "index_actor";
+pub const AUDIT_TARGET_INDEX: &str = "index_target";
+pub const AUDIT_ACTION_INDEX: &str = "index_action";
+pub const AUDIT_MONTH_INDEX: &str = "index_month";
+// dynamodb purges the rows by itself once this epoch (seconds) is reached
+pub const SESSION_TTL_FIELD_NAME: &str = "ttl";
+
+async fn enable_ttl(config: &Config, table_name: &str) -> ResultE<()> {
+    let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+    client
+        .update_time_to_live()
+        .table_name(table_name)
+        .time_to_live_specification(
+            TimeToLiveSpecification::builder()
+                .attribute_name(SESSION_TTL_FIELD_NAME)
+                .enabled(true)
+                .build()
+                .unwrap(),
+        )
+        .send()
+        .await?;
+    Ok(())
+}
+
+fn tags(config: &Config) -> Vec<Tag> {
+    vec![
+        Tag::builder()
+            .set_key(Some(TAG_ENVIRONMENT.to_string()))
+            .set_value(Some(config.env_vars().environment().unwrap()))
+            .build()
+            .unwrap(),
+        Tag::builder()
+            .set_key(Some(TAG_PROJECT.to_string()))
+            .set_value(Some(VALUE_PROJECT.to_string()))
+            .build()
+            .unwrap(),
+        Tag::builder()
+            .set_key(Some(TAG_SERVICE.to_string()))
+            .set_value(Some(API_DOMAIN.to_string()))
+            .build()
+            .unwrap(),
+    ]
+}
+
+pub struct RefreshTokenSchema;
+#[async_trait]
+impl Schema for RefreshTokenSchema {
+    async fn create_schema(config: &Config) -> ResultE<()> {
+
+        let exist = schema_exists(config, REFRESH_TOKENS_TABLE_NAME.as_str()).await?;
+        if exist{
+            return Ok(())
+        }
+
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+
+        let token_ad = AttributeDefinition::builder()
+            .attribute_name(REFRESH_TOKEN_FIELD_NAME_PK)
+            .attribute_type(ScalarAttributeType::S)
+            .build()
+            .unwrap();
+        let user_id_ad = AttributeDefinition::builder()
+            .attribute_name(USERID_FIELD_NAME_PK)
+            .attribute_type(ScalarAttributeType::S)
+            .build()
+            .unwrap();
+        let pk = KeySchemaElement::builder()
+            .attribute_name(REFRESH_TOKEN_FIELD_NAME_PK)
+            .key_type(KeyType::Hash)
+            .build()
+            .unwrap();
+        let second_index_by_user = GlobalSecondaryIndex::builder()
+            .index_name(REFRESH_TOKENS_USER_INDEX)
+            .key_schema(
+                KeySchemaElement::builder()
+                    .attribute_name(USERID_FIELD_NAME_PK)
+                    .key_type(KeyType::Hash)
+                    .build()
+                    .unwrap(),
+            )
+            .projection(
+                Projection::builder()
+                    .projection_type(ProjectionType::All)
+                    .build(),
+            )
+            .build()
+            .unwrap();
+
+        client
+            .create_table()
+            .table_name(REFRESH_TOKENS_TABLE_NAME.clone())
+            .key_schema(pk)
+            .global_secondary_indexes(second_index_by_user)
+            .attribute_definitions(token_ad)
+            .attribute_definitions(user_id_ad)
+            .billing_mode(BillingMode::PayPerRequest)
+            .set_tags(Some(tags(config)))
+            .send()
+            .await?;
+
+        wait_until_schema_is_active(config, REFRESH_TOKENS_TABLE_NAME.as_str()).await?;
+        enable_ttl(config, REFRESH_TOKENS_TABLE_NAME.as_str()).await?;
+        Ok(())
+    }
+
+    async fn delete_schema(config: &Config) -> ResultE<()> {
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+        client
+            .delete_table()
+            .table_name(REFRESH_TOKENS_TABLE_NAME.clone())
+            .send()
+            .await?;
+
+        Ok(())
+    }
+}
+
+pub struct RevokedTokenSchema;
+#[async_trait]
+impl Schema for RevokedTokenSchema {
+    async fn create_schema(config: &Config) -> ResultE<()> {
+
+        let exist = schema_exists(config, REVOKED_TOKENS_TABLE_NAME.as_str()).await?;
+        if exist{
+            return Ok(())
+        }
+
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+
+        let jti_ad = AttributeDefinition::builder()
+            .attribute_name(REVOKED_TOKEN_JTI_FIELD_NAME_PK)
+            .attribute_type(ScalarAttributeType::S)
+            .build()
+            .unwrap();
+        let pk = KeySchemaElement::builder()
+            .attribute_name(REVOKED_TOKEN_JTI_FIELD_NAME_PK)
+            .key_type(KeyType::Hash)
+            .build()
+            .unwrap();
+
+        client
+            .create_table()
+            .table_name(REVOKED_TOKENS_TABLE_NAME.clone())
+            .key_schema(pk)
+            .attribute_definitions(jti_ad)
+            .billing_mode(BillingMode::PayPerRequest)
+            .set_tags(Some(tags(config)))
+            .send()
+            .await?;
+
+        wait_until_schema_is_active(config, REVOKED_TOKENS_TABLE_NAME.as_str()).await?;
+        enable_ttl(config, REVOKED_TOKENS_TABLE_NAME.as_str()).await?;
+        Ok(())
+    }
+
+    async fn delete_schema(config: &Config) -> ResultE<()> {
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+        client
+            .delete_table()
+            .table_name(REVOKED_TOKENS_TABLE_NAME.clone())
+            .send()
+            .await?;
+
+        Ok(())
+    }
+}
+
+pub struct JwtKeySchema;
+#[async_trait]
+impl Schema for JwtKeySchema {
+    async fn create_schema(config: &Config) -> ResultE<()> {
+
+        let exist = schema_exists(config, JWT_KEYS_TABLE_NAME.as_str()).await?;
+        if exist{
+            return Ok(())
+        }
+
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+
+        let kid_ad = AttributeDefinition::builder()
+            .attribute_name(JWT_KEY_KID_FIELD_NAME_PK)
+            .attribute_type(ScalarAttributeType::S)
+            .build()
+            .unwrap();
+        let pk = KeySchemaElement::builder()
+            .attribute_name(JWT_KEY_KID_FIELD_NAME_PK)
+            .key_type(KeyType::Hash)
+            .build()
+            .unwrap();
+
+        client
+            .create_table()
+            .table_name(JWT_KEYS_TABLE_NAME.clone())
+            .key_schema(pk)
+            .attribute_definitions(kid_ad)
+            .billing_mode(BillingMode::PayPerRequest)
+            .set_tags(Some(tags(config)))
+            .send()
+            .await?;
+
+        wait_until_schema_is_active(config, JWT_KEYS_TABLE_NAME.as_str()).await?;
+        Ok(())
+    }
+
+    async fn delete_schema(config: &Config) -> ResultE<()> {
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+        client
+            .delete_table()
+            .table_name(JWT_KEYS_TABLE_NAME.clone())
+            .send()
+            .await?;
+
+        Ok(())
+    }
+}
+
+pub struct OneTimeTokenSchema;
+#[async_trait]
+impl Schema for OneTimeTokenSchema {
+    async fn create_schema(config: &Config) -> ResultE<()> {
+
+        let exist = schema_exists(config, ONE_TIME_TOKENS_TABLE_NAME.as_str()).await?;
+        if exist{
+            return Ok(())
+        }
+
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+
+        let token_ad = AttributeDefinition::builder()
+            .attribute_name(ONE_TIME_TOKEN_FIELD_NAME_PK)
+            .attribute_type(ScalarAttributeType::S)
+            .build()
+            .unwrap();
+        let user_id_ad = AttributeDefinition::builder()
+            .attribute_name(USERID_FIELD_NAME_PK)
+            .attribute_type(ScalarAttributeType::S)
+            .build()
+            .unwrap();
+        let pk = KeySchemaElement::builder()
+            .attribute_name(ONE_TIME_TOKEN_FIELD_NAME_PK)
+            .key_type(KeyType::Hash)
+            .build()
+            .unwrap();
+        let second_index_by_user = GlobalSecondaryIndex::builder()
+            .index_name(ONE_TIME_TOKENS_USER_INDEX)
+            .key_schema(
+                KeySchemaElement::builder()
+                    .attribute_name(USERID_FIELD_NAME_PK)
+                    .key_type(KeyType::Hash)
+                    .build()
+                    .unwrap(),
+            )
+            .projection(
+                Projection::builder()
+                    .projection_type(ProjectionType::All)
+                    .build(),
+            )
+            .build()
+            .unwrap();
+
+        client
+            .create_table()
+            .table_name(ONE_TIME_TOKENS_TABLE_NAME.clone())
+            .key_schema(pk)
+            .global_secondary_indexes(second_index_by_user)
+            .attribute_definitions(token_ad)
+            .attribute_definitions(user_id_ad)
+            .billing_mode(BillingMode::PayPerRequest)
+            .set_tags(Some(tags(config)))
+            .send()
+            .await?;
+
+        wait_until_schema_is_active(config, ONE_TIME_TOKENS_TABLE_NAME.as_str()).await?;
+        enable_ttl(config, ONE_TIME_TOKENS_TABLE_NAME.as_str()).await?;
+        Ok(())
+    }
+
+    async fn delete_schema(config: &Config) -> ResultE<()> {
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+        client
+            .delete_table()
+            .table_name(ONE_TIME_TOKENS_TABLE_NAME.clone())
+            .send()
+            .await?;
+
+        Ok(())
+    }
+}
+
+pub struct WalletNonceSchema;
+#[async_trait]
+impl Schema for WalletNonceSchema {
+    async fn create_schema(config: &Config) -> ResultE<()> {
+
+        let exist = schema_exists(config, WALLET_NONCES_TABLE_NAME.as_str()).await?;
+        if exist{
+            return Ok(())
+        }
+
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+
+        let nonce_ad = AttributeDefinition::builder()
+            .attribute_name(WALLET_NONCE_FIELD_NAME_PK)
+            .attribute_type(ScalarAttributeType::S)
+            .build()
+            .unwrap();
+        let pk = KeySchemaElement::builder()
+            .attribute_name(WALLET_NONCE_FIELD_NAME_PK)
+            .key_type(KeyType::Hash)
+            .build()
+            .unwrap();
+
+        client
+            .create_table()
+            .table_name(WALLET_NONCES_TABLE_NAME.clone())
+            .key_schema(pk)
+            .attribute_definitions(nonce_ad)
+            .billing_mode(BillingMode::PayPerRequest)
+            .set_tags(Some(tags(config)))
+            .send()
+            .await?;
+
+        wait_until_schema_is_active(config, WALLET_NONCES_TABLE_NAME.as_str()).await?;
+        enable_ttl(config, WALLET_NONCES_TABLE_NAME.as_str()).await?;
+        Ok(())
+    }
+
+    async fn delete_schema(config: &Config) -> ResultE<()> {
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+        client
+            .delete_table()
+            .table_name(WALLET_NONCES_TABLE_NAME.clone())
+            .send()
+            .await?;
+
+        Ok(())
+    }
+}
+
+pub struct DeviceCredentialSchema;
+#[async_trait]
+impl Schema for DeviceCredentialSchema {
+    async fn create_schema(config: &Config) -> ResultE<()> {
+
+        let exist = schema_exists(config, DEVICES_TABLE_NAME.as_str()).await?;
+        if exist{
+            return Ok(())
+        }
+
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+
+        let device_ad = AttributeDefinition::builder()
+            .attribute_name(DEVICE_FIELD_NAME_PK)
+            .attribute_type(ScalarAttributeType::S)
+            .build()
+            .unwrap();
+        let user_id_ad = AttributeDefinition::builder()
+            .attribute_name(USERID_FIELD_NAME_PK)
+            .attribute_type(ScalarAttributeType::S)
+            .build()
+            .unwrap();
+        let pk = KeySchemaElement::builder()
+            .attribute_name(DEVICE_FIELD_NAME_PK)
+            .key_type(KeyType::Hash)
+            .build()
+            .unwrap();
+        let second_index_by_user = GlobalSecondaryIndex::builder()
+            .index_name(DEVICES_USER_INDEX)
+            .key_schema(
+                KeySchemaElement::builder()
+                    .attribute_name(USERID_FIELD_NAME_PK)
+                    .key_type(KeyType::Hash)
+                    .build()
+                    .unwrap(),
+            )
+            .projection(
+                Projection::builder()
+                    .projection_type(ProjectionType::All)
+                    .build(),
+            )
+            .build()
+            .unwrap();
+
+        client
+            .create_table()
+            .table_name(DEVICES_TABLE_NAME.clone())
+            .key_schema(pk)
+            .global_secondary_indexes(second_index_by_user)
+            .attribute_definitions(device_ad)
+            .attribute_definitions(user_id_ad)
+            .billing_mode(BillingMode::PayPerRequest)
+            .set_tags(Some(tags(config)))
+            .send()
+            .await?;
+
+        wait_until_schema_is_active(config, DEVICES_TABLE_NAME.as_str()).await?;
+        Ok(())
+    }
+
+    async fn delete_schema(config: &Config) -> ResultE<()> {
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+        client
+            .delete_table()
+            .table_name(DEVICES_TABLE_NAME.clone())
+            .send()
+            .await?;
+
+        Ok(())
+    }
+}
+
+// one row per challenge handed out, so asking for a new one never spends a pending one
+pub struct DeviceChallengeSchema;
+#[async_trait]
+impl Schema for DeviceChallengeSchema {
+    async fn create_schema(config: &Config) -> ResultE<()> {
+
+        let exist = schema_exists(config, DEVICE_CHALLENGES_TABLE_NAME.as_str()).await?;
+        if exist{
+            return Ok(())
+        }
+
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+
+        let challenge_ad = AttributeDefinition::builder()
+            .attribute_name(DEVICE_CHALLENGE_FIELD_NAME_PK)
+            .attribute_type(ScalarAttributeType::S)
+            .build()
+            .unwrap();
+        let pk = KeySchemaElement::builder()
+            .attribute_name(DEVICE_CHALLENGE_FIELD_NAME_PK)
+            .key_type(KeyType::Hash)
+            .build()
+            .unwrap();
+
+        client
+            .create_table()
+            .table_name(DEVICE_CHALLENGES_TABLE_NAME.clone())
+            .key_schema(pk)
+            .attribute_definitions(challenge_ad)
+            .billing_mode(BillingMode::PayPerRequest)
+            .set_tags(Some(tags(config)))
+            .send()
+            .await?;
+
+        wait_until_schema_is_active(config, DEVICE_CHALLENGES_TABLE_NAME.as_str()).await?;
+        enable_ttl(config, DEVICE_CHALLENGES_TABLE_NAME.as_str()).await?;
+        Ok(())
+    }
+
+    async fn delete_schema(config: &Config) -> ResultE<()> {
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+        client
+            .delete_table()
+            .table_name(DEVICE_CHALLENGES_TABLE_NAME.clone())
+            .send()
+            .await?;
+
+        Ok(())
+    }
+}
+
+pub struct LoginAttemptSchema;
+#[async_trait]
+impl Schema for LoginAttemptSchema {
+    async fn create_schema(config: &Config) -> ResultE<()> {
+
+        let exist = schema_exists(config, LOGIN_ATTEMPTS_TABLE_NAME.as_str()).await?;
+        if exist{
+            return Ok(())
+        }
+
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+
+        let key_ad = AttributeDefinition::builder()
+            .attribute_name(LOGIN_ATTEMPT_FIELD_NAME_PK)
+            .attribute_type(ScalarAttributeType::S)
+            .build()
+            .unwrap();
+        let pk = KeySchemaElement::builder()
+            .attribute_name(LOGIN_ATTEMPT_FIELD_NAME_PK)
+            .key_type(KeyType::Hash)
+            .build()
+            .unwrap();
+
+        client
+            .create_table()
+            .table_name(LOGIN_ATTEMPTS_TABLE_NAME.clone())
+            .key_schema(pk)
+            .attribute_definitions(key_ad)
+            .billing_mode(BillingMode::PayPerRequest)
+            .set_tags(Some(tags(config)))
+            .send()
+            .await?;
+
+        wait_until_schema_is_active(config, LOGIN_ATTEMPTS_TABLE_NAME.as_str()).await?;
+        enable_ttl(config, LOGIN_ATTEMPTS_TABLE_NAME.as_str()).await?;
+        Ok(())
+    }
+
+    async fn delete_schema(config: &Config) -> ResultE<()> {
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+        client
+            .delete_table()
+            .table_name(LOGIN_ATTEMPTS_TABLE_NAME.clone())
+            .send()
+            .await?;
+
+        Ok(())
+    }
+}
+
+// one row per user, keyed by USERID_FIELD_NAME_PK
+pub struct MfaSchema;
+#[async_trait]
+impl Schema for MfaSchema {
+    async fn create_schema(config: &Config) -> ResultE<()> {
+
+        let exist = schema_exists(config, MFA_TABLE_NAME.as_str()).await?;
+        if exist{
+            return Ok(())
+        }
+
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+
+        let user_id_ad = AttributeDefinition::builder()
+            .attribute_name(USERID_FIELD_NAME_PK)
+            .attribute_type(ScalarAttributeType::S)
+            .build()
+            .unwrap();
+        let pk = KeySchemaElement::builder()
+            .attribute_name(USERID_FIELD_NAME_PK)
+            .key_type(KeyType::Hash)
+            .build()
+            .unwrap();
+
+        client
+            .create_table()
+            .table_name(MFA_TABLE_NAME.clone())
+            .key_schema(pk)
+            .attribute_definitions(user_id_ad)
+            .billing_mode(BillingMode::PayPerRequest)
+            .set_tags(Some(tags(config)))
+            .send()
+            .await?;
+
+        wait_until_schema_is_active(config, MFA_TABLE_NAME.as_str()).await?;
+        Ok(())
+    }
+
+    async fn delete_schema(config: &Config) -> ResultE<()> {
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+        client
+            .delete_table()
+            .table_name(MFA_TABLE_NAME.clone())
+            .send()
+            .await?;
+
+        Ok(())
+    }
+}
+
+pub struct ApiKeySchema;
+#[async_trait]
+impl Schema for ApiKeySchema {
+    async fn create_schema(config: &Config) -> ResultE<()> {
+
+        let exist = schema_exists(config, API_KEYS_TABLE_NAME.as_str()).await?;
+        if exist{
+            return Ok(())
+        }
+
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+
+        let key_ad = AttributeDefinition::builder()
+            .attribute_name(API_KEY_FIELD_NAME_PK)
+            .attribute_type(ScalarAttributeType::S)
+            .build()
+            .unwrap();
+        let user_id_ad = AttributeDefinition::builder()
+            .attribute_name(USERID_FIELD_NAME_PK)
+            .attribute_type(ScalarAttributeType::S)
+            .build()
+            .unwrap();
+        let pk = KeySchemaElement::builder()
+            .attribute_name(API_KEY_FIELD_NAME_PK)
+            .key_type(KeyType::Hash)
+            .build()
+            .unwrap();
+        let second_index_by_user = GlobalSecondaryIndex::builder()
+            .index_name(API_KEYS_USER_INDEX)
+            .key_schema(
+                KeySchemaElement::builder()
+                    .attribute_name(USERID_FIELD_NAME_PK)
+                    .key_type(KeyType::Hash)
+                    .build()
+                    .unwrap(),
+            )
+            .projection(
+                Projection::builder()
+                    .projection_type(ProjectionType::All)
+                    .build(),
+            )
+            .build()
+            .unwrap();
+
+        client
+            .create_table()
+            .table_name(API_KEYS_TABLE_NAME.clone())
+            .key_schema(pk)
+            .global_secondary_indexes(second_index_by_user)
+            .attribute_definitions(key_ad)
+            .attribute_definitions(user_id_ad)
+            .billing_mode(BillingMode::PayPerRequest)
+            .set_tags(Some(tags(config)))
+            .send()
+            .await?;
+
+        wait_until_schema_is_active(config, API_KEYS_TABLE_NAME.as_str()).await?;
+        Ok(())
+    }
+
+    async fn delete_schema(config: &Config) -> ResultE<()> {
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+        client
+            .delete_table()
+            .table_name(API_KEYS_TABLE_NAME.clone())
+            .send()
+            .await?;
+
+        Ok(())
+    }
+}
+
+// index on a partition field sorted by creation time
+fn audit_index(index_name: &str, partition: &str) -> GlobalSecondaryIndex {
+    GlobalSecondaryIndex::builder()
+        .index_name(index_name)
+        .key_schema(
+            KeySchemaElement::builder()
+                .attribute_name(partition)
+                .key_type(KeyType::Hash)
+                .build()
+                .unwrap(),
+        )
+        .key_schema(
+            KeySchemaElement::builder()
+                .attribute_name(AUDIT_CREATION_TIME_FIELD_NAME)
+                .key_type(KeyType::Range)
+                .build()
+                .unwrap(),
+        )
+        .projection(
+            Projection::builder()
+                .projection_type(ProjectionType::All)
+                .build(),
+        )
+        .build()
+        .unwrap()
+}
+
+pub struct AuditSchema;
+#[async_trait]
+impl Schema for AuditSchema {
+    async fn create_schema(config: &Config) -> ResultE<()> {
+
+        let exist = schema_exists(config, AUDIT_TABLE_NAME.as_str()).await?;
+        if exist{
+            return Ok(())
+        }
+
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+
+        let mut request = client
+            .create_table()
+            .table_name(AUDIT_TABLE_NAME.clone())
+            .key_schema(
+                KeySchemaElement::builder()
+                    .attribute_name(AUDIT_FIELD_NAME_PK)
+                    .key_type(KeyType::Hash)
+                    .build()
+                    .unwrap(),
+            )
+            .global_secondary_indexes(audit_index(AUDIT_ACTOR_INDEX, AUDIT_ACTOR_FIELD_NAME))
+            .global_secondary_indexes(audit_index(AUDIT_TARGET_INDEX, AUDIT_TARGET_FIELD_NAME))
+            .global_secondary_indexes(audit_index(AUDIT_ACTION_INDEX, AUDIT_ACTION_FIELD_NAME))
+            .global_secondary_indexes(audit_index(AUDIT_MONTH_INDEX, AUDIT_MONTH_FIELD_NAME))
+            .billing_mode(BillingMode::PayPerRequest)
+            .set_tags(Some(tags(config)));
+        for field in [
+            AUDIT_FIELD_NAME_PK,
+            AUDIT_ACTOR_FIELD_NAME,
+            AUDIT_TARGET_FIELD_NAME,
+            AUDIT_ACTION_FIELD_NAME,
+            AUDIT_MONTH_FIELD_NAME,
+            AUDIT_CREATION_TIME_FIELD_NAME,
+        ] {
+            request = request.attribute_definitions(
+                AttributeDefinition::builder()
+                    .attribute_name(field)
+                    .attribute_type(ScalarAttributeType::S)
+                    .build()
+                    .unwrap(),
+            );
+        }
+        request.send().await?;
+
+        wait_until_schema_is_active(config, AUDIT_TABLE_NAME.as_str()).await?;
+        Ok(())
+    }
+
+    async fn delete_schema(config: &Config) -> ResultE<()> {
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+        client
+            .delete_table()
+            .table_name(AUDIT_TABLE_NAME.clone())
+            .send()
+            .await?;
+
+        Ok(())
+    }
+}
@@ -1,9 +1,15 @@
 use crate::SERVICE;
+use super::schema_sessions::{
+    ApiKeySchema, AuditSchema, DeviceChallengeSchema, DeviceCredentialSchema, JwtKeySchema,
+    LoginAttemptSchema, MfaSchema, OneTimeTokenSchema, RefreshTokenSchema, RevokedTokenSchema,
+    WalletNonceSchema,
+};
 use async_trait::async_trait;
 use aws_sdk_dynamodb::types::{
-    builders::StreamSpecificationBuilder, AttributeDefinition, BillingMode, GlobalSecondaryIndex,
-    KeySchemaElement, KeyType, Projection, ProjectionType, ScalarAttributeType, StreamViewType,
-    Tag,
+    builders::StreamSpecificationBuilder, AttributeDefinition, BillingMode,
+    CreateGlobalSecondaryIndexAction, GlobalSecondaryIndex, GlobalSecondaryIndexUpdate,
+    KeySchemaElement, KeyType, LocalSecondaryIndex, Projection, ProjectionType,
+    ScalarAttributeType, StreamViewType, Tag,
 };
 use lib_config::{
     config::Config,
@@ -19,12 +25,19 @@ lazy_static! {
     pub static ref USERS_TABLE_NAME: String = format!("{}_{}_{}_users", VALUE_PROJECT, API_DOMAIN, SERVICE);
 }
 pub const USERID_FIELD_NAME_PK: &str = "userID";
+pub const USER_STATUS_FIELD_NAME: &str = "userStatus";
+pub const USER_CREATION_TIME_FIELD_NAME: &str = "creationTime";
+// status as partition, creation time as sort key: admin listings without walking the table
+pub const USER_STATUS_INDEX: &str = "index_status_creation";
 
 lazy_static! {
     pub static ref LOGIN_EMAIL_TABLE_NAME: String = format!("{}_{}_{}_login_emails", VALUE_PROJECT, API_DOMAIN, SERVICE);
 }
 pub const LOGIN_EMAIL_FIELD_NAME: &str = "email";
 pub const LOGIN_EMAIL_INDEX: &str = "index_email";
+// first character of the email, so a prefix can be looked up with begins_with on the sort key
+pub const LOGIN_EMAIL_INITIAL_FIELD_NAME: &str = "emailInitial";
+pub const LOGIN_EMAIL_PREFIX_INDEX: &str = "index_email_prefix";
 
 lazy_static! {
     pub static ref LOGIN_DEVICE_TABLE_NAME: String = format!("{}_{}_{}_login_devices", VALUE_PROJECT,API_DOMAIN, SERVICE);
@@ -38,26 +51,123 @@ lazy_static! {
 pub const LOGIN_WALLET_FIELD_NAME: &str = "wallet";
 pub const LOGIN_WALLET_INDEX: &str = "index_wallet";
 
+// one row per user and role, roles are a set in the users' table and sets can't be indexed
+lazy_static! {
+    pub static ref USER_ROLES_TABLE_NAME: String = format!("{}_{}_{}_user_roles", VALUE_PROJECT,API_DOMAIN, SERVICE);
+}
+pub const USER_ROLE_FIELD_NAME: &str = "userRole";
+pub const USER_ROLE_CREATION_INDEX: &str = "index_role_creation";
+
+// accounts of OpenID Connect providers, keyed by issuer and subject
+lazy_static! {
+    pub static ref LOGIN_IDENTITIES_TABLE_NAME: String = format!("{}_{}_{}_login_identities", VALUE_PROJECT,API_DOMAIN, SERVICE);
+}
+pub const LOGIN_IDENTITY_FIELD_NAME_PK: &str = "identityKey";
+pub const LOGIN_IDENTITIES_USER_INDEX: &str = "index_user";
+
+// tables created before an index existed get it added here, dynamodb backfills it by itself
+async fn add_index_if_missing(
+    config: &Config,
+    table_name: &str,
+    index: GlobalSecondaryIndex,
+    attributes: Vec<AttributeDefinition>,
+) -> ResultE<()> {
+    let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+
+    let description = client
+        .describe_table()
+        .table_name(table_name)
+        .send()
+        .await?;
+    let exists = match description.table() {
+        None => false,
+        Some(table) => table
+            .global_secondary_indexes()
+            .iter()
+            .any(|gsi| gsi.index_name() == Some(index.index_name())),
+    };
+    if exists {
+        return Ok(());
+    }
+
+    let action = CreateGlobalSecondaryIndexAction::builder()
+        .index_name(index.index_name())
+        .set_key_schema(Some(index.key_schema().to_vec()))
+        .set_projection(index.projection().cloned())
+        .build()?;
+    client
+        .update_table()
+        .table_name(table_name)
+        .set_attribute_definitions(Some(attributes))
+        .global_secondary_index_updates(
+            GlobalSecondaryIndexUpdate::builder().create(action).build(),
+        )
+        .send()
+        .await?;
+
+    wait_until_schema_is_active(config, table_name).await?;
+    Ok(())
+}
+
 
 pub struct UserSchema;
 #[async_trait]
 impl Schema for UserSchema {
     async fn create_schema(config: &Config) -> ResultE<()> {
 
+        let user_id_ad = AttributeDefinition::builder()
+            .attribute_name(USERID_FIELD_NAME_PK)
+            .attribute_type(ScalarAttributeType::S)
+            .build()
+            .unwrap();
+        let status_ad = AttributeDefinition::builder()
+            .attribute_name(USER_STATUS_FIELD_NAME)
+            .attribute_type(ScalarAttributeType::S)
+            .build()
+            .unwrap();
+        let creation_time_ad = AttributeDefinition::builder()
+            .attribute_name(USER_CREATION_TIME_FIELD_NAME)
+            .attribute_type(ScalarAttributeType::S)
+            .build()
+            .unwrap();
+        let second_index_by_status = GlobalSecondaryIndex::builder()
+            .index_name(USER_STATUS_INDEX)
+            .key_schema(
+                KeySchemaElement::builder()
+                    .attribute_name(USER_STATUS_FIELD_NAME)
+                    .key_type(KeyType::Hash)
+                    .build()
+                    .unwrap(),
+            )
+            .key_schema(
+                KeySchemaElement::builder()
+                    .attribute_name(USER_CREATION_TIME_FIELD_NAME)
+                    .key_type(KeyType::Range)
+                    .build()
+                    .unwrap(),
+            )
+            .projection(
+                Projection::builder()
+                    .projection_type(ProjectionType::All)
+                    .build(),
+            )
+            .build()
+            .unwrap();
+
         let exist = schema_exists(config, USERS_TABLE_NAME.as_str()).await?;
         if exist{
-            return Ok(())
+            return add_index_if_missing(
+                config,
+                USERS_TABLE_NAME.as_str(),
+                second_index_by_status,
+                vec![user_id_ad, status_ad, creation_time_ad],
+            )
+            .await;
         }
 
         // main users' table
         let client = aws_sdk_dynamodb::Client::new(config.aws_config());
 
-        let user_id_ad = AttributeDefinition::builder()
-            .attribute_name(USERID_FIELD_NAME_PK)
-            .attribute_type(ScalarAttributeType::S)
-            .build()
-            .unwrap();
-
         let pk = KeySchemaElement::builder()
             .attribute_name(USERID_FIELD_NAME_PK)
             .key_type(KeyType::Hash)
@@ -68,7 +178,10 @@ impl Schema for UserSchema {
             .create_table()
             .table_name(USERS_TABLE_NAME.clone())
             .key_schema(pk)
+            .global_secondary_indexes(second_index_by_status)
             .attribute_definitions(user_id_ad)
+            .attribute_definitions(status_ad)
+            .attribute_definitions(creation_time_ad)
             .billing_mode(BillingMode::PayPerRequest)
             .stream_specification(
                 StreamSpecificationBuilder::default()
@@ -226,9 +339,49 @@ pub struct LoginEmailSchema;
 impl Schema for LoginEmailSchema {
     async fn create_schema(config: &Config) -> ResultE<()> {
 
+        let email_initial_ad = AttributeDefinition::builder()
+            .attribute_name(LOGIN_EMAIL_INITIAL_FIELD_NAME)
+            .attribute_type(ScalarAttributeType::S)
+            .build()
+            .unwrap();
+        let email_prefix_ad = AttributeDefinition::builder()
+            .attribute_name(LOGIN_EMAIL_FIELD_NAME)
+            .attribute_type(ScalarAttributeType::S)
+            .build()
+            .unwrap();
+        let second_index_by_email_prefix = GlobalSecondaryIndex::builder()
+            .index_name(LOGIN_EMAIL_PREFIX_INDEX)
+            .key_schema(
+                KeySchemaElement::builder()
+                    .attribute_name(LOGIN_EMAIL_INITIAL_FIELD_NAME)
+                    .key_type(KeyType::Hash)
+                    .build()
+                    .unwrap(),
+            )
+            .key_schema(
+                KeySchemaElement::builder()
+                    .attribute_name(LOGIN_EMAIL_FIELD_NAME)
+                    .key_type(KeyType::Range)
+                    .build()
+                    .unwrap(),
+            )
+            .projection(
+                Projection::builder()
+                    .projection_type(ProjectionType::KeysOnly)
+                    .build(),
+            )
+            .build()
+            .unwrap();
+
         let exist = schema_exists(config, LOGIN_EMAIL_TABLE_NAME.as_str()).await?;
         if exist{
-            return Ok(())
+            return add_index_if_missing(
+                config,
+                LOGIN_EMAIL_TABLE_NAME.as_str(),
+                second_index_by_email_prefix,
+                vec![email_initial_ad, email_prefix_ad],
+            )
+            .await;
         }
         // main users' table
         let client = aws_sdk_dynamodb::Client::new(config.aws_config());
@@ -271,8 +424,10 @@ impl Schema for LoginEmailSchema {
             .table_name(LOGIN_EMAIL_TABLE_NAME.clone())
             .key_schema(email_pk)
             .global_secondary_indexes(second_index_by_email)
+            .global_secondary_indexes(second_index_by_email_prefix)
             .attribute_definitions(email_ad)
             .attribute_definitions(email_user_id_ad)
+            .attribute_definitions(email_initial_ad)
             .billing_mode(BillingMode::PayPerRequest)
             .stream_specification(
                 StreamSpecificationBuilder::default()
@@ -421,6 +576,202 @@ impl Schema for LoginWalletSchema {
     }
 }
 
+pub struct UserRoleSchema;
+#[async_trait]
+impl Schema for UserRoleSchema {
+    async fn create_schema(config: &Config) -> ResultE<()> {
+
+        let exist = schema_exists(config, USER_ROLES_TABLE_NAME.as_str()).await?;
+        if exist{
+            return Ok(())
+        }
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+
+        let role_ad = AttributeDefinition::builder()
+            .attribute_name(USER_ROLE_FIELD_NAME)
+            .attribute_type(ScalarAttributeType::S)
+            .build()
+            .unwrap();
+        let user_id_ad = AttributeDefinition::builder()
+            .attribute_name(USERID_FIELD_NAME_PK)
+            .attribute_type(ScalarAttributeType::S)
+            .build()
+            .unwrap();
+        let creation_time_ad = AttributeDefinition::builder()
+            .attribute_name(USER_CREATION_TIME_FIELD_NAME)
+            .attribute_type(ScalarAttributeType::S)
+            .build()
+            .unwrap();
+        let role_hash = KeySchemaElement::builder()
+            .attribute_name(USER_ROLE_FIELD_NAME)
+            .key_type(KeyType::Hash)
+            .build()
+            .unwrap();
+        let user_id_range = KeySchemaElement::builder()
+            .attribute_name(USERID_FIELD_NAME_PK)
+            .key_type(KeyType::Range)
+            .build()
+            .unwrap();
+        let local_index_by_creation = LocalSecondaryIndex::builder()
+            .index_name(USER_ROLE_CREATION_INDEX)
+            .key_schema(role_hash.clone())
+            .key_schema(
+                KeySchemaElement::builder()
+                    .attribute_name(USER_CREATION_TIME_FIELD_NAME)
+                    .key_type(KeyType::Range)
+                    .build()
+                    .unwrap(),
+            )
+            .projection(
+                Projection::builder()
+                    .projection_type(ProjectionType::All)
+                    .build(),
+            )
+            .build()
+            .unwrap();
+
+        client
+            .create_table()
+            .table_name(USER_ROLES_TABLE_NAME.clone())
+            .key_schema(role_hash)
+            .key_schema(user_id_range)
+            .local_secondary_indexes(local_index_by_creation)
+            .attribute_definitions(role_ad)
+            .attribute_definitions(user_id_ad)
+            .attribute_definitions(creation_time_ad)
+            .billing_mode(BillingMode::PayPerRequest)
+            .tags(
+                Tag::builder()
+                    .set_key(Some(TAG_ENVIRONMENT.to_string()))
+                    .set_value(Some(config.env_vars().environment().unwrap()))
+                    .build()
+                    .unwrap(),
+            )
+            .tags(
+                Tag::builder()
+                    .set_key(Some(TAG_PROJECT.to_string()))
+                    .set_value(Some(VALUE_PROJECT.to_string()))
+                    .build()
+                    .unwrap(),
+            )
+            .tags(
+                Tag::builder()
+                    .set_key(Some(TAG_SERVICE.to_string()))
+                    .set_value(Some(API_DOMAIN.to_string()))
+                    .build()
+                    .unwrap(),
+            )
+            .send()
+            .await?;
+
+        wait_until_schema_is_active(config, USER_ROLES_TABLE_NAME.as_str()).await?;
+        Ok(())
+    }
+
+    async fn delete_schema(config: &Config) -> ResultE<()> {
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+
+        client
+            .delete_table()
+            .table_name(USER_ROLES_TABLE_NAME.clone())
+            .send()
+            .await?;
+
+        Ok(())
+    }
+}
+
+pub struct LoginIdentitySchema;
+#[async_trait]
+impl Schema for LoginIdentitySchema {
+    async fn create_schema(config: &Config) -> ResultE<()> {
+        let exist = schema_exists(config, LOGIN_IDENTITIES_TABLE_NAME.as_str()).await?;
+        if exist {
+            return Ok(());
+        }
+
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+
+        let identity_ad = AttributeDefinition::builder()
+            .attribute_name(LOGIN_IDENTITY_FIELD_NAME_PK)
+            .attribute_type(ScalarAttributeType::S)
+            .build()
+            .unwrap();
+        let user_id_ad = AttributeDefinition::builder()
+            .attribute_name(USERID_FIELD_NAME_PK)
+            .attribute_type(ScalarAttributeType::S)
+            .build()
+            .unwrap();
+        let pk = KeySchemaElement::builder()
+            .attribute_name(LOGIN_IDENTITY_FIELD_NAME_PK)
+            .key_type(KeyType::Hash)
+            .build()
+            .unwrap();
+        let second_index_by_user = GlobalSecondaryIndex::builder()
+            .index_name(LOGIN_IDENTITIES_USER_INDEX)
+            .key_schema(
+                KeySchemaElement::builder()
+                    .attribute_name(USERID_FIELD_NAME_PK)
+                    .key_type(KeyType::Hash)
+                    .build()
+                    .unwrap(),
+            )
+            .projection(
+                Projection::builder()
+                    .projection_type(ProjectionType::All)
+                    .build(),
+            )
+            .build()
+            .unwrap();
+
+        client
+            .create_table()
+            .table_name(LOGIN_IDENTITIES_TABLE_NAME.clone())
+            .key_schema(pk)
+            .global_secondary_indexes(second_index_by_user)
+            .attribute_definitions(identity_ad)
+            .attribute_definitions(user_id_ad)
+            .billing_mode(BillingMode::PayPerRequest)
+            .tags(
+                Tag::builder()
+                    .set_key(Some(TAG_ENVIRONMENT.to_string()))
+                    .set_value(Some(config.env_vars().environment().unwrap()))
+                    .build()
+                    .unwrap(),
+            )
+            .tags(
+                Tag::builder()
+                    .set_key(Some(TAG_PROJECT.to_string()))
+                    .set_value(Some(VALUE_PROJECT.to_string()))
+                    .build()
+                    .unwrap(),
+            )
+            .tags(
+                Tag::builder()
+                    .set_key(Some(TAG_SERVICE.to_string()))
+                    .set_value(Some(API_DOMAIN.to_string()))
+                    .build()
+                    .unwrap(),
+            )
+            .send()
+            .await?;
+        wait_until_schema_is_active(config, LOGIN_IDENTITIES_TABLE_NAME.as_str()).await?;
+        Ok(())
+    }
+
+    async fn delete_schema(config: &Config) -> ResultE<()> {
+        let client = aws_sdk_dynamodb::Client::new(config.aws_config());
+
+        client
+            .delete_table()
+            .table_name(LOGIN_IDENTITIES_TABLE_NAME.clone())
+            .send()
+            .await?;
+
+        Ok(())
+    }
+}
+
 pub struct UserAllSchema;
 #[async_trait]
 impl Schema for UserAllSchema {
@@ -429,6 +780,19 @@ impl Schema for UserAllSchema {
         LoginDeviceSchema::create_schema(config).await?;
         LoginEmailSchema::create_schema(config).await?;
         LoginWalletSchema::create_schema(config).await?;
+        UserRoleSchema::create_schema(config).await?;
+        LoginIdentitySchema::create_schema(config).await?;
+        RefreshTokenSchema::create_schema(config).await?;
+        RevokedTokenSchema::create_schema(config).await?;
+        JwtKeySchema::create_schema(config).await?;
+        OneTimeTokenSchema::create_schema(config).await?;
+        WalletNonceSchema::create_schema(config).await?;
+        DeviceCredentialSchema::create_schema(config).await?;
+        DeviceChallengeSchema::create_schema(config).await?;
+        LoginAttemptSchema::create_schema(config).await?;
+        MfaSchema::create_schema(config).await?;
+        ApiKeySchema::create_schema(config).await?;
+        AuditSchema::create_schema(config).await?;
         Ok(())
     }
     async fn delete_schema(config: &Config) -> ResultE<()> {
@@ -436,6 +800,19 @@ impl Schema for UserAllSchema {
         LoginDeviceSchema::delete_schema(config).await?;
         LoginEmailSchema::delete_schema(config).await?;
         LoginWalletSchema::delete_schema(config).await?;
+        UserRoleSchema::delete_schema(config).await?;
+        LoginIdentitySchema::delete_schema(config).await?;
+        RefreshTokenSchema::delete_schema(config).await?;
+        RevokedTokenSchema::delete_schema(config).await?;
+        JwtKeySchema::delete_schema(config).await?;
+        OneTimeTokenSchema::delete_schema(config).await?;
+        WalletNonceSchema::delete_schema(config).await?;
+        DeviceCredentialSchema::delete_schema(config).await?;
+        DeviceChallengeSchema::delete_schema(config).await?;
+        LoginAttemptSchema::delete_schema(config).await?;
+        MfaSchema::delete_schema(config).await?;
+        ApiKeySchema::delete_schema(config).await?;
+        AuditSchema::delete_schema(config).await?;
         Ok(())
     }
 }
@@ -0,0 +1,377 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use aws_sdk_dynamodb::{
+    types::{AttributeValue, Select},
+    Client,
+};
+use chrono::{
+    prelude::{DateTime, Utc},
+    Local,
+};
+use lib_config::config::Config;
+use lib_config::timing::{from_iso8601, iso8601};
+use uuid::Uuid;
+
+use crate::errors::sessions::SessionDynamoDBError;
+use crate::models::session::{NoncePurpose, RefreshToken};
+
+use super::schema_sessions::{
+    REFRESH_TOKENS_TABLE_NAME, REFRESH_TOKENS_USER_INDEX, REFRESH_TOKEN_FIELD_NAME_PK,
+    REVOKED_TOKENS_TABLE_NAME, REVOKED_TOKEN_JTI_FIELD_NAME_PK, SESSION_TTL_FIELD_NAME,
+    WALLET_NONCES_TABLE_NAME, WALLET_NONCE_FIELD_NAME_PK,
+};
+use super::schema_user::USERID_FIELD_NAME_PK;
+
+static FAMILY_ID_FIELD_NAME: &str = "familyId";
+static AUDIENCE_FIELD_NAME: &str = "audience";
+static CREATIONTIME_FIELD_NAME: &str = "creationTime";
+static EXPIRES_AT_FIELD_NAME: &str = "expiresAt";
+static SESSION_EXPIRES_AT_FIELD_NAME: &str = "sessionExpiresAt";
+static REVOKED_FIELD_NAME: &str = "revoked";
+static PURPOSE_FIELD_NAME: &str = "purpose";
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+#[async_trait]
+pub trait SessionRepository {
+    async fn add_refresh_token(&self, token: &RefreshToken) -> ResultE<()>;
+    async fn get_refresh_token(&self, token_hash: &String) -> ResultE<Option<RefreshToken>>;
+    async fn get_refresh_tokens_by_user(&self, user_id: &String) -> ResultE<Vec<RefreshToken>>;
+    // true only when this call moved the token from active to revoked
+    async fn revoke_refresh_token(&self, token_hash: &String) -> ResultE<bool>;
+    async fn deny_jti(&self, jti: &String, expires_at: &DateTime<Utc>) -> ResultE<()>;
+    async fn is_jti_denied(&self, jti: &String) -> ResultE<bool>;
+    async fn add_nonce(
+        &self,
+        purpose: &NoncePurpose,
+        nonce: &String,
+        expires_at: &DateTime<Utc>,
+    ) -> ResultE<()>;
+    // true only when the nonce existed for this purpose, hadn't expired and this call removed it
+    async fn consume_nonce(&self, purpose: &NoncePurpose, nonce: &String) -> ResultE<bool>;
+}
+
+#[derive(Clone, Debug)]
+pub struct SessionsRepo {
+    client: Client,
+}
+
+impl SessionsRepo {
+    pub fn new(conf: &Config) -> SessionsRepo {
+        SessionsRepo {
+            client: Client::new(conf.aws_config()),
+        }
+    }
+}
+
+#[async_trait]
+impl SessionRepository for SessionsRepo {
+    async fn add_refresh_token(&self, token: &RefreshToken) -> ResultE<()> {
+        let request = self
+            .client
+            .put_item()
+            .table_name(REFRESH_TOKENS_TABLE_NAME.clone())
+            .item(
+                REFRESH_TOKEN_FIELD_NAME_PK,
+                AttributeValue::S(token.token_hash().clone()),
+            )
+            .item(USERID_FIELD_NAME_PK, AttributeValue::S(token.user_id().clone()))
+            .item(
+                FAMILY_ID_FIELD_NAME,
+                AttributeValue::S(token.family_id().to_string()),
+            )
+            .item(
+                AUDIENCE_FIELD_NAME,
+                AttributeValue::L(
+                    token
+                        .audience()
+                        .iter()
+                        .map(|aud| AttributeValue::S(aud.clone()))
+                        .collect(),
+                ),
+            )
+            .item(
+                CREATIONTIME_FIELD_NAME,
+                AttributeValue::S(iso8601(token.creation_time())),
+            )
+            .item(
+                EXPIRES_AT_FIELD_NAME,
+                AttributeValue::S(iso8601(token.expires_at())),
+            )
+            .item(
+                SESSION_EXPIRES_AT_FIELD_NAME,
+                AttributeValue::S(iso8601(token.session_expires_at())),
+            )
+            .item(
+                SESSION_TTL_FIELD_NAME,
+                AttributeValue::N(token.expires_at().timestamp().to_string()),
+            )
+            .item(REVOKED_FIELD_NAME, AttributeValue::Bool(token.revoked()));
+
+        match request.send().await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(SessionDynamoDBError(e.to_string()).into())
+            }
+        }
+    }
+
+    async fn get_refresh_token(&self, token_hash: &String) -> ResultE<Option<RefreshToken>> {
+        let request = self
+            .client
+            .get_item()
+            .table_name(REFRESH_TOKENS_TABLE_NAME.clone())
+            .key(
+                REFRESH_TOKEN_FIELD_NAME_PK,
+                AttributeValue::S(token_hash.clone()),
+            );
+
+        match request.send().await {
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(SessionDynamoDBError(e.to_string()).into())
+            }
+            Ok(data) => match data.item() {
+                None => Ok(None),
+                Some(doc) => {
+                    let mut token = RefreshToken::new();
+                    mapping_from_doc_to_refresh_token(doc, &mut token);
+                    Ok(Some(token))
+                }
+            },
+        }
+    }
+
+    async fn get_refresh_tokens_by_user(&self, user_id: &String) -> ResultE<Vec<RefreshToken>> {
+        let mut queried = Vec::new();
+        let filter = format!("{} = :value", USERID_FIELD_NAME_PK);
+
+        let request = self
+            .client
+            .query()
+            .table_name(REFRESH_TOKENS_TABLE_NAME.clone())
+            .index_name(REFRESH_TOKENS_USER_INDEX)
+            .key_condition_expression(filter)
+            .expression_attribute_values(":value".to_string(), AttributeValue::S(user_id.clone()))
+            .select(Select::AllProjectedAttributes);
+
+        match request.send().await {
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                return Err(SessionDynamoDBError(e.to_string()).into());
+            }
+            Ok(data) => {
+                for doc in data.items() {
+                    let mut token = RefreshToken::new();
+                    mapping_from_doc_to_refresh_token(doc, &mut token);
+                    queried.push(token);
+                }
+            }
+        }
+        Ok(queried)
+    }
+
+    async fn revoke_refresh_token(&self, token_hash: &String) -> ResultE<bool> {
+        let request = self
+            .client
+            .update_item()
+            .table_name(REFRESH_TOKENS_TABLE_NAME.clone())
+            .key(
+                REFRESH_TOKEN_FIELD_NAME_PK,
+                AttributeValue::S(token_hash.clone()),
+            )
+            .update_expression("SET #revoked = :revoked")
+            .condition_expression("attribute_exists(#pk) AND #revoked = :active")
+            .expression_attribute_names("#revoked", REVOKED_FIELD_NAME)
+            .expression_attribute_names("#pk", REFRESH_TOKEN_FIELD_NAME_PK)
+            .expression_attribute_values(":revoked", AttributeValue::Bool(true))
+            .expression_attribute_values(":active", AttributeValue::Bool(false));
+
+        match request.send().await {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                let service_error = e.into_service_error();
+                if service_error.is_conditional_check_failed_exception() {
+                    return Ok(false);
+                }
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    service_error
+                );
+                log::error!("{}", mssag);
+                Err(SessionDynamoDBError(service_error.to_string()).into())
+            }
+        }
+    }
+
+    async fn deny_jti(&self, jti: &String, expires_at: &DateTime<Utc>) -> ResultE<()> {
+        let request = self
+            .client
+            .put_item()
+            .table_name(REVOKED_TOKENS_TABLE_NAME.clone())
+            .item(REVOKED_TOKEN_JTI_FIELD_NAME_PK, AttributeValue::S(jti.clone()))
+            .item(
+                SESSION_TTL_FIELD_NAME,
+                AttributeValue::N(expires_at.timestamp().to_string()),
+            );
+
+        match request.send().await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(SessionDynamoDBError(e.to_string()).into())
+            }
+        }
+    }
+
+    async fn is_jti_denied(&self, jti: &String) -> ResultE<bool> {
+        let request = self
+            .client
+            .get_item()
+            .table_name(REVOKED_TOKENS_TABLE_NAME.clone())
+            .key(REVOKED_TOKEN_JTI_FIELD_NAME_PK, AttributeValue::S(jti.clone()));
+
+        match request.send().await {
+            Ok(data) => Ok(data.item().is_some()),
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(SessionDynamoDBError(e.to_string()).into())
+            }
+        }
+    }
+
+    async fn add_nonce(
+        &self,
+        purpose: &NoncePurpose,
+        nonce: &String,
+        expires_at: &DateTime<Utc>,
+    ) -> ResultE<()> {
+        let request = self
+            .client
+            .put_item()
+            .table_name(WALLET_NONCES_TABLE_NAME.clone())
+            .item(WALLET_NONCE_FIELD_NAME_PK, AttributeValue::S(nonce.clone()))
+            .item(PURPOSE_FIELD_NAME, AttributeValue::S(purpose.to_string()))
+            .item(
+                EXPIRES_AT_FIELD_NAME,
+                AttributeValue::N(expires_at.timestamp().to_string()),
+            )
+            .item(
+                SESSION_TTL_FIELD_NAME,
+                AttributeValue::N(expires_at.timestamp().to_string()),
+            );
+
+        match request.send().await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(SessionDynamoDBError(e.to_string()).into())
+            }
+        }
+    }
+
+    async fn consume_nonce(&self, purpose: &NoncePurpose, nonce: &String) -> ResultE<bool> {
+        // the ttl purge is lazy, so the expiration is checked here as well
+        let request = self
+            .client
+            .delete_item()
+            .table_name(WALLET_NONCES_TABLE_NAME.clone())
+            .key(WALLET_NONCE_FIELD_NAME_PK, AttributeValue::S(nonce.clone()))
+            .condition_expression(
+                "attribute_exists(#pk) AND #expires > :now AND #purpose = :purpose",
+            )
+            .expression_attribute_names("#pk", WALLET_NONCE_FIELD_NAME_PK)
+            .expression_attribute_names("#expires", EXPIRES_AT_FIELD_NAME)
+            .expression_attribute_names("#purpose", PURPOSE_FIELD_NAME)
+            .expression_attribute_values(":purpose", AttributeValue::S(purpose.to_string()))
+            .expression_attribute_values(":now", AttributeValue::N(Utc::now().timestamp().to_string()));
+
+        match request.send().await {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                let service_error = e.into_service_error();
+                if service_error.is_conditional_check_failed_exception() {
+                    return Ok(false);
+                }
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    service_error
+                );
+                log::error!("{}", mssag);
+                Err(SessionDynamoDBError(service_error.to_string()).into())
+            }
+        }
+    }
+}
+
+fn mapping_from_doc_to_refresh_token(doc: &HashMap<String, AttributeValue>, token: &mut RefreshToken) {
+    if let Some(hash) = doc.get(REFRESH_TOKEN_FIELD_NAME_PK) {
+        token.set_token_hash(hash.as_s().unwrap());
+    }
+    if let Some(user_id) = doc.get(USERID_FIELD_NAME_PK) {
+        token.set_user_id(user_id.as_s().unwrap());
+    }
+    if let Some(family) = doc.get(FAMILY_ID_FIELD_NAME) {
+        token.set_family_id(&Uuid::from_str(family.as_s().unwrap()).unwrap());
+    }
+    if let Some(audience) = doc.get(AUDIENCE_FIELD_NAME) {
+        let values = audience
+            .as_l()
+            .unwrap()
+            .iter()
+            .filter_map(|aud| aud.as_s().ok().cloned())
+            .collect();
+        token.set_audience(&values);
+    }
+    if let Some(creation_time) = doc.get(CREATIONTIME_FIELD_NAME) {
+        token.set_creation_time(&from_iso8601(creation_time.as_s().unwrap()));
+    }
+    if let Some(expires_at) = doc.get(EXPIRES_AT_FIELD_NAME) {
+        token.set_expires_at(&from_iso8601(expires_at.as_s().unwrap()));
+    }
+    // tokens stored before sessions had an end close with their own expiration
+    match doc.get(SESSION_EXPIRES_AT_FIELD_NAME) {
+        Some(session_expires_at) => {
+            token.set_session_expires_at(&from_iso8601(session_expires_at.as_s().unwrap()))
+        }
+        None => token.set_session_expires_at(&token.expires_at().clone()),
+    }
+    if let Some(revoked) = doc.get(REVOKED_FIELD_NAME) {
+        token.set_revoked(*revoked.as_bool().unwrap());
+    }
+}
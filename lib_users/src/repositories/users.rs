@@ -1,15 +1,21 @@
 use std::collections::HashMap;
 
-use argon2::{self};
+use aws_sdk_dynamodb::operation::query::builders::QueryFluentBuilder;
 use aws_sdk_dynamodb::operation::transact_write_items::builders::TransactWriteItemsFluentBuilder;
-use aws_sdk_dynamodb::types::{Put, TransactWriteItem};
+use aws_sdk_dynamodb::types::{Delete, Put, TransactWriteItem};
+use tokio::task::spawn_blocking;
 //use tracing::error;
-use uuid::Uuid;
 
 use crate::errors::users::{
     UserAlreadyExistsError, UserDynamoDBError, UserNoExistsError, UserParamNotAccepted,
+    UserPasswordError,
+};
+use crate::models::login_method::LoginMethodKind;
+use crate::models::password_hash::{
+    hash_password, needs_rehash, verify_password, PasswordHashParams,
 };
 use crate::models::user::{User, UserRoles, UserStatus};
+use crate::models::user_search::{SearchCursor, UserPage, UserSearch};
 use async_trait::async_trait;
 use aws_sdk_dynamodb::{
     types::{AttributeValue, Select},
@@ -24,15 +30,18 @@ use lib_config::{config::Config, environment::EnvironmentVariables};
 
 use super::schema_user::{
     LOGIN_DEVICE_FIELD_NAME, LOGIN_DEVICE_INDEX, LOGIN_DEVICE_TABLE_NAME, LOGIN_EMAIL_FIELD_NAME,
-    LOGIN_EMAIL_INDEX, LOGIN_EMAIL_TABLE_NAME, LOGIN_WALLET_FIELD_NAME, LOGIN_WALLET_INDEX,
-    LOGIN_WALLET_TABLE_NAME, USERID_FIELD_NAME_PK, USERS_TABLE_NAME,
+    LOGIN_EMAIL_INDEX, LOGIN_EMAIL_INITIAL_FIELD_NAME, LOGIN_EMAIL_PREFIX_INDEX,
+    LOGIN_EMAIL_TABLE_NAME, LOGIN_WALLET_FIELD_NAME, LOGIN_WALLET_INDEX, LOGIN_WALLET_TABLE_NAME,
+    USERID_FIELD_NAME_PK, USERS_TABLE_NAME, USER_CREATION_TIME_FIELD_NAME, USER_ROLES_TABLE_NAME,
+    USER_ROLE_CREATION_INDEX, USER_ROLE_FIELD_NAME, USER_STATUS_FIELD_NAME, USER_STATUS_INDEX,
 };
 
 static PASSWORD_FIELD_NAME: &str = "password";
-static CREATIONTIME_FIELD_NAME: &str = "creationTime";
+static CREATIONTIME_FIELD_NAME: &str = USER_CREATION_TIME_FIELD_NAME;
 static LASTUPDATETIME_FIELD_NAME: &str = "lastUpdateTime";
 static ROLES_FIELD_NAME: &str = "userRoles";
-static STATUS_FIELD_NAME: &str = "userStatus";
+static STATUS_FIELD_NAME: &str = USER_STATUS_FIELD_NAME;
+static EMAIL_VERIFIED_FIELD_NAME: &str = "emailVerified";
 
 type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
 
@@ -47,7 +56,11 @@ pub trait UserRepository {
     async fn get_by_email_and_password(&self, email: &String, password: &String) -> ResultE<User>;
     async fn get_by_email(&self, email: &String) -> ResultE<User>;
     async fn get_all(&self, page_number: u32, page_size: u32) -> ResultE<Vec<User>>;
+    async fn search(&self, filter: &UserSearch) -> ResultE<UserPage>;
     async fn remove(&self, user_id: &String) -> ResultE<()>;
+    async fn has_password(&self, id: &String) -> ResultE<bool>;
+    // drops the login row only, the user keeps the rest
+    async fn remove_login(&self, id: &String, kind: &LoginMethodKind) -> ResultE<()>;
 }
 
 #[derive(Clone, Debug)]
@@ -55,6 +68,7 @@ pub struct UsersRepo {
     client: Client,
     //config: Config
     environment_vars: EnvironmentVariables,
+    password_params: PasswordHashParams,
 }
 
 impl UsersRepo {
@@ -62,6 +76,9 @@ impl UsersRepo {
         UsersRepo {
             client: Client::new(conf.aws_config()),
             environment_vars: conf.env_vars().clone(),
+            password_params: PasswordHashParams::for_environment(
+                &conf.env_vars().environment().unwrap(),
+            ),
         }
     }
     async fn get_by_filter_key(
@@ -177,6 +194,7 @@ impl UsersRepo {
         }
     }
 
+    // a login value already held by another user, the user's own ones are fine
     async fn check_duplicates(&self, user: &User) -> ResultE<bool> {
         if let Some(email) = user.email() {
             let res = self
@@ -189,7 +207,7 @@ impl UsersRepo {
                 )
                 .await?;
 
-            if let Some(_) = res {
+            if res.is_some_and(|owner| owner != *user.user_id()) {
                 return Err(UserAlreadyExistsError("email is already in use".to_string()).into());
             }
         }
@@ -204,7 +222,7 @@ impl UsersRepo {
                     LOGIN_DEVICE_TABLE_NAME.as_str(),
                 )
                 .await?;
-            if let Some(_) = res {
+            if res.is_some_and(|owner| owner != *user.user_id()) {
                 return Err(UserAlreadyExistsError("device is already in use".to_string()).into());
             }
         }
@@ -219,7 +237,7 @@ impl UsersRepo {
                     LOGIN_WALLET_TABLE_NAME.as_str(),
                 )
                 .await?;
-            if let Some(_) = res {
+            if res.is_some_and(|owner| owner != *user.user_id()) {
                 return Err(
                     UserAlreadyExistsError("wallet address is already in use".to_string()).into(),
                 );
@@ -305,14 +323,16 @@ impl UsersRepo {
         let id_av = AttributeValue::S(new_user_data.user_id().clone());
         let roles_av = AttributeValue::Ss(UserRoles::to_vec_str(new_user_data.roles()).clone());
         let status_av: AttributeValue = AttributeValue::S(new_user_data.status().to_string());
+        let email_verified_av = AttributeValue::Bool(new_user_data.email_verified());
 
         let mut user_fields = Put::builder();
         user_fields = user_fields
             .item(USERID_FIELD_NAME_PK, id_av.clone())
-            .item(CREATIONTIME_FIELD_NAME, creation_time_av)
+            .item(CREATIONTIME_FIELD_NAME, creation_time_av.clone())
             .item(LASTUPDATETIME_FIELD_NAME, last_update_time_av)
             .item(ROLES_FIELD_NAME, roles_av)
-            .item(STATUS_FIELD_NAME, status_av);
+            .item(STATUS_FIELD_NAME, status_av.clone())
+            .item(EMAIL_VERIFIED_FIELD_NAME, email_verified_av);
 
         request = request.transact_items(
             TransactWriteItem::builder()
@@ -325,6 +345,25 @@ impl UsersRepo {
                 .build(),
         );
 
+        for role in new_user_data.roles() {
+            let role_fields = Put::builder()
+                .item(USER_ROLE_FIELD_NAME, AttributeValue::S(role.to_string()))
+                .item(USERID_FIELD_NAME_PK, id_av.clone())
+                .item(CREATIONTIME_FIELD_NAME, creation_time_av.clone())
+                .item(STATUS_FIELD_NAME, status_av.clone());
+
+            request = request.transact_items(
+                TransactWriteItem::builder()
+                    .put(
+                        role_fields
+                            .table_name(USER_ROLES_TABLE_NAME.clone())
+                            .build()
+                            .unwrap(),
+                    )
+                    .build(),
+            );
+        }
+
         if let Some(dvc) = new_user_data.device() {
             let device_av = AttributeValue::S(dvc.to_owned());
 
@@ -369,8 +408,12 @@ impl UsersRepo {
 
             match password {
                 Some(password) => {
-                    let hash =
-                        cypher_text(password, &self.environment_vars.hmac_secret().unwrap())?;
+                    let hash = cypher_text(
+                        password,
+                        &self.environment_vars.hmac_secret().unwrap(),
+                        &self.password_params,
+                    )
+                    .await?;
                     let password_av: AttributeValue = AttributeValue::S(hash);
                     email_fields = email_fields.item(PASSWORD_FIELD_NAME, password_av);
                 }
@@ -392,10 +435,13 @@ impl UsersRepo {
             }
 
             let email_av: AttributeValue = AttributeValue::S(email.to_owned());
+            let email_initial_av: AttributeValue =
+                AttributeValue::S(email.chars().take(1).collect());
 
             email_fields = email_fields
                 .item(USERID_FIELD_NAME_PK, id_av.clone())
-                .item(LOGIN_EMAIL_FIELD_NAME, email_av);
+                .item(LOGIN_EMAIL_FIELD_NAME, email_av)
+                .item(LOGIN_EMAIL_INITIAL_FIELD_NAME, email_initial_av);
 
             request = request.transact_items(
                 TransactWriteItem::builder()
@@ -410,6 +456,267 @@ impl UsersRepo {
         }
         Ok(request)
     }
+
+    // role rows the user doesn't hold anymore, the ones it holds are rewritten by new_or_update_builder
+    async fn stale_roles(
+        &self,
+        id: &String,
+        keep: &Vec<UserRoles>,
+    ) -> ResultE<Vec<TransactWriteItem>> {
+        let doc = self.get_by_id_hashmap(id).await?;
+        let mut stored = User::new();
+        mapping_from_doc_to_user(&doc, &mut stored);
+
+        let mut deletes = Vec::new();
+        for role in stored.roles().iter().filter(|role| !keep.contains(role)) {
+            deletes.push(
+                TransactWriteItem::builder()
+                    .delete(
+                        Delete::builder()
+                            .key(USER_ROLE_FIELD_NAME, AttributeValue::S(role.to_string()))
+                            .key(USERID_FIELD_NAME_PK, AttributeValue::S(id.clone()))
+                            .table_name(USER_ROLES_TABLE_NAME.as_str())
+                            .build()
+                            .unwrap(),
+                    )
+                    .build(),
+            );
+        }
+        Ok(deletes)
+    }
+
+    // one page of a query plus the key to resume it from, none when the query is over
+    async fn query_page(
+        &self,
+        request: QueryFluentBuilder,
+    ) -> ResultE<(
+        Vec<HashMap<String, AttributeValue>>,
+        Option<HashMap<String, String>>,
+    )> {
+        match request.send().await {
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(UserDynamoDBError(e.to_string()).into())
+            }
+            Ok(result) => {
+                let last_key = result.last_evaluated_key().map(|key| {
+                    key.iter()
+                        .filter_map(|(name, value)| {
+                            value.as_s().ok().map(|value| (name.clone(), value.clone()))
+                        })
+                        .collect()
+                });
+                Ok((result.items().to_vec(), last_key))
+            }
+        }
+    }
+
+    // the stored hash was made with older params: replace it while the plain password is at hand
+    async fn rehash_password(
+        &self,
+        user_id: &String,
+        password: &String,
+        old_hash: &String,
+    ) -> ResultE<()> {
+        let hash = cypher_text(
+            password,
+            &self.environment_vars.hmac_secret().unwrap(),
+            &self.password_params,
+        )
+        .await?;
+        let request = self
+            .client
+            .update_item()
+            .table_name(LOGIN_EMAIL_TABLE_NAME.as_str())
+            .key(USERID_FIELD_NAME_PK, AttributeValue::S(user_id.clone()))
+            .update_expression(format!("SET {} = :new", PASSWORD_FIELD_NAME))
+            // a password changed meanwhile wins
+            .condition_expression(format!("{} = :old", PASSWORD_FIELD_NAME))
+            .expression_attribute_values(":new", AttributeValue::S(hash))
+            .expression_attribute_values(":old", AttributeValue::S(old_hash.clone()));
+        match request.send().await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(UserDynamoDBError(e.to_string()).into())
+            }
+        }
+    }
+
+    async fn users_from_ids(
+        &self,
+        docs: &Vec<HashMap<String, AttributeValue>>,
+        filter: &UserSearch,
+    ) -> ResultE<Vec<User>> {
+        let mut users = Vec::new();
+        for doc in docs {
+            let user_id = doc.get(USERID_FIELD_NAME_PK).unwrap().as_s().unwrap();
+            let user = self.get_by_id(user_id).await?;
+            if filter.matches(&user) {
+                users.push(user);
+            }
+        }
+        Ok(users)
+    }
+
+    async fn search_by_email_prefix(
+        &self,
+        prefix: &String,
+        filter: &UserSearch,
+        cursor: Option<SearchCursor>,
+    ) -> ResultE<UserPage> {
+        let initial: String = prefix.chars().take(1).collect();
+        let condition = format!(
+            "{} = :initial AND begins_with({}, :prefix)",
+            LOGIN_EMAIL_INITIAL_FIELD_NAME, LOGIN_EMAIL_FIELD_NAME
+        );
+        let request = self
+            .client
+            .query()
+            .table_name(LOGIN_EMAIL_TABLE_NAME.as_str())
+            .index_name(LOGIN_EMAIL_PREFIX_INDEX)
+            .key_condition_expression(condition)
+            .expression_attribute_values(":initial", AttributeValue::S(initial))
+            .expression_attribute_values(":prefix", AttributeValue::S(prefix.clone()))
+            .scan_index_forward(filter.ascending)
+            .limit(filter.page_size as i32)
+            .set_exclusive_start_key(cursor.and_then(|c| start_key(&c)));
+
+        let (docs, last_key) = self.query_page(request).await?;
+        Ok(UserPage {
+            users: self.users_from_ids(&docs, filter).await?,
+            next_cursor: last_key.map(|key| {
+                SearchCursor {
+                    partition: None,
+                    key,
+                }
+                .encode()
+            }),
+        })
+    }
+
+    async fn search_by_role(
+        &self,
+        role: &UserRoles,
+        filter: &UserSearch,
+        cursor: Option<SearchCursor>,
+    ) -> ResultE<UserPage> {
+        let (range, mut values) = creation_time_condition(filter);
+        values.insert(":role".to_string(), AttributeValue::S(role.to_string()));
+        let mut request = self
+            .client
+            .query()
+            .table_name(USER_ROLES_TABLE_NAME.as_str())
+            .index_name(USER_ROLE_CREATION_INDEX)
+            .key_condition_expression(format!("{} = :role{}", USER_ROLE_FIELD_NAME, range))
+            .scan_index_forward(filter.ascending)
+            .limit(filter.page_size as i32)
+            .set_exclusive_start_key(cursor.and_then(|c| start_key(&c)));
+        if let Some(status) = &filter.status {
+            values.insert(":status".to_string(), AttributeValue::S(status.to_string()));
+            request = request.filter_expression(format!("{} = :status", STATUS_FIELD_NAME));
+        }
+        request = request.set_expression_attribute_values(Some(values));
+
+        let (docs, last_key) = self.query_page(request).await?;
+        Ok(UserPage {
+            users: self.users_from_ids(&docs, filter).await?,
+            next_cursor: last_key.map(|key| {
+                SearchCursor {
+                    partition: None,
+                    key,
+                }
+                .encode()
+            }),
+        })
+    }
+
+    // without a status both are read, enabled users first, the cursor remembers which one it was at
+    async fn search_by_status(
+        &self,
+        filter: &UserSearch,
+        cursor: Option<SearchCursor>,
+    ) -> ResultE<UserPage> {
+        let statuses = match &filter.status {
+            Some(status) => vec![status.to_string()],
+            None => vec![
+                UserStatus::Enabled.to_string(),
+                UserStatus::Disabled.to_string(),
+            ],
+        };
+        let (mut current, mut start) = match cursor {
+            None => (0, None),
+            Some(c) => (
+                statuses
+                    .iter()
+                    .position(|status| Some(status) == c.partition.as_ref())
+                    .unwrap_or(0),
+                start_key(&c),
+            ),
+        };
+
+        let mut users = Vec::new();
+        loop {
+            let (range, mut values) = creation_time_condition(filter);
+            values.insert(
+                ":status".to_string(),
+                AttributeValue::S(statuses[current].clone()),
+            );
+            let request = self
+                .client
+                .query()
+                .table_name(USERS_TABLE_NAME.as_str())
+                .index_name(USER_STATUS_INDEX)
+                .key_condition_expression(format!("{} = :status{}", STATUS_FIELD_NAME, range))
+                .set_expression_attribute_values(Some(values))
+                .scan_index_forward(filter.ascending)
+                .limit((filter.page_size as usize - users.len()) as i32)
+                .set_exclusive_start_key(start);
+
+            let (docs, last_key) = self.query_page(request).await?;
+            for doc in docs {
+                let mut user = User::new();
+                mapping_from_doc_to_user(&doc, &mut user);
+                self.mapping_from_doc_to_user_logins(&user.user_id().clone(), &mut user)
+                    .await?;
+                users.push(user);
+            }
+
+            if let Some(key) = last_key {
+                let partition = Some(statuses[current].clone());
+                return Ok(UserPage {
+                    users,
+                    next_cursor: Some(SearchCursor { partition, key }.encode()),
+                });
+            }
+            current += 1;
+            start = None;
+            if current == statuses.len() {
+                return Ok(UserPage {
+                    users,
+                    next_cursor: None,
+                });
+            }
+            if users.len() >= filter.page_size as usize {
+                let partition = Some(statuses[current].clone());
+                let key = HashMap::new();
+                return Ok(UserPage {
+                    users,
+                    next_cursor: Some(SearchCursor { partition, key }.encode()),
+                });
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -442,45 +749,91 @@ impl UserRepository for UsersRepo {
         }
     }
 
+    // the whole table, for maintenance tasks only: admin listings go through search
     async fn get_all(&self, _page_number: u32, _page_size: u32) -> ResultE<Vec<User>> {
         let mut usersqueried = Vec::new();
+        let mut start_key = None;
+
+        loop {
+            let results = self
+                .client
+                .scan()
+                .table_name(USERS_TABLE_NAME.as_str())
+                .set_exclusive_start_key(start_key)
+                .send()
+                .await;
+
+            match results {
+                Err(e) => {
+                    let mssag = format!(
+                        "Error at [{}] - {} ",
+                        Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                        e
+                    );
+                    //tracing::error!(mssag);
+                    log::error!("{}", mssag);
+                    return Err(UserDynamoDBError(e.to_string()).into());
+                }
+                Ok(result) => {
+                    if let Some(docs) = result.items {
+                        for doc in docs {
+                            let mut user = User::new();
 
-        let results = self
-            .client
-            .scan()
-            .table_name(USERS_TABLE_NAME.as_str())
-            .send()
-            .await;
-
-        match results {
-            Err(e) => {
-                let mssag = format!(
-                    "Error at [{}] - {} ",
-                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
-                    e
-                );
-                //tracing::error!(mssag);
-                log::error!("{}", mssag);
-                return Err(UserDynamoDBError(e.to_string()).into());
-            }
-            Ok(result) => {
-                if let Some(docs) = result.items {
-                    for doc in docs {
-                        let mut user = User::new();
-
-                        mapping_from_doc_to_user(&doc, &mut user);
-                        self.mapping_from_doc_to_user_logins(user.clone().user_id(), &mut user)
-                            .await?;
+                            mapping_from_doc_to_user(&doc, &mut user);
+                            self.mapping_from_doc_to_user_logins(user.clone().user_id(), &mut user)
+                                .await?;
 
-                        usersqueried.push(user.clone());
+                            usersqueried.push(user.clone());
+                        }
                     }
+                    start_key = result.last_evaluated_key;
                 }
             }
+            if start_key.is_none() {
+                break;
+            }
         }
 
         Ok(usersqueried)
     }
 
+    async fn search(&self, filter: &UserSearch) -> ResultE<UserPage> {
+        let cursor = match &filter.cursor {
+            None => None,
+            Some(cursor) => match SearchCursor::decode(cursor) {
+                None => return Err(UserParamNotAccepted("cursor".to_string()).into()),
+                Some(cursor) => Some(cursor),
+            },
+        };
+
+        // wallets and devices belong to one user at most
+        let exact = match (&filter.wallet, &filter.device) {
+            (Some(wallet), _) => Some(self.get_by_wallet_address(wallet).await),
+            (None, Some(device)) => Some(self.get_by_device(device).await),
+            (None, None) => None,
+        };
+        if let Some(found) = exact {
+            let users = match found {
+                Ok(user) if filter.matches(&user) => vec![user],
+                Ok(_) => vec![],
+                Err(e) if e.downcast_ref::<UserNoExistsError>().is_some() => vec![],
+                Err(e) => return Err(e),
+            };
+            return Ok(UserPage {
+                users,
+                next_cursor: None,
+            });
+        }
+
+        if let Some(prefix) = &filter.email_prefix {
+            return self.search_by_email_prefix(prefix, filter, cursor).await;
+        }
+        if let Some(role) = &filter.role {
+            return self.search_by_role(role, filter, cursor).await;
+        }
+        self.search_by_status(filter, cursor).await
+    }
+
     async fn get_by_id(&self, id: &String) -> ResultE<User> {
         let res = self.get_by_id_hashmap(id).await?;
         let mut user = User::new();
@@ -614,10 +967,11 @@ impl UserRepository for UsersRepo {
             }
             Some(password_stored_hashed) => {
                 let password_ok = cypher_check(
-                    &password,
+                    password,
                     &password_stored_hashed,
                     &self.environment_vars.hmac_secret().unwrap(),
-                )?;
+                )
+                .await?;
                 if password_ok {
                     let user_op = self
                         .get_by_filter_index(
@@ -629,6 +983,15 @@ impl UserRepository for UsersRepo {
                         )
                         .await?;
                     let user_id = user_op.unwrap();
+                    if needs_rehash(&password_stored_hashed, &self.password_params) {
+                        // the login goes on with the old hash, next one will try again
+                        if let Err(e) = self
+                            .rehash_password(&user_id, password, &password_stored_hashed)
+                            .await
+                        {
+                            log::warn!("password of {} not rehashed: {}", user_id, e);
+                        }
+                    }
                     let res = self.get_by_id_hashmap(&user_id).await?;
 
                     let mut user = User::new();
@@ -734,9 +1097,12 @@ impl UserRepository for UsersRepo {
     }
 
     async fn update(&self, id: &String, user_new_data: &User) -> ResultE<()> {
-        //self.check_duplicates(user).await?;
+        self.check_duplicates(user_new_data).await?;
 
-        let request = self.new_or_update_builder(user_new_data, &None).await?;
+        let mut request = self.new_or_update_builder(user_new_data, &None).await?;
+        for delete in self.stale_roles(id, user_new_data.roles()).await? {
+            request = request.transact_items(delete);
+        }
 
         match request.send().await {
             Ok(_updated) => {
@@ -795,14 +1161,32 @@ impl UserRepository for UsersRepo {
         }
     }
 
+    // the login rows go with the user, otherwise its email, device or wallet couldn't sign up again
     async fn remove(&self, id: &String) -> ResultE<()> {
         let user_id_av = AttributeValue::S(id.clone());
 
-        let request = self
-            .client
-            .delete_item()
-            .table_name(USERS_TABLE_NAME.as_str())
-            .key(USERID_FIELD_NAME_PK, user_id_av);
+        let mut request = self.client.transact_write_items();
+        for delete in self.stale_roles(id, &vec![]).await? {
+            request = request.transact_items(delete);
+        }
+        for table in [
+            USERS_TABLE_NAME.as_str(),
+            LOGIN_EMAIL_TABLE_NAME.as_str(),
+            LOGIN_DEVICE_TABLE_NAME.as_str(),
+            LOGIN_WALLET_TABLE_NAME.as_str(),
+        ] {
+            request = request.transact_items(
+                TransactWriteItem::builder()
+                    .delete(
+                        Delete::builder()
+                            .key(USERID_FIELD_NAME_PK, user_id_av.clone())
+                            .table_name(table)
+                            .build()
+                            .unwrap(),
+                    )
+                    .build(),
+            );
+        }
 
         let results = request.send().await;
         match results {
@@ -821,6 +1205,83 @@ impl UserRepository for UsersRepo {
             }
         }
     }
+
+    async fn has_password(&self, id: &String) -> ResultE<bool> {
+        let password = self
+            .get_by_filter_key(
+                USERID_FIELD_NAME_PK,
+                id,
+                PASSWORD_FIELD_NAME,
+                LOGIN_EMAIL_TABLE_NAME.as_str(),
+            )
+            .await?;
+        Ok(password.is_some())
+    }
+
+    async fn remove_login(&self, id: &String, kind: &LoginMethodKind) -> ResultE<()> {
+        let table = match kind {
+            LoginMethodKind::Email => LOGIN_EMAIL_TABLE_NAME.as_str(),
+            LoginMethodKind::Device => LOGIN_DEVICE_TABLE_NAME.as_str(),
+            LoginMethodKind::Wallet => LOGIN_WALLET_TABLE_NAME.as_str(),
+            LoginMethodKind::Oidc => {
+                return Err(UserParamNotAccepted("identities aren't user rows".to_string()).into())
+            }
+        };
+
+        let request = self
+            .client
+            .delete_item()
+            .table_name(table)
+            .key(USERID_FIELD_NAME_PK, AttributeValue::S(id.clone()));
+
+        match request.send().await {
+            Err(e) => {
+                let mssag = format!(
+                    "Error at [{}] - {} ",
+                    Local::now().format("%m-%d-%Y %H:%M:%S").to_string(),
+                    e
+                );
+                log::error!("{}", mssag);
+                Err(UserDynamoDBError(e.to_string()).into())
+            }
+            Ok(_) => Ok(()),
+        }
+    }
+}
+
+fn start_key(cursor: &SearchCursor) -> Option<HashMap<String, AttributeValue>> {
+    if cursor.key.is_empty() {
+        return None;
+    }
+    Some(
+        cursor
+            .key
+            .iter()
+            .map(|(name, value)| (name.clone(), AttributeValue::S(value.clone())))
+            .collect(),
+    )
+}
+
+// sort key condition on the creation time, to append to the partition one
+fn creation_time_condition(filter: &UserSearch) -> (String, HashMap<String, AttributeValue>) {
+    let mut values = HashMap::new();
+    let condition = match (&filter.created_from, &filter.created_to) {
+        (Some(from), Some(to)) => {
+            values.insert(":from".to_string(), AttributeValue::S(iso8601(from)));
+            values.insert(":to".to_string(), AttributeValue::S(iso8601(to)));
+            format!(" AND {} BETWEEN :from AND :to", CREATIONTIME_FIELD_NAME)
+        }
+        (Some(from), None) => {
+            values.insert(":from".to_string(), AttributeValue::S(iso8601(from)));
+            format!(" AND {} >= :from", CREATIONTIME_FIELD_NAME)
+        }
+        (None, Some(to)) => {
+            values.insert(":to".to_string(), AttributeValue::S(iso8601(to)));
+            format!(" AND {} <= :to", CREATIONTIME_FIELD_NAME)
+        }
+        (None, None) => String::new(),
+    };
+    (condition, values)
 }
 
 fn iso8601(st: &DateTime<Utc>) -> String {
@@ -865,39 +1326,34 @@ fn mapping_from_doc_to_user(doc: &HashMap<String, AttributeValue>, user: &mut Us
         }
         None => {}
     }
+    // users created before email verification existed have no flag and keep full access
+    let email_verified_t = doc.get(EMAIL_VERIFIED_FIELD_NAME);
+    match email_verified_t {
+        None => user.set_email_verified(true),
+        Some(verified) => user.set_email_verified(*verified.as_bool().unwrap_or(&false)),
+    }
 }
 
-fn cypher_text(text: &String, key: &String) -> ResultE<String> {
-    let salt = Uuid::new_v4().to_string();
-
-    let config = argon2::Config {
-        variant: argon2::Variant::Argon2i,
-        version: argon2::Version::Version13,
-        mem_cost: 65536,
-        time_cost: 10,
-        lanes: 4,
-        //thread_mode: argon2::ThreadMode::Parallel,
-        secret: key.as_bytes(),
-        ad: &[],
-        hash_length: 32,
-    };
-
-    let hash = argon2::hash_encoded(text.as_bytes(), salt.as_bytes(), &config).unwrap();
-
+async fn cypher_text(text: &String, key: &String, params: &PasswordHashParams) -> ResultE<String> {
+    let text = text.clone();
+    let key = key.clone();
+    let params = params.clone();
+    let hash = spawn_blocking(move || hash_password(&text, &key, &params))
+        .await
+        .map_err(|e| UserPasswordError(e.to_string()))??;
     Ok(hash)
 }
 
-fn cypher_check(text_to_check: &String, already_ciphered: &String, key: &String) -> ResultE<bool> {
-    let matches = argon2::verify_encoded_ext(
-        &already_ciphered,
-        text_to_check.as_bytes(),
-        key.as_bytes(),
-        b"",
-    )
-    .unwrap();
-
-    match matches {
-        true => Ok(true),
-        _ => Ok(false),
-    }
+async fn cypher_check(
+    text_to_check: &String,
+    already_ciphered: &String,
+    key: &String,
+) -> ResultE<bool> {
+    let text = text_to_check.clone();
+    let ciphered = already_ciphered.clone();
+    let key = key.clone();
+    let matches = spawn_blocking(move || verify_password(&text, &ciphered, &key))
+        .await
+        .map_err(|e| UserPasswordError(e.to_string()))??;
+    Ok(matches)
 }
@@ -0,0 +1,134 @@
+use crate::errors::api_keys::{ApiKeyError, ApiKeyNoExistsError};
+use crate::models::api_key::{
+    api_key_visible_prefix, generate_api_key, hash_api_key, ApiKey, ApiKeyScope, API_KEY_PREFIX,
+};
+use crate::repositories::api_keys::{ApiKeyRepository, ApiKeysRepo};
+use async_trait::async_trait;
+use chrono::Utc;
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+pub const MAX_API_KEYS_PER_USER: usize = 10;
+
+#[async_trait]
+pub trait ApiKeyManipulation {
+    // the raw key goes back to the caller and is never stored
+    async fn create(
+        &self,
+        user_id: &String,
+        name: &String,
+        scopes: &Vec<ApiKeyScope>,
+    ) -> ResultE<(ApiKey, String)>;
+    async fn get_by_user(&self, user_id: &String) -> ResultE<Vec<ApiKey>>;
+    async fn revoke(&self, user_id: &String, key_id: &String) -> ResultE<()>;
+    async fn revoke_all(&self, user_id: &String) -> ResultE<()>;
+    async fn authenticate(&self, raw: &String) -> ResultE<ApiKey>;
+}
+
+#[derive(Debug)]
+pub struct ApiKeyService {
+    repository: ApiKeysRepo,
+}
+
+impl ApiKeyService {
+    pub fn new(repo: ApiKeysRepo) -> ApiKeyService {
+        ApiKeyService { repository: repo }
+    }
+}
+
+#[async_trait]
+impl ApiKeyManipulation for ApiKeyService {
+    async fn create(
+        &self,
+        user_id: &String,
+        name: &String,
+        scopes: &Vec<ApiKeyScope>,
+    ) -> ResultE<(ApiKey, String)> {
+        if scopes.is_empty() {
+            return Err(ApiKeyError("at least one scope is needed".to_string()).into());
+        }
+        let active = self
+            .repository
+            .get_by_user(user_id)
+            .await?
+            .into_iter()
+            .filter(|k| !k.revoked())
+            .count();
+        if active >= MAX_API_KEYS_PER_USER {
+            return Err(ApiKeyError(format!(
+                "no more than {} active keys, revoke one first",
+                MAX_API_KEYS_PER_USER
+            ))
+            .into());
+        }
+
+        let raw = generate_api_key();
+        let mut unique: Vec<ApiKeyScope> = Vec::new();
+        for scope in scopes {
+            if !unique.contains(scope) {
+                unique.push(scope.clone());
+            }
+        }
+        let mut key = ApiKey::new();
+        key.set_key_hash(&hash_api_key(&raw));
+        key.set_user_id(user_id);
+        key.set_name(name);
+        key.set_prefix(&api_key_visible_prefix(&raw));
+        key.set_scopes(&unique);
+        self.repository.add(&key).await?;
+        Ok((key, raw))
+    }
+
+    async fn get_by_user(&self, user_id: &String) -> ResultE<Vec<ApiKey>> {
+        self.repository.get_by_user(user_id).await
+    }
+
+    async fn revoke(&self, user_id: &String, key_id: &String) -> ResultE<()> {
+        let key = self
+            .repository
+            .get_by_user(user_id)
+            .await?
+            .into_iter()
+            .find(|k| k.key_id() == key_id && !k.revoked());
+        match key {
+            None => Err(ApiKeyNoExistsError(key_id.clone()).into()),
+            Some(key) => {
+                self.repository.revoke(key.key_hash()).await?;
+                Ok(())
+            }
+        }
+    }
+
+    async fn revoke_all(&self, user_id: &String) -> ResultE<()> {
+        let keys = self.repository.get_by_user(user_id).await?;
+        for key in keys.iter().filter(|k| !k.revoked()) {
+            self.repository.revoke(key.key_hash()).await?;
+        }
+        Ok(())
+    }
+
+    async fn authenticate(&self, raw: &String) -> ResultE<ApiKey> {
+        if !raw.starts_with(API_KEY_PREFIX) {
+            return Err(ApiKeyError("unknown key".to_string()).into());
+        }
+        let hash = hash_api_key(raw);
+        let key = match self.repository.get(&hash).await? {
+            Some(key) if !key.revoked() => key,
+            _ => return Err(ApiKeyError("unknown or revoked key".to_string()).into()),
+        };
+        // last use is informative only, a failure here mustn't refuse the call
+        if let Err(e) = self.repository.touch(&hash, &Utc::now()).await {
+            log::warn!("{}", e);
+        }
+        Ok(key)
+    }
+}
+
+impl Clone for ApiKeyService {
+    fn clone(&self) -> ApiKeyService {
+        let aux = ApiKeyService {
+            repository: self.repository.clone(),
+        };
+        return aux;
+    }
+}
@@ -0,0 +1,65 @@
+use crate::errors::audit::AuditParamNotAccepted;
+use crate::models::audit::{AuditEntry, AuditPage, AuditSearch};
+use crate::models::user_search::{SEARCH_PAGE_SIZE_MAX, SEARCH_PAGE_SIZE_MIN};
+use crate::repositories::audit::{AuditRepo, AuditRepository};
+use async_trait::async_trait;
+use chrono::NaiveDate;
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+#[async_trait]
+pub trait AuditManipulation {
+    async fn record(&self, entry: &AuditEntry) -> ResultE<()>;
+    async fn complete(&self, entry: &AuditEntry) -> ResultE<()>;
+    async fn search(&self, filter: &AuditSearch) -> ResultE<AuditPage>;
+}
+
+#[derive(Debug)]
+pub struct AuditService {
+    repository: AuditRepo,
+}
+
+impl AuditService {
+    pub fn new(repo: AuditRepo) -> AuditService {
+        AuditService { repository: repo }
+    }
+}
+
+#[async_trait]
+impl AuditManipulation for AuditService {
+    async fn record(&self, entry: &AuditEntry) -> ResultE<()> {
+        if entry.actor_id().is_empty() || entry.target_id().is_empty() {
+            return Err(AuditParamNotAccepted("actor and target are needed".to_string()).into());
+        }
+        self.repository.add(entry).await
+    }
+
+    async fn complete(&self, entry: &AuditEntry) -> ResultE<()> {
+        if entry.status().is_none() {
+            return Err(AuditParamNotAccepted("the outcome is needed".to_string()).into());
+        }
+        self.repository.complete(entry).await
+    }
+
+    async fn search(&self, filter: &AuditSearch) -> ResultE<AuditPage> {
+        if filter.page_size < SEARCH_PAGE_SIZE_MIN || filter.page_size > SEARCH_PAGE_SIZE_MAX {
+            return Err(AuditParamNotAccepted(format!(
+                "pageSize must be between {} and {}",
+                SEARCH_PAGE_SIZE_MIN, SEARCH_PAGE_SIZE_MAX
+            ))
+            .into());
+        }
+        if let Some(month) = &filter.month {
+            if NaiveDate::parse_from_str(format!("{}-01", month).as_str(), "%Y-%m-%d").is_err() {
+                return Err(AuditParamNotAccepted("month must be YYYY-MM".to_string()).into());
+            }
+        }
+        if let (Some(from), Some(to)) = (&filter.from, &filter.to) {
+            if from > to {
+                return Err(AuditParamNotAccepted("creation range".to_string()).into());
+            }
+        }
+        let res = self.repository.search(filter).await?;
+        Ok(res)
+    }
+}
@@ -0,0 +1,172 @@
+use std::str::FromStr;
+
+use crate::errors::devices::{
+    DeviceAlreadyExistsError, DeviceKeyError, DeviceNoExistsError, DeviceSignatureError,
+};
+use crate::models::device::{
+    device_challenge_message, DeviceCredential, DeviceKeyAlgorithm, VerifiedDevice,
+};
+use crate::repositories::devices::{DeviceRepository, DevicesRepo};
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+pub const DEVICE_CHALLENGE_EXP_MINUTES: i64 = 5;
+
+#[async_trait]
+pub trait DeviceManipulation {
+    async fn register(
+        &self,
+        user_id: &String,
+        device_id: &String,
+        algorithm: &String,
+        public_key: &String,
+    ) -> ResultE<DeviceCredential>;
+    async fn pair_legacy(
+        &self,
+        user_id: &String,
+        device_id: &String,
+        algorithm: &String,
+        public_key: &String,
+    ) -> ResultE<DeviceCredential>;
+    async fn get_by_user(&self, user_id: &String) -> ResultE<Vec<DeviceCredential>>;
+    async fn revoke(&self, user_id: &String, device_id: &String) -> ResultE<()>;
+    async fn revoke_all(&self, user_id: &String) -> ResultE<()>;
+    async fn challenge(&self, device_id: &String) -> ResultE<String>;
+    async fn verify(
+        &self,
+        device_id: &String,
+        challenge: &String,
+        signature: &String,
+    ) -> ResultE<VerifiedDevice>;
+}
+
+#[derive(Debug)]
+pub struct DeviceService {
+    repository: DevicesRepo,
+}
+
+impl DeviceService {
+    pub fn new(repo: DevicesRepo) -> DeviceService {
+        DeviceService { repository: repo }
+    }
+}
+
+// checked before anything is stored, so signup can fail early on a bad key
+pub fn check_device_key(algorithm: &String, public_key: &String) -> ResultE<DeviceKeyAlgorithm> {
+    let algorithm = DeviceKeyAlgorithm::from_str(algorithm)
+        .map_err(|_| DeviceKeyError(format!("algorithm {} not supported", algorithm)))?;
+    if !algorithm.is_valid_public_key(public_key) {
+        return Err(DeviceKeyError(format!("not a base64 DER {} public key", algorithm)).into());
+    }
+    Ok(algorithm)
+}
+
+#[async_trait]
+impl DeviceManipulation for DeviceService {
+    async fn register(
+        &self,
+        user_id: &String,
+        device_id: &String,
+        algorithm: &String,
+        public_key: &String,
+    ) -> ResultE<DeviceCredential> {
+        let algorithm = check_device_key(algorithm, public_key)?;
+        let mut device = DeviceCredential::new();
+        device.set_device_id(device_id);
+        device.set_user_id(user_id);
+        device.set_algorithm(&algorithm);
+        device.set_public_key(public_key);
+        self.repository.add(&device).await?;
+        Ok(device)
+    }
+
+    // Accounts created before devices had keys only know the bare device string. Such a device
+    // can pair a key once without a session, as long as it has never been paired and the user
+    // has no credential at all; from then on it logs in through the challenge.
+    async fn pair_legacy(
+        &self,
+        user_id: &String,
+        device_id: &String,
+        algorithm: &String,
+        public_key: &String,
+    ) -> ResultE<DeviceCredential> {
+        check_device_key(algorithm, public_key)?;
+        if self.repository.get(device_id).await?.is_some()
+            || !self.repository.get_by_user(user_id).await?.is_empty()
+        {
+            return Err(DeviceAlreadyExistsError(device_id.clone()).into());
+        }
+        self.register(user_id, device_id, algorithm, public_key)
+            .await
+    }
+
+    async fn get_by_user(&self, user_id: &String) -> ResultE<Vec<DeviceCredential>> {
+        self.repository.get_by_user(user_id).await
+    }
+
+    async fn revoke(&self, user_id: &String, device_id: &String) -> ResultE<()> {
+        match self.repository.get(device_id).await? {
+            Some(device) if device.user_id() == user_id && !device.revoked() => {
+                self.repository.revoke(device_id).await?;
+                Ok(())
+            }
+            _ => Err(DeviceNoExistsError(device_id.clone()).into()),
+        }
+    }
+
+    async fn revoke_all(&self, user_id: &String) -> ResultE<()> {
+        let devices = self.repository.get_by_user(user_id).await?;
+        for device in devices.iter().filter(|d| !d.revoked()) {
+            self.repository.revoke(device.device_id()).await?;
+        }
+        Ok(())
+    }
+
+    // Unknown devices get a challenge as well, nobody learns which devices are paired, but only
+    // active ones get it stored. Asking again doesn't spend the challenges already handed out.
+    async fn challenge(&self, device_id: &String) -> ResultE<String> {
+        let challenge = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        let expires_at = Utc::now() + Duration::minutes(DEVICE_CHALLENGE_EXP_MINUTES);
+        if let Some(device) = self.repository.get(device_id).await? {
+            if !device.revoked() {
+                self.repository
+                    .add_challenge(device_id, &challenge, &expires_at)
+                    .await?;
+            }
+        }
+        Ok(challenge)
+    }
+
+    async fn verify(
+        &self,
+        device_id: &String,
+        challenge: &String,
+        signature: &String,
+    ) -> ResultE<VerifiedDevice> {
+        let device = match self.repository.get(device_id).await? {
+            Some(device) if !device.revoked() => device,
+            _ => return Err(DeviceSignatureError("unknown device".to_string()).into()),
+        };
+        if !device.verify(&device_challenge_message(device_id, challenge), signature) {
+            return Err(DeviceSignatureError("signature doesn't match the device key".to_string()).into());
+        }
+        if !self.repository.consume_challenge(device_id, challenge).await? {
+            return Err(
+                DeviceSignatureError("challenge unknown, expired or already used".to_string()).into(),
+            );
+        }
+        Ok(VerifiedDevice::new(&device))
+    }
+}
+
+impl Clone for DeviceService {
+    fn clone(&self) -> DeviceService {
+        let aux = DeviceService {
+            repository: self.repository.clone(),
+        };
+        return aux;
+    }
+}
@@ -0,0 +1,67 @@
+use crate::models::jwt_key::{JwtKey, JwtKeyStatus};
+use crate::repositories::jwt_keys::{JwtKeyRepository, JwtKeysRepo};
+use async_trait::async_trait;
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+#[async_trait]
+pub trait JwtKeyManipulation {
+    async fn get_verification_key(&self, kid: &String) -> ResultE<Option<JwtKey>>;
+    async fn get_verification_keys(&self) -> ResultE<Vec<JwtKey>>;
+    async fn rotate(&self, new_key: &JwtKey) -> ResultE<()>;
+}
+
+#[derive(Debug)]
+pub struct JwtKeyService {
+    repository: JwtKeysRepo,
+}
+
+impl JwtKeyService {
+    pub fn new(repo: JwtKeysRepo) -> JwtKeyService {
+        JwtKeyService { repository: repo }
+    }
+}
+
+#[async_trait]
+impl JwtKeyManipulation for JwtKeyService {
+    async fn get_verification_key(&self, kid: &String) -> ResultE<Option<JwtKey>> {
+        let res = self.repository.get_by_kid(kid).await?;
+        Ok(res.filter(|key| key.can_verify()))
+    }
+
+    async fn get_verification_keys(&self) -> ResultE<Vec<JwtKey>> {
+        let mut res = self.repository.get_all().await?;
+        res.retain(|key| key.can_verify());
+        res.sort_by(|a, b| b.creation_time().cmp(a.creation_time()));
+        Ok(res)
+    }
+
+    // the new key signs from now on, the current one only verifies until the next
+    // rotation and the previous one is retired
+    async fn rotate(&self, new_key: &JwtKey) -> ResultE<()> {
+        let existing = self.repository.get_all().await?;
+
+        let mut key = new_key.clone();
+        key.set_status(&JwtKeyStatus::Current);
+        self.repository.add(&key).await?;
+
+        for old in existing.iter() {
+            let demoted = match old.status() {
+                JwtKeyStatus::Current => JwtKeyStatus::Previous,
+                JwtKeyStatus::Previous => JwtKeyStatus::Retired,
+                JwtKeyStatus::Retired => continue,
+            };
+            self.repository.update_status(old.kid(), &demoted).await?;
+        }
+        Ok(())
+    }
+}
+
+impl Clone for JwtKeyService {
+    fn clone(&self) -> JwtKeyService {
+        let aux = JwtKeyService {
+            repository: self.repository.clone(),
+        };
+        return aux;
+    }
+}
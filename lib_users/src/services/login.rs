@@ -1,6 +1,9 @@
 use crate::errors::users::UserNoExistsError;
 use crate::errors::users::UserStatusError;
+use crate::errors::users::UserNotVerifiedError;
+use crate::models::device::VerifiedDevice;
 use crate::models::user::UserRoles;
+use crate::models::wallet::VerifiedWallet;
 use async_trait::async_trait;
 //use tracing::instrument;
 
@@ -19,8 +22,8 @@ pub struct LoginInfo {
 pub trait LoginOps {
     async fn login(
         &self,
-        device: &Option<String>,
-        wallet: &Option<String>,
+        device: &Option<VerifiedDevice>,
+        wallet: &Option<VerifiedWallet>,
         email: &Option<String>,
         passw: &Option<String>,
     ) -> ResultE<LoginInfo>;
@@ -31,8 +34,8 @@ impl LoginOps for UsersService {
     //#[instrument]
     async fn login(
         &self,
-        device: &Option<String>,
-        wallet: &Option<String>,
+        device: &Option<VerifiedDevice>,
+        wallet: &Option<VerifiedWallet>,
         email: &Option<String>,
         passw: &Option<String>,
     ) -> ResultE<LoginInfo> {
@@ -42,7 +45,8 @@ impl LoginOps for UsersService {
         };
         let usr;
         if let Some(dvc) = device {
-            usr = self.get_by_device(dvc).await?;
+            // the device answered its challenge, the credential already names the user
+            usr = self.get_by_id(dvc.user_id()).await?;
         } else if let Some(eml) = email {
             // && let Some(pwd) = passw {
             match passw {
@@ -54,13 +58,21 @@ impl LoginOps for UsersService {
                 }
             }
         } else if let Some(wall) = wallet {
-            usr = self.get_by_wallet(wall).await?
+            // only a signed SIWE message gets here; older accounts stored the address lowercased
+            usr = match self.get_by_wallet(wall.address()).await {
+                Err(e) if e.downcast_ref::<UserNoExistsError>().is_some() => {
+                    self.get_by_wallet(&wall.address().to_lowercase()).await?
+                }
+                other => other?,
+            };
         } else {
             return Err(UserNoExistsError("not correct parameters".to_string()).into());
         }
 
         if usr.status().is_disabled() {
             return Err(UserStatusError("user has been disabled".to_string()).into());
+        } else if !usr.is_verified() {
+            return Err(UserNotVerifiedError("check your inbox to activate the account".to_string()).into());
         } else {
             llt.user_id = usr.user_id().clone();
             llt.roles = usr.roles().clone();
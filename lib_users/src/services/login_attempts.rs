@@ -0,0 +1,183 @@
+use crate::errors::login_attempts::{LoginThrottledError, UserLockedError};
+use crate::models::login_attempt::{
+    email_attempt_key, ip_attempt_key, mfa_attempt_key, LoginAttempt,
+};
+use crate::repositories::login_attempts::{LoginAttemptRepository, LoginAttemptsRepo};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+// failures free of any delay, after them each one doubles the wait up to MAX_DELAY_SECONDS
+pub const FREE_FAILED_LOGINS: u32 = 3;
+pub const MAX_DELAY_SECONDS: i64 = 60;
+// reaching these counts locks the email (or the ip) for LOCKOUT_MINUTES
+pub const MAX_FAILED_LOGINS_BY_EMAIL: u32 = 10;
+pub const MAX_FAILED_LOGINS_BY_IP: u32 = 100;
+// wrong codes at the second step lock it for the user, whoever knows the password
+pub const MAX_FAILED_MFA_CODES: u32 = 5;
+pub const LOCKOUT_MINUTES: i64 = 30;
+// counters start over after this long without failures
+pub const FAILED_LOGINS_WINDOW_MINUTES: i64 = 60;
+
+#[async_trait]
+pub trait LoginAttemptManipulation {
+    // fails with UserLockedError or LoginThrottledError when the attempt must not even be tried,
+    // a request whose source ip is unknown is counted by email only, never in a shared bucket
+    async fn check(&self, email: &String, ip: &Option<String>) -> ResultE<()>;
+    // true when this failure has just locked the email
+    async fn failure(&self, email: &String, ip: &Option<String>) -> ResultE<bool>;
+    async fn success(&self, email: &String) -> ResultE<()>;
+    // the same for the second step of a login, counted by user
+    async fn check_mfa(&self, user_id: &String, ip: &Option<String>) -> ResultE<()>;
+    async fn mfa_failure(&self, user_id: &String, ip: &Option<String>) -> ResultE<bool>;
+    async fn mfa_success(&self, user_id: &String) -> ResultE<()>;
+    async fn unlock(&self, email: &String) -> ResultE<()>;
+    async fn get_locked(&self) -> ResultE<Vec<LoginAttempt>>;
+}
+
+#[derive(Debug)]
+pub struct LoginAttemptService {
+    repository: LoginAttemptsRepo,
+}
+
+impl LoginAttemptService {
+    pub fn new(repo: LoginAttemptsRepo) -> LoginAttemptService {
+        LoginAttemptService { repository: repo }
+    }
+
+    // a counter whose last failure is out of the window is as good as gone,
+    // dynamodb ttl can take a while to purge it
+    async fn current(&self, key: &String, now: &DateTime<Utc>) -> ResultE<Option<LoginAttempt>> {
+        match self.repository.get(key).await? {
+            Some(attempt)
+                if !attempt.is_locked_at(now)
+                    && *attempt.last_failure()
+                        < *now - Duration::minutes(FAILED_LOGINS_WINDOW_MINUTES) =>
+            {
+                self.repository.remove(key).await?;
+                Ok(None)
+            }
+            other => Ok(other),
+        }
+    }
+
+    async fn check_ip(&self, ip: &Option<String>, now: &DateTime<Utc>) -> ResultE<()> {
+        let ip = match ip {
+            None => return Ok(()),
+            Some(ip) => ip,
+        };
+        if let Some(attempt) = self.current(&ip_attempt_key(ip), now).await? {
+            if attempt.is_locked_at(now) {
+                return Err(LoginThrottledError(format!(
+                    "try again in {} minutes",
+                    LOCKOUT_MINUTES
+                ))
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    async fn count_ip(&self, ip: &Option<String>, now: &DateTime<Utc>) -> ResultE<()> {
+        if let Some(ip) = ip {
+            self.count(&ip_attempt_key(ip), MAX_FAILED_LOGINS_BY_IP, now)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn count(&self, key: &String, max: u32, now: &DateTime<Utc>) -> ResultE<bool> {
+        self.current(key, now).await?;
+        let attempt = self
+            .repository
+            .add_failure(key, now, &(*now + Duration::minutes(FAILED_LOGINS_WINDOW_MINUTES)))
+            .await?;
+        if attempt.failures() >= max && !attempt.is_locked_at(now) {
+            self.repository
+                .lock(key, &(*now + Duration::minutes(LOCKOUT_MINUTES)))
+                .await?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
+
+#[async_trait]
+impl LoginAttemptManipulation for LoginAttemptService {
+    async fn check(&self, email: &String, ip: &Option<String>) -> ResultE<()> {
+        let now = Utc::now();
+        if let Some(attempt) = self.current(&email_attempt_key(email), &now).await? {
+            if attempt.is_locked_at(&now) {
+                return Err(UserLockedError(
+                    "too many failed logins, use the link sent by email or wait".to_string(),
+                )
+                .into());
+            }
+            let retry = attempt.retry_after(FREE_FAILED_LOGINS, MAX_DELAY_SECONDS);
+            if retry > now {
+                return Err(LoginThrottledError(format!(
+                    "try again in {} seconds",
+                    (retry - now).num_seconds() + 1
+                ))
+                .into());
+            }
+        }
+        self.check_ip(ip, &now).await
+    }
+
+    async fn failure(&self, email: &String, ip: &Option<String>) -> ResultE<bool> {
+        let now = Utc::now();
+        self.count_ip(ip, &now).await?;
+        self.count(&email_attempt_key(email), MAX_FAILED_LOGINS_BY_EMAIL, &now)
+            .await
+    }
+
+    // only the email counter is cleared, one good password mustn't wipe what an ip has tried
+    async fn success(&self, email: &String) -> ResultE<()> {
+        self.repository.remove(&email_attempt_key(email)).await
+    }
+
+    async fn check_mfa(&self, user_id: &String, ip: &Option<String>) -> ResultE<()> {
+        let now = Utc::now();
+        if let Some(attempt) = self.current(&mfa_attempt_key(user_id), &now).await? {
+            if attempt.is_locked_at(&now) {
+                return Err(UserLockedError(format!(
+                    "too many wrong codes, try again in {} minutes",
+                    LOCKOUT_MINUTES
+                ))
+                .into());
+            }
+        }
+        self.check_ip(ip, &now).await
+    }
+
+    async fn mfa_failure(&self, user_id: &String, ip: &Option<String>) -> ResultE<bool> {
+        let now = Utc::now();
+        self.count_ip(ip, &now).await?;
+        self.count(&mfa_attempt_key(user_id), MAX_FAILED_MFA_CODES, &now)
+            .await
+    }
+
+    async fn mfa_success(&self, user_id: &String) -> ResultE<()> {
+        self.repository.remove(&mfa_attempt_key(user_id)).await
+    }
+
+    async fn unlock(&self, email: &String) -> ResultE<()> {
+        self.repository.remove(&email_attempt_key(email)).await
+    }
+
+    async fn get_locked(&self) -> ResultE<Vec<LoginAttempt>> {
+        let locked = self.repository.get_locked(&Utc::now()).await?;
+        Ok(locked.into_iter().filter(|a| a.email().is_some()).collect())
+    }
+}
+
+impl Clone for LoginAttemptService {
+    fn clone(&self) -> LoginAttemptService {
+        let aux = LoginAttemptService {
+            repository: self.repository.clone(),
+        };
+        return aux;
+    }
+}
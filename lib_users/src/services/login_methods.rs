@@ -0,0 +1,267 @@
+use crate::errors::login_methods::{LoginMethodLastError, LoginMethodNotFoundError};
+use crate::errors::users::{UserAlreadyExistsError, UserNoExistsError, UserParamNotAccepted};
+use crate::models::device::VerifiedDevice;
+use crate::models::identity::{LinkedIdentity, VerifiedIdentity};
+use crate::models::login_method::{LoginMethodKind, UserLoginMethods};
+use crate::models::wallet::VerifiedWallet;
+use crate::repositories::devices::{DeviceRepository, DevicesRepo};
+use crate::repositories::identities::{IdentitiesRepo, IdentityRepository};
+use crate::repositories::users::{UserRepository, UsersRepo};
+use async_trait::async_trait;
+use validator::validate_email;
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+#[async_trait]
+pub trait LoginMethodManipulation {
+    async fn get(&self, user_id: &String) -> ResultE<UserLoginMethods>;
+    // before mailing the confirmation link, so nobody gets a link that can't work
+    async fn check_email_available(&self, user_id: &String, email: &String) -> ResultE<()>;
+    // only once the confirmation link has been followed, the email is linked as verified
+    async fn link_email(&self, user_id: &String, email: &String) -> ResultE<()>;
+    // only a device that answered a challenge with the key paired to this user
+    async fn link_device(&self, user_id: &String, device: &VerifiedDevice) -> ResultE<()>;
+    async fn link_wallet(&self, user_id: &String, wallet: &VerifiedWallet) -> ResultE<()>;
+    async fn link_identity(&self, user_id: &String, identity: &VerifiedIdentity) -> ResultE<()>;
+    // email, device or wallet; the device key is revoked along with it
+    async fn unlink(&self, user_id: &String, kind: &LoginMethodKind) -> ResultE<()>;
+    async fn unlink_identity(
+        &self,
+        user_id: &String,
+        issuer: &String,
+        subject: &String,
+    ) -> ResultE<()>;
+    // an account merged into this one hands over its device, paired device credentials, wallet
+    // and identities; a device or wallet clashing with the one already here is dropped
+    async fn take_over(&self, user_id: &String, from_user_id: &String) -> ResultE<()>;
+}
+
+#[derive(Debug)]
+pub struct LoginMethodService {
+    users: UsersRepo,
+    identities: IdentitiesRepo,
+    devices: DevicesRepo,
+}
+
+impl LoginMethodService {
+    pub fn new(
+        users: UsersRepo,
+        identities: IdentitiesRepo,
+        devices: DevicesRepo,
+    ) -> LoginMethodService {
+        LoginMethodService {
+            users,
+            identities,
+            devices,
+        }
+    }
+
+    // the account must keep a way in once the method is gone
+    fn check_not_last(methods: &UserLoginMethods, remaining: &UserLoginMethods) -> ResultE<()> {
+        if methods.count() > 0 && remaining.count() == 0 {
+            return Err(
+                LoginMethodLastError("link another one before removing this".to_string()).into(),
+            );
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl LoginMethodManipulation for LoginMethodService {
+    async fn get(&self, user_id: &String) -> ResultE<UserLoginMethods> {
+        let user = self.users.get_by_id(user_id).await?;
+        let password = match user.email() {
+            None => false,
+            Some(_) => self.users.has_password(user_id).await?,
+        };
+        let device_paired = match user.device() {
+            None => false,
+            Some(device) => matches!(
+                self.devices.get(device).await?,
+                Some(credential) if credential.user_id() == user_id && !credential.revoked()
+            ),
+        };
+        Ok(UserLoginMethods {
+            email: user.email().clone(),
+            email_verified: user.email().is_some() && user.email_verified(),
+            password,
+            device: user.device().clone(),
+            device_paired,
+            wallet: user.wallet_address().clone(),
+            identities: self.identities.get_by_user(user_id).await?,
+        })
+    }
+
+    async fn check_email_available(&self, user_id: &String, email: &String) -> ResultE<()> {
+        if !validate_email(email) {
+            return Err(UserParamNotAccepted("email".to_string()).into());
+        }
+        match self.users.get_by_email(email).await {
+            Ok(owner) if owner.user_id() != user_id => {
+                Err(UserAlreadyExistsError("email is already in use".to_string()).into())
+            }
+            Ok(_) => Ok(()),
+            Err(e) if e.downcast_ref::<UserNoExistsError>().is_some() => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn link_email(&self, user_id: &String, email: &String) -> ResultE<()> {
+        let mut user = self.users.get_by_id(user_id).await?;
+        if user.email().as_ref() == Some(email) && user.email_verified() {
+            return Ok(());
+        }
+        // a former address is replaced, its password stays
+        user.set_email(email);
+        user.set_email_verified(true);
+        self.users.update(user_id, &user).await
+    }
+
+    async fn link_device(&self, user_id: &String, device: &VerifiedDevice) -> ResultE<()> {
+        if device.user_id() != user_id {
+            return Err(UserAlreadyExistsError(
+                "device is paired with another account".to_string(),
+            )
+            .into());
+        }
+        let device = device.device_id();
+        let mut user = self.users.get_by_id(user_id).await?;
+        if user.device().as_ref() == Some(device) {
+            return Ok(());
+        }
+        user.set_device(device);
+        self.users.update(user_id, &user).await
+    }
+
+    async fn link_wallet(&self, user_id: &String, wallet: &VerifiedWallet) -> ResultE<()> {
+        let mut user = self.users.get_by_id(user_id).await?;
+        if user.wallet_address().as_ref() == Some(wallet.address()) {
+            return Ok(());
+        }
+        user.set_wallet_address(wallet.address());
+        self.users.update(user_id, &user).await
+    }
+
+    async fn link_identity(&self, user_id: &String, identity: &VerifiedIdentity) -> ResultE<()> {
+        let mut link = LinkedIdentity::new();
+        link.set_issuer(identity.issuer());
+        link.set_subject(identity.subject());
+        link.set_user_id(user_id);
+        link.set_email(identity.email());
+        if self.identities.add(&link).await? {
+            return Ok(());
+        }
+        match self
+            .identities
+            .get(identity.issuer(), identity.subject())
+            .await?
+        {
+            Some(current) if current.user_id() == user_id => Ok(()),
+            _ => Err(UserAlreadyExistsError(
+                "identity is already linked to another account".to_string(),
+            )
+            .into()),
+        }
+    }
+
+    async fn unlink(&self, user_id: &String, kind: &LoginMethodKind) -> ResultE<()> {
+        let methods = self.get(user_id).await?;
+        let mut remaining = methods.clone();
+        let present = match kind {
+            LoginMethodKind::Email => remaining.email.take().is_some(),
+            LoginMethodKind::Device => {
+                remaining.device_paired = false;
+                remaining.device.take().is_some()
+            }
+            LoginMethodKind::Wallet => remaining.wallet.take().is_some(),
+            LoginMethodKind::Oidc => {
+                return Err(
+                    UserParamNotAccepted("identities go by issuer and subject".to_string()).into(),
+                )
+            }
+        };
+        if !present {
+            return Err(LoginMethodNotFoundError(kind.to_string()).into());
+        }
+        LoginMethodService::check_not_last(&methods, &remaining)?;
+        self.users.remove_login(user_id, kind).await?;
+        if let (LoginMethodKind::Device, Some(device)) = (kind, &methods.device) {
+            if methods.device_paired {
+                self.devices.revoke(device).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn unlink_identity(
+        &self,
+        user_id: &String,
+        issuer: &String,
+        subject: &String,
+    ) -> ResultE<()> {
+        let methods = self.get(user_id).await?;
+        let mut remaining = methods.clone();
+        let before = remaining.identities.len();
+        remaining
+            .identities
+            .retain(|identity| identity.issuer() != issuer || identity.subject() != subject);
+        if remaining.identities.len() == before {
+            return Err(LoginMethodNotFoundError(format!("{} at {}", subject, issuer)).into());
+        }
+        LoginMethodService::check_not_last(&methods, &remaining)?;
+        self.identities.remove(issuer, subject).await
+    }
+
+    async fn take_over(&self, user_id: &String, from_user_id: &String) -> ResultE<()> {
+        let from = self.get(from_user_id).await?;
+        let mut user = self.users.get_by_id(user_id).await?;
+        let mut changed = false;
+
+        // the old rows go first, otherwise the values would count as duplicates
+        if let Some(device) = &from.device {
+            self.users
+                .remove_login(from_user_id, &LoginMethodKind::Device)
+                .await?;
+            if user.device().is_none() {
+                user.set_device(device);
+                changed = true;
+            } else {
+                log::info!("device of {} dropped, {} has one", from_user_id, user_id);
+            }
+        }
+        if let Some(wallet) = &from.wallet {
+            self.users
+                .remove_login(from_user_id, &LoginMethodKind::Wallet)
+                .await?;
+            if user.wallet_address().is_none() {
+                user.set_wallet_address(wallet);
+                changed = true;
+            } else {
+                log::info!("wallet of {} dropped, {} has one", from_user_id, user_id);
+            }
+        }
+        if changed {
+            self.users.update(user_id, &user).await?;
+        }
+
+        // the credentials sign the device logins, they must resolve to this user from now on
+        for device in self.devices.get_by_user(from_user_id).await? {
+            if !device.revoked() {
+                self.devices
+                    .reassign(device.device_id(), from_user_id, user_id)
+                    .await?;
+            }
+        }
+
+        for identity in from.identities {
+            self.identities
+                .remove(identity.issuer(), identity.subject())
+                .await?;
+            let mut link = identity.clone();
+            link.set_user_id(user_id);
+            self.identities.add(&link).await?;
+        }
+        Ok(())
+    }
+}
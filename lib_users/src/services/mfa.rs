@@ -0,0 +1,140 @@
+use crate::errors::mfa::{MfaAlreadyEnabledError, MfaCodeError, MfaNotEnabledError};
+use crate::models::mfa::{
+    generate_recovery_code, generate_totp_secret, hash_recovery_code, MfaCredential,
+};
+use crate::models::user::UserRoles;
+use crate::repositories::mfa::{MfaRepo, MfaRepository};
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::Serialize;
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+pub const MFA_ISSUER: &str = "truly.video";
+pub const MFA_RECOVERY_CODES: usize = 10;
+// time to type the code once the password has been accepted
+pub const MFA_CHALLENGE_EXP_MINUTES: i64 = 5;
+
+// staff can read personal data and change other accounts, a password alone isn't enough for them
+pub fn mfa_required(roles: &Vec<UserRoles>) -> bool {
+    roles.iter().any(|role| role.is_staff())
+}
+
+// what the user sees once, when enrolling: nothing of it can be read again later
+#[derive(Clone, Serialize, Debug)]
+pub struct MfaEnrollment {
+    pub secret: String,
+    pub uri: String,
+    pub recovery_codes: Vec<String>,
+}
+
+#[async_trait]
+pub trait MfaManipulation {
+    async fn enroll(&self, user_id: &String, account: &String) -> ResultE<MfaEnrollment>;
+    async fn confirm(&self, user_id: &String, code: &String) -> ResultE<()>;
+    async fn is_enabled(&self, user_id: &String) -> ResultE<bool>;
+    // accepts a current totp code or one of the recovery codes, each works once
+    async fn verify(&self, user_id: &String, code: &String) -> ResultE<()>;
+    async fn disable(&self, user_id: &String, code: &String) -> ResultE<()>;
+    // no code asked, only for accounts being removed
+    async fn reset(&self, user_id: &String) -> ResultE<()>;
+}
+
+#[derive(Debug)]
+pub struct MfaService {
+    repository: MfaRepo,
+}
+
+impl MfaService {
+    pub fn new(repo: MfaRepo) -> MfaService {
+        MfaService { repository: repo }
+    }
+}
+
+#[async_trait]
+impl MfaManipulation for MfaService {
+    async fn enroll(&self, user_id: &String, account: &String) -> ResultE<MfaEnrollment> {
+        let recovery_codes: Vec<String> =
+            (0..MFA_RECOVERY_CODES).map(|_| generate_recovery_code()).collect();
+
+        let mut credential = MfaCredential::new();
+        credential.set_user_id(user_id);
+        credential.set_secret(&generate_totp_secret());
+        credential.set_recovery_codes(
+            &recovery_codes.iter().map(|code| hash_recovery_code(code)).collect(),
+        );
+        self.repository.add(&credential).await?;
+
+        Ok(MfaEnrollment {
+            secret: credential.secret().clone(),
+            uri: credential.provisioning_uri(MFA_ISSUER, account),
+            recovery_codes,
+        })
+    }
+
+    async fn confirm(&self, user_id: &String, code: &String) -> ResultE<()> {
+        let credential = match self.repository.get(user_id).await? {
+            None => return Err(MfaNotEnabledError("enroll first".to_string()).into()),
+            Some(credential) if credential.enabled() => {
+                return Err(MfaAlreadyEnabledError(user_id.clone()).into())
+            }
+            Some(credential) => credential,
+        };
+        let step = match credential.check_code(code, &Utc::now()) {
+            None => return Err(MfaCodeError("check the clock of your phone".to_string()).into()),
+            Some(step) => step,
+        };
+        if !self.repository.use_step(user_id, step).await? || !self.repository.enable(user_id).await? {
+            return Err(MfaCodeError("code already used".to_string()).into());
+        }
+        Ok(())
+    }
+
+    async fn is_enabled(&self, user_id: &String) -> ResultE<bool> {
+        Ok(self
+            .repository
+            .get(user_id)
+            .await?
+            .map(|credential| credential.enabled())
+            .unwrap_or(false))
+    }
+
+    async fn verify(&self, user_id: &String, code: &String) -> ResultE<()> {
+        let credential = match self.repository.get(user_id).await? {
+            Some(credential) if credential.enabled() => credential,
+            _ => return Err(MfaNotEnabledError(user_id.clone()).into()),
+        };
+        if let Some(step) = credential.check_code(code, &Utc::now()) {
+            if self.repository.use_step(user_id, step).await? {
+                return Ok(());
+            }
+            return Err(MfaCodeError("code already used, wait for the next one".to_string()).into());
+        }
+        if self
+            .repository
+            .use_recovery_code(user_id, &hash_recovery_code(code))
+            .await?
+        {
+            return Ok(());
+        }
+        Err(MfaCodeError("wrong code".to_string()).into())
+    }
+
+    async fn disable(&self, user_id: &String, code: &String) -> ResultE<()> {
+        self.verify(user_id, code).await?;
+        self.repository.remove(user_id).await
+    }
+
+    async fn reset(&self, user_id: &String) -> ResultE<()> {
+        self.repository.remove(user_id).await
+    }
+}
+
+impl Clone for MfaService {
+    fn clone(&self) -> MfaService {
+        let aux = MfaService {
+            repository: self.repository.clone(),
+        };
+        return aux;
+    }
+}
@@ -1,2 +1,13 @@
+pub mod api_keys;
+pub mod audit;
+pub mod devices;
+pub mod jwt_keys;
 pub mod login;
+pub mod login_attempts;
+pub mod login_methods;
+pub mod mfa;
+pub mod oidc_login;
+pub mod one_time_tokens;
+pub mod sessions;
 pub mod users;
+pub mod wallet_login;
@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::errors::identities::{OidcProviderError, OidcTokenError};
+use crate::errors::users::{UserNoExistsError, UserNotVerifiedError, UserStatusError};
+use crate::models::identity::{LinkedIdentity, OidcProvider, VerifiedIdentity};
+use crate::models::session::NoncePurpose;
+use crate::models::user::User;
+use crate::repositories::identities::{IdentitiesRepo, IdentityRepository};
+use crate::repositories::sessions::{SessionRepository, SessionsRepo};
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{decode, decode_header, jwk::JwkSet, Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use super::login::LoginInfo;
+use super::users::{UserManipulation, UsersService};
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+pub const OIDC_NONCE_EXP_MINUTES: i64 = 10;
+pub const OIDC_JWKS_TIMEOUT_SECONDS: u64 = 5;
+// an unknown kid refetches the provider's keys, but not more often than this
+pub const OIDC_JWKS_REFRESH_SECONDS: i64 = 60;
+
+// providers sign with asymmetric keys only, a token asking for HS256 is forged
+static ACCEPTED_ALGORITHMS: &[Algorithm] = &[
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::ES256,
+    Algorithm::ES384,
+    Algorithm::EdDSA,
+];
+
+#[derive(Debug, Deserialize)]
+struct IdTokenClaims {
+    iss: String,
+    sub: String,
+    #[serde(default)]
+    email: Option<String>,
+    // a bool at google, the string "true" at apple
+    #[serde(default)]
+    email_verified: Option<Value>,
+    #[serde(default)]
+    nonce: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UnverifiedIssuer {
+    iss: String,
+}
+
+#[async_trait]
+pub trait OidcLoginManipulation {
+    // the client hands it to the provider, it comes back inside the ID token
+    async fn nonce(&self) -> ResultE<String>;
+    // signature, issuer, audience, expiry and nonce, then spends the nonce
+    async fn verify(&self, id_token: &String, nonce: &String) -> ResultE<VerifiedIdentity>;
+    // the linked user, or a new one the first time the identity shows up
+    async fn login(
+        &self,
+        user_service: &UsersService,
+        identity: &VerifiedIdentity,
+    ) -> ResultE<LoginInfo>;
+    async fn get_by_user(&self, user_id: &String) -> ResultE<Vec<LinkedIdentity>>;
+    async fn unlink_all(&self, user_id: &String) -> ResultE<()>;
+}
+
+#[derive(Debug)]
+pub struct OidcLoginService {
+    identities: IdentitiesRepo,
+    sessions: SessionsRepo,
+    providers: Vec<OidcProvider>,
+    http: reqwest::Client,
+    // by jwks uri, with the time they were fetched
+    jwks: RwLock<HashMap<String, (JwkSet, DateTime<Utc>)>>,
+}
+
+impl OidcLoginService {
+    pub fn new(
+        identities: IdentitiesRepo,
+        sessions: SessionsRepo,
+        providers: Vec<OidcProvider>,
+    ) -> OidcLoginService {
+        OidcLoginService {
+            identities,
+            sessions,
+            providers,
+            http: reqwest::Client::new(),
+            jwks: RwLock::new(HashMap::new()),
+        }
+    }
+
+    async fn keys(&self, provider: &OidcProvider, kid: &String) -> ResultE<JwkSet> {
+        let cached = self.jwks.read().unwrap().get(&provider.jwks_uri).cloned();
+        if let Some((keys, fetched_at)) = cached {
+            let fresh = Utc::now() - fetched_at < Duration::seconds(OIDC_JWKS_REFRESH_SECONDS);
+            if keys.find(kid).is_some() || fresh {
+                return Ok(keys);
+            }
+        }
+
+        let keys = self
+            .http
+            .get(&provider.jwks_uri)
+            .timeout(std::time::Duration::from_secs(OIDC_JWKS_TIMEOUT_SECONDS))
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status())
+            .map_err(|e| OidcProviderError(format!("{}: {}", provider.name, e)))?
+            .json::<JwkSet>()
+            .await
+            .map_err(|e| OidcProviderError(format!("{}: {}", provider.name, e)))?;
+        self.jwks
+            .write()
+            .unwrap()
+            .insert(provider.jwks_uri.clone(), (keys.clone(), Utc::now()));
+        Ok(keys)
+    }
+
+    // the user an identity seen for the first time belongs to: the account with the same
+    // email when both sides verified it, a new account otherwise
+    async fn link_or_create(
+        &self,
+        user_service: &UsersService,
+        identity: &VerifiedIdentity,
+    ) -> ResultE<User> {
+        let verified_email = match identity.email() {
+            Some(email) if identity.email_verified() => Some(email.clone()),
+            _ => None,
+        };
+        let mut existing = None;
+        let mut email_taken = false;
+        if let Some(email) = &verified_email {
+            match user_service.get_by_email(email).await {
+                Ok(user) => {
+                    // an unverified account could have been opened by anyone with that address
+                    email_taken = true;
+                    if user.email_verified() {
+                        existing = Some(user);
+                    }
+                }
+                Err(e) if e.downcast_ref::<UserNoExistsError>().is_some() => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        let mut created = false;
+        let user = match existing {
+            Some(user) => user,
+            None => {
+                let mut user = User::new();
+                if let (Some(email), false) = (&verified_email, email_taken) {
+                    user.set_email(email);
+                    user.set_email_verified(true);
+                }
+                user_service.add(&mut user, &None).await?;
+                created = true;
+                user
+            }
+        };
+
+        let mut link = LinkedIdentity::new();
+        link.set_issuer(identity.issuer());
+        link.set_subject(identity.subject());
+        link.set_user_id(user.user_id());
+        link.set_email(identity.email());
+        if self.identities.add(&link).await? {
+            return Ok(user);
+        }
+
+        // a login running at the same time linked it first
+        if created {
+            user_service.remove_by_id(user.user_id()).await?;
+        }
+        match self
+            .identities
+            .get(identity.issuer(), identity.subject())
+            .await?
+        {
+            None => Err(UserNoExistsError("identity unlinked meanwhile".to_string()).into()),
+            Some(link) => user_service.get_by_id(link.user_id()).await,
+        }
+    }
+}
+
+// only to pick the provider, the token is fully validated with its keys afterwards
+fn unverified_issuer(id_token: &str) -> ResultE<String> {
+    let payload = id_token
+        .split('.')
+        .nth(1)
+        .ok_or_else(|| OidcTokenError("malformed token".to_string()))?;
+    let json = general_purpose::URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .map_err(|e| OidcTokenError(e.to_string()))?;
+    let claims: UnverifiedIssuer =
+        serde_json::from_slice(&json).map_err(|e| OidcTokenError(e.to_string()))?;
+    Ok(claims.iss)
+}
+
+#[async_trait]
+impl OidcLoginManipulation for OidcLoginService {
+    async fn nonce(&self) -> ResultE<String> {
+        let nonce = Uuid::new_v4().simple().to_string();
+        let expires_at = Utc::now() + Duration::minutes(OIDC_NONCE_EXP_MINUTES);
+        self.sessions
+            .add_nonce(&NoncePurpose::Oidc, &nonce, &expires_at)
+            .await?;
+        Ok(nonce)
+    }
+
+    async fn verify(&self, id_token: &String, nonce: &String) -> ResultE<VerifiedIdentity> {
+        let header = decode_header(id_token).map_err(|e| OidcTokenError(e.to_string()))?;
+        if !ACCEPTED_ALGORITHMS.contains(&header.alg) {
+            return Err(OidcTokenError(format!("algorithm {:?} not accepted", header.alg)).into());
+        }
+        let kid = header
+            .kid
+            .ok_or_else(|| OidcTokenError("kid missing".to_string()))?;
+
+        let issuer = unverified_issuer(id_token)?;
+        let provider = self
+            .providers
+            .iter()
+            .find(|provider| provider.issuer == issuer)
+            .ok_or_else(|| OidcTokenError(format!("issuer {} not trusted", issuer)))?;
+
+        let keys = self.keys(provider, &kid).await?;
+        let jwk = keys
+            .find(&kid)
+            .ok_or_else(|| OidcTokenError(format!("signing key {} unknown", kid)))?;
+        let key = DecodingKey::from_jwk(jwk).map_err(|e| OidcTokenError(e.to_string()))?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[provider.issuer.as_str()]);
+        validation.set_audience(&provider.client_ids);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        let claims = decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map_err(|e| OidcTokenError(e.to_string()))?
+            .claims;
+
+        // the nonce we handed out, as is or sha256'd as apple's sdk puts it in the token
+        let token_nonce = claims
+            .nonce
+            .ok_or_else(|| OidcTokenError("nonce missing".to_string()))?;
+        if token_nonce != *nonce && token_nonce != hex::encode(Sha256::digest(nonce.as_bytes())) {
+            return Err(OidcTokenError("nonce mismatch".to_string()).into());
+        }
+        if !self
+            .sessions
+            .consume_nonce(&NoncePurpose::Oidc, nonce)
+            .await?
+        {
+            return Err(
+                OidcTokenError("nonce unknown, expired or already used".to_string()).into(),
+            );
+        }
+
+        let email_verified = match claims.email_verified {
+            Some(Value::Bool(verified)) => verified,
+            Some(Value::String(verified)) => verified == "true",
+            _ => false,
+        };
+        Ok(VerifiedIdentity::new(
+            &claims.iss,
+            &claims.sub,
+            &claims.email,
+            email_verified,
+        ))
+    }
+
+    async fn login(
+        &self,
+        user_service: &UsersService,
+        identity: &VerifiedIdentity,
+    ) -> ResultE<LoginInfo> {
+        let linked = self
+            .identities
+            .get(identity.issuer(), identity.subject())
+            .await?;
+        let user = match linked {
+            Some(link) => user_service.get_by_id(link.user_id()).await?,
+            None => self.link_or_create(user_service, identity).await?,
+        };
+
+        if user.status().is_disabled() {
+            return Err(UserStatusError("user has been disabled".to_string()).into());
+        } else if !user.is_verified() {
+            return Err(UserNotVerifiedError(
+                "check your inbox to activate the account".to_string(),
+            )
+            .into());
+        }
+        Ok(LoginInfo {
+            user_id: user.user_id().clone(),
+            roles: user.roles().clone(),
+        })
+    }
+
+    async fn get_by_user(&self, user_id: &String) -> ResultE<Vec<LinkedIdentity>> {
+        self.identities.get_by_user(user_id).await
+    }
+
+    async fn unlink_all(&self, user_id: &String) -> ResultE<()> {
+        for identity in self.identities.get_by_user(user_id).await? {
+            self.identities
+                .remove(identity.issuer(), identity.subject())
+                .await?;
+        }
+        Ok(())
+    }
+}
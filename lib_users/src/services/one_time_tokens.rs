@@ -0,0 +1,116 @@
+use crate::errors::one_time_tokens::{OneTimeTokenError, OneTimeTokenRateLimitError};
+use crate::models::one_time_token::{
+    check_one_time_token_signature, generate_one_time_token, hash_one_time_token, OneTimeToken,
+    OneTimeTokenPurpose,
+};
+use crate::repositories::one_time_tokens::{OneTimeTokenRepository, OneTimeTokensRepo};
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use lib_config::config::Config;
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+pub const EMAIL_VERIFICATION_EXP_HOURS: i64 = 24;
+pub const PASSWORD_RESET_EXP_HOURS: i64 = 1;
+pub const ACCOUNT_UNLOCK_EXP_HOURS: i64 = 24;
+pub const EMAIL_LINK_EXP_HOURS: i64 = 24;
+pub const ACCOUNT_MERGE_EXP_HOURS: i64 = 24;
+// a user can't ask for more than MAX_TOKENS_PER_HOUR emails of the same kind,
+// and never two of them in less than MIN_SECONDS_BETWEEN_TOKENS
+pub const MAX_TOKENS_PER_HOUR: usize = 3;
+pub const MIN_SECONDS_BETWEEN_TOKENS: i64 = 60;
+
+#[async_trait]
+pub trait OneTimeTokenManipulation {
+    async fn issue(
+        &self,
+        user_id: &String,
+        email: &String,
+        purpose: &OneTimeTokenPurpose,
+        exp_hours: i64,
+    ) -> ResultE<String>;
+    async fn consume(&self, token: &String, purpose: &OneTimeTokenPurpose) -> ResultE<OneTimeToken>;
+}
+
+#[derive(Debug)]
+pub struct OneTimeTokenService {
+    repository: OneTimeTokensRepo,
+    hmac_secret: String,
+}
+
+impl OneTimeTokenService {
+    pub fn new(repo: OneTimeTokensRepo, conf: &Config) -> OneTimeTokenService {
+        OneTimeTokenService {
+            repository: repo,
+            hmac_secret: conf.env_vars().hmac_secret().unwrap(),
+        }
+    }
+}
+
+#[async_trait]
+impl OneTimeTokenManipulation for OneTimeTokenService {
+    // the email is kept so the token only vouches for the address it was sent to
+    async fn issue(
+        &self,
+        user_id: &String,
+        email: &String,
+        purpose: &OneTimeTokenPurpose,
+        exp_hours: i64,
+    ) -> ResultE<String> {
+        let now = Utc::now();
+        let recent: Vec<OneTimeToken> = self
+            .repository
+            .get_by_user(user_id)
+            .await?
+            .into_iter()
+            .filter(|t| t.purpose() == purpose && *t.creation_time() > now - Duration::hours(1))
+            .collect();
+        if recent.len() >= MAX_TOKENS_PER_HOUR {
+            return Err(OneTimeTokenRateLimitError("try again in an hour".to_string()).into());
+        }
+        if recent
+            .iter()
+            .any(|t| *t.creation_time() > now - Duration::seconds(MIN_SECONDS_BETWEEN_TOKENS))
+        {
+            return Err(OneTimeTokenRateLimitError("try again in a minute".to_string()).into());
+        }
+
+        let raw = generate_one_time_token(purpose, &self.hmac_secret);
+        let mut token = OneTimeToken::new();
+        token.set_token_hash(&hash_one_time_token(&raw));
+        token.set_user_id(user_id);
+        token.set_purpose(purpose);
+        token.set_email(email);
+        token.set_expires_at(&(now + Duration::hours(exp_hours)));
+        self.repository.add(&token).await?;
+        Ok(raw)
+    }
+
+    async fn consume(&self, token: &String, purpose: &OneTimeTokenPurpose) -> ResultE<OneTimeToken> {
+        if !check_one_time_token_signature(token, purpose, &self.hmac_secret) {
+            return Err(OneTimeTokenError("unknown token".to_string()).into());
+        }
+        let hash = hash_one_time_token(token);
+        let current = match self.repository.get(&hash).await? {
+            Some(current) if current.purpose() == purpose => current,
+            _ => return Err(OneTimeTokenError("unknown token".to_string()).into()),
+        };
+        if current.is_expired_at(&Utc::now()) {
+            return Err(OneTimeTokenError("token expired, ask for a new one".to_string()).into());
+        }
+        if current.used() || !self.repository.mark_used(&hash).await? {
+            return Err(OneTimeTokenError("token already used".to_string()).into());
+        }
+        Ok(current)
+    }
+}
+
+impl Clone for OneTimeTokenService {
+    fn clone(&self) -> OneTimeTokenService {
+        let aux = OneTimeTokenService {
+            repository: self.repository.clone(),
+            hmac_secret: self.hmac_secret.clone(),
+        };
+        return aux;
+    }
+}
@@ -0,0 +1,169 @@
+use crate::errors::sessions::RefreshTokenError;
+use crate::models::session::{generate_refresh_token, hash_refresh_token, RefreshToken};
+use crate::repositories::sessions::{SessionRepository, SessionsRepo};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+#[derive(Clone, Debug)]
+pub struct RefreshedSession {
+    pub user_id: String,
+    pub audience: Vec<String>,
+    pub refresh_token: String,
+}
+
+#[async_trait]
+pub trait SessionManipulation {
+    async fn start(
+        &self,
+        user_id: &String,
+        audience: &Vec<String>,
+        exp_hours: i64,
+    ) -> ResultE<String>;
+    async fn refresh(&self, refresh_token: &String, exp_hours: i64) -> ResultE<RefreshedSession>;
+    async fn logout(
+        &self,
+        user_id: &String,
+        refresh_token: &Option<String>,
+        jti: &String,
+        access_expires_at: &DateTime<Utc>,
+    ) -> ResultE<()>;
+    async fn revoke_all(&self, user_id: &String) -> ResultE<()>;
+    async fn is_revoked(&self, jti: &String) -> ResultE<bool>;
+}
+
+#[derive(Debug)]
+pub struct SessionService {
+    repository: SessionsRepo,
+}
+
+impl SessionService {
+    pub fn new(repo: SessionsRepo) -> SessionService {
+        SessionService { repository: repo }
+    }
+
+    async fn issue(
+        &self,
+        user_id: &String,
+        family_id: &Uuid,
+        audience: &Vec<String>,
+        exp_hours: i64,
+        session_expires_at: &DateTime<Utc>,
+    ) -> ResultE<String> {
+        let raw = generate_refresh_token();
+        let mut token = RefreshToken::new();
+        token.set_token_hash(&hash_refresh_token(&raw));
+        token.set_user_id(user_id);
+        token.set_family_id(family_id);
+        token.set_audience(audience);
+        token.set_expires_at(&(Utc::now() + Duration::hours(exp_hours)).min(*session_expires_at));
+        token.set_session_expires_at(session_expires_at);
+        self.repository.add_refresh_token(&token).await?;
+        Ok(raw)
+    }
+}
+
+#[async_trait]
+impl SessionManipulation for SessionService {
+    // the audience is kept so every refreshed access token is minted for the same lambdas.
+    // The session ends exp_hours after the login, however often it's refreshed.
+    async fn start(
+        &self,
+        user_id: &String,
+        audience: &Vec<String>,
+        exp_hours: i64,
+    ) -> ResultE<String> {
+        let session_expires_at = Utc::now() + Duration::hours(exp_hours);
+        self.issue(
+            user_id,
+            &Uuid::new_v4(),
+            audience,
+            exp_hours,
+            &session_expires_at,
+        )
+        .await
+    }
+
+    // every refresh token works once: it is swapped by a new one of the same family.
+    // Presenting a spent token means it leaked, so every session of the user is closed.
+    async fn refresh(&self, refresh_token: &String, exp_hours: i64) -> ResultE<RefreshedSession> {
+        let hash = hash_refresh_token(refresh_token);
+        let current = match self.repository.get_refresh_token(&hash).await? {
+            None => return Err(RefreshTokenError("unknown token".to_string()).into()),
+            Some(token) => token,
+        };
+
+        if current.is_expired_at(&Utc::now()) {
+            return Err(RefreshTokenError("session expired, login again".to_string()).into());
+        }
+
+        if current.revoked() || !self.repository.revoke_refresh_token(&hash).await? {
+            log::warn!(
+                "refresh token reused for user {}, closing all its sessions",
+                current.user_id()
+            );
+            self.revoke_all(current.user_id()).await?;
+            return Err(RefreshTokenError("token already used, login again".to_string()).into());
+        }
+
+        let new_token = self
+            .issue(
+                current.user_id(),
+                current.family_id(),
+                current.audience(),
+                exp_hours,
+                current.session_expires_at(),
+            )
+            .await?;
+        Ok(RefreshedSession {
+            user_id: current.user_id().clone(),
+            audience: current.audience().clone(),
+            refresh_token: new_token,
+        })
+    }
+
+    async fn logout(
+        &self,
+        user_id: &String,
+        refresh_token: &Option<String>,
+        jti: &String,
+        access_expires_at: &DateTime<Utc>,
+    ) -> ResultE<()> {
+        if let Some(raw) = refresh_token {
+            let hash = hash_refresh_token(raw);
+            match self.repository.get_refresh_token(&hash).await? {
+                Some(token) if token.user_id() == user_id => {
+                    self.repository.revoke_refresh_token(&hash).await?;
+                }
+                _ => {
+                    return Err(RefreshTokenError("token doesn't belong to the user".to_string()).into());
+                }
+            }
+        }
+        self.repository.deny_jti(jti, access_expires_at).await?;
+        Ok(())
+    }
+
+    async fn revoke_all(&self, user_id: &String) -> ResultE<()> {
+        let tokens = self.repository.get_refresh_tokens_by_user(user_id).await?;
+        for token in tokens.iter().filter(|t| !t.revoked()) {
+            self.repository.revoke_refresh_token(token.token_hash()).await?;
+        }
+        Ok(())
+    }
+
+    async fn is_revoked(&self, jti: &String) -> ResultE<bool> {
+        self.repository.is_jti_denied(jti).await
+    }
+}
+
+impl Clone for SessionService {
+    fn clone(&self) -> SessionService {
+        let aux = SessionService {
+            repository: self.repository.clone(),
+        };
+        return aux;
+    }
+}
@@ -1,4 +1,8 @@
+use crate::errors::users::{UserNotVerifiedError, UserParamNotAccepted};
 use crate::models::user::{User, UserRoles, UserStatus, Userer};
+use crate::models::user_search::{
+    UserPage, UserSearch, SEARCH_PAGE_SIZE_MAX, SEARCH_PAGE_SIZE_MIN,
+};
 use crate::repositories::users::{UserRepository, UsersRepo};
 use crate::validate_password;
 use async_trait::async_trait;
@@ -10,6 +14,9 @@ type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send
 #[async_trait]
 pub trait UserManipulation {
     async fn get_all(&self, page_number: u32, page_size: u32) -> ResultE<Vec<User>>;
+    async fn search(&self, filter: &UserSearch) -> ResultE<UserPage>;
+    // rewrites every user, rows stored before the search indexes existed get their keys
+    async fn reindex_all(&self) -> ResultE<usize>;
     async fn get_by_id(&self, id: &String) -> ResultE<User>;
     async fn get_by_device(&self, device: &String) -> ResultE<User>;
     async fn get_by_wallet(&self, wallet_address: &String) -> ResultE<User>;
@@ -19,8 +26,10 @@ pub trait UserManipulation {
     //async fn get_by_filter(&self, field: &String, value: &String) -> ResultE<Vec<User>>;
     async fn update(&self, id: &String, user: &UpdatableFildsUser) -> ResultE<()>;
     async fn promote_user_to(&self, id: &String, promo: &PromoteUser) -> ResultE<()>;
+    async fn update_roles(&self, id: &String, roles: &Vec<UserRoles>) -> ResultE<()>;
     async fn update_password(&self, id: &String, password: &String) -> ResultE<()>;
     async fn remove_by_id(&self, user_id: &String) -> ResultE<()>;
+    async fn mark_email_verified(&self, id: &String, email: &String) -> ResultE<()>;
 }
 
 #[derive(Debug)]
@@ -58,6 +67,36 @@ impl UserManipulation for UsersService {
         Ok(res)
     }
 
+    async fn search(&self, filter: &UserSearch) -> ResultE<UserPage> {
+        if filter.page_size < SEARCH_PAGE_SIZE_MIN || filter.page_size > SEARCH_PAGE_SIZE_MAX {
+            return Err(UserParamNotAccepted(format!(
+                "pageSize must be between {} and {}",
+                SEARCH_PAGE_SIZE_MIN, SEARCH_PAGE_SIZE_MAX
+            ))
+            .into());
+        }
+        if let Some(prefix) = &filter.email_prefix {
+            if prefix.is_empty() {
+                return Err(UserParamNotAccepted("email prefix".to_string()).into());
+            }
+        }
+        if let (Some(from), Some(to)) = (&filter.created_from, &filter.created_to) {
+            if from > to {
+                return Err(UserParamNotAccepted("creation range".to_string()).into());
+            }
+        }
+        let res = self.repository.search(filter).await?;
+        Ok(res)
+    }
+
+    async fn reindex_all(&self) -> ResultE<usize> {
+        let users = self.repository.get_all(0, 0).await?;
+        for user in users.iter() {
+            self.repository.update(user.user_id(), user).await?;
+        }
+        Ok(users.len())
+    }
+
     //#[tracing::instrument()]
     async fn get_by_id(&self, id: &String) -> ResultE<User> {
         let res = self.repository.get_by_id(id).await?;
@@ -139,6 +178,10 @@ impl UserManipulation for UsersService {
             user_changes.set_wallet_address(&wal);
         }
         if let Some(eml) = &user.email {
+            // a new address must be verified again
+            if dbuser.email().as_ref() != Some(eml) {
+                user_changes.set_email_verified(false);
+            }
             user_changes.set_email(&eml);
         }
         if let Some(dvc) = &user.device {
@@ -162,6 +205,21 @@ impl UserManipulation for UsersService {
         Ok(())
     }
 
+    // the address might have changed since the verification link was sent
+    async fn mark_email_verified(&self, id: &String, email: &String) -> ResultE<()> {
+        let dbuser = self.repository.get_by_id(id).await?;
+        if dbuser.email().as_ref() != Some(email) {
+            return Err(UserNotVerifiedError("email changed, verify the new one".to_string()).into());
+        }
+        if dbuser.email_verified() {
+            return Ok(());
+        }
+        let mut user_changes: User = dbuser.clone();
+        user_changes.set_email_verified(true);
+        self.repository.update(&id, &user_changes).await?;
+        Ok(())
+    }
+
     async fn promote_user_to(&self, id: &String, promo: &PromoteUser) -> ResultE<()> {
         let dbuser = self.repository.get_by_id(id).await?;
         let mut res: User = dbuser.clone();
@@ -177,6 +235,21 @@ impl UserManipulation for UsersService {
         self.repository.update(&id, &res).await?;
         Ok(())
     }
+
+    // Basic is implicit, every account keeps it whatever roles are handed out
+    async fn update_roles(&self, id: &String, roles: &Vec<UserRoles>) -> ResultE<()> {
+        let dbuser = self.repository.get_by_id(id).await?;
+        let mut new_roles = vec![UserRoles::Basic];
+        for role in roles {
+            if !new_roles.contains(role) {
+                new_roles.push(*role);
+            }
+        }
+        let mut res: User = dbuser.clone();
+        res.set_roles(&new_roles);
+        self.repository.update(&id, &res).await?;
+        Ok(())
+    }
 }
 
 impl Clone for UsersService {
@@ -0,0 +1,115 @@
+use std::str::FromStr;
+
+use crate::errors::sessions::WalletSignatureError;
+use crate::models::session::NoncePurpose;
+use crate::models::wallet::VerifiedWallet;
+use crate::repositories::sessions::{SessionRepository, SessionsRepo};
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use siwe::{eip55, Message, VerificationOpts};
+use uuid::Uuid;
+
+type ResultE<T> = std::result::Result<T, Box<dyn std::error::Error + Sync + Send>>;
+
+// the domain the SIWE message must be bound to, as shown by the wallet to the user
+pub const SIWE_DOMAIN: &str = "www.truly.video";
+// where the sign-in is meant to land and the network it speaks for, a message signed for another
+// site or chain can't be replayed here
+pub const SIWE_URI: &str = "https://www.truly.video";
+pub const SIWE_CHAIN_ID: u64 = 1;
+pub const WALLET_NONCE_EXP_MINUTES: i64 = 10;
+
+#[async_trait]
+pub trait WalletLoginManipulation {
+    async fn nonce(&self) -> ResultE<String>;
+    async fn verify(&self, message: &String, signature: &String) -> ResultE<VerifiedWallet>;
+}
+
+#[derive(Debug)]
+pub struct WalletLoginService {
+    repository: SessionsRepo,
+    domain: String,
+    uri: String,
+    chain_id: u64,
+}
+
+impl WalletLoginService {
+    pub fn new(repo: SessionsRepo, domain: &str, uri: &str, chain_id: u64) -> WalletLoginService {
+        WalletLoginService {
+            repository: repo,
+            domain: domain.to_string(),
+            uri: uri.to_string(),
+            chain_id,
+        }
+    }
+}
+
+#[async_trait]
+impl WalletLoginManipulation for WalletLoginService {
+    // EIP-4361 wants at least 8 alphanumeric characters
+    async fn nonce(&self) -> ResultE<String> {
+        let nonce = Uuid::new_v4().simple().to_string();
+        let expires_at = Utc::now() + Duration::minutes(WALLET_NONCE_EXP_MINUTES);
+        self.repository
+            .add_nonce(&NoncePurpose::Wallet, &nonce, &expires_at)
+            .await?;
+        Ok(nonce)
+    }
+
+    // checks domain, uri, chain, validity window and the secp256k1 signature, then spends the nonce
+    async fn verify(&self, message: &String, signature: &String) -> ResultE<VerifiedWallet> {
+        let siwe_message = Message::from_str(message)
+            .map_err(|e| WalletSignatureError(format!("malformed message: {}", e)))?;
+        if siwe_message.chain_id != self.chain_id {
+            return Err(WalletSignatureError(format!(
+                "chain {} not accepted",
+                siwe_message.chain_id
+            ))
+            .into());
+        }
+        if siwe_message.uri.as_str() != self.uri {
+            return Err(
+                WalletSignatureError(format!("uri {} not accepted", siwe_message.uri)).into(),
+            );
+        }
+        let signature = hex::decode(signature.trim_start_matches("0x"))
+            .map_err(|e| WalletSignatureError(format!("malformed signature: {}", e)))?;
+
+        let opts = VerificationOpts {
+            domain: Some(
+                self.domain
+                    .parse()
+                    .map_err(|_| WalletSignatureError("domain misconfigured".to_string()))?,
+            ),
+            ..Default::default()
+        };
+        siwe_message
+            .verify(&signature, &opts)
+            .await
+            .map_err(|e| WalletSignatureError(e.to_string()))?;
+
+        if !self
+            .repository
+            .consume_nonce(&NoncePurpose::Wallet, &siwe_message.nonce)
+            .await?
+        {
+            return Err(
+                WalletSignatureError("nonce unknown, expired or already used".to_string()).into(),
+            );
+        }
+
+        Ok(VerifiedWallet::new(&eip55(&siwe_message.address)))
+    }
+}
+
+impl Clone for WalletLoginService {
+    fn clone(&self) -> WalletLoginService {
+        let aux = WalletLoginService {
+            repository: self.repository.clone(),
+            domain: self.domain.clone(),
+            uri: self.uri.clone(),
+            chain_id: self.chain_id,
+        };
+        return aux;
+    }
+}
@@ -0,0 +1,92 @@
+use lib_config::config::Config;
+use lib_config::environment::{DEV_ENV, ENV_VAR_ENVIRONMENT};
+use lib_config::infra::build_local_stack_connection;
+use lib_config::schema::Schema;
+use lib_users::errors::api_keys::{ApiKeyError, ApiKeyNoExistsError};
+use lib_users::models::api_key::{ApiKeyScope, API_KEY_PREFIX};
+use lib_users::repositories::api_keys::ApiKeysRepo;
+use lib_users::repositories::schema_user::UserAllSchema;
+use lib_users::services::api_keys::{ApiKeyManipulation, ApiKeyService};
+use std::env;
+use testcontainers::*;
+
+#[tokio::test]
+async fn api_keys_test() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    env::set_var("RUST_LOG", "debug");
+    env::set_var(ENV_VAR_ENVIRONMENT, DEV_ENV);
+    env::set_var("AWS_REGION", "eu-central-1");
+
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let docker = clients::Cli::default();
+
+    let mut local_stack = images::local_stack::LocalStack::default();
+    local_stack.set_services("dynamodb");
+    let node = docker.run(local_stack);
+    let host_port = node.get_host_port_ipv4(4566);
+
+    let shared_config = build_local_stack_connection(host_port).await;
+
+    let mut config = Config::new();
+    config.setup().await;
+    config.set_aws_config(&shared_config); //rewrite configuration to use our current testcontainer instead
+
+    let creation = UserAllSchema::create_schema(&config).await;
+    assert!(creation.is_ok());
+
+    let api_key_service = ApiKeyService::new(ApiKeysRepo::new(&config));
+    let user_id = "user-api-keys-1".to_string();
+
+    let empty = api_key_service
+        .create(&user_id, &"ci".to_string(), &vec![])
+        .await;
+    assert!(empty.unwrap_err().downcast_ref::<ApiKeyError>().is_some());
+
+    let scopes = vec![ApiKeyScope::AssetsWrite, ApiKeyScope::SimilarRead];
+    let (created, raw) = api_key_service
+        .create(&user_id, &"ci".to_string(), &scopes)
+        .await?;
+    assert!(raw.starts_with(API_KEY_PREFIX));
+    assert!(raw.starts_with(created.prefix().as_str()));
+    assert_ne!(created.key_hash(), &raw);
+
+    let authenticated = api_key_service.authenticate(&raw).await?;
+    assert_eq!(authenticated.user_id(), &user_id);
+    assert_eq!(authenticated.key_id(), created.key_id());
+    assert!(authenticated.has_scope(&ApiKeyScope::AssetsWrite));
+    assert!(authenticated.has_scope(&ApiKeyScope::SimilarRead));
+    assert!(!authenticated.has_scope(&ApiKeyScope::LicensesWrite));
+
+    let listed = api_key_service.get_by_user(&user_id).await?;
+    assert_eq!(listed.len(), 1);
+    assert!(listed[0].last_used_time().is_some());
+    // neither the raw key nor its hash ever leave the service
+    let json = serde_json::to_string(&listed[0])?;
+    assert!(!json.contains(listed[0].key_hash().as_str()));
+    assert!(json.contains("similar:read"));
+
+    let forged = format!("{}{}", API_KEY_PREFIX, "0".repeat(32));
+    let refused = api_key_service.authenticate(&forged).await;
+    assert!(refused.unwrap_err().downcast_ref::<ApiKeyError>().is_some());
+    let refused = api_key_service.authenticate(&"not-a-key".to_string()).await;
+    assert!(refused.unwrap_err().downcast_ref::<ApiKeyError>().is_some());
+
+    let other = api_key_service
+        .revoke(&"someone-else".to_string(), created.key_id())
+        .await;
+    assert!(other
+        .unwrap_err()
+        .downcast_ref::<ApiKeyNoExistsError>()
+        .is_some());
+
+    api_key_service.revoke(&user_id, created.key_id()).await?;
+    let refused = api_key_service.authenticate(&raw).await;
+    assert!(refused.unwrap_err().downcast_ref::<ApiKeyError>().is_some());
+    let again = api_key_service.revoke(&user_id, created.key_id()).await;
+    assert!(again
+        .unwrap_err()
+        .downcast_ref::<ApiKeyNoExistsError>()
+        .is_some());
+
+    Ok(())
+}
@@ -0,0 +1,167 @@
+mod common;
+
+use chrono::{Duration, TimeZone, Utc};
+use lib_config::environment::{DEV_ENV, ENV_VAR_ENVIRONMENT};
+use lib_config::infra::build_local_stack_connection;
+use lib_config::schema::Schema;
+use lib_config::{config::Config, secrets::SECRETS_MANAGER_APP_KEYS};
+use lib_users::models::audit::{AuditAction, AuditEntry, AuditSearch};
+use lib_users::repositories::audit::{AuditRepo, AuditRepository};
+use lib_users::repositories::schema_user::UserAllSchema;
+use lib_users::services::audit::{AuditManipulation, AuditService};
+use serde_json::json;
+use std::env;
+use testcontainers::*;
+
+use crate::common::create_secrets;
+
+#[test]
+fn audit_months_test() {
+    let mut filter = AuditSearch::new();
+    filter.from = Some(Utc.with_ymd_and_hms(2023, 11, 15, 0, 0, 0).unwrap());
+    filter.to = Some(Utc.with_ymd_and_hms(2024, 2, 1, 0, 0, 0).unwrap());
+    assert_eq!(
+        filter.months(),
+        vec!["2024-02", "2024-01", "2023-12", "2023-11"]
+    );
+    filter.ascending = true;
+    assert_eq!(filter.months()[0], "2023-11");
+
+    filter.month = Some("2024-01".to_string());
+    assert_eq!(filter.months(), vec!["2024-01"]);
+}
+
+#[tokio::test]
+async fn audit_log_test() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    env::set_var("RUST_LOG", "debug");
+    env::set_var(ENV_VAR_ENVIRONMENT, DEV_ENV);
+    env::set_var("AWS_REGION", "eu-central-1");
+
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let docker = clients::Cli::default();
+
+    let mut local_stack = images::local_stack::LocalStack::default();
+    local_stack.set_services("dynamodb,secretsmanager");
+    let node = docker.run(local_stack);
+    let host_port = node.get_host_port_ipv4(4566);
+
+    let shared_config = build_local_stack_connection(host_port).await;
+
+    let secrets_client = aws_sdk_secretsmanager::Client::new(&shared_config);
+    let creation2 = create_secrets(&secrets_client).await;
+    assert!(&creation2.is_ok());
+
+    let mut config = Config::new();
+    config.setup().await;
+    config.set_aws_config(&shared_config);
+    config.load_secret(SECRETS_MANAGER_APP_KEYS.clone()).await;
+
+    let creation = UserAllSchema::create_schema(&config).await;
+    assert!(&creation.is_ok());
+
+    let audit_service = AuditService::new(AuditRepo::new(&config));
+
+    let admin = "admin-1".to_string();
+    let support = "support-1".to_string();
+    let target = "user-1".to_string();
+    let now = Utc::now();
+    let actions = [
+        (&admin, AuditAction::UpdateUser),
+        (&admin, AuditAction::PromoteUser),
+        (&support, AuditAction::UnlockUser),
+        (&admin, AuditAction::ResetPassword),
+        (&admin, AuditAction::UpdateRoles),
+        (&support, AuditAction::UnlockUser),
+        (&admin, AuditAction::UpdateUser),
+    ];
+    for (i, (actor, action)) in actions.iter().enumerate() {
+        let mut entry = AuditEntry::new();
+        entry.set_actor_id(actor);
+        entry.set_action(action);
+        entry.set_target_id(&target);
+        entry.set_before(&Some(json!({ "step": i })));
+        entry.set_after(&Some(json!({ "step": i + 1 })));
+        entry.set_request_id(&format!("request-{}", i));
+        entry.set_creation_time(&(now - Duration::seconds(10 - i as i64)));
+        audit_service.record(&entry).await?;
+    }
+
+    // an existing entry can't be written again
+    let mut first = AuditSearch::new();
+    first.target_id = Some(target.clone());
+    first.ascending = true;
+    let page = audit_service.search(&first).await?;
+    let mut again = page.entries[0].clone();
+    again.set_after(&None);
+    let repo = AuditRepo::new(&config);
+    assert!(repo.add(&again).await.is_err());
+
+    // by target, paging the seven entries newest first
+    let mut filter = AuditSearch::new();
+    filter.target_id = Some(target.clone());
+    let page = audit_service.search(&filter).await?;
+    assert_eq!(page.entries.len(), 5);
+    assert_eq!(page.entries[0].request_id(), "request-6");
+    assert_eq!(page.entries[0].after(), &Some(json!({ "step": 7 })));
+    filter.cursor = page.next_cursor.clone();
+    let page = audit_service.search(&filter).await?;
+    assert_eq!(page.entries.len(), 2);
+    assert_eq!(page.next_cursor, None);
+
+    // by actor and action
+    let mut filter = AuditSearch::new();
+    filter.actor_id = Some(admin.clone());
+    filter.action = Some(AuditAction::UpdateUser);
+    let page = audit_service.search(&filter).await?;
+    assert_eq!(page.entries.len(), 2);
+    assert!(page.entries.iter().all(|e| e.actor_id() == &admin));
+
+    // by action only
+    let mut filter = AuditSearch::new();
+    filter.action = Some(AuditAction::UnlockUser);
+    let page = audit_service.search(&filter).await?;
+    assert_eq!(page.entries.len(), 2);
+    assert!(page.entries.iter().all(|e| e.actor_id() == &support));
+
+    // no filter walks the current month
+    let mut filter = AuditSearch::new();
+    filter.page_size = 10;
+    let page = audit_service.search(&filter).await?;
+    assert_eq!(page.entries.len(), 7);
+
+    let mut filter = AuditSearch::new();
+    filter.month = Some("2024-13".to_string());
+    assert!(audit_service.search(&filter).await.is_err());
+
+    // written before the action, its outcome is set once afterwards
+    let mut pending = AuditEntry::new();
+    pending.set_actor_id(&admin);
+    pending.set_action(&AuditAction::DeleteUser);
+    pending.set_target_id(&"user-2".to_string());
+    pending.set_before(&Some(json!({ "user_id": "user-2" })));
+    pending.set_request_id(&"request-7".to_string());
+    audit_service.record(&pending).await?;
+    assert!(audit_service.complete(&pending).await.is_err());
+
+    pending.set_status(&Some(200));
+    audit_service.complete(&pending).await?;
+    pending.set_status(&Some(500));
+    assert!(audit_service.complete(&pending).await.is_err());
+
+    let mut filter = AuditSearch::new();
+    filter.target_id = Some("user-2".to_string());
+    let page = audit_service.search(&filter).await?;
+    assert_eq!(page.entries.len(), 1);
+    assert_eq!(page.entries[0].status(), &Some(200));
+    assert_eq!(page.entries[0].after(), &None);
+
+    // nothing to complete when the entry was never written
+    let mut lost = AuditEntry::new();
+    lost.set_actor_id(&admin);
+    lost.set_target_id(&target);
+    lost.set_status(&Some(200));
+    assert!(audit_service.complete(&lost).await.is_err());
+
+    Ok(())
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use lib_config::environment::{DEV_ENV, ENV_VAR_ENVIRONMENT};
+use lib_config::infra::build_local_stack_connection;
+use lib_config::schema::Schema;
+use lib_config::{config::Config, secrets::SECRETS_MANAGER_APP_KEYS};
+use lib_users::errors::one_time_tokens::{OneTimeTokenError, OneTimeTokenRateLimitError};
+use lib_users::models::one_time_token::OneTimeTokenPurpose;
+use lib_users::models::user::User;
+use lib_users::repositories::one_time_tokens::OneTimeTokensRepo;
+use lib_users::repositories::schema_user::UserAllSchema;
+use lib_users::repositories::users::UsersRepo;
+use lib_users::services::login::LoginOps;
+use lib_users::services::one_time_tokens::{OneTimeTokenManipulation, OneTimeTokenService};
+use lib_users::services::users::{UpdatableFildsUser, UserManipulation, UsersService};
+use std::env;
+use testcontainers::*;
+
+use crate::common::create_secrets;
+
+#[tokio::test]
+async fn email_verification_test() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    env::set_var("RUST_LOG", "debug");
+    env::set_var(ENV_VAR_ENVIRONMENT, DEV_ENV);
+    env::set_var("AWS_REGION", "eu-central-1");
+
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let docker = clients::Cli::default();
+
+    let mut local_stack = images::local_stack::LocalStack::default();
+    local_stack.set_services("dynamodb,secretsmanager");
+    let node = docker.run(local_stack);
+    let host_port = node.get_host_port_ipv4(4566);
+
+    let shared_config = build_local_stack_connection(host_port).await;
+
+    let secrets_client = aws_sdk_secretsmanager::Client::new(&shared_config);
+    let creation2 = create_secrets(&secrets_client).await;
+    assert!(&creation2.is_ok());
+
+    let mut config = Config::new();
+    config.setup().await;
+    config.set_aws_config(&shared_config);
+    config.load_secret(SECRETS_MANAGER_APP_KEYS.clone()).await;
+
+    let creation = UserAllSchema::create_schema(&config).await;
+    assert!(&creation.is_ok());
+
+    let user_service = UsersService::new(UsersRepo::new(&config));
+    let token_service = OneTimeTokenService::new(OneTimeTokensRepo::new(&config), &config);
+
+    let email = "pepe@test.cat.io".to_string();
+    let password = Some("123456789aA$%^@2".to_string());
+    let mut new_user = User::new();
+    new_user.set_email(&email);
+    let new_id = user_service.add(&mut new_user, &password).await?;
+
+    let user_db = user_service.get_by_id(&new_id).await?;
+    assert!(!user_db.is_verified());
+    let unverified = user_service.login(&None, &None, &Some(email.clone()), &password).await;
+    assert!(unverified.is_err());
+
+    let token = token_service
+        .issue(&new_id, &email, &OneTimeTokenPurpose::EmailVerification, 24)
+        .await?;
+
+    // resends are throttled
+    let too_soon = token_service
+        .issue(&new_id, &email, &OneTimeTokenPurpose::EmailVerification, 24)
+        .await;
+    assert!(too_soon
+        .err()
+        .unwrap()
+        .downcast_ref::<OneTimeTokenRateLimitError>()
+        .is_some());
+
+    // a tampered token or one of another purpose is refused
+    let forged = token_service
+        .consume(&format!("{}0", token), &OneTimeTokenPurpose::EmailVerification)
+        .await;
+    assert!(forged.is_err());
+    let other_purpose = token_service
+        .consume(&token, &OneTimeTokenPurpose::PasswordReset)
+        .await;
+    assert!(other_purpose.is_err());
+
+    let consumed = token_service
+        .consume(&token, &OneTimeTokenPurpose::EmailVerification)
+        .await?;
+    assert_eq!(consumed.user_id(), &new_id);
+    user_service
+        .mark_email_verified(consumed.user_id(), consumed.email())
+        .await?;
+
+    let user_db = user_service.get_by_id(&new_id).await?;
+    assert!(user_db.is_verified());
+    let res = user_service.login(&None, &None, &Some(email.clone()), &password).await?;
+    assert_eq!(res.user_id, new_id);
+
+    let reused = token_service
+        .consume(&token, &OneTimeTokenPurpose::EmailVerification)
+        .await;
+    assert!(reused
+        .err()
+        .unwrap()
+        .downcast_ref::<OneTimeTokenError>()
+        .is_some());
+
+    // changing the address asks for a new verification
+    let update_fields = UpdatableFildsUser {
+        email: Some("pepe2@test.cat.io".to_string()),
+        device: None,
+        status: None,
+        wallet: None,
+    };
+    user_service.update(&new_id, &update_fields).await?;
+    let user_db = user_service.get_by_id(&new_id).await?;
+    assert!(!user_db.is_verified());
+
+    // the old link doesn't vouch for the new address
+    let stale = user_service.mark_email_verified(&new_id, &email).await;
+    assert!(stale.is_err());
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use lib_config::environment::{DEV_ENV, ENV_VAR_ENVIRONMENT};
+use lib_config::infra::build_local_stack_connection;
+use lib_config::schema::Schema;
+use lib_config::{config::Config, secrets::SECRETS_MANAGER_APP_KEYS};
+use lib_users::models::jwt_key::JwtKey;
+use lib_users::repositories::jwt_keys::JwtKeysRepo;
+use lib_users::repositories::schema_user::UserAllSchema;
+use lib_users::services::jwt_keys::{JwtKeyManipulation, JwtKeyService};
+use std::env;
+use testcontainers::*;
+
+use crate::common::create_secrets;
+
+fn new_key(kid: &str) -> JwtKey {
+    let mut key = JwtKey::new();
+    key.set_kid(&kid.to_string());
+    key.set_algorithm(&"EdDSA".to_string());
+    key.set_jwk(&format!(
+        r#"{{"kty":"OKP","use":"sig","alg":"EdDSA","kid":"{}","crv":"Ed25519","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}}"#,
+        kid
+    ));
+    key
+}
+
+#[tokio::test]
+async fn jwt_keys_rotation_test() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    env::set_var("RUST_LOG", "debug");
+    env::set_var(ENV_VAR_ENVIRONMENT, DEV_ENV);
+    env::set_var("AWS_REGION", "eu-central-1");
+
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let docker = clients::Cli::default();
+
+    let mut local_stack = images::local_stack::LocalStack::default();
+    local_stack.set_services("dynamodb,secretsmanager");
+    let node = docker.run(local_stack);
+    let host_port = node.get_host_port_ipv4(4566);
+
+    let shared_config = build_local_stack_connection(host_port).await;
+
+    let secrets_client = aws_sdk_secretsmanager::Client::new(&shared_config);
+    let creation2 = create_secrets(&secrets_client).await;
+    assert!(&creation2.is_ok());
+
+    let mut config = Config::new();
+    config.setup().await;
+    config.set_aws_config(&shared_config);
+    config.load_secret(SECRETS_MANAGER_APP_KEYS.clone()).await;
+
+    let creation = UserAllSchema::create_schema(&config).await;
+    assert!(&creation.is_ok());
+
+    let key_service = JwtKeyService::new(JwtKeysRepo::new(&config));
+    assert!(key_service.get_verification_keys().await?.is_empty());
+
+    key_service.rotate(&new_key("kid-1")).await?;
+    key_service.rotate(&new_key("kid-2")).await?;
+
+    // current and previous keys both verify
+    let keys = key_service.get_verification_keys().await?;
+    assert_eq!(keys.len(), 2);
+    assert!(key_service.get_verification_key(&"kid-1".to_string()).await?.is_some());
+
+    // a third rotation retires the first key
+    key_service.rotate(&new_key("kid-3")).await?;
+    let keys = key_service.get_verification_keys().await?;
+    assert_eq!(keys.len(), 2);
+    assert!(keys.iter().any(|k| k.kid() == "kid-3"));
+    assert!(key_service.get_verification_key(&"kid-1".to_string()).await?.is_none());
+    assert!(key_service.get_verification_key(&"kid-2".to_string()).await?.is_some());
+
+    Ok(())
+}
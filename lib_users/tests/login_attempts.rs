@@ -0,0 +1,105 @@
+use lib_config::config::Config;
+use lib_config::environment::{DEV_ENV, ENV_VAR_ENVIRONMENT};
+use lib_config::infra::build_local_stack_connection;
+use lib_config::schema::Schema;
+use lib_users::errors::login_attempts::{LoginThrottledError, UserLockedError};
+use lib_users::repositories::login_attempts::LoginAttemptsRepo;
+use lib_users::repositories::schema_user::UserAllSchema;
+use lib_users::services::login_attempts::{
+    LoginAttemptManipulation, LoginAttemptService, FREE_FAILED_LOGINS, MAX_FAILED_LOGINS_BY_EMAIL,
+    MAX_FAILED_MFA_CODES,
+};
+use std::env;
+use testcontainers::*;
+
+#[tokio::test]
+async fn login_attempts_lockout_test() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    env::set_var("RUST_LOG", "debug");
+    env::set_var(ENV_VAR_ENVIRONMENT, DEV_ENV);
+    env::set_var("AWS_REGION", "eu-central-1");
+
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let docker = clients::Cli::default();
+
+    let mut local_stack = images::local_stack::LocalStack::default();
+    local_stack.set_services("dynamodb");
+    let node = docker.run(local_stack);
+    let host_port = node.get_host_port_ipv4(4566);
+
+    let shared_config = build_local_stack_connection(host_port).await;
+
+    let mut config = Config::new();
+    config.setup().await;
+    config.set_aws_config(&shared_config); //rewrite configuration to use our current testcontainer instead
+
+    let creation = UserAllSchema::create_schema(&config).await;
+    assert!(creation.is_ok());
+
+    let attempts = LoginAttemptService::new(LoginAttemptsRepo::new(&config));
+
+    let email = "Pepe@test.cat.io".to_string();
+    let other = "juan@test.cat.io".to_string();
+    let ip = Some("10.0.0.1".to_string());
+
+    attempts.check(&email, &ip).await?;
+
+    // the first failures don't slow anybody down
+    for _ in 1..FREE_FAILED_LOGINS {
+        assert!(!attempts.failure(&email, &ip).await?);
+    }
+    attempts.check(&email, &ip).await?;
+
+    // from then on the next try has to wait
+    assert!(!attempts.failure(&email, &ip).await?);
+    let delayed = attempts.check(&email, &ip).await;
+    assert!(delayed.unwrap_err().downcast_ref::<LoginThrottledError>().is_some());
+
+    // another account behind the same ip is not affected
+    attempts.check(&other, &ip).await?;
+
+    // the counter ignores the case of the email
+    let mut locked = false;
+    for _ in FREE_FAILED_LOGINS..MAX_FAILED_LOGINS_BY_EMAIL {
+        locked = attempts.failure(&email.to_lowercase(), &ip).await?;
+    }
+    assert!(locked);
+
+    let refused = attempts.check(&email, &ip).await;
+    assert!(refused.unwrap_err().downcast_ref::<UserLockedError>().is_some());
+
+    let locked_list = attempts.get_locked().await?;
+    assert_eq!(locked_list.len(), 1);
+    assert_eq!(locked_list[0].email(), Some("pepe@test.cat.io"));
+
+    attempts.unlock(&email).await?;
+    attempts.check(&email, &ip).await?;
+    assert!(attempts.get_locked().await?.is_empty());
+
+    // a good password clears the counter of the email
+    attempts.failure(&other, &ip).await?;
+    attempts.success(&other).await?;
+    for _ in 1..FREE_FAILED_LOGINS {
+        attempts.failure(&other, &ip).await?;
+    }
+    attempts.check(&other, &ip).await?;
+
+    // wrong codes at the second step lock the user, not the email of the first one
+    let user_id = "user-with-mfa".to_string();
+    let mut locked = false;
+    for _ in 0..MAX_FAILED_MFA_CODES {
+        attempts.check_mfa(&user_id, &ip).await?;
+        locked = attempts.mfa_failure(&user_id, &ip).await?;
+    }
+    assert!(locked);
+    let refused = attempts.check_mfa(&user_id, &ip).await;
+    assert!(refused.unwrap_err().downcast_ref::<UserLockedError>().is_some());
+    attempts.check_mfa(&"another-user".to_string(), &ip).await?;
+    assert!(attempts.get_locked().await?.is_empty());
+
+    // a good code clears it
+    attempts.mfa_success(&user_id).await?;
+    attempts.check_mfa(&user_id, &ip).await?;
+
+    Ok(())
+}
@@ -1,15 +1,26 @@
+use base64::{engine::general_purpose, Engine as _};
+use ed25519_dalek::pkcs8::EncodePublicKey;
+use ed25519_dalek::{Signer, SigningKey};
 use lib_config::config::Config;
 use lib_config::environment::{DEV_ENV, ENV_VAR_ENVIRONMENT};
 use lib_config::infra::build_local_stack_connection;
 use lib_config::schema::Schema;
+use lib_users::models::device::device_challenge_message;
 use lib_users::models::user::User;
+use lib_users::repositories::devices::DevicesRepo;
 use lib_users::repositories::schema_user::UserAllSchema;
 use lib_users::repositories::users::UsersRepo;
+use lib_users::services::devices::{DeviceManipulation, DeviceService};
 use lib_users::services::login::LoginOps;
 use lib_users::services::users::{UserManipulation, UsersService};
 use std::env;
 use testcontainers::*;
 
+fn sign(key: &SigningKey, device: &String, challenge: &String) -> String {
+    let signature = key.sign(device_challenge_message(device, challenge).as_bytes());
+    general_purpose::STANDARD.encode(signature.to_bytes())
+}
+
 #[tokio::test]
 async fn login_user_device_test() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     env::set_var("RUST_LOG", "debug");
@@ -37,18 +48,145 @@ async fn login_user_device_test() -> Result<(), Box<dyn std::error::Error + Send
     let user_repo = UsersRepo::new(&config);
     let user_service = UsersService::new(user_repo);
 
+    let device_repo = DevicesRepo::new(&config);
+    let device_service = DeviceService::new(device_repo);
+
     let device = "1234".to_string();
+    let key = SigningKey::from_bytes(&[7u8; 32]);
+    let public_key =
+        general_purpose::STANDARD.encode(key.verifying_key().to_public_key_der()?.as_bytes());
 
     let mut new_user = User::new();
     new_user.set_device(&device);
 
     let new_id = user_service.add(&mut new_user, &None).await?;
 
+    // garbage keys are refused before anything is stored
+    let wrong = device_service
+        .register(&new_id, &device, &"EdDSA".to_string(), &"bm90IGEga2V5".to_string())
+        .await;
+    assert!(wrong.is_err());
+
+    device_service
+        .register(&new_id, &device, &"EdDSA".to_string(), &public_key)
+        .await?;
+
+    let challenge = device_service.challenge(&device).await?;
+    let signature = sign(&key, &device, &challenge);
+    let verified = device_service.verify(&device, &challenge, &signature).await?;
+
     let res = user_service
-        .login(&Some(device), &None, &None, &None)
+        .login(&Some(verified), &None, &None, &None)
         .await?;
 
     assert_eq!(new_id, res.user_id);
 
+    // a challenge can be answered only once
+    let replay = device_service.verify(&device, &challenge, &signature).await;
+    assert!(replay.is_err());
+
+    // knowing the device string isn't enough, the signature must come from its key
+    let challenge = device_service.challenge(&device).await?;
+    let forged = sign(&SigningKey::from_bytes(&[8u8; 32]), &device, &challenge);
+    assert!(device_service.verify(&device, &challenge, &forged).await.is_err());
+
+    // anybody may ask for a challenge, that doesn't spend the one the device is answering
+    let pending = device_service.challenge(&device).await?;
+    device_service.challenge(&device).await?;
+    let signature = sign(&key, &device, &pending);
+    device_service.verify(&device, &pending, &signature).await?;
+
+    let devices = device_service.get_by_user(&new_id).await?;
+    assert_eq!(devices.len(), 1);
+    assert_eq!(devices[0].device_id(), &device);
+
+    device_service.revoke(&new_id, &device).await?;
+
+    let challenge = device_service.challenge(&device).await?;
+    let signature = sign(&key, &device, &challenge);
+    let revoked = device_service.verify(&device, &challenge, &signature).await;
+    assert!(revoked.is_err());
+
+    // a device that has been paired before can't come back through the legacy pairing
+    let repaired = device_service
+        .pair_legacy(&new_id, &device, &"EdDSA".to_string(), &public_key)
+        .await;
+    assert!(repaired.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn pair_legacy_device_test() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    env::set_var("RUST_LOG", "debug");
+    env::set_var(ENV_VAR_ENVIRONMENT, DEV_ENV);
+    env::set_var("AWS_REGION", "eu-central-1");
+
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let docker = clients::Cli::default();
+
+    let mut local_stack = images::local_stack::LocalStack::default();
+    local_stack.set_services("dynamodb");
+    let node = docker.run(local_stack);
+    let host_port = node.get_host_port_ipv4(4566);
+
+    let shared_config = build_local_stack_connection(host_port).await;
+
+    let mut config = Config::new();
+    config.setup().await;
+    config.set_aws_config(&shared_config); //rewrite configuration to use our current testcontainer instead
+
+    let creation = UserAllSchema::create_schema(&config).await;
+    assert!(creation.is_ok());
+
+    let user_repo = UsersRepo::new(&config);
+    let user_service = UsersService::new(user_repo);
+
+    let device_repo = DevicesRepo::new(&config);
+    let device_service = DeviceService::new(device_repo);
+
+    // an account from before devices had keys, it only knows the device string
+    let device = "legacy-1234".to_string();
+    let mut legacy_user = User::new();
+    legacy_user.set_device(&device);
+    let legacy_id = user_service.add(&mut legacy_user, &None).await?;
+
+    let key = SigningKey::from_bytes(&[9u8; 32]);
+    let public_key =
+        general_purpose::STANDARD.encode(key.verifying_key().to_public_key_der()?.as_bytes());
+
+    let owner = user_service.get_by_device(&device).await?;
+    device_service
+        .pair_legacy(owner.user_id(), &device, &"EdDSA".to_string(), &public_key)
+        .await?;
+
+    let challenge = device_service.challenge(&device).await?;
+    let signature = sign(&key, &device, &challenge);
+    let verified = device_service
+        .verify(&device, &challenge, &signature)
+        .await?;
+    assert_eq!(verified.user_id(), &legacy_id);
+
+    // the first key wins, nobody can pair the same device again without a session
+    let other_key = SigningKey::from_bytes(&[10u8; 32]);
+    let other_public_key =
+        general_purpose::STANDARD.encode(other_key.verifying_key().to_public_key_der()?.as_bytes());
+    let second = device_service
+        .pair_legacy(&legacy_id, &device, &"EdDSA".to_string(), &other_public_key)
+        .await;
+    assert!(second.is_err());
+
+    // nor pair another device for a user that already has a credential
+    let third = device_service
+        .pair_legacy(
+            &legacy_id,
+            &"legacy-5678".to_string(),
+            &"EdDSA".to_string(),
+            &other_public_key,
+        )
+        .await;
+    assert!(third.is_err());
+
     Ok(())
 }
@@ -61,6 +61,13 @@ async fn login_user_email_password_test() -> Result<(), Box<dyn std::error::Erro
 
     let new_id = user_service.add(&mut new_user, &password).await?;
 
+    let unverified = user_service.login(&None, &None, &email, &password).await;
+    assert!(unverified.is_err());
+
+    user_service
+        .mark_email_verified(&new_id, &email.clone().unwrap())
+        .await?;
+
     let res = user_service.login(&None, &None, &email, &password).await?;
     assert_eq!(new_id, res.user_id);
 
@@ -0,0 +1,214 @@
+mod common;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::Utc;
+use ed25519_dalek::pkcs8::EncodePrivateKey;
+use ed25519_dalek::SigningKey;
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use lib_config::environment::{DEV_ENV, ENV_VAR_ENVIRONMENT};
+use lib_config::infra::build_local_stack_connection;
+use lib_config::schema::Schema;
+use lib_config::{config::Config, secrets::SECRETS_MANAGER_APP_KEYS};
+use lib_users::models::identity::OidcProvider;
+use lib_users::models::user::User;
+use lib_users::repositories::identities::IdentitiesRepo;
+use lib_users::repositories::schema_user::UserAllSchema;
+use lib_users::repositories::sessions::SessionsRepo;
+use lib_users::repositories::users::UsersRepo;
+use lib_users::services::oidc_login::{OidcLoginManipulation, OidcLoginService};
+use lib_users::services::users::{UserManipulation, UsersService};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::env;
+use testcontainers::*;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+use crate::common::create_secrets;
+
+static CLIENT_ID: &str = "truly-mobile";
+static KID: &str = "mock-key-1";
+
+// a local identity provider: it only serves its keys, the test signs the tokens
+async fn mock_identity_provider(key: &SigningKey) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let issuer = format!("http://{}", listener.local_addr().unwrap());
+    let jwks = json!({
+        "keys": [{
+            "kty": "OKP",
+            "use": "sig",
+            "alg": "EdDSA",
+            "kid": KID,
+            "crv": "Ed25519",
+            "x": URL_SAFE_NO_PAD.encode(key.verifying_key().as_bytes()),
+        }]
+    })
+    .to_string();
+
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 4096];
+            let _ = socket.read(&mut request).await;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                jwks.len(),
+                jwks
+            );
+            let _ = socket.write_all(response.as_bytes()).await;
+        }
+    });
+    issuer
+}
+
+fn id_token(
+    key: &SigningKey,
+    issuer: &String,
+    audience: &str,
+    subject: &str,
+    email: &str,
+    nonce: &String,
+) -> String {
+    let mut header = Header::new(Algorithm::EdDSA);
+    header.kid = Some(KID.to_string());
+    let claims = json!({
+        "iss": issuer,
+        "aud": audience,
+        "sub": subject,
+        "email": email,
+        "email_verified": true,
+        "nonce": nonce,
+        "iat": Utc::now().timestamp(),
+        "exp": Utc::now().timestamp() + 600,
+    });
+    let der = key.to_pkcs8_der().unwrap();
+    encode(&header, &claims, &EncodingKey::from_ed_der(der.as_bytes())).unwrap()
+}
+
+#[tokio::test]
+async fn login_user_oidc_test() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    env::set_var("RUST_LOG", "debug");
+    env::set_var(ENV_VAR_ENVIRONMENT, DEV_ENV);
+    env::set_var("AWS_REGION", "eu-central-1");
+
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let docker = clients::Cli::default();
+
+    let mut local_stack = images::local_stack::LocalStack::default();
+    local_stack.set_services("dynamodb,secretsmanager");
+    let node = docker.run(local_stack);
+    let host_port = node.get_host_port_ipv4(4566);
+
+    let shared_config = build_local_stack_connection(host_port).await;
+
+    let secrets_client = aws_sdk_secretsmanager::Client::new(&shared_config);
+    let creation2 = create_secrets(&secrets_client).await;
+    assert!(creation2.is_ok());
+
+    let mut config = Config::new();
+    config.setup().await;
+    config.set_aws_config(&shared_config); //rewrite configuration to use our current testcontainer instead
+    config.load_secret(SECRETS_MANAGER_APP_KEYS.clone()).await;
+
+    let creation = UserAllSchema::create_schema(&config).await;
+    assert!(creation.is_ok());
+
+    let user_service = UsersService::new(UsersRepo::new(&config));
+
+    let key = SigningKey::from_bytes(&[7u8; 32]);
+    let issuer = mock_identity_provider(&key).await;
+    let provider = OidcProvider {
+        name: "mock".to_string(),
+        issuer: issuer.clone(),
+        jwks_uri: format!("{}/keys", issuer),
+        client_ids: vec![CLIENT_ID.to_string()],
+    };
+    let oidc_service = OidcLoginService::new(
+        IdentitiesRepo::new(&config),
+        SessionsRepo::new(&config),
+        vec![provider],
+    );
+
+    // first login creates the user
+    let nonce = oidc_service.nonce().await?;
+    let token = id_token(&key, &issuer, CLIENT_ID, "sub-1", "new@truly.test", &nonce);
+    let identity = oidc_service.verify(&token, &nonce).await?;
+    assert_eq!(identity.subject(), "sub-1");
+    let first = oidc_service.login(&user_service, &identity).await?;
+
+    let created = user_service.get_by_id(&first.user_id).await?;
+    assert_eq!(created.email(), &Some("new@truly.test".to_string()));
+    assert!(created.email_verified());
+
+    // the nonce works only once
+    let replayed = oidc_service.verify(&token, &nonce).await;
+    assert!(replayed.is_err());
+
+    // the same identity logs the same user in again
+    let nonce = oidc_service.nonce().await?;
+    let token = id_token(&key, &issuer, CLIENT_ID, "sub-1", "new@truly.test", &nonce);
+    let identity = oidc_service.verify(&token, &nonce).await?;
+    let second = oidc_service.login(&user_service, &identity).await?;
+    assert_eq!(first.user_id, second.user_id);
+
+    let linked = oidc_service.get_by_user(&first.user_id).await?;
+    assert_eq!(linked.len(), 1);
+    assert_eq!(linked[0].issuer(), &issuer);
+
+    // tokens issued for another app are refused
+    let nonce = oidc_service.nonce().await?;
+    let token = id_token(
+        &key,
+        &issuer,
+        "another-app",
+        "sub-1",
+        "new@truly.test",
+        &nonce,
+    );
+    assert!(oidc_service.verify(&token, &nonce).await.is_err());
+
+    // someone else's key can't sign for the provider
+    let nonce = oidc_service.nonce().await?;
+    let intruder = SigningKey::from_bytes(&[9u8; 32]);
+    let token = id_token(
+        &intruder,
+        &issuer,
+        CLIENT_ID,
+        "sub-1",
+        "new@truly.test",
+        &nonce,
+    );
+    assert!(oidc_service.verify(&token, &nonce).await.is_err());
+
+    // untrusted issuers are refused
+    let nonce = oidc_service.nonce().await?;
+    let other = "http://127.0.0.1:1".to_string();
+    let token = id_token(&key, &other, CLIENT_ID, "sub-1", "new@truly.test", &nonce);
+    assert!(oidc_service.verify(&token, &nonce).await.is_err());
+
+    // a verified email links to the account already holding it, the nonce comes hashed
+    let mut existing = User::new();
+    existing.set_email(&"linked@truly.test".to_string());
+    existing.set_email_verified(true);
+    let existing_id = user_service.add(&mut existing, &None).await?;
+
+    let nonce = oidc_service.nonce().await?;
+    let hashed = hex::encode(Sha256::digest(nonce.as_bytes()));
+    let token = id_token(
+        &key,
+        &issuer,
+        CLIENT_ID,
+        "sub-2",
+        "linked@truly.test",
+        &hashed,
+    );
+    let identity = oidc_service.verify(&token, &nonce).await?;
+    let res = oidc_service.login(&user_service, &identity).await?;
+    assert_eq!(existing_id, res.user_id);
+
+    oidc_service.unlink_all(&existing_id).await?;
+    assert!(oidc_service.get_by_user(&existing_id).await?.is_empty());
+
+    Ok(())
+}
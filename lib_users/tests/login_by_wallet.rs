@@ -4,16 +4,58 @@ use lib_config::environment::{DEV_ENV, ENV_VAR_ENVIRONMENT};
 use lib_config::infra::build_local_stack_connection;
 use lib_config::schema::Schema;
 use lib_config::{config::Config, secrets::SECRETS_MANAGER_APP_KEYS};
+use chrono::{SecondsFormat, Utc};
+use k256::ecdsa::SigningKey;
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use lib_users::models::session::NoncePurpose;
 use lib_users::models::user::User;
 use lib_users::repositories::schema_user::UserAllSchema;
+use lib_users::repositories::sessions::{SessionRepository, SessionsRepo};
 use lib_users::repositories::users::UsersRepo;
 use lib_users::services::login::LoginOps;
 use lib_users::services::users::{UserManipulation, UsersService};
+use lib_users::services::wallet_login::{
+    WalletLoginManipulation, WalletLoginService, SIWE_CHAIN_ID, SIWE_DOMAIN, SIWE_URI,
+};
+use sha3::{Digest, Keccak256};
 use std::env;
 use testcontainers::*;
 
 use crate::common::create_secrets;
 
+fn wallet_address(key: &SigningKey) -> String {
+    let point = key.verifying_key().to_encoded_point(false);
+    let hash = Keccak256::digest(&point.as_bytes()[1..]);
+    let address: [u8; 20] = hash[12..].try_into().unwrap();
+    siwe::eip55(&address)
+}
+
+fn siwe_message(address: &String, nonce: &String) -> String {
+    siwe_message_for(address, nonce, SIWE_URI, SIWE_CHAIN_ID)
+}
+
+fn siwe_message_for(address: &String, nonce: &String, uri: &str, chain_id: u64) -> String {
+    format!(
+        "{domain} wants you to sign in with your Ethereum account:\n{address}\n\nSign in to truly.video\n\nURI: {uri}\nVersion: 1\nChain ID: {chain_id}\nNonce: {nonce}\nIssued At: {issued_at}",
+        domain = SIWE_DOMAIN,
+        address = address,
+        uri = uri,
+        chain_id = chain_id,
+        nonce = nonce,
+        issued_at = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+    )
+}
+
+// EIP-191 personal_sign, r || s || v
+fn sign(key: &SigningKey, message: &String) -> String {
+    let mut hasher = Keccak256::new();
+    hasher.update(format!("\x19Ethereum Signed Message:\n{}", message.len()).as_bytes());
+    hasher.update(message.as_bytes());
+    let (signature, recovery_id) = key.sign_prehash_recoverable(&hasher.finalize()).unwrap();
+    let mut bytes = signature.to_bytes().to_vec();
+    bytes.push(27 + recovery_id.to_byte());
+    format!("0x{}", hex::encode(bytes))
+}
 
 #[tokio::test]
 async fn login_user_wallet_test() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
@@ -53,17 +95,86 @@ async fn login_user_wallet_test() -> Result<(), Box<dyn std::error::Error + Send
     let user_repo = UsersRepo::new(&config);
     let user_service = UsersService::new(user_repo);
 
-    let wallet = Some("wallet-2r1234-12341234-12341234-1234-123".to_string());
+    let wallet_service = WalletLoginService::new(
+        SessionsRepo::new(&config),
+        SIWE_DOMAIN,
+        SIWE_URI,
+        SIWE_CHAIN_ID,
+    );
+
+    let key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+    let address = wallet_address(&key);
 
     let mut new_user = User::new();
-    new_user.set_wallet_address(&wallet.clone().unwrap());
+    new_user.set_wallet_address(&address);
 
     let new_id = user_service.add(&mut new_user, &None).await?;
 
-    let res = user_service.login(&None, &wallet, &None, &None).await?;
+    let nonce = wallet_service.nonce().await?;
+    let message = siwe_message(&address, &nonce);
+    let signature = sign(&key, &message);
+
+    let wallet = wallet_service.verify(&message, &signature).await?;
+    assert_eq!(wallet.address(), &address);
+
+    let res = user_service.login(&None, &Some(wallet), &None, &None).await?;
 
     assert_eq!(new_id, res.user_id);
 
+    // the nonce works only once
+    let replayed = wallet_service.verify(&message, &signature).await;
+    assert!(replayed.is_err());
+
+    // someone else's key can't sign for the address
+    let nonce = wallet_service.nonce().await?;
+    let message = siwe_message(&address, &nonce);
+    let intruder = SigningKey::from_slice(&[9u8; 32]).unwrap();
+    let forged = wallet_service.verify(&message, &sign(&intruder, &message)).await;
+    assert!(forged.is_err());
+
+    // unknown nonces are refused even with a good signature
+    let message = siwe_message(&address, &"abcdef0123456789".to_string());
+    let unknown = wallet_service.verify(&message, &sign(&key, &message)).await;
+    assert!(unknown.is_err());
+
+    // messages signed for another site or another chain don't log in here
+    let nonce = wallet_service.nonce().await?;
+    let message = siwe_message_for(&address, &nonce, "https://evil.example", SIWE_CHAIN_ID);
+    let other_site = wallet_service.verify(&message, &sign(&key, &message)).await;
+    assert!(other_site.is_err());
+    let message = siwe_message_for(&address, &nonce, SIWE_URI, 137);
+    let other_chain = wallet_service.verify(&message, &sign(&key, &message)).await;
+    assert!(other_chain.is_err());
+
+    // and the nonce they carried is still good for the right message
+    let message = siwe_message(&address, &nonce);
+    wallet_service
+        .verify(&message, &sign(&key, &message))
+        .await?;
+
+    // an oidc login can't spend a wallet nonce, nor the other way round
+    let sessions = SessionsRepo::new(&config);
+    let nonce = wallet_service.nonce().await?;
+    assert!(!sessions.consume_nonce(&NoncePurpose::Oidc, &nonce).await?);
+    let message = siwe_message(&address, &nonce);
+    wallet_service
+        .verify(&message, &sign(&key, &message))
+        .await?;
+
+    let expires_at = Utc::now() + chrono::Duration::minutes(10);
+    let oidc_nonce = "0123456789abcdef".to_string();
+    sessions
+        .add_nonce(&NoncePurpose::Oidc, &oidc_nonce, &expires_at)
+        .await?;
+    let message = siwe_message(&address, &oidc_nonce);
+    let crossed = wallet_service.verify(&message, &sign(&key, &message)).await;
+    assert!(crossed.is_err());
+    assert!(
+        sessions
+            .consume_nonce(&NoncePurpose::Oidc, &oidc_nonce)
+            .await?
+    );
+
     Ok(())
 }
 
@@ -0,0 +1,316 @@
+mod common;
+
+use base64::{engine::general_purpose, Engine as _};
+use ed25519_dalek::pkcs8::EncodePublicKey;
+use ed25519_dalek::{Signer, SigningKey};
+use lib_config::environment::{DEV_ENV, ENV_VAR_ENVIRONMENT};
+use lib_config::infra::build_local_stack_connection;
+use lib_config::schema::Schema;
+use lib_config::{config::Config, secrets::SECRETS_MANAGER_APP_KEYS};
+use lib_users::errors::login_methods::{LoginMethodLastError, LoginMethodNotFoundError};
+use lib_users::errors::users::UserAlreadyExistsError;
+use lib_users::models::device::{device_challenge_message, VerifiedDevice};
+use lib_users::models::identity::LinkedIdentity;
+use lib_users::models::login_method::LoginMethodKind;
+use lib_users::models::user::User;
+use lib_users::repositories::devices::DevicesRepo;
+use lib_users::repositories::identities::{IdentitiesRepo, IdentityRepository};
+use lib_users::repositories::schema_user::UserAllSchema;
+use lib_users::repositories::users::UsersRepo;
+use lib_users::services::devices::{DeviceManipulation, DeviceService};
+use lib_users::services::login::LoginOps;
+use lib_users::services::login_methods::{LoginMethodManipulation, LoginMethodService};
+use lib_users::services::users::{UpdatableFildsUser, UserManipulation, UsersService};
+use std::env;
+use testcontainers::*;
+
+use crate::common::create_secrets;
+
+// pairs the device key with the user and answers a challenge with it
+async fn pair_and_verify(
+    device_service: &DeviceService,
+    user_id: &String,
+    device: &String,
+    seed: u8,
+) -> Result<VerifiedDevice, Box<dyn std::error::Error + Send + Sync>> {
+    let key = SigningKey::from_bytes(&[seed; 32]);
+    let public_key =
+        general_purpose::STANDARD.encode(key.verifying_key().to_public_key_der()?.as_bytes());
+    device_service
+        .register(user_id, device, &"EdDSA".to_string(), &public_key)
+        .await?;
+    let challenge = device_service.challenge(device).await?;
+    let signature = key.sign(device_challenge_message(device, &challenge).as_bytes());
+    device_service
+        .verify(
+            device,
+            &challenge,
+            &general_purpose::STANDARD.encode(signature.to_bytes()),
+        )
+        .await
+}
+
+#[tokio::test]
+async fn link_and_unlink_login_methods_test() -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+{
+    env::set_var("RUST_LOG", "debug");
+    env::set_var(ENV_VAR_ENVIRONMENT, DEV_ENV);
+    env::set_var("AWS_REGION", "eu-central-1");
+
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let docker = clients::Cli::default();
+
+    let mut local_stack = images::local_stack::LocalStack::default();
+    local_stack.set_services("dynamodb,secretsmanager");
+    let node = docker.run(local_stack);
+    let host_port = node.get_host_port_ipv4(4566);
+
+    let shared_config = build_local_stack_connection(host_port).await;
+
+    let secrets_client = aws_sdk_secretsmanager::Client::new(&shared_config);
+    let creation2 = create_secrets(&secrets_client).await;
+    assert!(creation2.is_ok());
+
+    let mut config = Config::new();
+    config.setup().await;
+    config.set_aws_config(&shared_config); //rewrite configuration to use our current testcontainer instead
+    config.load_secret(SECRETS_MANAGER_APP_KEYS.clone()).await;
+
+    let creation = UserAllSchema::create_schema(&config).await;
+    assert!(creation.is_ok());
+
+    let user_service = UsersService::new(UsersRepo::new(&config));
+    let device_service = DeviceService::new(DevicesRepo::new(&config));
+    let login_method_service = LoginMethodService::new(
+        UsersRepo::new(&config),
+        IdentitiesRepo::new(&config),
+        DevicesRepo::new(&config),
+    );
+
+    let email = "pepe@test.cat.io".to_string();
+    let password = Some("123456789aA$%^@2".to_string());
+    let mut new_user = User::new();
+    new_user.set_email(&email);
+    let user_id = user_service.add(&mut new_user, &password).await?;
+
+    let mut other_user = User::new();
+    other_user.set_email(&"other@test.cat.io".to_string());
+    other_user.set_device(&"device-other".to_string());
+    let other_id = user_service.add(&mut other_user, &None).await?;
+
+    let methods = login_method_service.get(&user_id).await?;
+    assert_eq!(methods.email, Some(email.clone()));
+    assert!(methods.password);
+    assert_eq!(methods.count(), 1);
+
+    // the only way in can't go
+    let last = login_method_service
+        .unlink(&user_id, &LoginMethodKind::Email)
+        .await;
+    assert!(last
+        .unwrap_err()
+        .downcast_ref::<LoginMethodLastError>()
+        .is_some());
+
+    let missing = login_method_service
+        .unlink(&user_id, &LoginMethodKind::Wallet)
+        .await;
+    assert!(missing
+        .unwrap_err()
+        .downcast_ref::<LoginMethodNotFoundError>()
+        .is_some());
+
+    // a device paired with another account collides
+    let other_device =
+        pair_and_verify(&device_service, &other_id, &"device-other".to_string(), 3).await?;
+    let taken = login_method_service
+        .link_device(&user_id, &other_device)
+        .await;
+    assert!(taken
+        .unwrap_err()
+        .downcast_ref::<UserAlreadyExistsError>()
+        .is_some());
+    let taken = login_method_service
+        .check_email_available(&user_id, &"other@test.cat.io".to_string())
+        .await;
+    assert!(taken
+        .unwrap_err()
+        .downcast_ref::<UserAlreadyExistsError>()
+        .is_some());
+    login_method_service
+        .check_email_available(&user_id, &email)
+        .await?;
+
+    // profile updates go through the same check
+    let updates = UpdatableFildsUser {
+        email: None,
+        device: Some("device-other".to_string()),
+        wallet: None,
+        status: None,
+    };
+    let taken = user_service.update(&user_id, &updates).await;
+    assert!(taken
+        .unwrap_err()
+        .downcast_ref::<UserAlreadyExistsError>()
+        .is_some());
+    assert_eq!(
+        user_service.get_by_id(&other_id).await?.device(),
+        &Some("device-other".to_string())
+    );
+
+    // with a second method the email can be removed, and logs in no more
+    let mine = "device-mine".to_string();
+    let my_device = pair_and_verify(&device_service, &user_id, &mine, 4).await?;
+    login_method_service
+        .link_device(&user_id, &my_device)
+        .await?;
+    let methods = login_method_service.get(&user_id).await?;
+    assert_eq!(methods.device, Some(mine.clone()));
+    assert!(methods.device_paired);
+    assert_eq!(methods.count(), 2);
+
+    // once its key is revoked the device is no way in, the email has to stay
+    device_service.revoke(&user_id, &mine).await?;
+    let methods = login_method_service.get(&user_id).await?;
+    assert!(!methods.device_paired);
+    assert_eq!(methods.count(), 1);
+    let last = login_method_service
+        .unlink(&user_id, &LoginMethodKind::Email)
+        .await;
+    assert!(last
+        .unwrap_err()
+        .downcast_ref::<LoginMethodLastError>()
+        .is_some());
+    pair_and_verify(&device_service, &user_id, &mine, 5).await?;
+
+    login_method_service
+        .unlink(&user_id, &LoginMethodKind::Email)
+        .await?;
+    let methods = login_method_service.get(&user_id).await?;
+    assert_eq!(methods.email, None);
+    assert!(!methods.password);
+    assert!(user_service
+        .login(&None, &None, &Some(email.clone()), &password)
+        .await
+        .is_err());
+    assert!(user_service.get_by_email(&email).await.is_err());
+
+    let by_device = user_service
+        .get_by_device(&"device-mine".to_string())
+        .await?;
+    assert_eq!(by_device.user_id(), &user_id);
+
+    let last = login_method_service
+        .unlink(&user_id, &LoginMethodKind::Device)
+        .await;
+    assert!(last
+        .unwrap_err()
+        .downcast_ref::<LoginMethodLastError>()
+        .is_some());
+
+    // a confirmed email comes back verified
+    login_method_service.link_email(&user_id, &email).await?;
+    let user = user_service.get_by_id(&user_id).await?;
+    assert_eq!(user.email(), &Some(email.clone()));
+    assert!(user.email_verified());
+    assert_eq!(login_method_service.get(&user_id).await?.count(), 2);
+
+    login_method_service
+        .unlink(&user_id, &LoginMethodKind::Device)
+        .await?;
+    assert!(user_service
+        .get_by_device(&"device-mine".to_string())
+        .await
+        .is_err());
+    // and its key goes along
+    let devices = device_service.get_by_user(&user_id).await?;
+    assert!(devices.iter().all(|d| d.revoked()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn take_over_login_methods_test() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    env::set_var("RUST_LOG", "debug");
+    env::set_var(ENV_VAR_ENVIRONMENT, DEV_ENV);
+    env::set_var("AWS_REGION", "eu-central-1");
+
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let docker = clients::Cli::default();
+
+    let mut local_stack = images::local_stack::LocalStack::default();
+    local_stack.set_services("dynamodb,secretsmanager");
+    let node = docker.run(local_stack);
+    let host_port = node.get_host_port_ipv4(4566);
+
+    let shared_config = build_local_stack_connection(host_port).await;
+
+    let secrets_client = aws_sdk_secretsmanager::Client::new(&shared_config);
+    let creation2 = create_secrets(&secrets_client).await;
+    assert!(creation2.is_ok());
+
+    let mut config = Config::new();
+    config.setup().await;
+    config.set_aws_config(&shared_config); //rewrite configuration to use our current testcontainer instead
+    config.load_secret(SECRETS_MANAGER_APP_KEYS.clone()).await;
+
+    let creation = UserAllSchema::create_schema(&config).await;
+    assert!(creation.is_ok());
+
+    let user_service = UsersService::new(UsersRepo::new(&config));
+    let identities_repo = IdentitiesRepo::new(&config);
+    let login_method_service = LoginMethodService::new(
+        UsersRepo::new(&config),
+        IdentitiesRepo::new(&config),
+        DevicesRepo::new(&config),
+    );
+
+    let email = "pepe@test.cat.io".to_string();
+    let mut full_user = User::new();
+    full_user.set_email(&email);
+    let full_id = user_service
+        .add(&mut full_user, &Some("123456789aA$%^@2".to_string()))
+        .await?;
+
+    let device = "device-anonymous".to_string();
+    let mut device_user = User::new();
+    device_user.set_device(&device);
+    let device_id = user_service.add(&mut device_user, &None).await?;
+
+    let mut identity = LinkedIdentity::new();
+    identity.set_issuer(&"https://accounts.test.io".to_string());
+    identity.set_subject(&"subject-1".to_string());
+    identity.set_user_id(&device_id);
+    assert!(identities_repo.add(&identity).await?);
+
+    login_method_service.take_over(&full_id, &device_id).await?;
+
+    // the device now signs in to the full account
+    let by_device = user_service.get_by_device(&device).await?;
+    assert_eq!(by_device.user_id(), &full_id);
+    let methods = login_method_service.get(&full_id).await?;
+    assert_eq!(methods.email, Some(email.clone()));
+    assert_eq!(methods.device, Some(device.clone()));
+    assert_eq!(methods.identities.len(), 1);
+    assert_eq!(methods.identities[0].user_id(), &full_id);
+
+    let left = login_method_service.get(&device_id).await?;
+    assert_eq!(left.count(), 0);
+
+    // a device clashing with the one already there is dropped
+    let other_device = "device-other".to_string();
+    let mut other_user = User::new();
+    other_user.set_device(&other_device);
+    let other_id = user_service.add(&mut other_user, &None).await?;
+
+    login_method_service.take_over(&full_id, &other_id).await?;
+    assert_eq!(
+        login_method_service.get(&full_id).await?.device,
+        Some(device.clone())
+    );
+    assert!(user_service.get_by_device(&other_device).await.is_err());
+
+    Ok(())
+}
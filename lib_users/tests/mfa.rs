@@ -0,0 +1,82 @@
+use chrono::Utc;
+use data_encoding::BASE32_NOPAD;
+use lib_config::config::Config;
+use lib_config::environment::{DEV_ENV, ENV_VAR_ENVIRONMENT};
+use lib_config::infra::build_local_stack_connection;
+use lib_config::schema::Schema;
+use lib_users::errors::mfa::{MfaAlreadyEnabledError, MfaCodeError};
+use lib_users::models::mfa::{totp_code, TOTP_PERIOD_SECONDS};
+use lib_users::models::user::UserRoles;
+use lib_users::repositories::mfa::MfaRepo;
+use lib_users::repositories::schema_user::UserAllSchema;
+use lib_users::services::mfa::{mfa_required, MfaManipulation, MfaService, MFA_RECOVERY_CODES};
+use std::env;
+use testcontainers::*;
+
+#[tokio::test]
+async fn mfa_totp_test() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    env::set_var("RUST_LOG", "debug");
+    env::set_var(ENV_VAR_ENVIRONMENT, DEV_ENV);
+    env::set_var("AWS_REGION", "eu-central-1");
+
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let docker = clients::Cli::default();
+
+    let mut local_stack = images::local_stack::LocalStack::default();
+    local_stack.set_services("dynamodb");
+    let node = docker.run(local_stack);
+    let host_port = node.get_host_port_ipv4(4566);
+
+    let shared_config = build_local_stack_connection(host_port).await;
+
+    let mut config = Config::new();
+    config.setup().await;
+    config.set_aws_config(&shared_config); //rewrite configuration to use our current testcontainer instead
+
+    let creation = UserAllSchema::create_schema(&config).await;
+    assert!(creation.is_ok());
+
+    let mfa_service = MfaService::new(MfaRepo::new(&config));
+
+    assert!(mfa_required(&vec![UserRoles::Basic, UserRoles::Admin]));
+    assert!(!mfa_required(&vec![UserRoles::Basic]));
+
+    let user_id = "user-mfa-1".to_string();
+    assert!(!mfa_service.is_enabled(&user_id).await?);
+
+    let enrollment = mfa_service.enroll(&user_id, &"pepe@test.cat.io".to_string()).await?;
+    assert!(enrollment.uri.starts_with("otpauth://totp/truly.video:pepe@test.cat.io?secret="));
+    assert_eq!(enrollment.recovery_codes.len(), MFA_RECOVERY_CODES);
+    // pending until the first code arrives
+    assert!(!mfa_service.is_enabled(&user_id).await?);
+
+    let key = BASE32_NOPAD.decode(enrollment.secret.as_bytes())?;
+    let step = Utc::now().timestamp() / TOTP_PERIOD_SECONDS;
+
+    let wrong = mfa_service.confirm(&user_id, &"000000".to_string()).await;
+    if totp_code(&key, step) != "000000" {
+        assert!(wrong.unwrap_err().downcast_ref::<MfaCodeError>().is_some());
+    }
+
+    mfa_service.confirm(&user_id, &totp_code(&key, step)).await?;
+    assert!(mfa_service.is_enabled(&user_id).await?);
+
+    let again = mfa_service.enroll(&user_id, &"pepe@test.cat.io".to_string()).await;
+    assert!(again.unwrap_err().downcast_ref::<MfaAlreadyEnabledError>().is_some());
+
+    // the code used to confirm can't log in
+    let replay = mfa_service.verify(&user_id, &totp_code(&key, step)).await;
+    assert!(replay.is_err());
+    mfa_service.verify(&user_id, &totp_code(&key, step + 1)).await?;
+
+    // recovery codes work once, typed in any case
+    let recovery = enrollment.recovery_codes[0].to_lowercase();
+    mfa_service.verify(&user_id, &recovery).await?;
+    assert!(mfa_service.verify(&user_id, &recovery).await.is_err());
+
+    mfa_service.disable(&user_id, &enrollment.recovery_codes[1]).await?;
+    assert!(!mfa_service.is_enabled(&user_id).await?);
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use lib_config::environment::{DEV_ENV, PROD_ENV};
+use lib_users::models::password_hash::{
+    hash_password, needs_rehash, verify_password, PasswordHashParams,
+};
+
+#[test]
+fn argon2id_hash_and_verify_test() {
+    let params = PasswordHashParams::for_environment(DEV_ENV);
+    let secret = "pepper";
+
+    let hash = hash_password("123456789aA$%^@2", secret, &params).unwrap();
+    assert!(hash.starts_with("$argon2id$v=19$m=19456,t=2,p=1$"));
+    assert!(!needs_rehash(&hash, &params));
+
+    assert!(verify_password("123456789aA$%^@2", &hash, secret).unwrap());
+    assert!(!verify_password("123456789aA$%^@3", &hash, secret).unwrap());
+    assert!(!verify_password("123456789aA$%^@2", &hash, "other").unwrap());
+
+    // same password, new salt
+    let again = hash_password("123456789aA$%^@2", secret, &params).unwrap();
+    assert_ne!(hash, again);
+}
+
+#[test]
+fn outdated_hashes_need_rehash_test() {
+    let dev = PasswordHashParams::for_environment(DEV_ENV);
+    let prod = PasswordHashParams::for_environment(PROD_ENV);
+    let secret = "pepper";
+
+    let hash = hash_password("123456789aA$%^@2", secret, &dev).unwrap();
+    assert!(needs_rehash(&hash, &prod));
+
+    // what passwords were stored with before moving to argon2id
+    let legacy_config = argon2::Config {
+        variant: argon2::Variant::Argon2i,
+        version: argon2::Version::Version13,
+        mem_cost: 1024,
+        time_cost: 1,
+        lanes: 1,
+        secret: secret.as_bytes(),
+        ad: &[],
+        hash_length: 32,
+    };
+    let legacy = argon2::hash_encoded(
+        "123456789aA$%^@2".as_bytes(),
+        "a-salt-of-uuid-size".as_bytes(),
+        &legacy_config,
+    )
+    .unwrap();
+    assert!(verify_password("123456789aA$%^@2", &legacy, secret).unwrap());
+    assert!(needs_rehash(&legacy, &dev));
+
+    assert!(needs_rehash("not a hash", &dev));
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use lib_config::environment::{DEV_ENV, ENV_VAR_ENVIRONMENT};
+use lib_config::infra::build_local_stack_connection;
+use lib_config::schema::Schema;
+use lib_config::{config::Config, secrets::SECRETS_MANAGER_APP_KEYS};
+use lib_users::models::one_time_token::OneTimeTokenPurpose;
+use lib_users::models::user::User;
+use lib_users::repositories::one_time_tokens::OneTimeTokensRepo;
+use lib_users::repositories::schema_user::UserAllSchema;
+use lib_users::repositories::users::UsersRepo;
+use lib_users::services::login::LoginOps;
+use lib_users::services::one_time_tokens::{
+    OneTimeTokenManipulation, OneTimeTokenService, PASSWORD_RESET_EXP_HOURS,
+};
+use lib_users::services::users::{UserManipulation, UsersService};
+use std::env;
+use testcontainers::*;
+
+use crate::common::create_secrets;
+
+#[tokio::test]
+async fn password_reset_test() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    env::set_var("RUST_LOG", "debug");
+    env::set_var(ENV_VAR_ENVIRONMENT, DEV_ENV);
+    env::set_var("AWS_REGION", "eu-central-1");
+
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let docker = clients::Cli::default();
+
+    let mut local_stack = images::local_stack::LocalStack::default();
+    local_stack.set_services("dynamodb,secretsmanager");
+    let node = docker.run(local_stack);
+    let host_port = node.get_host_port_ipv4(4566);
+
+    let shared_config = build_local_stack_connection(host_port).await;
+
+    let secrets_client = aws_sdk_secretsmanager::Client::new(&shared_config);
+    let creation2 = create_secrets(&secrets_client).await;
+    assert!(&creation2.is_ok());
+
+    let mut config = Config::new();
+    config.setup().await;
+    config.set_aws_config(&shared_config);
+    config.load_secret(SECRETS_MANAGER_APP_KEYS.clone()).await;
+
+    let creation = UserAllSchema::create_schema(&config).await;
+    assert!(&creation.is_ok());
+
+    let user_service = UsersService::new(UsersRepo::new(&config));
+    let token_service = OneTimeTokenService::new(OneTimeTokensRepo::new(&config), &config);
+
+    let email = Some("pepe@test.cat.io".to_string());
+    let password = Some("123456789aA$%^@2".to_string());
+    let mut new_user = User::new();
+    new_user.set_email(&email.clone().unwrap());
+    let new_id = user_service.add(&mut new_user, &password).await?;
+    user_service
+        .mark_email_verified(&new_id, &email.clone().unwrap())
+        .await?;
+
+    let token = token_service
+        .issue(
+            &new_id,
+            &email.clone().unwrap(),
+            &OneTimeTokenPurpose::PasswordReset,
+            PASSWORD_RESET_EXP_HOURS,
+        )
+        .await?;
+
+    // a reset token can't verify an email and the other way around
+    let wrong_purpose = token_service
+        .consume(&token, &OneTimeTokenPurpose::EmailVerification)
+        .await;
+    assert!(wrong_purpose.is_err());
+
+    let consumed = token_service
+        .consume(&token, &OneTimeTokenPurpose::PasswordReset)
+        .await?;
+    assert_eq!(consumed.user_id(), &new_id);
+
+    let new_password = Some("123456789aA$%^@2asdSDasd".to_string());
+    user_service
+        .update_password(consumed.user_id(), &new_password.clone().unwrap())
+        .await?;
+
+    let res = user_service.login(&None, &None, &email, &new_password).await?;
+    assert_eq!(res.user_id, new_id);
+    let old = user_service.login(&None, &None, &email, &password).await;
+    assert!(old.is_err());
+
+    let reused = token_service
+        .consume(&token, &OneTimeTokenPurpose::PasswordReset)
+        .await;
+    assert!(reused.is_err());
+
+    Ok(())
+}
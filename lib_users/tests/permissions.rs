@@ -0,0 +1,67 @@
+use lib_users::models::permission::{has_permission, role_permissions, Permission};
+use lib_users::models::user::UserRoles;
+use lib_users::services::mfa::mfa_required;
+
+#[test]
+fn permission_matrix_test() {
+    let basic = vec![UserRoles::Basic];
+    assert!(role_permissions(&UserRoles::Basic).is_empty());
+    assert!(!has_permission(&basic, &Permission::ViewPii));
+    assert!(!has_permission(
+        &vec![UserRoles::Basic, UserRoles::VerifiedPress],
+        &Permission::DisableAsset
+    ));
+
+    let admin = vec![UserRoles::Basic, UserRoles::Admin];
+    for permission in [
+        Permission::ViewPii,
+        Permission::ManageUsers,
+        Permission::ManageRoles,
+        Permission::UnlockUsers,
+        Permission::DisableAsset,
+        Permission::ReprocessJobs,
+        Permission::GrantLicenses,
+        Permission::ViewAudit,
+    ] {
+        assert!(has_permission(&admin, &permission));
+    }
+
+    let moderator = vec![UserRoles::Basic, UserRoles::Moderator];
+    assert!(has_permission(&moderator, &Permission::DisableAsset));
+    assert!(has_permission(&moderator, &Permission::ReprocessJobs));
+    assert!(!has_permission(&moderator, &Permission::ViewPii));
+    assert!(!has_permission(&moderator, &Permission::ManageRoles));
+
+    let support = vec![UserRoles::Basic, UserRoles::Support];
+    assert!(has_permission(&support, &Permission::ViewPii));
+    assert!(has_permission(&support, &Permission::UnlockUsers));
+    assert!(!has_permission(&support, &Permission::ManageUsers));
+    assert!(!has_permission(&support, &Permission::DisableAsset));
+    assert!(!has_permission(&support, &Permission::ViewAudit));
+
+    let partner = vec![UserRoles::Basic, UserRoles::Partner];
+    assert!(has_permission(&partner, &Permission::GrantLicenses));
+    assert!(!has_permission(&partner, &Permission::ViewPii));
+
+    // a user holds the union of its roles
+    let both = vec![UserRoles::Moderator, UserRoles::Support];
+    assert!(has_permission(&both, &Permission::DisableAsset));
+    assert!(has_permission(&both, &Permission::ViewPii));
+
+    // staff work from lambda_admin and need mfa, partners and press don't
+    assert!(mfa_required(&moderator));
+    assert!(mfa_required(&support));
+    assert!(!mfa_required(&partner));
+    assert!(!mfa_required(&vec![UserRoles::VerifiedPress]));
+
+    let names = UserRoles::to_vec_str(&vec![UserRoles::Moderator, UserRoles::VerifiedPress]);
+    assert_eq!(
+        names,
+        vec!["Moderator".to_string(), "VerifiedPress".to_string()]
+    );
+    assert_eq!(
+        UserRoles::from_vec_str(&names),
+        vec![UserRoles::Moderator, UserRoles::VerifiedPress]
+    );
+    assert_eq!(UserRoles::deserialize("Root"), None);
+}
@@ -0,0 +1,159 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use lib_config::environment::{DEV_ENV, ENV_VAR_ENVIRONMENT};
+use lib_config::infra::build_local_stack_connection;
+use lib_config::schema::Schema;
+use lib_config::{config::Config, secrets::SECRETS_MANAGER_APP_KEYS};
+use lib_users::errors::sessions::RefreshTokenError;
+use lib_users::models::session::{generate_refresh_token, hash_refresh_token, RefreshToken};
+use lib_users::repositories::schema_user::UserAllSchema;
+use lib_users::repositories::sessions::{SessionRepository, SessionsRepo};
+use lib_users::services::sessions::{SessionManipulation, SessionService};
+use std::env;
+use testcontainers::*;
+
+use crate::common::create_secrets;
+
+#[tokio::test]
+async fn refresh_token_rotation_test() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    env::set_var("RUST_LOG", "debug");
+    env::set_var(ENV_VAR_ENVIRONMENT, DEV_ENV);
+    env::set_var("AWS_REGION", "eu-central-1");
+
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let docker = clients::Cli::default();
+
+    let mut local_stack = images::local_stack::LocalStack::default();
+    local_stack.set_services("dynamodb,secretsmanager");
+    let node = docker.run(local_stack);
+    let host_port = node.get_host_port_ipv4(4566);
+
+    let shared_config = build_local_stack_connection(host_port).await;
+
+    let secrets_client = aws_sdk_secretsmanager::Client::new(&shared_config);
+    let creation2 = create_secrets(&secrets_client).await;
+    assert!(&creation2.is_ok());
+
+    let mut config = Config::new();
+    config.setup().await;
+    config.set_aws_config(&shared_config);
+    config.load_secret(SECRETS_MANAGER_APP_KEYS.clone()).await;
+
+    let creation = UserAllSchema::create_schema(&config).await;
+    assert!(&creation.is_ok());
+
+    let session_service = SessionService::new(SessionsRepo::new(&config));
+    let user_id = "user-1".to_string();
+
+    let audience = vec!["login".to_string(), "user".to_string()];
+
+    let first = session_service.start(&user_id, &audience, 8).await?;
+    let second = session_service.refresh(&first, 8).await?;
+    assert_eq!(second.user_id, user_id);
+    assert_eq!(second.audience, audience);
+    assert_ne!(second.refresh_token, first);
+
+    // the spent token is refused and takes the whole session down with it
+    let reused = session_service.refresh(&first, 8).await;
+    let err = reused.err().unwrap();
+    assert!(err.downcast_ref::<RefreshTokenError>().is_some());
+    let after_reuse = session_service.refresh(&second.refresh_token, 8).await;
+    assert!(after_reuse.is_err());
+
+    let unknown = session_service.refresh(&"not-a-token".to_string(), 8).await;
+    assert!(unknown.is_err());
+
+    // rotating carries the end of the session forward and never past it
+    let repo = SessionsRepo::new(&config);
+    let first = session_service.start(&user_id, &audience, 8).await?;
+    let started = repo
+        .get_refresh_token(&hash_refresh_token(&first))
+        .await?
+        .unwrap();
+    let rotated = session_service.refresh(&first, 24).await?;
+    let rotated = repo
+        .get_refresh_token(&hash_refresh_token(&rotated.refresh_token))
+        .await?
+        .unwrap();
+    assert_eq!(rotated.session_expires_at(), started.session_expires_at());
+    assert_eq!(rotated.expires_at(), started.session_expires_at());
+
+    // a token still valid on its own is refused once its session is over
+    let raw = generate_refresh_token();
+    let mut stale = RefreshToken::new();
+    stale.set_token_hash(&hash_refresh_token(&raw));
+    stale.set_user_id(&user_id);
+    stale.set_audience(&audience);
+    stale.set_expires_at(&(Utc::now() + Duration::hours(8)));
+    stale.set_session_expires_at(&(Utc::now() - Duration::minutes(1)));
+    repo.add_refresh_token(&stale).await?;
+    let over = session_service.refresh(&raw, 8).await;
+    let err = over.err().unwrap();
+    assert!(err.downcast_ref::<RefreshTokenError>().is_some());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn logout_revokes_tokens_test() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    env::set_var("RUST_LOG", "debug");
+    env::set_var(ENV_VAR_ENVIRONMENT, DEV_ENV);
+    env::set_var("AWS_REGION", "eu-central-1");
+
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let docker = clients::Cli::default();
+
+    let mut local_stack = images::local_stack::LocalStack::default();
+    local_stack.set_services("dynamodb,secretsmanager");
+    let node = docker.run(local_stack);
+    let host_port = node.get_host_port_ipv4(4566);
+
+    let shared_config = build_local_stack_connection(host_port).await;
+
+    let secrets_client = aws_sdk_secretsmanager::Client::new(&shared_config);
+    let creation2 = create_secrets(&secrets_client).await;
+    assert!(&creation2.is_ok());
+
+    let mut config = Config::new();
+    config.setup().await;
+    config.set_aws_config(&shared_config);
+    config.load_secret(SECRETS_MANAGER_APP_KEYS.clone()).await;
+
+    let creation = UserAllSchema::create_schema(&config).await;
+    assert!(&creation.is_ok());
+
+    let session_service = SessionService::new(SessionsRepo::new(&config));
+    let user_id = "user-1".to_string();
+    let jti = "b7e0c5f4-3c1d-4a38-9d8e-2f0d1a7c9e11".to_string();
+    let access_expires_at = Utc::now() + Duration::minutes(15);
+
+    let refresh_token = session_service
+        .start(&user_id, &vec!["login".to_string()], 8)
+        .await?;
+    assert!(!session_service.is_revoked(&jti).await?);
+
+    // someone else's refresh token can't be closed
+    let foreign = session_service
+        .logout(
+            &"user-2".to_string(),
+            &Some(refresh_token.clone()),
+            &jti,
+            &access_expires_at,
+        )
+        .await;
+    assert!(foreign.is_err());
+    assert!(!session_service.is_revoked(&jti).await?);
+
+    session_service
+        .logout(&user_id, &Some(refresh_token.clone()), &jti, &access_expires_at)
+        .await?;
+    assert!(session_service.is_revoked(&jti).await?);
+
+    let refreshed = session_service.refresh(&refresh_token, 8).await;
+    assert!(refreshed.is_err());
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use lib_config::environment::{DEV_ENV, ENV_VAR_ENVIRONMENT};
+use lib_config::infra::build_local_stack_connection;
+use lib_config::schema::Schema;
+use lib_config::{config::Config, secrets::SECRETS_MANAGER_APP_KEYS};
+use lib_users::models::api_key::ApiKeyScope;
+use lib_users::models::user::User;
+use lib_users::repositories::api_keys::ApiKeysRepo;
+use lib_users::repositories::schema_user::UserAllSchema;
+use lib_users::repositories::users::UsersRepo;
+use lib_users::services::api_keys::{ApiKeyManipulation, ApiKeyService};
+use lib_users::services::users::{UserManipulation, UsersService};
+use std::env;
+use testcontainers::*;
+
+use crate::common::create_secrets;
+
+#[tokio::test]
+async fn remove_user_and_logins_test() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    env::set_var("RUST_LOG", "debug");
+    env::set_var(ENV_VAR_ENVIRONMENT, DEV_ENV);
+    env::set_var("AWS_REGION", "eu-central-1");
+
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let docker = clients::Cli::default();
+
+    let mut local_stack = images::local_stack::LocalStack::default();
+    local_stack.set_services("dynamodb,secretsmanager");
+    let node = docker.run(local_stack);
+    let host_port = node.get_host_port_ipv4(4566);
+
+    let shared_config = build_local_stack_connection(host_port).await;
+
+    let secrets_client = aws_sdk_secretsmanager::Client::new(&shared_config);
+    let creation2 = create_secrets(&secrets_client).await;
+    assert!(&creation2.is_ok());
+
+    let mut config = Config::new();
+    config.setup().await;
+    config.set_aws_config(&shared_config);
+    config.load_secret(SECRETS_MANAGER_APP_KEYS.clone()).await;
+
+    let creation = UserAllSchema::create_schema(&config).await;
+    assert!(&creation.is_ok());
+
+    let user_service = UsersService::new(UsersRepo::new(&config));
+    let api_key_service = ApiKeyService::new(ApiKeysRepo::new(&config));
+
+    let email = "gone@test.cat.io".to_string();
+    let device = "a-device-to-forget".to_string();
+    let password = Some("123456789aA$%^@2".to_string());
+
+    let mut new_user = User::new();
+    new_user.set_email(&email);
+    new_user.set_device(&device);
+    let new_id = user_service.add(&mut new_user, &password).await?;
+
+    let (_, raw) = api_key_service
+        .create(&new_id, &"ci".to_string(), &vec![ApiKeyScope::LicensesRead])
+        .await?;
+    api_key_service.revoke_all(&new_id).await?;
+    assert!(api_key_service.authenticate(&raw).await.is_err());
+
+    user_service.remove_by_id(&new_id).await?;
+
+    assert!(user_service.get_by_id(&new_id).await.is_err());
+    assert!(user_service.get_by_email(&email).await.is_err());
+    assert!(user_service.get_by_device(&device).await.is_err());
+
+    // login rows went with the user, so the same email and device can sign up again
+    let mut again = User::new();
+    again.set_email(&email);
+    again.set_device(&device);
+    let again_id = user_service.add(&mut again, &password).await?;
+    assert_ne!(new_id, again_id);
+
+    Ok(())
+}
@@ -0,0 +1,159 @@
+mod common;
+
+use lib_config::environment::{DEV_ENV, ENV_VAR_ENVIRONMENT};
+use lib_config::infra::build_local_stack_connection;
+use lib_config::schema::Schema;
+use lib_config::{config::Config, secrets::SECRETS_MANAGER_APP_KEYS};
+use lib_users::models::user::{User, UserRoles, UserStatus};
+use lib_users::models::user_search::{SearchCursor, UserSearch};
+use lib_users::repositories::schema_user::UserAllSchema;
+use lib_users::repositories::users::UsersRepo;
+use lib_users::services::users::{UpdatableFildsUser, UserManipulation, UsersService};
+use std::collections::HashMap;
+use std::env;
+use testcontainers::*;
+
+use crate::common::create_secrets;
+
+#[test]
+fn search_cursor_roundtrip_test() {
+    let mut key = HashMap::new();
+    key.insert("userID".to_string(), "1234".to_string());
+    key.insert(
+        "creationTime".to_string(),
+        "2024-01-31T00:00:00+00:00".to_string(),
+    );
+    let cursor = SearchCursor {
+        partition: Some("Enabled".to_string()),
+        key,
+    };
+
+    let encoded = cursor.encode();
+    assert!(!encoded.contains('/') && !encoded.contains('+'));
+    assert_eq!(SearchCursor::decode(&encoded), Some(cursor));
+    assert_eq!(SearchCursor::decode("not a cursor"), None);
+}
+
+#[tokio::test]
+async fn search_users_test() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    env::set_var("RUST_LOG", "debug");
+    env::set_var(ENV_VAR_ENVIRONMENT, DEV_ENV);
+    env::set_var("AWS_REGION", "eu-central-1");
+
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let docker = clients::Cli::default();
+
+    let mut local_stack = images::local_stack::LocalStack::default();
+    local_stack.set_services("dynamodb,secretsmanager");
+    let node = docker.run(local_stack);
+    let host_port = node.get_host_port_ipv4(4566);
+
+    let shared_config = build_local_stack_connection(host_port).await;
+
+    let secrets_client = aws_sdk_secretsmanager::Client::new(&shared_config);
+    let creation2 = create_secrets(&secrets_client).await;
+    assert!(&creation2.is_ok());
+
+    let mut config = Config::new();
+    config.setup().await;
+    config.set_aws_config(&shared_config);
+    config.load_secret(SECRETS_MANAGER_APP_KEYS.clone()).await;
+
+    let creation = UserAllSchema::create_schema(&config).await;
+    assert!(&creation.is_ok());
+
+    let user_service = UsersService::new(UsersRepo::new(&config));
+    let password = Some("123456789aA$%^@2".to_string());
+
+    let mut ids = Vec::new();
+    for i in 0..7 {
+        let mut user = User::new();
+        user.set_email(&format!("support{}@test.cat.io", i));
+        user.set_device(&format!("device-{}", i));
+        ids.push(user_service.add(&mut user, &password).await?);
+    }
+    let mut other = User::new();
+    other.set_email(&"press@test.cat.io".to_string());
+    let press_id = user_service.add(&mut other, &password).await?;
+    user_service
+        .update_roles(&press_id, &vec![UserRoles::VerifiedPress])
+        .await?;
+
+    // email prefix, paginated
+    let mut filter = UserSearch::new();
+    filter.email_prefix = Some("support".to_string());
+    filter.ascending = true;
+    let first = user_service.search(&filter).await?;
+    assert_eq!(first.users.len(), 5);
+    assert_eq!(
+        first.users[0].email(),
+        &Some("support0@test.cat.io".to_string())
+    );
+    filter.cursor = first.next_cursor.clone();
+    let second = user_service.search(&filter).await?;
+    assert_eq!(second.users.len(), 2);
+    assert_eq!(
+        second.users[1].email(),
+        &Some("support6@test.cat.io".to_string())
+    );
+
+    // role
+    let mut filter = UserSearch::new();
+    filter.role = Some(UserRoles::VerifiedPress);
+    let press = user_service.search(&filter).await?;
+    assert_eq!(press.users.len(), 1);
+    assert_eq!(press.users[0].user_id(), &press_id);
+
+    user_service.update_roles(&press_id, &vec![]).await?;
+    let press = user_service.search(&filter).await?;
+    assert!(press.users.is_empty());
+
+    // device, exact
+    let mut filter = UserSearch::new();
+    filter.device = Some("device-3".to_string());
+    let by_device = user_service.search(&filter).await?;
+    assert_eq!(by_device.users.len(), 1);
+    assert_eq!(by_device.users[0].user_id(), &ids[3]);
+
+    // status, newest first
+    let disable = UpdatableFildsUser {
+        email: None,
+        device: None,
+        status: Some(UserStatus::Disabled.to_string()),
+        wallet: None,
+    };
+    user_service.update(&ids[0], &disable).await?;
+    let mut filter = UserSearch::new();
+    filter.status = Some(UserStatus::Disabled);
+    let disabled = user_service.search(&filter).await?;
+    assert_eq!(disabled.users.len(), 1);
+    assert_eq!(disabled.users[0].user_id(), &ids[0]);
+
+    // no criteria: every user, enabled ones first, across pages
+    let mut filter = UserSearch::new();
+    filter.page_size = 5;
+    let mut seen = Vec::new();
+    loop {
+        let page = user_service.search(&filter).await?;
+        seen.extend(page.users.into_iter().map(|user| user.user_id().clone()));
+        match page.next_cursor {
+            None => break,
+            Some(cursor) => filter.cursor = Some(cursor),
+        }
+    }
+    assert_eq!(seen.len(), 8);
+    assert_eq!(seen.last(), Some(&ids[0]));
+
+    // creation range in the future
+    let mut filter = UserSearch::new();
+    filter.created_from = Some(chrono::Utc::now() + chrono::Duration::days(1));
+    let none = user_service.search(&filter).await?;
+    assert!(none.users.is_empty());
+
+    let mut filter = UserSearch::new();
+    filter.page_size = 500;
+    assert!(user_service.search(&filter).await.is_err());
+
+    Ok(())
+}
@@ -166,6 +166,9 @@ async fn update_password_user_test() -> Result<(), Box<dyn std::error::Error + S
     let password = Some("123456789aA$%^@2".to_string());
 
     let new_id = user_service.add(&mut new_user, &password).await?;
+    user_service
+        .mark_email_verified(&new_id, &email.clone().unwrap())
+        .await?;
 
     let res = user_service.login(&None, &None, &email, &password).await;
     assert!(res.is_ok());
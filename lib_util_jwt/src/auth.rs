@@ -0,0 +1,212 @@
+use lambda_http::http::header::AUTHORIZATION;
+use lambda_http::request::RequestContext;
+use lambda_http::{http::StatusCode, Request, RequestExt, Response};
+use lib_config::config::Config;
+use lib_users::errors::api_keys::ApiKeyScopeError;
+use lib_users::models::api_key::ApiKeyScope;
+use lib_users::models::permission::{has_permission, Permission};
+use lib_users::models::user::UserRoles;
+use lib_users::repositories::api_keys::ApiKeysRepo;
+use lib_users::repositories::users::UsersRepo;
+use lib_users::services::api_keys::{ApiKeyManipulation, ApiKeyService};
+use lib_users::services::users::{UserManipulation, UsersService};
+
+use crate::build::build_resp;
+use crate::jwt::{get_header_jwt, TokenVerifier, AUDIENCE_LICENSE};
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+// api keys are meant for integrators, they never reach the user or admin lambdas
+pub const API_KEY_AUDIENCES: [&str; 1] = [AUDIENCE_LICENSE];
+
+// The gateway knows the caller address. Outside of it only the last x-forwarded-for hop is
+// trusted, the one the proxy appended: the first ones are whatever the client sent.
+pub fn source_ip(req: &Request) -> Option<String> {
+    match req.request_context_ref() {
+        Some(RequestContext::ApiGatewayV2(ctx)) if ctx.http.source_ip.is_some() => {
+            return ctx.http.source_ip.clone();
+        }
+        Some(RequestContext::ApiGatewayV1(ctx)) if ctx.identity.source_ip.is_some() => {
+            return ctx.identity.source_ip.clone();
+        }
+        _ => {}
+    }
+    req.headers()
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(',').last())
+        .map(|ip| ip.trim().to_string())
+        .filter(|ip| !ip.is_empty())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AuthMethod {
+    Jwt,
+    ApiKey,
+}
+
+// Who is calling, worked out once per request.
+#[derive(Clone, Debug)]
+pub struct AuthContext {
+    user_id: String,
+    roles: Vec<UserRoles>,
+    scopes: Vec<ApiKeyScope>,
+    // jti of the jwt or id of the api key
+    token_id: String,
+    method: AuthMethod,
+}
+
+impl AuthContext {
+    pub fn user_id(&self) -> &String {
+        &self.user_id
+    }
+    pub fn roles(&self) -> &Vec<UserRoles> {
+        &self.roles
+    }
+    pub fn scopes(&self) -> &Vec<ApiKeyScope> {
+        &self.scopes
+    }
+    pub fn token_id(&self) -> &String {
+        &self.token_id
+    }
+    pub fn method(&self) -> &AuthMethod {
+        &self.method
+    }
+
+    // a user logged with a jwt may do everything its own account may do
+    pub fn has_scope(&self, scope: &ApiKeyScope) -> bool {
+        match self.method {
+            AuthMethod::Jwt => true,
+            AuthMethod::ApiKey => self.scopes.contains(scope),
+        }
+    }
+
+    // staff powers need a real login, never an api key
+    pub fn has_permission(&self, permission: &Permission) -> bool {
+        self.method == AuthMethod::Jwt && has_permission(&self.roles, permission)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum AuthOutcome {
+    // no credentials at all
+    Anonymous,
+    // credentials present but refused, never to be taken as anonymous
+    Invalid(String),
+    Authenticated(AuthContext),
+}
+
+impl AuthOutcome {
+    // the verifier is built once per lambda and knows which audience the tokens must carry
+    pub async fn extract(req: &Request, config: &Config, verifier: &TokenVerifier) -> AuthOutcome {
+        let headers = req.headers();
+        if let Some(value) = headers.get(API_KEY_HEADER) {
+            if !API_KEY_AUDIENCES.contains(&verifier.audience().as_str()) {
+                return AuthOutcome::Invalid("api keys aren't accepted here".to_string());
+            }
+            return match value.to_str() {
+                Err(_) => AuthOutcome::Invalid("api key header not valid".to_string()),
+                Ok(raw) => from_api_key(config, &raw.trim().to_string()).await,
+            };
+        }
+        if headers.get(AUTHORIZATION).is_none() {
+            return AuthOutcome::Anonymous;
+        }
+
+        match get_header_jwt(headers, verifier).await {
+            Err(e) => AuthOutcome::Invalid(e.to_string()),
+            Ok(claims) => AuthOutcome::Authenticated(AuthContext {
+                user_id: claims.uid,
+                roles: UserRoles::from_vec_str(&claims.roles),
+                scopes: Vec::new(),
+                token_id: claims.jti,
+                method: AuthMethod::Jwt,
+            }),
+        }
+    }
+
+    pub fn required(&self) -> Result<AuthContext, Response<String>> {
+        match self {
+            AuthOutcome::Authenticated(auth) => Ok(auth.clone()),
+            AuthOutcome::Anonymous => Err(unauthorized("no credentials present".to_string())),
+            AuthOutcome::Invalid(e) => Err(unauthorized(e.clone())),
+        }
+    }
+
+    // anonymous callers are welcome, callers with bad credentials aren't
+    pub fn optional(&self) -> Result<Option<AuthContext>, Response<String>> {
+        match self {
+            AuthOutcome::Authenticated(auth) => Ok(Some(auth.clone())),
+            AuthOutcome::Anonymous => Ok(None),
+            AuthOutcome::Invalid(e) => Err(unauthorized(e.clone())),
+        }
+    }
+
+    pub fn require_scope(&self, scope: &ApiKeyScope) -> Result<AuthContext, Response<String>> {
+        let auth = self.required()?;
+        check_scope(auth, scope)
+    }
+
+    pub fn optional_scope(
+        &self,
+        scope: &ApiKeyScope,
+    ) -> Result<Option<AuthContext>, Response<String>> {
+        match self.optional()? {
+            None => Ok(None),
+            Some(auth) => check_scope(auth, scope).map(Some),
+        }
+    }
+
+    // the roles come from the token, so a role taken away stops working when the access token expires
+    pub fn require_permission(
+        &self,
+        permission: &Permission,
+    ) -> Result<AuthContext, Response<String>> {
+        let auth = self.required()?;
+        if !auth.has_permission(permission) {
+            return Err(build_resp(
+                format!("permission {} needed", permission),
+                StatusCode::FORBIDDEN,
+            )
+            .unwrap());
+        }
+        Ok(auth)
+    }
+}
+
+fn check_scope(auth: AuthContext, scope: &ApiKeyScope) -> Result<AuthContext, Response<String>> {
+    if !auth.has_scope(scope) {
+        return Err(build_resp(
+            ApiKeyScopeError(scope.to_string()).to_string(),
+            StatusCode::FORBIDDEN,
+        )
+        .unwrap());
+    }
+    Ok(auth)
+}
+
+fn unauthorized(msg: String) -> Response<String> {
+    build_resp(msg, StatusCode::UNAUTHORIZED).unwrap()
+}
+
+// keys outlive any jwt, so the owner is checked on every call
+async fn from_api_key(config: &Config, raw: &String) -> AuthOutcome {
+    let api_key_service = ApiKeyService::new(ApiKeysRepo::new(config));
+    let key = match api_key_service.authenticate(raw).await {
+        Err(e) => return AuthOutcome::Invalid(e.to_string()),
+        Ok(key) => key,
+    };
+    let user_service = UsersService::new(UsersRepo::new(config));
+    match user_service.get_by_id(key.user_id()).await {
+        Err(e) => AuthOutcome::Invalid(e.to_string()),
+        Ok(user) if user.status().is_disabled() => {
+            AuthOutcome::Invalid("user disabled".to_string())
+        }
+        Ok(user) => AuthOutcome::Authenticated(AuthContext {
+            user_id: key.user_id().clone(),
+            roles: user.roles().clone(),
+            scopes: key.scopes().clone(),
+            token_id: key.key_id().clone(),
+            method: AuthMethod::ApiKey,
+        }),
+    }
+}
@@ -1,19 +1,10 @@
 
 use lambda_http::Context;
 use lambda_http::{http::StatusCode,Request,Response};
-use lib_config::config::Config;
 use lib_config::environment::{DEV_ENV, STAGE_ENV};
 use serde_json::json;
 
 use crate::error::ApiLambdaError;
-use crate::jwt::{JWTSecurityError, get_header_jwt};
-
-pub fn jwt_mandatory(req: &Request, config: &Config) -> Result<String, Response<String>> {
-    match check_jwt_token_as_user_logged(&req, config) {
-        Err(e) => Err(build_resp(e.to_string(), StatusCode::UNAUTHORIZED).unwrap()),
-        Ok(id) => Ok(id),
-    }
-}
 
 pub fn build_resp(
     msg: String,
@@ -30,7 +21,6 @@ pub fn build_resp(
     }
 }
 
-
 pub fn build_resp_no_cache(
     msg: String,
     status_code: StatusCode,
@@ -69,27 +59,6 @@ pub fn build_resp_env(
         Ok(resp) => Ok(resp),
     }
 }
-pub fn check_jwt_token_as_user_logged(
-    req: &Request,
-    config: &Config,
-) -> Result<String, JWTSecurityError> {
-    let user_id;
-    let req_headers = req.headers();
-
-    let jwt_secret = config.env_vars().jwt_token_base().unwrap();
-    let claim_ops = get_header_jwt(req_headers, &jwt_secret);
-
-    match claim_ops {
-        Ok(clm) => {
-            user_id = clm.uid;
-        }
-        Err(e) => {
-            return Err(e);
-        }
-    }
-    Ok(user_id)
-}
-
 pub fn not_allowed(
     _req: &Request,
     _c: &Context,
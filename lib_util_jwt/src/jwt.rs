@@ -1,28 +1,81 @@
 //use http::header::AUTHORIZATION;
 //use http::{HeaderMap, HeaderValue};
 
-use chrono::Utc;
-use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
-use lambda_http::{Request, http::{HeaderMap, HeaderValue, header::AUTHORIZATION}};
+use std::sync::{Arc, RwLock};
+
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::jwk::Jwk;
+use jsonwebtoken::{decode, decode_header, encode, Algorithm, DecodingKey, Validation};
+use lambda_http::http::{HeaderMap, HeaderValue, header::AUTHORIZATION};
 use lib_config::config::Config;
-use lib_users::models::user::UserRoles;
+use lib_config::constants::{API_DOMAIN, VALUE_PROJECT};
+use lib_users::models::jwt_key::JwtKey;
+use lib_users::repositories::jwt_keys::JwtKeysRepo;
+use lib_users::repositories::sessions::SessionsRepo;
+use lib_users::services::jwt_keys::{JwtKeyManipulation, JwtKeyService};
+use lib_users::services::sessions::{SessionManipulation, SessionService};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::keys::{parse_algorithm, JwtSigner, SIGNER_CACHE_SECONDS, SIGNER_RETRY_SECONDS};
 
 pub const BEARER: &str = "Bearer ";
+// access tokens are short-lived, sessions are kept alive with refresh tokens
+pub const ACCESS_TOKEN_EXP_MINUTES: i64 = 15;
+// seconds of clock skew tolerated between lambdas
+pub const CLOCK_LEEWAY_SECONDS: u64 = 60;
+// verification keys are read again after this long, or sooner when a token names an unknown
+// kid, but never more often than every VERIFICATION_KEYS_MIN_REFRESH_SECONDS
+pub const VERIFICATION_KEYS_CACHE_SECONDS: i64 = 60;
+pub const VERIFICATION_KEYS_MIN_REFRESH_SECONDS: i64 = 5;
+// warm login lambdas may keep signing with HS512 until they see the first asymmetric key, and
+// what they signed must still be accepted until it expires
+pub const HMAC_GRACE_SECONDS: i64 =
+    SIGNER_CACHE_SECONDS + SIGNER_RETRY_SECONDS + ACCESS_TOKEN_EXP_MINUTES * 60;
+
+// every lambda only accepts tokens minted for it
+pub const AUDIENCE_LOGIN: &str = "login";
+pub const AUDIENCE_USER: &str = "user";
+pub const AUDIENCE_LICENSE: &str = "license";
+pub const AUDIENCE_ADMIN: &str = "admin";
+// only for the second step of a login with mfa, no lambda but lambda_login accepts it
+pub const AUDIENCE_MFA: &str = "mfa";
+
+pub fn token_issuer() -> String {
+    format!("{}_{}", VALUE_PROJECT, API_DOMAIN)
+}
+
+// login is always there, otherwise the token couldn't be logged out
+pub fn default_audience() -> Vec<String> {
+    vec![
+        AUDIENCE_LOGIN.to_string(),
+        AUDIENCE_USER.to_string(),
+        AUDIENCE_LICENSE.to_string(),
+    ]
+}
+
+pub fn admin_audience() -> Vec<String> {
+    vec![AUDIENCE_LOGIN.to_string(), AUDIENCE_ADMIN.to_string()]
+}
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Claims {
     pub uid: String,
     pub roles: Vec<String>,
+    pub iss: String,
+    pub aud: Vec<String>,
+    pub iat: usize,
+    pub nbf: usize,
     pub exp: usize,
+    pub jti: String,
 }
 
 impl std::fmt::Display for Claims {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "uid: {}, roles: {:?}, expire: {}",
-            self.uid, self.roles, self.exp
+            "uid: {}, roles: {:?}, audience: {:?}, issued: {}, expire: {}, jti: {}",
+            self.uid, self.roles, self.aud, self.iat, self.exp, self.jti
         )
     }
 }
@@ -30,44 +83,208 @@ impl std::fmt::Display for Claims {
 pub fn create_jwt(
     uid: &str,
     roles: Vec<String>,
-    token_secret: &String,
-    exp_hours: i64,
+    audience: &Vec<String>,
+    signer: &JwtSigner,
+    exp_minutes: i64,
 ) -> Result<String, JWTSecurityError> {
-    let expiration = Utc::now()
-        .checked_add_signed(chrono::Duration::hours(exp_hours))
+    let now = Utc::now();
+    let expiration = now
+        .checked_add_signed(chrono::Duration::minutes(exp_minutes))
         .expect("valid timestamp")
         .timestamp();
 
     let claims = Claims {
         uid: uid.to_owned(),
         roles, //.clone(),
+        iss: token_issuer(),
+        aud: audience.clone(),
+        iat: now.timestamp() as usize,
+        nbf: now.timestamp() as usize,
         exp: expiration as usize,
+        jti: Uuid::new_v4().to_string(),
     };
-    let header = Header::new(Algorithm::HS512);
-    let key = EncodingKey::from_secret(token_secret.as_bytes());
-    let jwt = encode(&header, &claims, &key);
+    let jwt = encode(&signer.header(), &claims, &signer.encoding_key());
     match jwt {
         Ok(x) => Ok(x),
         Err(_) => Err(JWTSecurityError::from("fail creating a token".to_string())),
     }
 }
 
-pub fn check_jwt_token(token: &str, token_secret: &String) -> Result<Claims, JWTSecurityError> {
+// everything needed to trust a token: the verification keys published in the
+// jwks, the legacy shared secret, the logout denylist and the audience of the
+// lambda doing the check
+#[derive(Clone, Debug)]
+pub struct TokenVerifier {
+    hmac_secret: String,
+    audience: String,
+    sessions: SessionService,
+    keys: JwtKeyService,
+    // Current and Previous keys, with the time they were read
+    cached_keys: Arc<RwLock<Option<(Vec<JwtKey>, DateTime<Utc>)>>>,
+}
+
+impl TokenVerifier {
+    pub fn new(config: &Config, audience: &str) -> TokenVerifier {
+        TokenVerifier {
+            hmac_secret: config.env_vars().jwt_token_base().unwrap(),
+            audience: audience.to_string(),
+            sessions: SessionService::new(SessionsRepo::new(config)),
+            keys: JwtKeyService::new(JwtKeysRepo::new(config)),
+            cached_keys: Arc::new(RwLock::new(None)),
+        }
+    }
+    pub fn audience(&self) -> &String {
+        &self.audience
+    }
+    pub fn sessions(&self) -> &SessionService {
+        &self.sessions
+    }
+    pub fn keys(&self) -> &JwtKeyService {
+        &self.keys
+    }
+
+    async fn verification_keys(
+        &self,
+        kid: &Option<String>,
+    ) -> Result<Vec<JwtKey>, JWTSecurityError> {
+        let now = Utc::now();
+        let cached = self.cached_keys.read().unwrap().clone();
+        if let Some((keys, read_at)) = cached {
+            let known = match kid {
+                None => true,
+                Some(kid) => keys.iter().any(|key| key.kid() == kid),
+            };
+            let age = now - read_at;
+            if age < Duration::seconds(VERIFICATION_KEYS_MIN_REFRESH_SECONDS)
+                || (known && age < Duration::seconds(VERIFICATION_KEYS_CACHE_SECONDS))
+            {
+                return Ok(keys);
+            }
+        }
+        match self.keys.get_verification_keys().await {
+            Err(e) => {
+                log::error!("{}", e);
+                Err(JWTSecurityError::from(
+                    "token couldn't be checked, try again later".to_string(),
+                ))
+            }
+            Ok(keys) => {
+                *self.cached_keys.write().unwrap() = Some((keys.clone(), now));
+                Ok(keys)
+            }
+        }
+    }
+
+    async fn decoding_key(
+        &self,
+        kid: &Option<String>,
+    ) -> Result<(DecodingKey, Algorithm), JWTSecurityError> {
+        let keys = self.verification_keys(kid).await?;
+        match kid {
+            Some(kid) => {
+                let stored = match keys.iter().find(|key| key.kid() == kid) {
+                    None => {
+                        return Err(JWTSecurityError::from(
+                            "token signed with an unknown key, login again".to_string(),
+                        ))
+                    }
+                    Some(stored) => stored,
+                };
+                let algorithm = parse_algorithm(stored.algorithm())?;
+                let key = serde_json::from_str::<Jwk>(stored.jwk())
+                    .map_err(|e| JWTSecurityError::from(e.to_string()))
+                    .and_then(|jwk| {
+                        DecodingKey::from_jwk(&jwk)
+                            .map_err(|e| JWTSecurityError::from(e.to_string()))
+                    })?;
+                Ok((key, algorithm))
+            }
+            None => {
+                // shared secret tokens stop being valid once an asymmetric key exists, otherwise
+                // any verifier could still mint them; only the very first key leaves a grace
+                let accepted = match keys.as_slice() {
+                    [] => true,
+                    [first] => {
+                        Utc::now() - *first.creation_time() < Duration::seconds(HMAC_GRACE_SECONDS)
+                    }
+                    _ => false,
+                };
+                if !accepted {
+                    return Err(JWTSecurityError::from(
+                        "token signed with a retired method, login again".to_string(),
+                    ));
+                }
+                Ok((
+                    DecodingKey::from_secret(self.hmac_secret.as_bytes()),
+                    Algorithm::HS512,
+                ))
+            }
+        }
+    }
+}
+
+pub async fn decode_jwt_token(
+    token: &str,
+    verifier: &TokenVerifier,
+) -> Result<Claims, JWTSecurityError> {
     if !token.starts_with(BEARER) {
         return Err(JWTSecurityError::from("jwt error".to_string()));
     }
     let jwt = token.trim_start_matches(BEARER).to_owned();
 
-    let decoded = decode::<Claims>(
-        &jwt,
-        &DecodingKey::from_secret(token_secret.as_bytes()),
-        &Validation::new(Algorithm::HS512),
-    );
-    match decoded {
-        Err(_) => Err(JWTSecurityError::from(
+    let header = match decode_header(&jwt) {
+        Err(_) => {
+            return Err(JWTSecurityError::from(
+                "token present but invalid, login again".to_string(),
+            ))
+        }
+        Ok(header) => header,
+    };
+    let (key, algorithm) = verifier.decoding_key(&header.kid).await?;
+
+    let mut validation = Validation::new(algorithm);
+    validation.leeway = CLOCK_LEEWAY_SECONDS;
+    validation.validate_nbf = true;
+    validation.set_issuer(&[token_issuer()]);
+    validation.set_audience(&[verifier.audience()]);
+    validation.set_required_spec_claims(&["exp", "nbf", "iss", "aud"]);
+
+    let claims = match decode::<Claims>(&jwt, &key, &validation) {
+        Err(e) => {
+            log::info!("token refused: {}", e);
+            return Err(JWTSecurityError::from(
+                "token present but invalid, login again".to_string(),
+            ));
+        }
+        Ok(deco) => deco.claims,
+    };
+
+    let now = Utc::now().timestamp() as usize;
+    if claims.iat > now + CLOCK_LEEWAY_SECONDS as usize || claims.jti.is_empty() {
+        return Err(JWTSecurityError::from(
             "token present but invalid, login again".to_string(),
+        ));
+    }
+    Ok(claims)
+}
+
+// same as decode_jwt_token, but tokens closed by a logout are rejected too
+pub async fn check_jwt_token(
+    token: &str,
+    verifier: &TokenVerifier,
+) -> Result<Claims, JWTSecurityError> {
+    let claims = decode_jwt_token(token, verifier).await?;
+    match verifier.sessions.is_revoked(&claims.jti).await {
+        Ok(false) => Ok(claims),
+        Ok(true) => Err(JWTSecurityError::from(
+            "token has been revoked, login again".to_string(),
         )),
-        Ok(deco) => Ok(deco.claims),
+        Err(e) => {
+            log::error!("{}", e);
+            Err(JWTSecurityError::from(
+                "token couldn't be checked, try again later".to_string(),
+            ))
+        }
     }
 }
 
@@ -88,9 +305,9 @@ impl From<String> for JWTSecurityError {
     }
 }
 
-pub fn get_header_jwt(
+pub async fn get_header_jwt(
     req_headers: &HeaderMap<HeaderValue>,
-    jwt_secret: &String,
+    verifier: &TokenVerifier,
 ) -> Result<Claims, JWTSecurityError> {
     match req_headers.get(AUTHORIZATION) {
         Some(header_v) => {
@@ -98,7 +315,7 @@ pub fn get_header_jwt(
                 Ok(header_field_value) => {
                     //let jwt_secret =  config.env_vars().jwt_token_base();
 
-                    let claim = check_jwt_token(header_field_value, jwt_secret);
+                    let claim = check_jwt_token(header_field_value, verifier).await;
 
                     match claim {
                         Ok(clm) => Ok(clm),
@@ -115,31 +332,3 @@ pub fn get_header_jwt(
         )),
     }
 }
-
-pub fn check_jwt_token_as_admin(req: &Request, config: &Config) -> Result<bool, JWTSecurityError> {
-    let auth_flag;
-    let req_headers = req.headers();
-
-    let jwt_secret = config.env_vars().jwt_token_base().unwrap();
-    let claim_ops = get_header_jwt(req_headers, &jwt_secret);
-    match claim_ops {
-        Ok(clm) => {
-            log::info!("{}", clm.to_string());
-            let matches = clm
-                .roles
-                .into_iter()
-                .map(|i| UserRoles::deserialize(i.as_str()).unwrap())
-                .filter(|i| i.is_admin())
-                .count();
-            if matches == 0 {
-                auth_flag = false;
-            } else {
-                auth_flag = true;
-            }
-        }
-        Err(e) => {
-            return Err(e);
-        }
-    }
-    Ok(auth_flag)
-}
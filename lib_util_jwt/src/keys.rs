@@ -0,0 +1,214 @@
+use std::sync::{Arc, RwLock};
+
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::jwk::{Jwk, JwkSet};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use lib_config::config::Config;
+use lib_users::models::jwt_key::{JwtKey, JwtKeyStatus};
+use lib_users::repositories::jwt_keys::JwtKeysRepo;
+use lib_users::services::jwt_keys::{JwtKeyManipulation, JwtKeyService};
+use serde::{Deserialize, Serialize};
+
+use crate::jwt::JWTSecurityError;
+
+// secrets manager entry holding the private key of the current signing key
+pub const JWT_SIGNING_KEY_SECRET: &str = "truly_jwt_signing_key";
+
+pub const RS256: &str = "RS256";
+pub const EDDSA: &str = "EdDSA";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StoredSigningKey {
+    pub kid: String,
+    pub algorithm: String,
+    pub private_pem: String,
+}
+
+pub fn parse_algorithm(name: &str) -> Result<Algorithm, JWTSecurityError> {
+    match name {
+        RS256 => Ok(Algorithm::RS256),
+        EDDSA => Ok(Algorithm::EdDSA),
+        _ => Err(JWTSecurityError::from(format!(
+            "signing algorithm {} not supported",
+            name
+        ))),
+    }
+}
+
+#[derive(Clone)]
+pub enum JwtSigner {
+    // shared secret, kept only until the first asymmetric key is rotated in
+    Hmac(String),
+    Asymmetric {
+        kid: String,
+        algorithm: Algorithm,
+        key: EncodingKey,
+    },
+}
+
+impl JwtSigner {
+    pub fn from_stored(stored: &StoredSigningKey) -> Result<JwtSigner, JWTSecurityError> {
+        let algorithm = parse_algorithm(&stored.algorithm)?;
+        let pem = stored.private_pem.as_bytes();
+        let key = match algorithm {
+            Algorithm::RS256 => EncodingKey::from_rsa_pem(pem),
+            _ => EncodingKey::from_ed_pem(pem),
+        }
+        .map_err(|e| JWTSecurityError::from(format!("signing key unreadable: {}", e)))?;
+        Ok(JwtSigner::Asymmetric {
+            kid: stored.kid.clone(),
+            algorithm,
+            key,
+        })
+    }
+
+    pub fn header(&self) -> Header {
+        match self {
+            JwtSigner::Hmac(_) => Header::new(Algorithm::HS512),
+            JwtSigner::Asymmetric { kid, algorithm, .. } => {
+                let mut header = Header::new(*algorithm);
+                header.kid = Some(kid.clone());
+                header
+            }
+        }
+    }
+
+    pub fn encoding_key(&self) -> EncodingKey {
+        match self {
+            JwtSigner::Hmac(secret) => EncodingKey::from_secret(secret.as_bytes()),
+            JwtSigner::Asymmetric { key, .. } => key.clone(),
+        }
+    }
+}
+
+// a rotation reaches warm login lambdas after at most this long
+pub const SIGNER_CACHE_SECONDS: i64 = 300;
+// when the key in the secret can't sign yet the one in use is kept, and asked again this soon
+pub const SIGNER_RETRY_SECONDS: i64 = 30;
+
+// None when no asymmetric key was ever rotated in
+async fn read_signing_key(
+    client: &aws_sdk_secretsmanager::Client,
+) -> Result<Option<StoredSigningKey>, JWTSecurityError> {
+    let secret = client
+        .get_secret_value()
+        .secret_id(JWT_SIGNING_KEY_SECRET)
+        .send()
+        .await;
+    match secret {
+        Err(e) => {
+            let service_error = e.into_service_error();
+            if service_error.is_resource_not_found_exception() {
+                return Ok(None);
+            }
+            let mssag = format!("signing key secret couldn't be read: {}", service_error);
+            log::error!("{}", mssag);
+            Err(JWTSecurityError::from(mssag))
+        }
+        Ok(value) => {
+            serde_json::from_str::<StoredSigningKey>(value.secret_string().unwrap_or_default())
+                .map(Some)
+                .map_err(|e| {
+                    JWTSecurityError::from(format!("signing key secret is malformed: {}", e))
+                })
+        }
+    }
+}
+
+// Only the login lambda needs it: every other service just verifies. The signer is worked out
+// again every SIGNER_CACHE_SECONDS, so warm lambdas follow rotations without a cold start.
+#[derive(Clone)]
+pub struct SignerCache {
+    secrets: aws_sdk_secretsmanager::Client,
+    hmac_secret: String,
+    keys: JwtKeyService,
+    // with the time it has to be worked out again
+    cached: Arc<RwLock<Option<(JwtSigner, DateTime<Utc>)>>>,
+}
+
+impl SignerCache {
+    // fails when there is no signer to start with, the login lambda must not run then
+    pub async fn load(config: &Config) -> Result<SignerCache, JWTSecurityError> {
+        let cache = SignerCache {
+            secrets: aws_sdk_secretsmanager::Client::new(config.aws_config()),
+            hmac_secret: config.env_vars().jwt_token_base().unwrap(),
+            keys: JwtKeyService::new(JwtKeysRepo::new(config)),
+            cached: Arc::new(RwLock::new(None)),
+        };
+        cache.current().await?;
+        Ok(cache)
+    }
+
+    pub async fn current(&self) -> Result<JwtSigner, JWTSecurityError> {
+        let now = Utc::now();
+        let cached = self.cached.read().unwrap().clone();
+        if let Some((signer, refresh_at)) = &cached {
+            if *refresh_at > now {
+                return Ok(signer.clone());
+            }
+        }
+        match self.resolve().await {
+            Ok(signer) => {
+                *self.cached.write().unwrap() = Some((
+                    signer.clone(),
+                    now + Duration::seconds(SIGNER_CACHE_SECONDS),
+                ));
+                Ok(signer)
+            }
+            Err(e) => match cached {
+                None => Err(e),
+                Some((signer, _)) => {
+                    log::warn!("signing key not refreshed, the one in use is kept: {}", e);
+                    *self.cached.write().unwrap() = Some((
+                        signer.clone(),
+                        now + Duration::seconds(SIGNER_RETRY_SECONDS),
+                    ));
+                    Ok(signer)
+                }
+            },
+        }
+    }
+
+    // The secret is written before the public half is published, so its key signs only once
+    // the jwt keys table has it as Current. HS512 is kept only while no asymmetric key exists,
+    // verifiers refuse it shortly after the first one is published.
+    async fn resolve(&self) -> Result<JwtSigner, JWTSecurityError> {
+        let stored = read_signing_key(&self.secrets).await?;
+        if let Some(stored) = &stored {
+            match self.keys.get_verification_key(&stored.kid).await {
+                Err(e) => {
+                    return Err(JWTSecurityError::from(format!(
+                        "signing key {} couldn't be checked: {}",
+                        stored.kid, e
+                    )))
+                }
+                Ok(Some(key)) if *key.status() == JwtKeyStatus::Current => {
+                    return JwtSigner::from_stored(stored)
+                }
+                Ok(_) => {}
+            }
+        }
+        match self.keys.get_verification_keys().await {
+            Err(e) => Err(JWTSecurityError::from(format!(
+                "signing keys couldn't be checked: {}",
+                e
+            ))),
+            Ok(keys) if keys.is_empty() => {
+                log::warn!("no asymmetric signing key available, tokens signed with HS512");
+                Ok(JwtSigner::Hmac(self.hmac_secret.clone()))
+            }
+            Ok(_) => Err(JWTSecurityError::from(
+                "the signing key secret doesn't hold the current key".to_string(),
+            )),
+        }
+    }
+}
+
+pub fn jwk_set(keys: &Vec<JwtKey>) -> JwkSet {
+    let keys = keys
+        .iter()
+        .filter(|key| key.can_verify())
+        .filter_map(|key| serde_json::from_str::<Jwk>(key.jwk()).ok())
+        .collect();
+    JwkSet { keys }
+}
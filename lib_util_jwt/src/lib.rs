@@ -1,4 +1,7 @@
+pub mod auth;
 pub mod jwt;
+pub mod keys;
+pub mod oidc;
 pub mod randoms;
 pub mod build;
 pub mod error;
@@ -0,0 +1,27 @@
+use lib_config::config::Config;
+use lib_users::models::identity::OidcProvider;
+
+// secrets manager entry holding the json list of trusted OpenID Connect providers
+pub const OIDC_PROVIDERS_SECRET: &str = "truly_oidc_providers";
+
+// no secret or a malformed one turns oidc login off, every token is rejected
+pub async fn load_oidc_providers(config: &Config) -> Vec<OidcProvider> {
+    let client = aws_sdk_secretsmanager::Client::new(config.aws_config());
+    let secret = client
+        .get_secret_value()
+        .secret_id(OIDC_PROVIDERS_SECRET)
+        .send()
+        .await;
+    match secret {
+        Err(e) => log::warn!("no oidc providers configured, oidc login disabled: {}", e),
+        Ok(value) => {
+            match serde_json::from_str::<Vec<OidcProvider>>(
+                value.secret_string().unwrap_or_default(),
+            ) {
+                Ok(providers) => return providers,
+                Err(e) => log::error!("oidc providers secret is malformed: {}", e),
+            }
+        }
+    }
+    Vec::new()
+}
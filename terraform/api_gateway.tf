@@ -5,11 +5,23 @@ locals {
     aws_apigatewayv2_route.truly_licenses_route_asset,
     aws_apigatewayv2_route.truly_licenses_route_asset_by_id,
     aws_apigatewayv2_route.truly_licenses_route_hash_by_id,
+    aws_apigatewayv2_route.truly_licenses_route_hash_reprocess,
     aws_apigatewayv2_route.truly_licenses_route_similar,
     aws_apigatewayv2_route.truly_licenses_route_similar_by_id,
     aws_apigatewayv2_route.truly_licenses_route_subscribe,
     aws_apigatewayv2_route.truly_licenses_route_subscribe_confirmation,
     aws_apigatewayv2_route.truly_licenses_route_subscribe_remove,
+    aws_apigatewayv2_route.truly_licenses_route_license,
+    aws_apigatewayv2_route.truly_licenses_route_asset_license,
+    aws_apigatewayv2_route.truly_licenses_route_asset_owners,
+    aws_apigatewayv2_route.truly_login_route_jwks,
+    aws_apigatewayv2_route.truly_login_route_wallet_nonce,
+    aws_apigatewayv2_route.truly_login_route_oidc_nonce,
+    aws_apigatewayv2_route.truly_user_route_devices_by_id,
+    aws_apigatewayv2_route.truly_user_route_mfa_confirm,
+    aws_apigatewayv2_route.truly_user_route_api_keys_by_id,
+    aws_apigatewayv2_route.truly_user_route_logins,
+    aws_apigatewayv2_route.truly_user_route_merge_confirm,
     aws_apigatewayv2_route.truly_login_route,
     aws_apigatewayv2_route.truly_user_route,
     aws_apigatewayv2_route.truly_user_route_by_id
@@ -233,6 +245,19 @@ resource "aws_lambda_permission" "truly_licenses_permission_hash_by_id" {
   source_arn    = "${aws_apigatewayv2_api.truly_api.execution_arn}/*/${split(" ", aws_apigatewayv2_route.truly_licenses_route_hash_by_id.route_key)[0]}${split(" ", aws_apigatewayv2_route.truly_licenses_route_hash_by_id.route_key)[1]}"
 }
 
+resource "aws_apigatewayv2_route" "truly_licenses_route_hash_reprocess" {
+  api_id    = aws_apigatewayv2_api.truly_api.id
+  route_key = "POST /api/hash/reprocess"
+  target    = "integrations/${aws_apigatewayv2_integration.truly_licenses_integration.id}"
+}
+
+resource "aws_lambda_permission" "truly_licenses_permission_hash_reprocess" {
+  function_name = module.lambda_licenses.lambda.function_name
+  action        = "lambda:InvokeFunction"
+  principal     = "apigateway.amazonaws.com"
+  source_arn    = "${aws_apigatewayv2_api.truly_api.execution_arn}/*/${split(" ", aws_apigatewayv2_route.truly_licenses_route_hash_reprocess.route_key)[0]}${split(" ", aws_apigatewayv2_route.truly_licenses_route_hash_reprocess.route_key)[1]}"
+}
+
 resource "aws_apigatewayv2_route" "truly_licenses_route_similar" {
   api_id    = aws_apigatewayv2_api.truly_api.id
   route_key = "ANY /api/similar"
@@ -296,6 +321,149 @@ resource "aws_lambda_permission" "truly_licenses_permission_subscribe_remove" {
   source_arn    = "${aws_apigatewayv2_api.truly_api.execution_arn}/*/${split(" ", aws_apigatewayv2_route.truly_licenses_route_subscribe_remove.route_key)[0]}${split(" ", aws_apigatewayv2_route.truly_licenses_route_subscribe_remove.route_key)[1]}"
 }
 
+resource "aws_apigatewayv2_route" "truly_licenses_route_license" {
+  api_id    = aws_apigatewayv2_api.truly_api.id
+  route_key = "ANY /api/license/{proxy+}"
+  target    = "integrations/${aws_apigatewayv2_integration.truly_licenses_integration.id}"
+}
+
+resource "aws_lambda_permission" "truly_licenses_permission_license" {
+  function_name = module.lambda_licenses.lambda.function_name
+  action        = "lambda:InvokeFunction"
+  principal     = "apigateway.amazonaws.com"
+  source_arn    = "${aws_apigatewayv2_api.truly_api.execution_arn}/*/${split(" ", aws_apigatewayv2_route.truly_licenses_route_license.route_key)[0]}${split(" ", aws_apigatewayv2_route.truly_licenses_route_license.route_key)[1]}"
+}
+
+resource "aws_apigatewayv2_route" "truly_licenses_route_asset_license" {
+  api_id    = aws_apigatewayv2_api.truly_api.id
+  route_key = "ANY /api/asset/{id}/license/{proxy+}"
+  target    = "integrations/${aws_apigatewayv2_integration.truly_licenses_integration.id}"
+}
+
+resource "aws_lambda_permission" "truly_licenses_permission_asset_license" {
+  function_name = module.lambda_licenses.lambda.function_name
+  action        = "lambda:InvokeFunction"
+  principal     = "apigateway.amazonaws.com"
+  source_arn    = "${aws_apigatewayv2_api.truly_api.execution_arn}/*/${split(" ", aws_apigatewayv2_route.truly_licenses_route_asset_license.route_key)[0]}${split(" ", aws_apigatewayv2_route.truly_licenses_route_asset_license.route_key)[1]}"
+}
+
+resource "aws_apigatewayv2_route" "truly_licenses_route_asset_owners" {
+  api_id    = aws_apigatewayv2_api.truly_api.id
+  route_key = "ANY /api/asset/{id}/owners"
+  target    = "integrations/${aws_apigatewayv2_integration.truly_licenses_integration.id}"
+}
+
+resource "aws_lambda_permission" "truly_licenses_permission_asset_owners" {
+  function_name = module.lambda_licenses.lambda.function_name
+  action        = "lambda:InvokeFunction"
+  principal     = "apigateway.amazonaws.com"
+  source_arn    = "${aws_apigatewayv2_api.truly_api.execution_arn}/*/${split(" ", aws_apigatewayv2_route.truly_licenses_route_asset_owners.route_key)[0]}${split(" ", aws_apigatewayv2_route.truly_licenses_route_asset_owners.route_key)[1]}"
+}
+
+resource "aws_apigatewayv2_route" "truly_login_route_jwks" {
+  api_id    = aws_apigatewayv2_api.truly_api.id
+  route_key = "GET /.well-known/jwks.json"
+  target    = "integrations/${aws_apigatewayv2_integration.truly_login_integration.id}"
+}
+
+resource "aws_lambda_permission" "truly_login_permission_jwks" {
+  function_name = module.lambda_login.lambda.function_name
+  action        = "lambda:InvokeFunction"
+  principal     = "apigateway.amazonaws.com"
+  source_arn    = "${aws_apigatewayv2_api.truly_api.execution_arn}/*/${split(" ", aws_apigatewayv2_route.truly_login_route_jwks.route_key)[0]}${split(" ", aws_apigatewayv2_route.truly_login_route_jwks.route_key)[1]}"
+}
+
+resource "aws_apigatewayv2_route" "truly_login_route_wallet_nonce" {
+  api_id    = aws_apigatewayv2_api.truly_api.id
+  route_key = "GET /auth/wallet/nonce"
+  target    = "integrations/${aws_apigatewayv2_integration.truly_login_integration.id}"
+}
+
+resource "aws_lambda_permission" "truly_login_permission_wallet_nonce" {
+  function_name = module.lambda_login.lambda.function_name
+  action        = "lambda:InvokeFunction"
+  principal     = "apigateway.amazonaws.com"
+  source_arn    = "${aws_apigatewayv2_api.truly_api.execution_arn}/*/${split(" ", aws_apigatewayv2_route.truly_login_route_wallet_nonce.route_key)[0]}${split(" ", aws_apigatewayv2_route.truly_login_route_wallet_nonce.route_key)[1]}"
+}
+
+resource "aws_apigatewayv2_route" "truly_login_route_oidc_nonce" {
+  api_id    = aws_apigatewayv2_api.truly_api.id
+  route_key = "GET /auth/oidc/nonce"
+  target    = "integrations/${aws_apigatewayv2_integration.truly_login_integration.id}"
+}
+
+resource "aws_lambda_permission" "truly_login_permission_oidc_nonce" {
+  function_name = module.lambda_login.lambda.function_name
+  action        = "lambda:InvokeFunction"
+  principal     = "apigateway.amazonaws.com"
+  source_arn    = "${aws_apigatewayv2_api.truly_api.execution_arn}/*/${split(" ", aws_apigatewayv2_route.truly_login_route_oidc_nonce.route_key)[0]}${split(" ", aws_apigatewayv2_route.truly_login_route_oidc_nonce.route_key)[1]}"
+}
+
+resource "aws_apigatewayv2_route" "truly_user_route_devices_by_id" {
+  api_id    = aws_apigatewayv2_api.truly_api.id
+  route_key = "ANY /api/user/devices/{id}"
+  target    = "integrations/${aws_apigatewayv2_integration.truly_user_integration.id}"
+}
+
+resource "aws_lambda_permission" "truly_user_permission_devices_by_id" {
+  function_name = module.lambda_user.lambda.function_name
+  action        = "lambda:InvokeFunction"
+  principal     = "apigateway.amazonaws.com"
+  source_arn    = "${aws_apigatewayv2_api.truly_api.execution_arn}/*/${split(" ", aws_apigatewayv2_route.truly_user_route_devices_by_id.route_key)[0]}${split(" ", aws_apigatewayv2_route.truly_user_route_devices_by_id.route_key)[1]}"
+}
+
+resource "aws_apigatewayv2_route" "truly_user_route_mfa_confirm" {
+  api_id    = aws_apigatewayv2_api.truly_api.id
+  route_key = "POST /api/user/mfa/confirm"
+  target    = "integrations/${aws_apigatewayv2_integration.truly_user_integration.id}"
+}
+
+resource "aws_lambda_permission" "truly_user_permission_mfa_confirm" {
+  function_name = module.lambda_user.lambda.function_name
+  action        = "lambda:InvokeFunction"
+  principal     = "apigateway.amazonaws.com"
+  source_arn    = "${aws_apigatewayv2_api.truly_api.execution_arn}/*/${split(" ", aws_apigatewayv2_route.truly_user_route_mfa_confirm.route_key)[0]}${split(" ", aws_apigatewayv2_route.truly_user_route_mfa_confirm.route_key)[1]}"
+}
+
+resource "aws_apigatewayv2_route" "truly_user_route_api_keys_by_id" {
+  api_id    = aws_apigatewayv2_api.truly_api.id
+  route_key = "ANY /api/user/keys/{id}"
+  target    = "integrations/${aws_apigatewayv2_integration.truly_user_integration.id}"
+}
+
+resource "aws_lambda_permission" "truly_user_permission_api_keys_by_id" {
+  function_name = module.lambda_user.lambda.function_name
+  action        = "lambda:InvokeFunction"
+  principal     = "apigateway.amazonaws.com"
+  source_arn    = "${aws_apigatewayv2_api.truly_api.execution_arn}/*/${split(" ", aws_apigatewayv2_route.truly_user_route_api_keys_by_id.route_key)[0]}${split(" ", aws_apigatewayv2_route.truly_user_route_api_keys_by_id.route_key)[1]}"
+}
+
+resource "aws_apigatewayv2_route" "truly_user_route_logins" {
+  api_id    = aws_apigatewayv2_api.truly_api.id
+  route_key = "ANY /api/user/logins/{proxy+}"
+  target    = "integrations/${aws_apigatewayv2_integration.truly_user_integration.id}"
+}
+
+resource "aws_lambda_permission" "truly_user_permission_logins" {
+  function_name = module.lambda_user.lambda.function_name
+  action        = "lambda:InvokeFunction"
+  principal     = "apigateway.amazonaws.com"
+  source_arn    = "${aws_apigatewayv2_api.truly_api.execution_arn}/*/${split(" ", aws_apigatewayv2_route.truly_user_route_logins.route_key)[0]}${split(" ", aws_apigatewayv2_route.truly_user_route_logins.route_key)[1]}"
+}
+
+resource "aws_apigatewayv2_route" "truly_user_route_merge_confirm" {
+  api_id    = aws_apigatewayv2_api.truly_api.id
+  route_key = "POST /api/user/merge/confirm"
+  target    = "integrations/${aws_apigatewayv2_integration.truly_user_integration.id}"
+}
+
+resource "aws_lambda_permission" "truly_user_permission_merge_confirm" {
+  function_name = module.lambda_user.lambda.function_name
+  action        = "lambda:InvokeFunction"
+  principal     = "apigateway.amazonaws.com"
+  source_arn    = "${aws_apigatewayv2_api.truly_api.execution_arn}/*/${split(" ", aws_apigatewayv2_route.truly_user_route_merge_confirm.route_key)[0]}${split(" ", aws_apigatewayv2_route.truly_user_route_merge_confirm.route_key)[1]}"
+}
+
 //---------------- register all lambdas below ----------------------------
 resource "aws_apigatewayv2_deployment" "truly_api_deployment" {
   api_id      = aws_apigatewayv2_api.truly_api.id
@@ -314,10 +482,22 @@ resource "aws_apigatewayv2_deployment" "truly_api_deployment" {
     aws_apigatewayv2_route.truly_licenses_route_asset,
     aws_apigatewayv2_route.truly_licenses_route_asset_by_id,
     aws_apigatewayv2_route.truly_licenses_route_hash_by_id,
+    aws_apigatewayv2_route.truly_licenses_route_hash_reprocess,
     aws_apigatewayv2_route.truly_licenses_route_similar_by_id,
     aws_apigatewayv2_route.truly_licenses_route_subscribe,
     aws_apigatewayv2_route.truly_licenses_route_subscribe_confirmation,
     aws_apigatewayv2_route.truly_licenses_route_subscribe_remove,
+    aws_apigatewayv2_route.truly_licenses_route_license,
+    aws_apigatewayv2_route.truly_licenses_route_asset_license,
+    aws_apigatewayv2_route.truly_licenses_route_asset_owners,
+    aws_apigatewayv2_route.truly_login_route_jwks,
+    aws_apigatewayv2_route.truly_login_route_wallet_nonce,
+    aws_apigatewayv2_route.truly_login_route_oidc_nonce,
+    aws_apigatewayv2_route.truly_user_route_devices_by_id,
+    aws_apigatewayv2_route.truly_user_route_mfa_confirm,
+    aws_apigatewayv2_route.truly_user_route_api_keys_by_id,
+    aws_apigatewayv2_route.truly_user_route_logins,
+    aws_apigatewayv2_route.truly_user_route_merge_confirm,
     aws_apigatewayv2_route.truly_login_route,
     aws_apigatewayv2_route.truly_user_route,
     aws_apigatewayv2_route.truly_user_route_by_id
@@ -0,0 +1,4 @@
+output "lambda" {
+  description = "lambda execution runtime to expire time-bounded licenses"
+  value = aws_lambda_function.truly_lambda_license_expiry
+}
@@ -128,3 +128,60 @@ resource "aws_iam_role_policy_attachment" "truly_lambda_execution_policy" {
   policy_arn = "arn:aws:iam::aws:policy/service-role/AWSLambdaBasicExecutionRole"
 }
 
+// -------------- admin execution role -------------------
+// the audit log is written only by lambda_admin: the shared role can't even put rows in it,
+// this one can add them but never change or remove them
+resource "aws_iam_role" "truly_lambda_admin_execution_role" {
+  name = "truly_lambda_admin_exec_role-${local.region_prefix}"
+  assume_role_policy = file("./role_policies/assume.json")
+  
+  tags = merge(local.common_tags,{})
+}
+resource "aws_iam_policy" "truly_lambda_admin_dynamodb_policy" {
+  name        = "truly_lambda_admin_dynamodb_policy-${local.region_prefix}"
+  path        = "/"
+  description = "IAM policy for Dynamodb from the admin lambda within truly api"
+
+  policy = file("./role_policies/dynamodb_admin.json")
+  
+}
+resource "aws_iam_role_policy_attachment" "truly_lambda_admin_dynamodb" {
+  role       = aws_iam_role.truly_lambda_admin_execution_role.name
+  policy_arn = aws_iam_policy.truly_lambda_admin_dynamodb_policy.arn
+}
+resource "aws_iam_role_policy_attachment" "truly_lambda_admin_logs" {
+  role       = aws_iam_role.truly_lambda_admin_execution_role.name
+  policy_arn = aws_iam_policy.truly_lambda_logging_policy.arn
+}
+resource "aws_iam_role_policy_attachment" "truly_lambda_admin_S3" {
+  role       = aws_iam_role.truly_lambda_admin_execution_role.name
+  policy_arn = aws_iam_policy.truly_lambda_S3_policy.arn
+}
+resource "aws_iam_role_policy_attachment" "truly_lambda_admin_SNS" {
+  role       = aws_iam_role.truly_lambda_admin_execution_role.name
+  policy_arn = aws_iam_policy.truly_lambda_SNS_policy.arn
+}
+resource "aws_iam_role_policy_attachment" "truly_lambda_admin_XRAY" {
+  role       = aws_iam_role.truly_lambda_admin_execution_role.name
+  policy_arn = aws_iam_policy.truly_lambda_XRAY_policy.arn
+}
+resource "aws_iam_role_policy_attachment" "truly_lambda_admin_SECRETSMAN" {
+  role       = aws_iam_role.truly_lambda_admin_execution_role.name
+  policy_arn = aws_iam_policy.truly_lambda_SECRETSMAN_policy.arn
+}
+resource "aws_iam_role_policy_attachment" "truly_lambda_admin_KMS" {
+  role       = aws_iam_role.truly_lambda_admin_execution_role.name
+  policy_arn = aws_iam_policy.truly_lambda_KMS_policy.arn
+}
+resource "aws_iam_role_policy_attachment" "truly_lambda_admin_SQS" {
+  role       = aws_iam_role.truly_lambda_admin_execution_role.name
+  policy_arn = aws_iam_policy.truly_lambda_SQS_policy.arn
+}
+resource "aws_iam_role_policy_attachment" "truly_lambda_admin_QLDB" {
+  role       = aws_iam_role.truly_lambda_admin_execution_role.name
+  policy_arn = aws_iam_policy.truly_lambda_QLDB_policy.arn
+}
+resource "aws_iam_role_policy_attachment" "truly_lambda_admin_execution_policy" {
+  role       = aws_iam_role.truly_lambda_admin_execution_role.name
+  policy_arn = "arn:aws:iam::aws:policy/service-role/AWSLambdaBasicExecutionRole"
+}
@@ -87,7 +87,7 @@ module "lambda_admin" {
 
   service_name = "admin"
   common_tags  = local.common_tags
-  role         = aws_iam_role.truly_lambda_execution_role.arn
+  role         = aws_iam_role.truly_lambda_admin_execution_role.arn
 
   environment_flag = var.environment_flag
   trace_log        = var.trace_log
@@ -237,3 +237,23 @@ module "lambda_notifications" {
   smtp_from               = var.email
 
 }
+
+module "lambda_license_expiry" {
+  source = "./lambda_license_expiry"
+
+  service_name     = "license_expiry"
+  common_tags      = local.common_tags
+  role             = aws_iam_role.truly_lambda_execution_role.arn
+  environment_flag = var.environment_flag
+  rust_log         = var.rust_log
+
+  rust_backtrace = var.rust_backtrace
+
+  aws_region    = var.aws_region
+  architectures = var.architectures
+
+  ecr_image = var.ecr_license_expiry_lambda
+
+  trace_level = var.trace_level
+
+}
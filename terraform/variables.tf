@@ -147,6 +147,9 @@ variable "ecr_alert_similars_lambda"{
 variable "ecr_notifications_lambda"{
   type=string
 }
+variable "ecr_license_expiry_lambda"{
+  type=string
+}
 
 
 variable "trace_level" {
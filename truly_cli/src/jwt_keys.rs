@@ -0,0 +1,129 @@
+use aws_sdk_dynamodb::types::error::ResourceNotFoundException;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use lib_config::config::Config;
+use lib_users::{
+    models::jwt_key::JwtKey,
+    repositories::jwt_keys::JwtKeysRepo,
+    services::jwt_keys::{JwtKeyManipulation, JwtKeyService},
+};
+use lib_util_jwt::keys::{StoredSigningKey, EDDSA, JWT_SIGNING_KEY_SECRET, RS256};
+use rsa::{
+    pkcs8::{EncodePrivateKey, LineEnding},
+    traits::PublicKeyParts,
+    RsaPrivateKey,
+};
+use serde_json::json;
+
+// returns the private key as pkcs8 pem and the public one as jwk
+fn generate_key_pair(
+    kid: &String,
+    algorithm: &String,
+) -> Result<(String, String), Box<dyn std::error::Error + Send + Sync>> {
+    match algorithm.as_str() {
+        RS256 => {
+            let private = RsaPrivateKey::new(&mut rand::thread_rng(), 2048)?;
+            let pem = private.to_pkcs8_pem(LineEnding::LF)?.to_string();
+            let jwk = json!({
+                "kty": "RSA",
+                "use": "sig",
+                "alg": RS256,
+                "kid": kid,
+                "n": URL_SAFE_NO_PAD.encode(private.n().to_bytes_be()),
+                "e": URL_SAFE_NO_PAD.encode(private.e().to_bytes_be()),
+            });
+            Ok((pem, jwk.to_string()))
+        }
+        EDDSA => {
+            let private = ed25519_dalek::SigningKey::generate(&mut rand::rngs::OsRng);
+            let pem = private.to_pkcs8_pem(LineEnding::LF)?.to_string();
+            let jwk = json!({
+                "kty": "OKP",
+                "use": "sig",
+                "alg": EDDSA,
+                "kid": kid,
+                "crv": "Ed25519",
+                "x": URL_SAFE_NO_PAD.encode(private.verifying_key().as_bytes()),
+            });
+            Ok((pem, jwk.to_string()))
+        }
+        _ => Err(format!(
+            "algorithm {} not supported, use {} or {}",
+            algorithm, RS256, EDDSA
+        )
+        .into()),
+    }
+}
+
+async fn store_signing_key(
+    stored: &StoredSigningKey,
+    config: &Config,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let client = aws_sdk_secretsmanager::Client::new(config.aws_config());
+    let value = serde_json::to_string(stored)?;
+    let updated = client
+        .put_secret_value()
+        .secret_id(JWT_SIGNING_KEY_SECRET)
+        .secret_string(value.clone())
+        .send()
+        .await;
+    if let Err(e) = updated {
+        let service_error = e.into_service_error();
+        if !service_error.is_resource_not_found_exception() {
+            return Err(service_error.into());
+        }
+        client
+            .create_secret()
+            .name(JWT_SIGNING_KEY_SECRET)
+            .secret_string(value)
+            .send()
+            .await?;
+    }
+    Ok(())
+}
+
+// The private key is stored first: the login lambda signs with it only once rotate has
+// published it as Current, within SIGNER_CACHE_SECONDS. If the publication fails the lambda keeps
+// its key and running the command again rotates a new one in.
+pub async fn manage_jwt_keys(
+    algorithm: String,
+    create: bool,
+    delete: bool,
+    config: &Config,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let er = ResourceNotFoundException::builder().build();
+    if create {
+        let kid = uuid::Uuid::new_v4().to_string();
+        let (private_pem, jwk) = generate_key_pair(&kid, &algorithm)?;
+
+        let mut key = JwtKey::new();
+        key.set_kid(&kid);
+        key.set_algorithm(&algorithm);
+        key.set_jwk(&jwk);
+
+        store_signing_key(
+            &StoredSigningKey {
+                kid: kid.clone(),
+                algorithm,
+                private_pem,
+            },
+            config,
+        )
+        .await?;
+
+        let key_service = JwtKeyService::new(JwtKeysRepo::new(config));
+        if let Err(e) = key_service.rotate(&key).await {
+            return Err(format!(
+                "jwt signing key {} stored but not published, run it again: {}",
+                kid, e
+            )
+            .into());
+        }
+        println!("jwt signing key {} rotated in.", kid);
+    } else if delete {
+        return Err("jwt keys can't be deleted, rotate a new key instead".into());
+    } else {
+        return Err(aws_sdk_dynamodb::Error::ResourceNotFoundException(er).into());
+    }
+
+    Ok(())
+}
@@ -1,4 +1,6 @@
 use admin_user::create_admin_user;
+use jwt_keys::manage_jwt_keys;
+use oidc_providers::manage_oidc_providers;
 use aws_sdk_dynamodb::types::error::ResourceNotFoundException;
 //use blockchains::manage_blockchains;
 //use contracts::manage_contracts;
@@ -11,10 +13,12 @@ use serde::{Deserialize, Serialize};
 use std::{env, process};
 //use store_key::create_store_key;
 use structopt::StructOpt;
-use users::manage_user;
+use users::{manage_user, reindex_all_users};
 
 mod admin_user;
 mod async_jobs;
+mod jwt_keys;
+mod oidc_providers;
 //mod blockchains;
 //mod contracts;
 mod schemas;
@@ -34,12 +38,18 @@ async fn command(
         service,
         create,
         delete,
+        export,
+        output,
+        transfer_to,
+        reindex_users,
         environment,
         store_secret,
         key,
         adminuser,
         user_id,
         password,
+        jwt_key,
+        oidc_providers,
         // contract,
         // blockchain,
         //ledger,
@@ -79,7 +89,19 @@ async fn command(
     }
 
     if let Some(id) = user_id {
-        manage_user(id, create, delete, environment.clone(), &mut config).await?;
+        manage_user(id, create, delete, export, output, transfer_to, environment.clone(), &mut config).await?;
+    }
+
+    if reindex_users {
+        reindex_all_users(&mut config).await?;
+    }
+
+    if let Some(algorithm) = jwt_key {
+        manage_jwt_keys(algorithm, create, delete, &config).await?;
+    }
+
+    if let Some(path) = oidc_providers {
+        manage_oidc_providers(path, create, delete, &config).await?;
     }
 
     // if let Some(contract_path) = contract {
@@ -119,6 +141,22 @@ pub struct Opt {
     #[structopt(long = "delete")]
     pub delete: bool,
 
+    // personal data of --user_id, as json
+    #[structopt(long = "export")]
+    pub export: bool,
+
+    // file to write the export to, stdout otherwise
+    #[structopt(long = "output")]
+    pub output: Option<String>,
+
+    // with --delete, the user receiving the assets of --user_id, anonymized otherwise
+    #[structopt(long = "transfer_to")]
+    pub transfer_to: Option<String>,
+
+    // rewrites all users so the admin search indexes cover them
+    #[structopt(long = "reindex_users")]
+    pub reindex_users: bool,
+
     #[structopt(env = "ENVIRONMENT")]
     pub environment: String,
 
@@ -139,6 +177,14 @@ pub struct Opt {
     #[structopt(long = "password")]
     pub password: Option<String>,
 
+    // RS256 or EdDSA
+    #[structopt(long = "jwt_key")]
+    pub jwt_key: Option<String>,
+
+    // json file with the trusted openid connect providers
+    #[structopt(long = "oidc_providers")]
+    pub oidc_providers: Option<String>,
+
     // #[structopt(long = "contract")]
     // pub contract: Option<String>,
 
@@ -0,0 +1,49 @@
+use std::fs;
+
+use aws_sdk_dynamodb::types::error::ResourceNotFoundException;
+use lib_config::config::Config;
+use lib_users::models::identity::OidcProvider;
+use lib_util_jwt::oidc::OIDC_PROVIDERS_SECRET;
+
+// the whole list is replaced, the login lambda picks it up on its next cold start
+pub async fn manage_oidc_providers(
+    providers_json_path: String,
+    create: bool,
+    delete: bool,
+    config: &Config,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let er = ResourceNotFoundException::builder().build();
+    if create {
+        let contents = fs::read_to_string(providers_json_path)?;
+        // a malformed list would turn oidc login off, better to fail here
+        let providers: Vec<OidcProvider> = serde_json::from_str(&contents)?;
+        let value = serde_json::to_string(&providers)?;
+
+        let client = aws_sdk_secretsmanager::Client::new(config.aws_config());
+        let updated = client
+            .put_secret_value()
+            .secret_id(OIDC_PROVIDERS_SECRET)
+            .secret_string(value.clone())
+            .send()
+            .await;
+        if let Err(e) = updated {
+            let service_error = e.into_service_error();
+            if !service_error.is_resource_not_found_exception() {
+                return Err(service_error.into());
+            }
+            client
+                .create_secret()
+                .name(OIDC_PROVIDERS_SECRET)
+                .secret_string(value)
+                .send()
+                .await?;
+        }
+        println!("{} oidc providers stored.", providers.len());
+    } else if delete {
+        panic!("not allowed, store an empty list instead")
+    } else {
+        return Err(aws_sdk_dynamodb::Error::ResourceNotFoundException(er).into());
+    }
+
+    Ok(())
+}
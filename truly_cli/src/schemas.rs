@@ -3,12 +3,14 @@ use lib_config::config::Config;
 use lib_config::schema::Schema;
 use lib_engage::repositories::schema_alert_similar::AlertSimilarSchema;
 use lib_licenses::repositories::{
-    schema_asset::AssetAllSchema, schema_licenses::LicenseSchema, schema_owners::OwnerSchema,
+    schema_asset::AssetAllSchema, schema_license_requests::LicenseRequestAllSchema,
+    schema_licenses::LicenseSchema, schema_owners::OwnerSchema,
 };
 use lib_engage::repositories::schema_subscription::SubscriptionSchema;
 use lib_licenses::{
     services::assets::SERVICE as ASSET_SERVICE, services::licenses::SERVICE as LICENSE_SERVICE,
     services::owners::SERVICE as OWNER_SERVICE,
+    services::license_requests::SERVICE as LICENSE_REQUEST_SERVICE,
 };
 use lib_engage::services::subscription::SERVICE as SUBSCRIPTION_SERVICE;
 use lib_engage::services::alert_similar::SERVICE as ALERT_SIMILAR_SERVICE;
@@ -69,6 +71,15 @@ pub async fn create_schemas(
                 return Err(aws_sdk_dynamodb::Error::ResourceNotFoundException(er).into());
             }
         }
+        LICENSE_REQUEST_SERVICE => {
+            if create {
+                LicenseRequestAllSchema::create_schema(config).await?;
+            } else if delete {
+                LicenseRequestAllSchema::delete_schema(config).await?;
+            } else {
+                return Err(aws_sdk_dynamodb::Error::ResourceNotFoundException(er).into());
+            }
+        }
         SUBSCRIPTION_SERVICE => {
             if create {
                 SubscriptionSchema::create_schema(config).await?;
@@ -94,6 +105,7 @@ pub async fn create_schemas(
                 AssetAllSchema::create_schema(config).await?;
                 UserAllSchema::create_schema(config).await?;
                 LicenseSchema::create_schema(config).await?;
+                LicenseRequestAllSchema::create_schema(config).await?;
                 SubscriptionSchema::create_schema(config).await?;
                 AlertSimilarSchema::create_schema(config).await?;
             } else if delete {
@@ -101,6 +113,7 @@ pub async fn create_schemas(
                 AssetAllSchema::delete_schema(config).await?;
                 UserAllSchema::delete_schema(config).await?;
                 LicenseSchema::delete_schema(config).await?;
+                LicenseRequestAllSchema::delete_schema(config).await?;
                 SubscriptionSchema::delete_schema(config).await?;
                 AlertSimilarSchema::delete_schema(config).await?;
             } else {
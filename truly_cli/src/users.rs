@@ -1,21 +1,41 @@
 use lib_config::config::Config;
+use lib_engage::services::account_deletion::{AccountDeletionService, AssetPolicy};
+use lib_engage::services::data_export::DataExportService;
 use lib_users::{
     repositories::users::UsersRepo,
     services::users::{UserManipulation, UsersService},
 };
 
+#[allow(clippy::too_many_arguments)]
 pub async fn manage_user(
     id: String,
     _create: bool,
     delete: bool,
+    export: bool,
+    output: Option<String>,
+    transfer_to: Option<String>,
     _environment: String,
     config: &mut Config,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     config.load_secrets().await;
-    let user_repo = UsersRepo::new(&config);
-    let user_service = UsersService::new(user_repo);
-    if delete {
-        let op = user_service.remove_by_id(&id).await;
+    if export {
+        let data_export_service = DataExportService::new(&config);
+        let data = data_export_service.export(&id).await?;
+        let json = serde_json::to_string_pretty(&data)?;
+        match output {
+            None => println!("{}", json),
+            Some(path) => {
+                std::fs::write(&path, json)?;
+                println!("user {} exported to {}", id, path)
+            }
+        }
+    } else if delete {
+        let policy = match transfer_to {
+            None => AssetPolicy::Anonymize,
+            Some(heir) => AssetPolicy::Transfer(heir),
+        };
+        let account_deletion_service = AccountDeletionService::new(&config);
+        let op = account_deletion_service.delete(&id, &policy).await;
         match op {
             Err(e) => {
                 println!("{}", e);
@@ -30,3 +50,14 @@ pub async fn manage_user(
 
     Ok(())
 }
+
+pub async fn reindex_all_users(
+    config: &mut Config,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    config.load_secrets().await;
+    let user_repo = UsersRepo::new(&config);
+    let user_service = UsersService::new(user_repo);
+    let total = user_service.reindex_all().await?;
+    println!("{} users reindexed", total);
+    Ok(())
+}